@@ -1,4 +1,6 @@
-use std::io::{self, Stdout};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Stdout};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
@@ -13,26 +15,62 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     Terminal, TerminalOptions, Viewport,
 };
+use tokio::sync::oneshot;
+use tokio::time::{Interval, MissedTickBehavior};
 
 use crate::commands::Command;
-use crate::config::{AgentRuntimeConfig, Config};
+use crate::compaction::sources_request;
+use crate::config::{AgentRuntimeConfig, Config, AUDIT_FILENAME, BACKUPS_DIR, CODEY_DIR};
 use crate::effect::{Effect, EffectPoll, EffectQueue, PendingEffect};
 use crate::ide::{Ide, IdeEvent, Nvim};
-use crate::llm::{Agent, AgentId, AgentRegistry, AgentStatus, AgentStep, RequestMode};
+use crate::llm::{
+    Agent, AgentId, AgentRegistry, AgentStatus, AgentStep, ContextLevel, ModelPricing, ReasoningLevel, RequestMode,
+};
 #[cfg(feature = "profiling")]
 use crate::{profile_frame, profile_span};
+use crate::artifacts::{Artifact, Artifacts};
+use crate::links::{transcript_links, Link};
 use crate::notifications::{Notification, NotificationQueue};
-use crate::prompts::{SystemPrompt, COMPACTION_PROMPT, welcome_message};
-use crate::tool_filter::ToolFilters;
+use crate::pinned::PinnedFiles;
+use crate::prompts::{
+    compaction_prompt, init_prompt, session_greeting, SystemPrompt, PLAN_APPROVED_PROMPT, PLAN_MODE_PROMPT,
+    PROJECT_BANNER_FILENAME,
+};
+use crate::tool_filter::{is_read_only_command, ToolFilters};
 use crate::tools::{
-    init_agent_context, init_browser_context, update_agent_oauth, EffectResult, ToolDecision,
-    ToolEvent, ToolExecutor, ToolRegistry,
+    init_agent_context, mcp, names, update_agent_oauth, ApprovalSource, AuditLog, Backups,
+    BrowserContext, CustomCommandTool, DownloadTool, EditFileTool, EffectResult, FetchHtmlTool, FileCache, FuzzyMatch,
+    MemoryWriteTool, PostEditCheck, ReadFileTool, ReadTracker, ReplaceInFilesTool, ShellSandbox, ShellTool, Ssh,
+    Tool, ToolDecision, ToolEvent, ToolExecutor, ToolRegistry, WebSearchTool,
 };
-use crate::transcript::{Block, BlockType, NotificationBlock, Role, Status, TextBlock, Transcript};
-use crate::ui::{Attachment, ChatView, InputBox};
+use crate::transcript::{
+    set_max_line_length, Block, BlockType, NotificationBlock, Role, SessionStats, Status,
+    TextBlock, Transcript, TurnCost,
+};
+use crate::ui::{Attachment, ChatView, InputBox, Segment};
+use crate::variables::SessionVariables;
 
 const MIN_FRAME_TIME: Duration = Duration::from_millis(16);
 
+/// Spinner animation shown while waiting on the model or a running tool
+const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(80);
+
+/// How long after a first Ctrl+C a second one quits
+const QUIT_CONFIRM_WINDOW: Duration = Duration::from_secs(2);
+
+/// Consecutive IDE preview timeouts before the IDE is treated as unresponsive
+const IDE_MAX_TIMEOUTS: u32 = 3;
+
+/// Number of transcripts `/resume` lists
+const RESUME_LIST_LIMIT: usize = 20;
+
+/// File in `CODEY_DIR` that `/debug messages` writes the message history to
+const DEBUG_MESSAGES_FILENAME: &str = "messages.txt";
+
+/// Largest text artifact `/artifacts attach` inlines; bigger files are attached by path
+const ARTIFACT_INLINE_MAX_BYTES: usize = 64 * 1024;
+
 pub const APP_NAME: &str = "Codey";
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -50,6 +88,28 @@ enum InputMode {
     Normal,
     Streaming,
     ToolApproval,
+    /// The pending tool call's params are open in the input box as JSON
+    EditParams,
+}
+
+/// Vi-style modal editing of the input (`general.vi_mode`, `/vi`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViMode {
+    Off,
+    /// Keys type text as usual; Esc switches to normal mode
+    Insert,
+    /// Keys are motions and editing commands. `pending` holds an operator
+    /// waiting for its motion (the first `d` of `dd`).
+    Normal { pending: Option<char> },
+}
+
+/// Where vi's insert commands (`i`, `a`, `I`, `A`) put the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViInsertAt {
+    Cursor,
+    After,
+    LineStart,
+    LineEnd,
 }
 
 /// Actions that can be triggered by terminal events
@@ -65,6 +125,16 @@ enum Action {
     CursorRight,
     CursorHome,
     CursorEnd,
+    // Vi mode
+    ViNormal,
+    ViInsert(ViInsertAt),
+    ViPending(Option<char>),
+    WordForward,
+    WordBack,
+    LineStart,
+    LineEnd,
+    DeleteChar,
+    DeleteLine,
     // Input control
     Submit,
     ClearInput,
@@ -78,12 +148,19 @@ enum Action {
     // Tool approval
     ApproveTool,
     DenyTool,
+    EditToolParams,
+    CancelParamEdit,
+    // Display
+    ToggleToolParams,
+    // Links
+    FocusLink,
+    OpenLink,
 }
 
 /// Map a terminal event to an action based on the current input mode
-fn map_event(mode: InputMode, event: Event) -> Option<Action> {
+fn map_event(mode: InputMode, vi: ViMode, event: Event) -> Option<Action> {
     match event {
-        Event::Key(key) => map_key(mode, key),
+        Event::Key(key) => map_key(mode, vi, key),
         Event::Paste(content) => Some(Action::Paste(content)),
         Event::Resize(w, h) => Some(Action::Resize(w, h)),
         _ => None,
@@ -91,16 +168,26 @@ fn map_event(mode: InputMode, event: Event) -> Option<Action> {
 }
 
 /// Map a key event to an action based on the current input mode
-fn map_key(mode: InputMode, key: KeyEvent) -> Option<Action> {
+fn map_key(mode: InputMode, vi: ViMode, key: KeyEvent) -> Option<Action> {
     // Only handle key press events, not release or repeat
     if key.kind != KeyEventKind::Press {
         return None;
     }
 
+    // Vi mode layers over typing, but not over approval prompts
+    if !matches!(mode, InputMode::ToolApproval | InputMode::EditParams) {
+        match vi {
+            ViMode::Normal { pending } => return map_key_vi_normal(mode, pending, key),
+            ViMode::Insert if key.code == KeyCode::Esc => return Some(Action::ViNormal),
+            _ => {},
+        }
+    }
+
     match mode {
         InputMode::Normal => map_key_normal(key),
         InputMode::Streaming => map_key_streaming(key),
         InputMode::ToolApproval => map_key_tool_approval(key),
+        InputMode::EditParams => map_key_edit_params(key),
     }
 }
 
@@ -115,6 +202,9 @@ fn map_key_normal(key: KeyEvent) -> Option<Action> {
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         return match key.code {
             KeyCode::Char('c') => Some(Action::Quit),
+            KeyCode::Char('o') => Some(Action::ToggleToolParams),
+            KeyCode::Char('l') => Some(Action::FocusLink),
+            KeyCode::Char('g') => Some(Action::OpenLink),
             _ => None,
         };
     }
@@ -136,6 +226,41 @@ fn map_key_normal(key: KeyEvent) -> Option<Action> {
     }
 }
 
+/// Keybindings for vi normal mode (`general.vi_mode`)
+fn map_key_vi_normal(mode: InputMode, pending: Option<char>, key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return map_key_normal(key);
+    }
+
+    if let Some(op) = pending {
+        return match (op, key.code) {
+            ('d', KeyCode::Char('d')) => Some(Action::DeleteLine),
+            _ => Some(Action::ViPending(None)),
+        };
+    }
+
+    match key.code {
+        KeyCode::Esc if mode == InputMode::Streaming => Some(Action::Interrupt),
+        KeyCode::Esc => Some(Action::ViPending(None)),
+        KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => Some(Action::CursorLeft),
+        KeyCode::Char('l') | KeyCode::Right => Some(Action::CursorRight),
+        KeyCode::Char('k') | KeyCode::Up => Some(Action::HistoryPrev),
+        KeyCode::Char('j') | KeyCode::Down => Some(Action::HistoryNext),
+        KeyCode::Char('w') => Some(Action::WordForward),
+        KeyCode::Char('b') => Some(Action::WordBack),
+        KeyCode::Char('0') | KeyCode::Home => Some(Action::LineStart),
+        KeyCode::Char('$') | KeyCode::End => Some(Action::LineEnd),
+        KeyCode::Char('x') | KeyCode::Delete => Some(Action::DeleteChar),
+        KeyCode::Char('d') => Some(Action::ViPending(Some('d'))),
+        KeyCode::Char('i') => Some(Action::ViInsert(ViInsertAt::Cursor)),
+        KeyCode::Char('a') => Some(Action::ViInsert(ViInsertAt::After)),
+        KeyCode::Char('I') => Some(Action::ViInsert(ViInsertAt::LineStart)),
+        KeyCode::Char('A') => Some(Action::ViInsert(ViInsertAt::LineEnd)),
+        KeyCode::Enter => Some(Action::Submit),
+        _ => None,
+    }
+}
+
 /// Keybindings for streaming input mode
 fn map_key_streaming(key: KeyEvent) -> Option<Action> {
     match key.code {
@@ -146,13 +271,45 @@ fn map_key_streaming(key: KeyEvent) -> Option<Action> {
 
 /// Keybindings for tool approval mode
 fn map_key_tool_approval(key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        return Some(Action::Quit);
+    }
     match key.code {
         KeyCode::Char('y') | KeyCode::Enter => Some(Action::ApproveTool),
         KeyCode::Char('n') | KeyCode::Esc => Some(Action::DenyTool),
+        KeyCode::Char('e') => Some(Action::ToggleToolParams),
+        KeyCode::Char('p') => Some(Action::EditToolParams),
         _ => None,
     }
 }
 
+/// Keybindings while editing a pending tool call's params
+fn map_key_edit_params(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Esc => Some(Action::CancelParamEdit),
+        KeyCode::Up | KeyCode::Down | KeyCode::Tab => None,
+        _ => map_key_normal(key),
+    }
+}
+
+/// Keybindings listed by `/help`; keep in sync with the `map_key_*` functions
+pub const KEYBINDINGS: &[(&str, &str)] = &[
+    ("Enter", "Send message"),
+    ("Shift+Enter / Alt+Enter", "New line"),
+    ("Up / Down", "Input history"),
+    ("Tab", "Complete slash command"),
+    ("Esc", "Clear input, or interrupt while the agent is working"),
+    ("Ctrl+C", "Interrupt and quit (press twice unless ui.confirm_quit = false)"),
+    ("Ctrl+O", "Expand/collapse the params of the latest tool call"),
+    ("Ctrl+L", "Focus the next file path or URL in the transcript, newest first"),
+    ("Ctrl+G", "Open the focused link (files in the IDE, URLs in the browser)"),
+    ("y / Enter", "Approve tool (when prompted)"),
+    ("n / Esc", "Deny tool (when prompted)"),
+    ("e", "Expand/collapse tool params (when prompted)"),
+    ("p", "Edit tool params as JSON, then Enter to approve or Esc to go back (when prompted)"),
+    ("Esc (vi mode)", "Normal mode: h/l/w/b/0/$ move, j/k history, x/dd delete, i/a/I/A insert"),
+];
+
 /// Application state
 pub struct App {
     config: Config,
@@ -162,6 +319,8 @@ pub struct App {
     input: InputBox,
     /// Flag to indicate a quit request
     should_quit: bool,
+    /// When Ctrl+C was last pressed; a second press within `QUIT_CONFIRM_WINDOW` quits
+    quit_armed: Option<Instant>,
     continue_session: bool,
     /// Last render time for frame rate limiting
     last_render: Instant,
@@ -169,6 +328,8 @@ pub struct App {
     alert: Option<String>,
     /// Compiled tool parameter filters for auto-approve/deny
     tool_filters: ToolFilters,
+    /// Filter preset compiled into `tool_filters`, if any
+    filter_preset: Option<String>,
     /// IDE connection for editor integration (e.g., Neovim)
     ide: Option<Box<dyn Ide>>,
     /// Terminal event stream
@@ -185,6 +346,44 @@ pub struct App {
     effects: EffectQueue,
     /// Notifications to inject into next tool result
     notifications: NotificationQueue,
+    /// Plan mode: primary agent requests use `RequestMode::Plan` until /execute
+    plan_mode: bool,
+    /// Threshold-triggered compaction is suspended (`/nocompact`)
+    compaction_paused: bool,
+    /// Prompt sent with compaction requests (configurable, defaults to COMPACTION_PROMPT)
+    compaction_prompt: String,
+    /// Whether the IDE's open files have been shared with the agent yet
+    open_files_shared: bool,
+    /// Animation tick for the spinner (None when disabled or not on a TTY)
+    spinner: Option<Interval>,
+    spinner_frame: usize,
+    /// A request was sent and the primary agent hasn't produced output yet
+    awaiting_response: bool,
+    /// Consecutive IDE preview calls that timed out
+    ide_timeouts: u32,
+    /// `/ide on` asked for editor discovery, done once the command returns
+    ide_connect_requested: bool,
+    /// Vi-style modal input (`general.vi_mode`, `/vi`)
+    vi: ViMode,
+    /// Notes the agent left in the editor that haven't been cleared yet
+    ide_annotated: bool,
+    /// `/annotations` asked to clear them, done once the command returns
+    clear_annotations_requested: bool,
+    /// When the current approval prompt is decided automatically (`tools.approval_timeout_secs`)
+    approval_deadline: Option<tokio::time::Instant>,
+    /// What was typed before the input box was taken over to edit tool params
+    param_draft: Option<Vec<Segment>>,
+    /// Files whose contents are added to every request (`general.pinned_files`, `/pin`)
+    pinned: PinnedFiles,
+    /// Values set with `/set`, used by custom tools and the system prompt
+    variables: SessionVariables,
+    /// Files tools produced this session (`/artifacts`)
+    artifacts: Artifacts,
+    /// Files the agent has read, forgotten whenever its history is dropped
+    reads: SessionReads,
+    /// Shell calls started for `Effect::RunCommand`, keyed by their call_id.
+    /// Their result goes back to the requesting tool instead of the agent.
+    followups: HashMap<String, oneshot::Sender<EffectResult>>,
 }
 
 impl App {
@@ -246,37 +445,49 @@ impl App {
         .context("Failed to create terminal")?;
 
         // Load existing transcript or create new one
-        let transcript = if continue_session {
+        let mut transcript = if continue_session {
             Transcript::load().context("Failed to load transcript")?
         } else {
             Transcript::new_numbered().context("Failed to create new transcript")?
         };
+        transcript.set_tool_output_limit(config.general.transcript_tool_output_limit);
+        transcript.set_thinking_limit(config.general.transcript_thinking_limit);
+        transcript.set_param_limit(config.general.param_limit());
 
         // Compile tool filters from config
-        let tool_filters = ToolFilters::compile(&config.tools.filters())
+        let tool_filters = config
+            .tools
+            .compile_filters(config.tools.filter_preset.as_deref())
             .context("Failed to compile tool filters")?;
 
         // Try to connect to neovim if enabled
-        let ide: Option<Box<dyn Ide>> = if config.ide.nvim.enabled {
-            match Nvim::discover(&config.ide.nvim).await {
-                Ok(Some(nvim)) => {
-                    tracing::info!("Connected to {} at {:?}", nvim.name(), nvim.socket_path());
-                    Some(Box::new(nvim))
-                },
-                Ok(None) => {
-                    tracing::debug!("No nvim instance found");
-                    None
-                },
-                Err(e) => {
-                    tracing::warn!("Failed to connect to nvim: {}", e);
-                    None
-                },
-            }
+        let ide = if config.ide.nvim.enabled {
+            discover_ide(&config).await
         } else {
             None
         };
 
+        set_max_line_length(config.ui.max_line_length);
         let agent_name = config.agent.name().to_string();
+        let compaction_prompt =
+            compaction_prompt(&config.general).context("Failed to load compaction prompt")?;
+
+        let spinner = (config.ui.spinner && io::stdout().is_terminal()).then(|| {
+            let mut tick = tokio::time::interval(SPINNER_INTERVAL);
+            tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            tick
+        });
+
+        let variables = SessionVariables::new();
+        let reads = SessionReads::default();
+        let tool_executor = tool_executor(&config, &variables, &reads).await?;
+        // Tell the user up front if the sandbox is weaker than they configured
+        let sandbox_warning = shell_sandbox(&config).and_then(|sandbox| sandbox.warnings().first().cloned());
+
+        let pinned = PinnedFiles::new(
+            config.general.pinned_files.iter().cloned(),
+            config.general.pinned_max_bytes,
+        );
 
         Ok(Self {
             config,
@@ -284,18 +495,39 @@ impl App {
             chat: ChatView::new(transcript, terminal_size.0, chat_height, agent_name),
             input: InputBox::new(),
             should_quit: false,
+            quit_armed: None,
             continue_session,
             last_render: Instant::now(),
-            alert: None,
+            alert: sandbox_warning,
             tool_filters,
+            filter_preset: config.tools.filter_preset.clone(),
             ide,
             events: EventStream::new(),
             input_mode: InputMode::Normal,
             agents: AgentRegistry::new(),
-            tool_executor: ToolExecutor::new(ToolRegistry::new()),
+            tool_executor,
             oauth: None,
             effects: EffectQueue::new(),
             notifications: NotificationQueue::new(),
+            plan_mode: false,
+            compaction_paused: false,
+            compaction_prompt,
+            open_files_shared: false,
+            spinner,
+            spinner_frame: 0,
+            awaiting_response: false,
+            ide_timeouts: 0,
+            ide_connect_requested: false,
+            vi: if config.general.vi_mode { ViMode::Insert } else { ViMode::Off },
+            ide_annotated: false,
+            clear_annotations_requested: false,
+            approval_deadline: None,
+            param_draft: None,
+            pinned,
+            variables,
+            artifacts: Artifacts::new(),
+            reads,
+            followups: HashMap::new(),
         })
     }
 
@@ -336,11 +568,10 @@ impl App {
             self.oauth.clone(),
         );
 
-        // Initialize browser context for fetch_html
-        init_browser_context(&self.config.browser);
-
         // Use dynamic prompt builder so mdsh commands are re-executed on each LLM call
-        let system_prompt = SystemPrompt::with_config(&self.config);
+        let system_prompt = SystemPrompt::with_config(&self.config)
+            .with_pinned(self.pinned.clone())
+            .with_variables(self.variables.clone());
         let agent_name = system_prompt.agent_name().to_string();
         let mut agent = Agent::with_dynamic_prompt(
             AgentRuntimeConfig::foreground(&self.config),
@@ -351,9 +582,8 @@ impl App {
 
         if self.continue_session {
             agent.restore_from_transcript(&self.chat.transcript);
-        } else {
-            self.chat
-                .add_turn(Role::Assistant, TextBlock::pending(&welcome_message(&agent_name)));
+        } else if let Some(greeting) = session_greeting(&self.config.general, &agent_name) {
+            self.chat.add_turn(Role::Assistant, TextBlock::pending(&greeting));
         }
         self.agents.register(agent);
 
@@ -390,6 +620,15 @@ impl App {
                     let notifications = self.notifications.drain_all();
                     self.handle_notifications(notifications).await?;
                 }
+                // Advance the spinner while waiting on the model or a tool
+                Some(_) = async { Some(self.spinner.as_mut()?.tick().await) }, if self.spinner_status().is_some() => {
+                    self.spinner_frame = self.spinner_frame.wrapping_add(1);
+                    self.draw();
+                }
+                // Decide an approval prompt nobody answered in time
+                Some(_) = async { Some(tokio::time::sleep_until(self.approval_deadline?).await) }, if self.input_mode == InputMode::ToolApproval => {
+                    self.approval_timed_out().await;
+                }
                 // Handle pending effects (IDE previews waiting for slot, etc.)
                 _ = std::future::ready(()), if self.effects.has_pollable() => {
                     if let Some(pending) = self.effects.poll_next() {
@@ -423,15 +662,23 @@ impl App {
         }
 
         // Layer 2: No foreground tool running — cancel the entire turn.
+        // Any tool calls still awaiting a decision are abandoned so the agent
+        // doesn't wait for results that will never arrive.
         if let Some(agent_mutex) = self.agents.primary() {
-            agent_mutex.lock().await.cancel();
+            if agent_mutex.lock().await.abort_tool_wait() {
+                tracing::info!("Cancel: abandoned pending tool calls");
+            }
         }
         self.tool_executor.cancel();
         self.effects.clear();
+        self.awaiting_response = false;
         self.chat.finish_turn(&mut self.terminal);
         if let Err(e) = self.chat.transcript.save() {
             tracing::error!("Failed to save transcript on cancel: {}", e);
         }
+        if let Some(draft) = self.param_draft.take() {
+            self.input.restore_draft(draft);
+        }
         self.input_mode = InputMode::Normal;
         Ok(())
     }
@@ -472,13 +719,25 @@ impl App {
             .primary()
             .and_then(|m| m.try_lock().ok())
             .map_or("", |a| a.model_icon());
+        let model = if self.plan_mode {
+            format!("{} (plan)", self.config.agents.foreground.model)
+        } else {
+            self.config.agents.foreground.model.clone()
+        };
         let input_widget = self.input.widget(
-            &self.config.agents.foreground.model,
+            &model,
             model_icon,
             context_tokens,
             self.tool_executor.running_background_count() + self.agents.running_background_count(),
             self.input_mode != InputMode::Normal,
         );
+        let status = (self.input_mode == InputMode::EditParams)
+            .then(|| "editing tool params: Enter approves, Esc goes back".to_string())
+            .or_else(|| self.spinner_status())
+            .or_else(|| matches!(self.vi, ViMode::Normal { .. }).then(|| "-- NORMAL --".to_string()))
+            .or_else(|| self.compaction_paused.then(|| "compaction paused".to_string()));
+        let context_level = ContextLevel::of(context_tokens, self.config.general.compaction_threshold);
+        let input_widget = input_widget.status(status.as_deref()).context_level(context_level);
         let alert = self.alert.clone();
 
         if let Err(e) = self.terminal.draw(|frame| {
@@ -505,6 +764,23 @@ impl App {
         }
     }
 
+    /// Spinner frame and label, if something is in progress with nothing to show for it yet
+    fn spinner_status(&self) -> Option<String> {
+        self.spinner.as_ref()?;
+        if self.input_mode != InputMode::Streaming {
+            return None;
+        }
+        let label = if self.tool_executor.has_running_foreground() {
+            "running tool"
+        } else if self.awaiting_response {
+            "waiting for response"
+        } else {
+            return None;
+        };
+        let frame = SPINNER_FRAMES[self.spinner_frame % SPINNER_FRAMES.len()];
+        Some(format!("{} {}", frame, label))
+    }
+
     /// Draw with frame rate limiting - skips if called too frequently
     /// Returns true if a draw actually occurred
     fn draw_throttled(&mut self) -> bool {
@@ -529,13 +805,19 @@ impl App {
     /// Queue a user message or command for processing.
     /// Stages a block for rendering and queues a notification for processing.
     fn queue_message(&mut self, content: String) {
-        if let Some(command) = Command::parse(&content) {
-            let name = command.name().to_string();
+        if let Some(name) = Command::name(&content) {
+            let name = name.to_string();
+            let args = Command::args(&content).to_string();
             // Stage block for visual feedback
-            let block = TextBlock::pending(format!("/{}", name));
+            let label = if args.is_empty() {
+                format!("/{}", name)
+            } else {
+                format!("/{} {}", name, args)
+            };
+            let block = TextBlock::pending(label);
             let block_id = self.chat.transcript.stage.push(Box::new(block));
             self.notifications
-                .push(Notification::Command { name, block_id });
+                .push(Notification::Command { name, args, block_id });
         } else {
             // Stage block for visual feedback
             let block = TextBlock::pending(&content);
@@ -555,9 +837,633 @@ impl App {
         self.notifications.push(Notification::Compaction { block_id });
     }
 
+    /// Suspend or resume automatic compaction. While paused, turns that end
+    /// over the threshold only warn; `/compact` still works.
+    pub fn set_compaction_paused(&mut self, paused: bool) {
+        self.compaction_paused = paused;
+    }
+
+    /// `/nocompact [on|off]`
+    pub fn set_compaction_paused_arg(&mut self, arg: &str) -> Result<Option<String>> {
+        let paused = match arg {
+            "" => !self.compaction_paused,
+            "on" => true,
+            "off" => false,
+            other => anyhow::bail!("Unknown argument '{}' (expected on or off)", other),
+        };
+        self.set_compaction_paused(paused);
+        Ok(Some(if paused {
+            "Automatic compaction paused until /nocompact off".to_string()
+        } else {
+            "Automatic compaction resumed".to_string()
+        }))
+    }
+
+    /// Shrink the primary agent's context by eliding old tool results,
+    /// leaving the conversation itself intact
+    async fn compact_tool_results(&mut self) {
+        let Some(agent_mutex) = self.agents.primary() else {
+            return;
+        };
+        let keep_recent = self.config.general.tool_result_keep_recent;
+        let (elided, saved) = agent_mutex.lock().await.compact_tool_results(keep_recent);
+        if elided > 0 {
+            self.reads.clear();
+            self.alert = Some(format!(
+                "Elided {} old tool results ({} chars) to save context",
+                elided, saved
+            ));
+        }
+    }
+
+    /// Usage and estimated cost of the primary agent's last turn, priced from
+    /// `general.pricing` or the active model's list prices
+    async fn turn_cost(&self) -> Option<TurnCost> {
+        let agent = self.agents.primary()?.lock().await;
+        let usage = agent.turn_usage();
+        let pricing = self
+            .config
+            .general
+            .pricing
+            .or_else(|| ModelPricing::for_model(agent.active_model()));
+        Some(TurnCost {
+            input_tokens: usage.context_tokens,
+            output_tokens: usage.output_tokens,
+            dollars: pricing.map(|p| usage.cost(&p)),
+        })
+    }
+
+    /// Restore the full pre-compaction context into the primary agent.
+    /// Undoes the lossy summary at the cost of a larger context.
+    /// With a number, shows the turn that source of the summary came from instead.
+    pub fn uncompact(&mut self, args: &str) -> Result<Option<String>> {
+        if !args.is_empty() {
+            return self.show_compaction_source(args);
+        }
+        let path = self
+            .chat
+            .transcript
+            .compacted_from()
+            .map(|p| p.to_path_buf())
+            .context("No compacted context to restore")?;
+        let previous = Transcript::load_from(&path)
+            .with_context(|| format!("Failed to load transcript {}", path.display()))?;
+
+        let agent_mutex = self.agents.primary().context("No primary agent")?;
+        let mut agent = agent_mutex
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+        agent.restore_uncompacted(&previous, &self.chat.transcript);
+        self.reads.clear();
+
+        Ok(Some(format!(
+            "Restored {} messages from {}. Context will be compacted again once it exceeds the threshold.",
+            agent.message_count(),
+            path.display()
+        )))
+    }
+
+    /// Show the turn a cited fact of the compaction summary came from
+    fn show_compaction_source(&self, number: &str) -> Result<Option<String>> {
+        let number: usize = number
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Expected a source number, got '{}'", number))?;
+        let sources = self.chat.transcript.compaction_sources();
+        let source = number
+            .checked_sub(1)
+            .and_then(|i| sources.get(i))
+            .with_context(|| format!("No source {} (the summary cites {})", number, sources.len()))?;
+        let path = self
+            .chat
+            .transcript
+            .compacted_from()
+            .context("No compacted context to trace sources in")?;
+        let previous = Transcript::load_from(path)
+            .with_context(|| format!("Failed to load transcript {}", path.display()))?;
+        let turn = previous
+            .turns()
+            .iter()
+            .find(|turn| turn.id == source.turn)
+            .with_context(|| format!("Turn {} not found in {}", source.turn, path.display()))?;
+
+        let content: Vec<&str> = turn.content.iter().filter_map(|block| block.text()).collect();
+        Ok(Some(format!(
+            "Source {}: {}\nFrom turn {} of {}:\n\n{}",
+            number,
+            source.fact,
+            source.turn,
+            path.display(),
+            content.join("\n\n")
+        )))
+    }
+
+    /// Override max output tokens for the next message, or show the current setting
+    pub fn set_next_max_tokens(&mut self, args: &str) -> Result<Option<String>> {
+        let agent_mutex = self.agents.primary().context("No primary agent")?;
+        let mut agent = agent_mutex
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+
+        match args {
+            "" => {
+                let (default, next) = agent.max_tokens();
+                Ok(Some(match next {
+                    Some(next) => format!("Next message: {} max tokens (then back to {})", next, default),
+                    None => format!("Max tokens: {} (usage: /maxtokens <n>)", default),
+                }))
+            },
+            "reset" => {
+                agent.clear_next_max_tokens();
+                Ok(Some(format!("Max tokens back to {}", agent.max_tokens().0)))
+            },
+            n => {
+                let max_tokens: u32 = n
+                    .replace('_', "")
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid token count '{}'", n))?;
+                agent.set_next_max_tokens(max_tokens).map_err(anyhow::Error::msg)?;
+                Ok(Some(format!("Next message: {} max tokens", max_tokens)))
+            },
+        }
+    }
+
+    /// Switch the primary agent's reasoning level, or show it when no level
+    /// is given. `/reasoning default` goes back to `thinking_budget`.
+    pub fn set_reasoning(&mut self, args: &str) -> Result<Option<String>> {
+        let agent_mutex = self.agents.primary().context("No primary agent")?;
+        let mut agent = agent_mutex
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+
+        let reasoning = match args {
+            "" => {
+                return Ok(Some(match agent.reasoning() {
+                    Some(level) => format!(
+                        "Reasoning: {} ({} thinking tokens; usage: /reasoning off|low|medium|high|default)",
+                        level,
+                        agent.thinking_budget()
+                    ),
+                    None => format!(
+                        "Reasoning: thinking_budget ({} tokens; usage: /reasoning off|low|medium|high)",
+                        agent.thinking_budget()
+                    ),
+                }))
+            },
+            "default" => None,
+            level => Some(level.parse::<ReasoningLevel>().map_err(anyhow::Error::msg)?),
+        };
+        agent.set_reasoning(reasoning);
+        self.config.general.reasoning = reasoning;
+        Ok(Some(match reasoning {
+            Some(ReasoningLevel::Off) => "Reasoning off".to_string(),
+            Some(level) => format!("Reasoning: {} ({} thinking tokens)", level, agent.thinking_budget()),
+            None => format!("Reasoning back to thinking_budget ({} tokens)", agent.thinking_budget()),
+        }))
+    }
+
+    /// Debugging aids. `/debug messages` writes the primary agent's raw
+    /// message history to `.codey/messages.txt`.
+    pub fn debug(&mut self, args: &str) -> Result<Option<String>> {
+        match args {
+            "messages" => {
+                let agent_mutex = self.agents.primary().context("No primary agent")?;
+                let agent = agent_mutex
+                    .try_lock()
+                    .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+                let path = std::path::Path::new(CODEY_DIR).join(DEBUG_MESSAGES_FILENAME);
+                std::fs::create_dir_all(CODEY_DIR)?;
+                std::fs::write(&path, agent.dump_messages())
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(Some(format!(
+                    "Wrote {} messages to {}",
+                    agent.message_count(),
+                    path.display()
+                )))
+            },
+            _ => anyhow::bail!("Usage: /debug messages"),
+        }
+    }
+
+    /// Switch to a saved transcript, or list them when no number is given.
+    /// The primary agent's history is replaced with the loaded conversation.
+    pub fn resume(&mut self, args: &str) -> Result<Option<String>> {
+        let current = self.chat.transcript.path().map(|p| p.to_path_buf());
+        if args.is_empty() {
+            let transcripts = Transcript::list(RESUME_LIST_LIMIT).context("Failed to list transcripts")?;
+            let mut out = String::from("Saved transcripts (resume with /resume <number>):");
+            for info in transcripts {
+                let modified = info
+                    .modified
+                    .map(|m| m.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let marker = if current.as_deref() == Some(info.path.as_path()) {
+                    " (current)"
+                } else {
+                    ""
+                };
+                out.push_str(&format!(
+                    "\n  {:>4}  {}  {}{}",
+                    info.number,
+                    modified,
+                    info.title.as_deref().unwrap_or("(untitled)"),
+                    marker
+                ));
+            }
+            return Ok(Some(out));
+        }
+
+        if self.input_mode != InputMode::Normal {
+            anyhow::bail!("Can't resume while the agent is working");
+        }
+        let number: u32 = args
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Usage: /resume <number> (or /resume to list)"))?;
+        let mut transcript = Transcript::load_numbered(number)
+            .with_context(|| format!("Failed to load transcript {}", number))?;
+        if transcript.path().map(|p| p.to_path_buf()) == current {
+            return Ok(Some(format!("Already in transcript {}", number)));
+        }
+        transcript.set_tool_output_limit(self.config.general.transcript_tool_output_limit);
+        transcript.set_thinking_limit(self.config.general.transcript_thinking_limit);
+        transcript.set_param_limit(self.config.general.param_limit());
+        let title = transcript.title();
+
+        let agent_mutex = self.agents.primary().context("No primary agent")?;
+        let mut agent = agent_mutex
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+        if let Err(e) = self.chat.transcript.save() {
+            tracing::error!("Failed to save transcript before resuming: {}", e);
+        }
+        agent.restore_from_transcript(&transcript);
+        let messages = agent.message_count();
+        drop(agent);
+        self.reads.clear();
+
+        self.chat.reset_transcript(transcript, &mut self.terminal);
+        Ok(Some(format!(
+            "Resumed transcript {}{} ({} messages)",
+            number,
+            title.map(|t| format!(": {}", t)).unwrap_or_default(),
+            messages
+        )))
+    }
+
+    /// Save the current transcript and start an empty, numbered one, as if
+    /// codey had been relaunched. The primary agent's history is cleared; the
+    /// old session stays available to `/resume`.
+    pub fn new_session(&mut self) -> Result<Option<String>> {
+        if self.input_mode != InputMode::Normal {
+            anyhow::bail!("Can't start a new session while the agent is working");
+        }
+        let agent_mutex = self.agents.primary().context("No primary agent")?;
+        let mut agent = agent_mutex
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Agent is busy"))?;
+
+        // Saved first, so the new transcript is numbered after it
+        self.chat
+            .transcript
+            .save()
+            .context("Failed to save the current transcript")?;
+        let mut transcript = Transcript::new_numbered().context("Failed to create new transcript")?;
+        transcript.set_tool_output_limit(self.config.general.transcript_tool_output_limit);
+        transcript.set_thinking_limit(self.config.general.transcript_thinking_limit);
+        transcript.set_param_limit(self.config.general.param_limit());
+        agent.restore_from_transcript(&transcript);
+        drop(agent);
+
+        let previous = self.chat.transcript.path().map(|p| p.display().to_string());
+        self.plan_mode = false;
+        self.compaction_paused = false;
+        self.artifacts = Artifacts::new();
+        self.reads.clear();
+        // The notes refer to the old conversation, so they go with it
+        self.clear_annotations_requested = self.ide_annotated;
+        self.chat.reset_transcript(transcript, &mut self.terminal);
+        Ok(Some(match previous {
+            Some(path) => format!("Started a new session; the previous one is saved at {}", path),
+            None => "Started a new session".to_string(),
+        }))
+    }
+
+    /// Set the transcript's title, or show it when no title is given
+    pub fn set_title(&mut self, title: &str) -> Result<Option<String>> {
+        if title.is_empty() {
+            return Ok(Some(match self.chat.transcript.title() {
+                Some(title) => format!("Title: {} (change with /title <text>)", title),
+                None => "No title yet (set one with /title <text>)".to_string(),
+            }));
+        }
+        self.chat.transcript.set_title(title);
+        self.chat.transcript.save().context("Failed to save transcript")?;
+        Ok(Some(format!("Title set to: {}", title)))
+    }
+
+    /// Switch the tool filter preset, or show the current one when no name is
+    /// given. `/preset none` goes back to the per-tool rules alone.
+    pub fn set_filter_preset(&mut self, name: &str) -> Result<Option<String>> {
+        if name.is_empty() {
+            let available = self.config.tools.preset_names().join(", ");
+            return Ok(Some(match &self.filter_preset {
+                Some(current) => format!("Filter preset: {} (available: {}, none)", current, available),
+                None => format!("No filter preset (available: {})", available),
+            }));
+        }
+
+        let preset = (name != "none").then(|| name.to_string());
+        self.tool_filters = self
+            .config
+            .tools
+            .compile_filters(preset.as_deref())
+            .context("Failed to compile tool filters")?;
+        self.filter_preset = preset;
+        Ok(Some(match &self.filter_preset {
+            Some(name) => format!("Filter preset: {}", name),
+            None => "Filter preset cleared".to_string(),
+        }))
+    }
+
+    /// Turn IDE integration on or off for this session, or show whether it is
+    /// connected. Connecting happens after the command returns (`connect_ide`).
+    pub fn set_ide(&mut self, arg: &str) -> Result<Option<String>> {
+        match (arg, &self.ide) {
+            ("", Some(ide)) => Ok(Some(format!("IDE: connected to {} (/ide off to disconnect)", ide.name()))),
+            ("", None) => Ok(Some("IDE: not connected (/ide on to connect)".to_string())),
+            ("on", Some(ide)) => Ok(Some(format!("Already connected to {}", ide.name()))),
+            ("on", None) => {
+                self.ide_connect_requested = true;
+                Ok(None)
+            },
+            ("off", _) => {
+                self.ide_timeouts = 0;
+                Ok(Some(match self.ide.take() {
+                    Some(ide) => format!("Disconnected from {}; previews and buffer reloads are off", ide.name()),
+                    None => "IDE integration is already off".to_string(),
+                }))
+            },
+            (other, _) => anyhow::bail!("Unknown argument '{}' (expected on or off)", other),
+        }
+    }
+
+    /// Turn vi-style modal input on or off, for `/vi`; no argument toggles it
+    pub fn set_vi_mode(&mut self, arg: &str) -> Result<Option<String>> {
+        let enable = match arg {
+            "" => self.vi == ViMode::Off,
+            "on" => true,
+            "off" => false,
+            other => anyhow::bail!("Unknown argument '{}' (expected on or off)", other),
+        };
+        self.vi = if enable { ViMode::Insert } else { ViMode::Off };
+        Ok(Some(if enable {
+            "Vi mode on: Esc switches to normal mode, i/a/I/A back to insert".to_string()
+        } else {
+            "Vi mode off".to_string()
+        }))
+    }
+
+    /// Run IDE discovery again, for `/ide on`
+    async fn connect_ide(&mut self) {
+        self.ide = discover_ide(&self.config).await;
+        self.ide_timeouts = 0;
+        self.alert = Some(match &self.ide {
+            Some(ide) => format!("Connected to {}", ide.name()),
+            None => "No editor found to connect to (see /tmp/codey.log)".to_string(),
+        });
+    }
+
+    /// Clear the agent's editor notes, for `/annotations`
+    pub fn request_clear_annotations(&mut self) -> Result<Option<String>> {
+        if !self.ide_annotated {
+            return Ok(Some("No notes to clear".to_string()));
+        }
+        self.clear_annotations_requested = true;
+        Ok(None)
+    }
+
+    /// Remove the notes the agent left in the editor, if there are any
+    async fn clear_annotations(&mut self) {
+        if !std::mem::take(&mut self.ide_annotated) {
+            return;
+        }
+        if let Some(ide) = &self.ide {
+            if let Err(e) = ide.clear_annotations().await {
+                tracing::warn!("Failed to clear IDE annotations: {}", e);
+            }
+        }
+    }
+
+    /// Text of the most recent assistant turn, if any
+    pub fn last_assistant_text(&self) -> Option<String> {
+        self.chat.transcript.last_assistant_text()
+    }
+
+    /// Duration and active/idle breakdown of the current session
+    pub fn session_stats(&self) -> SessionStats {
+        self.chat.transcript.stats()
+    }
+
+    /// Pin a file into context, or list pinned files when no path is given
+    pub fn pin(&mut self, path: &str) -> Result<Option<String>> {
+        if path.is_empty() {
+            let paths = self.pinned.paths();
+            if paths.is_empty() {
+                return Ok(Some("No pinned files (usage: /pin <file>)".to_string()));
+            }
+            let list: Vec<String> = paths.iter().map(|p| format!("  {}", p.display())).collect();
+            return Ok(Some(format!("Pinned files:\n{}", list.join("\n"))));
+        }
+
+        let path = std::path::Path::new(path);
+        if !self.pinned.pin(path)? {
+            return Ok(Some(format!("{} is already pinned", path.display())));
+        }
+        let total = self.pinned.total_bytes();
+        let mut message = format!("Pinned {}; its contents are included with every request", path.display());
+        if total > self.pinned.max_bytes() {
+            message.push_str(&format!(
+                "\nWarning: pinned files total {} bytes, over the {} byte limit; files past the limit are listed without their contents",
+                total,
+                self.pinned.max_bytes()
+            ));
+        }
+        Ok(Some(message))
+    }
+
+    /// Unpin a file, so its contents are no longer added to requests
+    pub fn unpin(&mut self, path: &str) -> Result<Option<String>> {
+        if path.is_empty() {
+            anyhow::bail!("Usage: /unpin <file>");
+        }
+        let path = std::path::Path::new(path);
+        if !self.pinned.unpin(path) {
+            anyhow::bail!("{} is not pinned", path.display());
+        }
+        Ok(Some(format!("Unpinned {}", path.display())))
+    }
+
+    /// List session artifacts, or attach or open one by number
+    pub fn artifacts_command(&mut self, args: &str) -> Result<Option<String>> {
+        let mut args = args.split_whitespace();
+        let action = args.next();
+        if action.is_none() {
+            if self.artifacts.is_empty() {
+                return Ok(Some("No artifacts yet".to_string()));
+            }
+            return Ok(Some(format!("Artifacts:\n{}", self.artifacts.list())));
+        }
+
+        let usage = "Usage: /artifacts [attach|open <n>]";
+        let number = args
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .context(usage)?;
+        let artifact = self
+            .artifacts
+            .get(number)
+            .with_context(|| format!("No artifact {} (/artifacts lists them)", number))?
+            .clone();
+        if !artifact.path.is_file() {
+            anyhow::bail!("{} no longer exists", artifact.path.display());
+        }
+
+        match action {
+            Some("attach") => {
+                // Text is inlined (within the paste limit); anything else is
+                // referenced by path
+                let content = std::fs::read(&artifact.path)
+                    .ok()
+                    .filter(|bytes| bytes.len() <= ARTIFACT_INLINE_MAX_BYTES)
+                    .and_then(|bytes| String::from_utf8(bytes).ok());
+                self.input.add_attachment(Attachment::artifact(
+                    artifact.path.display().to_string(),
+                    artifact.kind,
+                    content,
+                ));
+                Ok(None)
+            },
+            Some("open") => {
+                open::that_detached(&artifact.path)?;
+                Ok(Some(format!("Opened {}", artifact.path.display())))
+            },
+            _ => anyhow::bail!(usage),
+        }
+    }
+
+    /// Set a session variable (`/set name=value`), or list them when no
+    /// assignment is given
+    pub fn set_variable(&mut self, args: &str) -> Result<Option<String>> {
+        if args.is_empty() {
+            return self.list_variables();
+        }
+        let Some((name, value)) = args.split_once('=') else {
+            anyhow::bail!("Usage: /set <name>=<value>");
+        };
+        let (name, value) = (name.trim(), value.trim());
+        match self.variables.set(name, value)? {
+            Some(old) => Ok(Some(format!("Set {} = {} (was {})", name, value, old))),
+            None => Ok(Some(format!("Set {} = {}", name, value))),
+        }
+    }
+
+    pub fn unset_variable(&mut self, name: &str) -> Result<Option<String>> {
+        if name.is_empty() {
+            anyhow::bail!("Usage: /unset <name>");
+        }
+        if self.variables.unset(name).is_none() {
+            anyhow::bail!("{} is not set", name);
+        }
+        Ok(Some(format!("Unset {}", name)))
+    }
+
+    pub fn list_variables(&self) -> Result<Option<String>> {
+        let vars = self.variables.all();
+        if vars.is_empty() {
+            return Ok(Some("No session variables (usage: /set <name>=<value>)".to_string()));
+        }
+        let list: Vec<String> = vars
+            .iter()
+            .map(|(name, value)| format!("  {} = {}", name, value))
+            .collect();
+        Ok(Some(format!("Session variables:\n{}", list.join("\n"))))
+    }
+
+    /// Put the newest backup of a file back in place.
+    /// The current contents are backed up first, so a restore can be undone.
+    pub fn restore_backup(&mut self, path: &str) -> Result<Option<String>> {
+        if path.is_empty() {
+            anyhow::bail!("Usage: /restore <file>");
+        }
+        let path = std::path::Path::new(path);
+        let backup = backups(&self.config).restore(path)?;
+        let name = backup.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(Some(format!("Restored {} from {}", path.display(), name)))
+    }
+
+    /// Enter plan mode: the primary agent can explore but not modify files.
+    pub fn enter_plan_mode(&mut self) -> Result<Option<String>> {
+        if self.plan_mode {
+            return Ok(Some("Already in plan mode. Use /execute to approve the plan.".to_string()));
+        }
+        self.plan_mode = true;
+
+        Ok(Some(
+            "Plan mode: file modifications are blocked until you approve the plan with /execute."
+                .to_string(),
+        ))
+    }
+
+    /// Approve the plan: return to normal requests and tell the agent to proceed.
+    pub fn approve_plan(&mut self) -> Result<Option<String>> {
+        if !self.plan_mode {
+            anyhow::bail!("Not in plan mode");
+        }
+        self.plan_mode = false;
+
+        self.queue_message(PLAN_APPROVED_PROMPT.to_string());
+        Ok(None)
+    }
+
+    /// Have the agent explore the project and write a CODEY.md describing it.
+    /// An existing one is only revised when asked with `/init update`.
+    pub fn init_project(&mut self, args: &str) -> Result<Option<String>> {
+        let update = match args {
+            "" => false,
+            "update" => true,
+            _ => anyhow::bail!("Usage: /init [update]"),
+        };
+        let exists = std::path::Path::new(PROJECT_BANNER_FILENAME).exists();
+        if exists && !update {
+            return Ok(Some(format!(
+                "{} already exists; /init update has the agent revise it",
+                PROJECT_BANNER_FILENAME
+            )));
+        }
+        if self.plan_mode {
+            anyhow::bail!("/init writes {}, which plan mode blocks; /execute first", PROJECT_BANNER_FILENAME);
+        }
+
+        self.queue_message(init_prompt(exists));
+        Ok(None)
+    }
+
+    /// The approval prompt went unanswered for `tools.approval_timeout_secs`:
+    /// apply the configured timeout action
+    async fn approval_timed_out(&mut self) {
+        let decision = self.config.tools.approval_timeout_action.decision();
+        tracing::warn!(
+            "Approval timed out after {}s, applying {:?}",
+            self.config.tools.approval_timeout_secs.unwrap_or_default(),
+            decision
+        );
+        self.decide_pending_tool(decision, ApprovalSource::Timeout).await;
+        self.draw();
+    }
+
     /// Execute a tool decision (approve/deny) for the currently active tool
-    async fn decide_pending_tool(&mut self, decision: ToolDecision) {
-        tracing::debug!("decide_pending_tool: decision={:?}", decision);
+    async fn decide_pending_tool(&mut self, decision: ToolDecision, source: ApprovalSource) {
+        tracing::debug!("decide_pending_tool: decision={:?} source={:?}", decision, source);
+        self.approval_deadline = None;
 
         // Take the currently active approval
         let pending = match self.effects.take_active_approval() {
@@ -568,6 +1474,9 @@ impl App {
             },
         };
 
+        self.tool_executor
+            .record_decision(&pending.call_id, decision, source);
+
         // Find block in stage, update status, and promote to transcript
         if let Some(mut block) = self.chat.transcript.stage.remove_by_call_id(&pending.call_id) {
             block.set_status(match decision {
@@ -581,6 +1490,11 @@ impl App {
         // Convert decision to EffectResult and send to executor
         let result: EffectResult = match decision {
             ToolDecision::Approve => Ok(None),
+            ToolDecision::Deny if source == ApprovalSource::Timeout => Err(format!(
+                "Denied: not approved within {}s",
+                self.config.tools.approval_timeout_secs.unwrap_or_default()
+            )),
+            ToolDecision::Deny if source == ApprovalSource::UnknownTool => Err("Denied: unknown tool".to_string()),
             ToolDecision::Deny => Err("Denied by user".to_string()),
             _ => Err("Unexpected decision".to_string()),
         };
@@ -599,6 +1513,90 @@ impl App {
         self.chat.render(&mut self.terminal);
         self.draw();
     }
+
+    /// Open the pending tool call's params in the input box as JSON, to be
+    /// edited before approving. Whatever was typed is set aside until the
+    /// edit is submitted or cancelled.
+    fn edit_tool_params(&mut self) {
+        let Some(Effect::AwaitApproval { params, .. }) = self.effects.active_approval().map(|p| &p.effect) else {
+            return;
+        };
+        let json = serde_json::to_string_pretty(params).unwrap_or_else(|_| params.to_string());
+        self.param_draft = Some(self.input.take_draft());
+        self.input.set_content(&json);
+        self.input_mode = InputMode::EditParams;
+    }
+
+    /// Leave param editing unchanged and go back to the approval prompt
+    fn cancel_param_edit(&mut self) {
+        if let Some(draft) = self.param_draft.take() {
+            self.input.restore_draft(draft);
+        }
+        self.input_mode = InputMode::ToolApproval;
+    }
+
+    /// Approve the pending tool call with the params in the input box
+    async fn submit_param_edit(&mut self) {
+        let params = match serde_json::from_str::<serde_json::Value>(&self.input.content()) {
+            Ok(params) if params.is_object() => params,
+            Ok(_) => {
+                self.alert = Some("Tool params must be a JSON object".to_string());
+                return;
+            },
+            Err(e) => {
+                self.alert = Some(format!("Invalid JSON: {}", e));
+                return;
+            },
+        };
+        let Some(pending) = self.effects.take_active_approval() else {
+            self.cancel_param_edit();
+            return;
+        };
+        let (name, background) = match &pending.effect {
+            Effect::AwaitApproval { name, background, .. } => (name.clone(), *background),
+            _ => return,
+        };
+        let call_id = pending.call_id.clone();
+        if !self.tool_executor.edit_params(&call_id, params.clone()) {
+            self.effects.requeue(pending);
+            self.cancel_param_edit();
+            self.alert = Some("The tool call is no longer waiting for approval".to_string());
+            return;
+        }
+        // The executor no longer waits on this approval's responder
+        drop(pending);
+        self.approval_deadline = None;
+        self.tool_executor
+            .record_decision(&call_id, ToolDecision::Approve, ApprovalSource::Manual);
+
+        // Show the call with the params it runs with
+        if let Some(staged) = self.chat.transcript.stage.remove_by_call_id(&call_id) {
+            let mut block = match self.tool_executor.tool_for_call(&call_id, &name) {
+                Some(tool) => {
+                    let mut block = tool.create_block(&call_id, params, background);
+                    if let Some(label) = staged.agent_label() {
+                        block.set_agent_label(label.to_string());
+                    }
+                    block
+                },
+                None => staged,
+            };
+            block.set_status(Status::Running);
+            self.chat.transcript.start_block(block);
+        }
+
+        if let Some(draft) = self.param_draft.take() {
+            self.input.restore_draft(draft);
+        }
+        self.input_mode = if self.effects.has_active_approval() {
+            InputMode::ToolApproval
+        } else {
+            InputMode::Streaming
+        };
+        self.chat.render(&mut self.terminal);
+        self.draw();
+    }
+
     /// Handle an action. Returns the result indicating what the main loop should do.
     async fn handle_action(&mut self, action: Action) -> ActionResult {
         // Clear alert on any input action
@@ -607,37 +1605,115 @@ impl App {
         if !matches!(action, Action::InsertChar(_)) {
             tracing::debug!("Action received: {:?}", action);
         }
+        // A pending vi operator only applies to the very next key
+        if let ViMode::Normal { pending } = &mut self.vi {
+            *pending = None;
+        }
         match action {
             Action::Interrupt => {
                 return ActionResult::Interrupt;
             },
             Action::Quit => {
-                self.should_quit = true;
-                return ActionResult::Interrupt;
+                let confirmed = self
+                    .quit_armed
+                    .is_some_and(|armed| armed.elapsed() < QUIT_CONFIRM_WINDOW);
+                if confirmed || !self.config.ui.confirm_quit {
+                    self.should_quit = true;
+                    return ActionResult::Interrupt;
+                }
+                // First press: stop whatever is in flight and ask for a second
+                self.quit_armed = Some(Instant::now());
+                self.alert = Some("Press Ctrl+C again to quit".to_string());
+                self.draw();
+                if self.input_mode != InputMode::Normal {
+                    return ActionResult::Interrupt;
+                }
             },
             Action::ApproveTool => {
-                self.decide_pending_tool(ToolDecision::Approve).await;
+                self.decide_pending_tool(ToolDecision::Approve, ApprovalSource::Manual).await;
             },
             Action::DenyTool => {
-                self.decide_pending_tool(ToolDecision::Deny).await;
+                self.decide_pending_tool(ToolDecision::Deny, ApprovalSource::Manual).await;
+            },
+            Action::EditToolParams => self.edit_tool_params(),
+            Action::CancelParamEdit => self.cancel_param_edit(),
+            Action::ToggleToolParams => {
+                if let Some(block) = self.chat.transcript.last_tool_block_mut() {
+                    block.toggle_expanded();
+                }
             },
             Action::InsertChar(c) => self.input.insert_char(c),
             Action::InsertNewline => self.input.insert_newline(),
             Action::DeleteBack => self.input.delete_char(),
             Action::Paste(content) => {
-                self.input.add_attachment(Attachment::pasted(content));
+                // Short pastes stay editable; long ones collapse into a pill
+                // (except into tool params, which must stay plain JSON)
+                if self.input_mode != InputMode::EditParams
+                    && content.chars().count() > self.config.ui.paste_attach_threshold
+                {
+                    self.input.add_attachment(Attachment::pasted(content));
+                } else {
+                    self.input.insert_str(&content);
+                }
             },
             Action::CursorLeft => self.input.move_cursor_left(),
             Action::CursorRight => self.input.move_cursor_right(),
             Action::CursorHome => self.input.move_cursor_start(),
             Action::CursorEnd => self.input.move_cursor_end(),
+            Action::ViNormal => {
+                self.vi = ViMode::Normal { pending: None };
+                if self.chat.focused_link.take().is_some() {
+                    self.chat.render(&mut self.terminal);
+                }
+            },
+            Action::ViInsert(at) => {
+                match at {
+                    ViInsertAt::Cursor => {},
+                    ViInsertAt::After => self.input.move_cursor_right(),
+                    ViInsertAt::LineStart => self.input.move_line_start(),
+                    ViInsertAt::LineEnd => self.input.move_line_end(),
+                }
+                self.vi = ViMode::Insert;
+            },
+            Action::ViPending(op) => {
+                if let ViMode::Normal { pending } = &mut self.vi {
+                    *pending = op;
+                }
+            },
+            Action::WordForward => self.input.move_word_forward(),
+            Action::WordBack => self.input.move_word_back(),
+            Action::LineStart => self.input.move_line_start(),
+            Action::LineEnd => self.input.move_line_end(),
+            Action::DeleteChar => self.input.delete_char_forward(),
+            Action::DeleteLine => self.input.delete_line(),
+            Action::Submit if self.input_mode == InputMode::EditParams => {
+                self.submit_param_edit().await;
+            },
             Action::Submit => {
+                // Each new message starts out in insert mode
+                if self.vi != ViMode::Off {
+                    self.vi = ViMode::Insert;
+                }
                 let content = self.input.submit();
                 if !content.trim().is_empty() {
                     self.queue_message(content);
                 }
             },
-            Action::ClearInput => self.input.clear(),
+            Action::ClearInput => {
+                self.input.clear();
+                if self.chat.focused_link.take().is_some() {
+                    self.chat.render(&mut self.terminal);
+                }
+            },
+            Action::FocusLink => self.focus_next_link(),
+            Action::OpenLink => match self.chat.focused_link.clone() {
+                Some(link) => {
+                    if let Err(e) = self.open_link(&link).await {
+                        self.alert = Some(format!("Failed to open {}: {}", link.label(), e));
+                    }
+                },
+                None => self.alert = Some("No link focused (Ctrl+L to pick one)".to_string()),
+            },
             Action::HistoryPrev => {
                 self.input.history_prev();
             },
@@ -660,6 +1736,49 @@ impl App {
         ActionResult::Continue
     }
 
+    /// Move link focus to the next older link in the transcript, wrapping
+    /// around to the newest
+    fn focus_next_link(&mut self) {
+        let links = transcript_links(&self.chat.transcript);
+        if links.is_empty() {
+            self.alert = Some("No file paths or URLs in the transcript".to_string());
+            return;
+        }
+        let next = self
+            .chat
+            .focused_link
+            .as_ref()
+            .and_then(|focused| links.iter().position(|link| link == focused))
+            .map_or(0, |i| (i + 1) % links.len());
+        self.alert = Some(format!(
+            "Link {}/{}: {} (Ctrl+G to open, Esc to clear)",
+            next + 1,
+            links.len(),
+            links[next].label()
+        ));
+        self.chat.focused_link = Some(links[next].clone());
+        self.chat.render(&mut self.terminal);
+    }
+
+    /// Open a file in the IDE (or the system's default app without one), or a
+    /// URL in the browser
+    async fn open_link(&mut self, link: &Link) -> Result<()> {
+        match link {
+            Link::Url(url) => open::that_detached(url)?,
+            Link::File { path, line, column } => {
+                if !path.exists() {
+                    anyhow::bail!("no such file");
+                }
+                match &self.ide {
+                    Some(ide) => ide.navigate_to(&path.to_string_lossy(), *line, *column).await?,
+                    None => open::that_detached(path)?,
+                }
+            },
+        }
+        self.alert = Some(format!("Opened {}", link.label()));
+        Ok(())
+    }
+
     /// Handle a terminal event
     async fn handle_term_event(&mut self, event: std::io::Result<Event>) -> Result<()> {
         let event = match event {
@@ -670,7 +1789,7 @@ impl App {
             },
         };
 
-        let Some(action) = map_event(self.input_mode, event) else {
+        let Some(action) = map_event(self.input_mode, self.vi, event) else {
             return Ok(());
         };
 
@@ -714,7 +1833,7 @@ impl App {
 
         // Separate by type: messages get batched, commands execute individually
         let mut messages: Vec<(String, usize)> = Vec::new();  // (content, block_id)
-        let mut commands: Vec<(String, usize)> = Vec::new();  // (name, block_id)
+        let mut commands: Vec<(String, String, usize)> = Vec::new();  // (name, args, block_id)
         let mut background_tools: Vec<(String, String, usize)> = Vec::new();  // (label, result, block_id)
         let mut background_agents: Vec<(String, String, usize)> = Vec::new();  // (label, result, block_id)
         let mut has_compaction = false;
@@ -724,8 +1843,8 @@ impl App {
                 Notification::Message { content, block_id } => {
                     messages.push((content, block_id));
                 }
-                Notification::Command { name, block_id } => {
-                    commands.push((name, block_id));
+                Notification::Command { name, args, block_id } => {
+                    commands.push((name, args, block_id));
                 }
                 Notification::BackgroundTool { label, result, block_id } => {
                     background_tools.push((label, result, block_id));
@@ -748,7 +1867,7 @@ impl App {
         }
 
         // Process commands first (they don't send to agent, execute locally)
-        for (name, block_id) in commands {
+        for (name, args, block_id) in commands {
             // Promote block from stage to transcript
             if let Some(mut block) = self.chat.transcript.stage.remove(block_id) {
                 block.set_status(Status::Complete);
@@ -759,7 +1878,7 @@ impl App {
             }
 
             if let Some(command) = Command::get(&name) {
-                match command.execute(self) {
+                match command.execute(self, &args) {
                     Ok(None) => {
                         self.chat.render(&mut self.terminal);
                         self.draw();
@@ -777,6 +1896,22 @@ impl App {
                         self.alert = Some(format!("Command error: {}", e));
                     }
                 }
+            } else {
+                let output = format!("Unknown command: /{}\n\n{}", name, Command::help_text());
+                let idx = self.chat.transcript.add_empty(Role::Assistant);
+                if let Some(turn) = self.chat.transcript.get_mut(idx) {
+                    turn.start_block(Box::new(TextBlock::complete(&output)));
+                }
+                self.chat.render(&mut self.terminal);
+                self.draw();
+            }
+
+            // `/ide on` discovers the editor here, since commands run synchronously
+            if std::mem::take(&mut self.ide_connect_requested) {
+                self.connect_ide().await;
+            }
+            if std::mem::take(&mut self.clear_annotations_requested) {
+                self.clear_annotations().await;
             }
         }
 
@@ -795,6 +1930,30 @@ impl App {
             }
             combined_content.push_str(content);
         }
+        // Notes from the previous turn are stale once the user replies
+        if !messages.is_empty() {
+            self.clear_annotations().await;
+        }
+        // The first message of a session tells the agent what the user has open
+        if !messages.is_empty() && !self.open_files_shared {
+            self.open_files_shared = true;
+            if let Some(ide) = &self.ide {
+                match ide.open_files().await {
+                    Ok(files) if !files.is_empty() => {
+                        combined_content.push_str(&format!(
+                            "\n\n<open-files>\nFiles the user has open in their editor:\n{}\n</open-files>",
+                            format_open_files(&files)
+                        ));
+                    },
+                    Ok(_) => {},
+                    Err(e) => tracing::warn!("Failed to list open files: {}", e),
+                }
+            }
+        }
+        if self.plan_mode && !messages.is_empty() {
+            combined_content.push_str("\n\n");
+            combined_content.push_str(PLAN_MODE_PROMPT);
+        }
 
         for (label, result, block_id) in &background_tools {
             // Collect block for promotion
@@ -837,24 +1996,32 @@ impl App {
             self.chat.render(&mut self.terminal);
             self.draw();
 
+            let mode = if self.plan_mode {
+                RequestMode::Plan
+            } else {
+                RequestMode::Normal
+            };
             if let Some(agent_mutex) = self.agents.primary() {
                 agent_mutex
                     .lock()
                     .await
-                    .send_request(&combined_content, RequestMode::Normal);
+                    .send_request(&combined_content, mode);
             }
             self.chat.begin_turn(Role::Assistant, &mut self.terminal);
             self.input_mode = InputMode::Streaming;
+            self.awaiting_response = true;
         } else if has_compaction {
             // Handle compaction only if no messages (compaction gets its own request)
+            let prompt = format!("{}{}", self.compaction_prompt, sources_request(&self.chat.transcript));
             if let Some(agent_mutex) = self.agents.primary() {
                 agent_mutex
                     .lock()
                     .await
-                    .send_request(COMPACTION_PROMPT, RequestMode::Compaction);
+                    .send_request(&prompt, RequestMode::Compaction);
             }
             self.chat.begin_turn(Role::Assistant, &mut self.terminal);
             self.input_mode = InputMode::Streaming;
+            self.awaiting_response = true;
         }
 
         self.chat.render(&mut self.terminal);
@@ -919,6 +2086,14 @@ impl App {
                     block.set_status(Status::Complete);
                 }
 
+                // A follow-up command reports back to the tool that ran it
+                if let Some(responder) = self.followups.remove(&call_id) {
+                    let _ = responder.send(Ok(Some(content)));
+                    self.chat.render(&mut self.terminal);
+                    self.draw();
+                    return Ok(());
+                }
+
                 // Drain injectable notifications and append to content
                 let injectable = self.notifications.drain_injectable();
                 let content = if !injectable.is_empty() {
@@ -958,6 +2133,9 @@ impl App {
                         .await
                         .submit_tool_result(&call_id, content);
                 }
+                if self.agents.primary_id() == Some(agent_id) {
+                    self.awaiting_response = true;
+                }
 
                 // Tool is done - only switch to streaming if no more approvals pending
                 if !self.effects.has_pending_approvals() {
@@ -980,6 +2158,13 @@ impl App {
                     block.set_status(Status::Error);
                 }
 
+                if let Some(responder) = self.followups.remove(&call_id) {
+                    let _ = responder.send(Err(content));
+                    self.chat.render(&mut self.terminal);
+                    self.draw();
+                    return Ok(());
+                }
+
                 // Tell agent about the error - route to the correct agent by ID
                 if let Some(agent_mutex) = self.agents.get(agent_id) {
                     agent_mutex
@@ -987,6 +2172,9 @@ impl App {
                         .await
                         .submit_tool_result(&call_id, content);
                 }
+                if self.agents.primary_id() == Some(agent_id) {
+                    self.awaiting_response = true;
+                }
 
                 // Tool is done - only switch to streaming if no more approvals pending
                 if !self.effects.has_pending_approvals() {
@@ -1057,6 +2245,14 @@ impl App {
     /// Handle a single agent step during streaming
     async fn handle_agent_step(&mut self, agent_id: AgentId, step: AgentStep) -> Result<()> {
         let is_primary = self.agents.primary_id() == Some(agent_id);
+        if is_primary
+            && !matches!(
+                step,
+                AgentStep::Retrying { .. } | AgentStep::FallbackModel { .. } | AgentStep::ContextWarning { .. }
+            )
+        {
+            self.awaiting_response = false;
+        }
 
         match step {
             AgentStep::TextDelta(text) => {
@@ -1089,14 +2285,34 @@ impl App {
                     .collect();
                 self.tool_executor.enqueue(tool_calls);
             },
-            AgentStep::Retrying { attempt, error } => {
-                let delay_secs = 2u64.pow(attempt);
-                self.alert = Some(format!(
-                    "Request failed (attempt {}): {}. Retrying in {}s...",
-                    attempt, error, delay_secs
-                ));
+            AgentStep::Retrying { attempt, error, delay_secs, overloaded } => {
+                self.alert = Some(if overloaded {
+                    format!("API overloaded (attempt {}). Waiting {}s before retrying...", attempt, delay_secs)
+                } else {
+                    format!("Request failed (attempt {}): {}. Retrying in {}s...", attempt, error, delay_secs)
+                });
                 tracing::warn!("Retrying request: attempt {}, error: {}, backoff: {}s", attempt, error, delay_secs);
             },
+            AgentStep::FallbackModel { from, to, error } => {
+                self.alert = Some(format!("{} failed: {}. Falling back to {}...", from, error, to));
+                tracing::warn!("Falling back from {} to {}: {}", from, to, error);
+            },
+            AgentStep::Continuing { continuation, max } => {
+                if is_primary {
+                    self.awaiting_response = true;
+                    self.alert = Some(format!(
+                        "Response hit max_tokens, continuing ({}/{})...",
+                        continuation, max
+                    ));
+                }
+            },
+            AgentStep::ContextWarning { level, context_tokens, threshold } => {
+                // The token counter in the input box changes color; this is just for the log
+                tracing::info!(
+                    "Agent {} context at {:?}: {} of {} tokens before compaction",
+                    agent_id, level, context_tokens, threshold
+                );
+            },
             AgentStep::Finished { usage } => {
                 if is_primary {
                     self.input_mode = InputMode::Normal;
@@ -1108,6 +2324,7 @@ impl App {
                         .transcript
                         .is_streaming_block_type(BlockType::Compaction)
                     {
+                        self.reads.clear();
                         self.chat.transcript.finish_turn();
                         if let Err(e) = self.chat.transcript.save() {
                             tracing::error!("Failed to save transcript before compaction: {}", e);
@@ -1128,14 +2345,33 @@ impl App {
                         }
                     } else {
                         // Normal completion
+                        if self.config.general.show_per_turn_cost {
+                            if let Some(cost) = self.turn_cost().await {
+                                self.chat.transcript.set_turn_cost(cost);
+                            }
+                        }
                         self.chat.transcript.finish_turn();
                         if let Err(e) = self.chat.transcript.save() {
                             tracing::error!("Failed to save transcript: {}", e);
                         }
 
-                        // Check if compaction is needed
-                        if usage.context_tokens >= self.config.general.compaction_threshold {
+                        // Check if compaction is needed, trying the lighter
+                        // tool-result compaction first if it's configured
+                        let general = &self.config.general;
+                        if self.compaction_paused {
+                            if usage.context_tokens >= general.compaction_threshold {
+                                self.alert = Some(format!(
+                                    "Context is {} tokens, over the compaction threshold ({}); compaction is paused (/nocompact off)",
+                                    usage.context_tokens, general.compaction_threshold
+                                ));
+                            }
+                        } else if usage.context_tokens >= general.compaction_threshold {
                             self.queue_compaction();
+                        } else if general
+                            .tool_result_compaction_threshold
+                            .is_some_and(|threshold| usage.context_tokens >= threshold)
+                        {
+                            self.compact_tool_results().await;
                         }
                     }
                 } else {
@@ -1171,22 +2407,17 @@ impl App {
                     self.draw();
                 }
             },
-            AgentStep::Error(msg) => {
+            AgentStep::Error(error) => {
                 self.chat.transcript.mark_active_block(Status::Error);
                 if let Err(e) = self.chat.transcript.save() {
                     tracing::error!("Failed to save transcript on error: {}", e);
                 }
                 self.input_mode = InputMode::Normal;
 
-                let alert_msg = if let Some(start) = msg.find('{') {
-                    serde_json::from_str::<serde_json::Value>(&msg[start..])
-                        .ok()
-                        .and_then(|json| json["error"]["message"].as_str().map(String::from))
-                        .unwrap_or_else(|| msg.clone())
-                } else {
-                    msg.clone()
-                };
-                self.alert = Some(alert_msg);
+                self.alert = Some(match error.guidance() {
+                    Some(guidance) => format!("{} ({})", error, guidance),
+                    None => error.to_string(),
+                });
             },
         }
 
@@ -1245,6 +2476,22 @@ impl App {
             return;
         }
 
+        // Commands run as their own shell call and answer when it finishes
+        if let Effect::RunCommand { command, working_dir } = pending.effect {
+            let mut params = serde_json::json!({ "command": command });
+            if let Some(dir) = working_dir {
+                params["working_dir"] = serde_json::json!(dir);
+            }
+            let call_id = self.tool_executor.start_followup(
+                pending.agent_id,
+                &pending.call_id,
+                names::SHELL,
+                params,
+            );
+            self.followups.insert(call_id, pending.responder);
+            return;
+        }
+
         // All other effects execute immediately
         let PendingEffect {
             agent_id,
@@ -1275,7 +2522,12 @@ impl App {
         };
 
         self.draw(); // flush any pending text
-        let tool = self.tool_executor.tools().get(name);
+        let Some(tool) = self.tool_executor.tool_for_call(call_id, name) else {
+            // Only registered tools ask for approval, but don't take the app down if not
+            tracing::warn!("Approval requested for unknown tool {}", name);
+            self.decide_pending_tool(ToolDecision::Deny, ApprovalSource::UnknownTool).await;
+            return;
+        };
         let mut block = tool.create_block(call_id, params.clone(), background);
 
         // Set agent label for sub-agent tools
@@ -1289,10 +2541,22 @@ impl App {
         self.chat.render(&mut self.terminal);
         self.draw();
 
-        // Check filters for auto-approve/deny
-        match self.tool_filters.evaluate(name, &params) {
-            Some(decision) => {
-                self.decide_pending_tool(decision).await;
+        // Plan mode blocks anything outside the read-only set, even if the
+        // model calls a tool it wasn't offered, and shell commands that
+        // could change something
+        let read_only = match name {
+            names::SHELL => params["command"].as_str().is_some_and(is_read_only_command),
+            _ => names::READ_ONLY.contains(&name),
+        };
+        if self.plan_mode && is_primary && !read_only {
+            tracing::info!("Plan mode: denying {}", name);
+            self.decide_pending_tool(ToolDecision::Deny, ApprovalSource::PlanMode).await;
+            return;
+        }
+
+        match configured_decision(&self.config, &self.tool_filters, tool.as_ref(), &params) {
+            Some((decision, source)) => {
+                self.decide_pending_tool(decision, source).await;
             },
             None => {
                 // Wait for user approval
@@ -1302,6 +2566,11 @@ impl App {
                     }
                 }
                 self.input_mode = InputMode::ToolApproval;
+                self.approval_deadline = self
+                    .config
+                    .tools
+                    .approval_timeout_secs
+                    .map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
                 self.draw();
             },
         }
@@ -1309,45 +2578,79 @@ impl App {
 
     /// Try to execute an IDE preview effect. Returns Ready if completed, Pending if slot not available.
     /// Only handles IdeShowPreview and IdeShowDiffPreview - all other effects go through apply_effect.
+    /// A preview the IDE doesn't show in time is skipped rather than blocking approval.
     async fn try_execute_effect(&mut self, effect: &Effect) -> EffectPoll {
-        match effect {
+        if !matches!(effect, Effect::IdeShowPreview { .. } | Effect::IdeShowDiffPreview { .. }) {
+            // All other effects should go through apply_effect, not here
+            return EffectPoll::Ready(Err(anyhow::anyhow!(
+                "Effect {:?} should not be polled - use apply_effect",
+                effect
+            )));
+        }
+        let timeout = Duration::from_millis(self.config.ide.timeout_ms);
+
+        let Some(ide) = &self.ide else {
+            return EffectPoll::Ready(Ok(None));
+        };
+        let claimed = tokio::time::timeout(timeout, ide.try_claim_preview()).await;
+        match self.check_ide_timeout("try_claim_preview", claimed) {
+            Some(Ok(true)) => {},
+            Some(Ok(false)) => return EffectPoll::Pending,
+            Some(Err(e)) => return EffectPoll::Ready(Err(e)),
+            None => return EffectPoll::Ready(Ok(None)),
+        }
+
+        let Some(ide) = &self.ide else {
+            return EffectPoll::Ready(Ok(None));
+        };
+        let shown = match effect {
             Effect::IdeShowPreview { preview } => {
-                if let Some(ide) = &self.ide {
-                    match ide.try_claim_preview().await {
-                        Ok(true) => match ide.show_preview(preview).await {
-                            Ok(_) => EffectPoll::Ready(Ok(None)),
-                            Err(e) => EffectPoll::Ready(Err(e)),
-                        },
-                        Ok(false) => EffectPoll::Pending,
-                        Err(e) => EffectPoll::Ready(Err(e)),
-                    }
-                } else {
-                    EffectPoll::Ready(Ok(None))
-                }
+                tokio::time::timeout(timeout, ide.show_preview(preview)).await
             },
-
             Effect::IdeShowDiffPreview { path, edits } => {
-                if let Some(ide) = &self.ide {
-                    match ide.try_claim_preview().await {
-                        Ok(true) => {
-                            match ide.show_diff_preview(&path.to_string_lossy(), edits).await {
-                                Ok(_) => EffectPoll::Ready(Ok(None)),
-                                Err(e) => EffectPoll::Ready(Err(e)),
-                            }
-                        },
-                        Ok(false) => EffectPoll::Pending,
-                        Err(e) => EffectPoll::Ready(Err(e)),
+                tokio::time::timeout(timeout, ide.show_diff_preview(&path.to_string_lossy(), edits))
+                    .await
+            },
+            _ => unreachable!("checked above"),
+        };
+        match self.check_ide_timeout("show_preview", shown) {
+            Some(result) => EffectPoll::Ready(result.map(|_| None)),
+            None => EffectPoll::Ready(Ok(None)),
+        }
+    }
+
+    /// Unwrap the result of a timed IDE call.
+    /// Returns None if the call timed out; after `IDE_MAX_TIMEOUTS` timeouts in a
+    /// row the IDE is disconnected so later calls can't stall the UI.
+    fn check_ide_timeout<T>(
+        &mut self,
+        operation: &str,
+        result: Result<Result<T>, tokio::time::error::Elapsed>,
+    ) -> Option<Result<T>> {
+        match result {
+            Ok(result) => {
+                self.ide_timeouts = 0;
+                Some(result)
+            },
+            Err(_) => {
+                self.ide_timeouts += 1;
+                tracing::warn!(
+                    "IDE {} timed out after {}ms ({} in a row)",
+                    operation,
+                    self.config.ide.timeout_ms,
+                    self.ide_timeouts
+                );
+                if self.ide_timeouts >= IDE_MAX_TIMEOUTS {
+                    if let Some(ide) = self.ide.take() {
+                        tracing::warn!("{} is not responding, disabling IDE integration", ide.name());
+                        self.alert = Some(format!(
+                            "{} is not responding; IDE integration disabled for this session",
+                            ide.name()
+                        ));
                     }
-                } else {
-                    EffectPoll::Ready(Ok(None))
                 }
+                None
             },
-
-            // All other effects should go through apply_effect, not here
-            _ => EffectPoll::Ready(Err(anyhow::anyhow!(
-                "Effect {:?} should not be polled - use apply_effect",
-                effect
-            ))),
         }
     }
 
@@ -1365,6 +2668,10 @@ impl App {
                     "IDE preview effects should be polled via try_execute_effect, not apply_effect"
                 )
             },
+            // Commands are started as follow-up shell calls, not here
+            Effect::RunCommand { .. } => {
+                unreachable!("RunCommand should be started via handle_pending_effect, not apply_effect")
+            },
             Effect::IdeReloadBuffer { path } => {
                 if let Some(ide) = &self.ide {
                     ide.reload_buffer(&path.to_string_lossy()).await?;
@@ -1380,7 +2687,11 @@ impl App {
             },
             Effect::IdeClosePreview => {
                 if let Some(ide) = &self.ide {
-                    ide.close_preview().await?;
+                    let timeout = Duration::from_millis(self.config.ide.timeout_ms);
+                    let closed = tokio::time::timeout(timeout, ide.close_preview()).await;
+                    if let Some(result) = self.check_ide_timeout("close_preview", closed) {
+                        result?;
+                    }
                 }
                 Ok(None)
             },
@@ -1395,6 +2706,29 @@ impl App {
                 }
                 Ok(None)
             },
+            Effect::IdeOpenFiles => match &self.ide {
+                Some(ide) => {
+                    let files = ide.open_files().await?;
+                    if files.is_empty() {
+                        Ok(Some("No files open in the IDE".to_string()))
+                    } else {
+                        Ok(Some(format_open_files(&files)))
+                    }
+                },
+                None => Ok(Some("No IDE connected".to_string())),
+            },
+            Effect::IdeAnnotate { path, line, text } => match &self.ide {
+                Some(ide) => {
+                    ide.annotate(&path.to_string_lossy(), line, &text).await?;
+                    self.ide_annotated = true;
+                    Ok(None)
+                },
+                None => Ok(Some("No IDE connected; nothing was annotated".to_string())),
+            },
+            Effect::Artifact { path, kind } => {
+                self.artifacts.add(Artifact { path, kind });
+                Ok(None)
+            },
             Effect::ListBackgroundTasks => {
                 let tasks = self.tool_executor.list_tasks();
                 if tasks.is_empty() {
@@ -1519,5 +2853,191 @@ impl App {
 impl Drop for App {
     fn drop(&mut self) {
         let _ = self.restore_terminal();
+        self.artifacts.cleanup();
+    }
+}
+
+/// Format IDE file paths as a list, relative to the working directory where possible
+fn format_open_files(files: &[String]) -> String {
+    let cwd = std::env::current_dir().ok();
+    files
+        .iter()
+        .map(|file| {
+            let path = std::path::Path::new(file);
+            let relative = cwd
+                .as_deref()
+                .and_then(|cwd| path.strip_prefix(cwd).ok())
+                .unwrap_or(path);
+            format!("- {}", relative.display())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// What the agent has read this session: the `read_file` cache, and the reads
+/// `tools.require_read_before_edit` checks edits against. Clones share state.
+#[derive(Clone, Default)]
+pub struct SessionReads {
+    cache: Arc<Mutex<FileCache>>,
+    tracker: ReadTracker,
+}
+
+impl SessionReads {
+    /// Forget every read, once the agent's history no longer holds their results
+    pub fn clear(&self) {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.tracker.clear();
+    }
+}
+
+/// Build the tool executor: built-in tools plus any discovered on configured
+/// MCP servers, with the audit log if enabled. Custom tools read `variables`
+/// for placeholders the agent leaves out, and file tools share `reads`.
+/// Shared by the TUI and script mode.
+pub async fn tool_executor(
+    config: &Config,
+    variables: &SessionVariables,
+    reads: &SessionReads,
+) -> Result<ToolExecutor> {
+    let mut tools = ToolRegistry::new();
+    let mut shell = ShellTool::with_interactive_commands(&config.tools.interactive_commands)?
+        .with_max_output_bytes(config.tools.shell_max_output_bytes);
+    let sandbox = shell_sandbox(config);
+    if let Some(ref sandbox) = sandbox {
+        for warning in sandbox.warnings() {
+            tracing::warn!("{}", warning);
+        }
+        shell = shell.with_sandbox(sandbox.clone());
+    }
+    tools.register(Arc::new(shell));
+    let mut edit_file = EditFileTool::new().with_inline_diffs(config.ui.inline_diffs);
+    let mut replace_in_files = ReplaceInFilesTool::new();
+    if config.tools.backup_on_write {
+        edit_file = edit_file.with_backups(backups(config));
+        replace_in_files = replace_in_files.with_backups(backups(config));
     }
+    if config.tools.edit_file.fuzzy {
+        edit_file = edit_file.with_fuzzy_matching(FuzzyMatch {
+            collapse_whitespace: config.tools.edit_file.fuzzy_collapse_whitespace,
+        });
+    }
+    let mut read_file = ReadFileTool::new().with_cache(reads.cache.clone());
+    if config.tools.ssh.enabled {
+        read_file = read_file.with_ssh(Ssh {
+            options: config.tools.ssh.options.clone(),
+            timeout_secs: config.tools.ssh.timeout_secs,
+        });
+    }
+    if config.tools.require_read_before_edit {
+        read_file = read_file.with_read_tracker(reads.tracker.clone());
+        replace_in_files = replace_in_files.with_read_tracker(reads.tracker.clone());
+        edit_file = edit_file.with_read_tracker(reads.tracker.clone());
+    }
+    tools.register(Arc::new(edit_file));
+    tools.register(Arc::new(replace_in_files));
+    tools.register(Arc::new(read_file));
+    tools.register(Arc::new(
+        WebSearchTool::new().with_timeout(config.tools.web_search.timeout_secs),
+    ));
+    tools.register(Arc::new(
+        DownloadTool::new()
+            .with_max_bytes(config.tools.download.max_bytes)
+            .with_timeout(config.tools.download.timeout_secs)
+            .with_network_disabled(config.tools.shell.sandbox.enabled && config.tools.shell.sandbox.no_network),
+    ));
+    tools.register(Arc::new(FetchHtmlTool::with_browser(BrowserContext::from_config(
+        &config.browser,
+    ))));
+    // Only registered here, where the configured size bound is known
+    tools.register(Arc::new(
+        MemoryWriteTool::new().with_max_bytes(config.tools.memory.max_bytes),
+    ));
+    for tool in mcp::connect_servers(&config.mcp.servers).await {
+        tools.register(tool);
+    }
+    for custom in &config.tools.custom {
+        if tools.contains(&custom.name) {
+            tracing::warn!("Custom tool '{}' conflicts with an existing tool, skipping", custom.name);
+            continue;
+        }
+        match CustomCommandTool::new(custom) {
+            Ok(mut tool) => {
+                tool = tool.with_variables(variables.clone());
+                if let Some(ref sandbox) = sandbox {
+                    tool = tool.with_sandbox(sandbox.clone());
+                }
+                tools.register(Arc::new(tool));
+            },
+            Err(e) => tracing::warn!("Skipping custom tool '{}': {}", custom.name, e),
+        }
+    }
+
+    let mut tool_executor = ToolExecutor::new(tools);
+    if config.general.audit_log {
+        let path = std::path::Path::new(CODEY_DIR).join(AUDIT_FILENAME);
+        tool_executor = tool_executor.with_audit_log(AuditLog::new(path));
+    }
+    if let Some(ref command) = config.tools.post_edit_check {
+        tool_executor = tool_executor.with_post_edit_check(PostEditCheck {
+            command: command.clone(),
+            timeout_secs: config.tools.post_edit_check_timeout_secs,
+            max_output_bytes: Some(config.tools.shell_max_output_bytes),
+            sandbox: sandbox.clone(),
+        });
+    }
+    Ok(tool_executor)
+}
+
+/// Decision for a tool call that config settles without asking: the tool's
+/// filters first, then the default for its category. Nothing approves a call
+/// that needs confirmation, filters included. Shared by the TUI and script mode.
+pub(crate) fn configured_decision(
+    config: &Config,
+    filters: &ToolFilters,
+    tool: &dyn Tool,
+    params: &serde_json::Value,
+) -> Option<(ToolDecision, ApprovalSource)> {
+    filters
+        .evaluate(tool.name(), params)
+        .map(|decision| (decision, ApprovalSource::Filter))
+        .or_else(|| {
+            config
+                .tools
+                .approval
+                .decision(tool.category())
+                .map(|decision| (decision, ApprovalSource::Category))
+        })
+        .filter(|(decision, _)| *decision != ToolDecision::Approve || !tool.requires_confirmation(params))
+}
+
+/// The shell sandbox, if `[tools.shell.sandbox]` is enabled
+pub(crate) fn shell_sandbox(config: &Config) -> Option<ShellSandbox> {
+    let sandbox = &config.tools.shell.sandbox;
+    sandbox.enabled.then(|| ShellSandbox::new(sandbox))
+}
+
+/// Connect to a running editor, if one can be found
+async fn discover_ide(config: &Config) -> Option<Box<dyn Ide>> {
+    match Nvim::discover(&config.ide.nvim).await {
+        Ok(Some(nvim)) => {
+            tracing::info!("Connected to {} at {:?}", nvim.name(), nvim.socket_path());
+            Some(Box::new(nvim))
+        },
+        Ok(None) => {
+            tracing::debug!("No nvim instance found");
+            None
+        },
+        Err(e) => {
+            tracing::warn!("Failed to connect to nvim: {}", e);
+            None
+        },
+    }
+}
+
+/// Backup store for files modified by tools
+fn backups(config: &Config) -> Backups {
+    Backups::new(
+        std::path::Path::new(CODEY_DIR).join(BACKUPS_DIR),
+        config.tools.backup_retention,
+    )
 }
@@ -0,0 +1,166 @@
+//! Files produced by tools during a session
+//!
+//! Some tool calls produce a file rather than text: a downloaded image, a
+//! PDF, a generated diagram. Tools report these with `Effect::Artifact` and
+//! the app keeps them here so the user can list them (`/artifacts`), open one,
+//! or attach it to a follow-up message. The registry is bounded; files the
+//! session created in its own temp directory are deleted when they're evicted
+//! and when the session ends.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Most artifacts kept per session; the oldest is dropped beyond this
+pub const MAX_ARTIFACTS: usize = 20;
+
+/// Directory for this session's artifact files
+pub fn artifact_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("codey-artifacts-{}", std::process::id()))
+}
+
+/// Fresh path in the artifact directory, named after the last segment of
+/// `source` (a URL or path) so the extension survives
+pub fn artifact_path(source: &str) -> PathBuf {
+    let name: String = source
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let name = match name.trim_start_matches('.') {
+        "" => id[..8].to_string(),
+        name => format!("{}-{}", &id[..8], name),
+    };
+    artifact_dir().join(name)
+}
+
+/// A file a tool produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub path: PathBuf,
+    /// What it is, e.g. `download`
+    pub kind: String,
+}
+
+impl Artifact {
+    /// One-line description with the file size, as listed by `/artifacts`
+    pub fn describe(&self) -> String {
+        match fs::metadata(&self.path) {
+            Ok(meta) => format!("{} ({}, {} bytes)", self.path.display(), self.kind, meta.len()),
+            Err(_) => format!("{} ({}, missing)", self.path.display(), self.kind),
+        }
+    }
+}
+
+/// The session's artifacts, oldest first
+#[derive(Debug, Default)]
+pub struct Artifacts {
+    items: VecDeque<Artifact>,
+}
+
+impl Artifacts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an artifact, evicting the oldest beyond `MAX_ARTIFACTS`.
+    /// Re-registering a path updates its kind.
+    pub fn add(&mut self, artifact: Artifact) {
+        if let Some(existing) = self.items.iter_mut().find(|a| a.path == artifact.path) {
+            existing.kind = artifact.kind;
+            return;
+        }
+        self.items.push_back(artifact);
+        while self.items.len() > MAX_ARTIFACTS {
+            if let Some(evicted) = self.items.pop_front() {
+                remove_if_owned(&evicted.path);
+            }
+        }
+    }
+
+    /// Artifact by its 1-based number in the listing
+    pub fn get(&self, number: usize) -> Option<&Artifact> {
+        number.checked_sub(1).and_then(|i| self.items.get(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Numbered listing, as shown by `/artifacts`
+    pub fn list(&self) -> String {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(i, artifact)| format!("{}. {}", i + 1, artifact.describe()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Forget all artifacts and delete the files the session created
+    pub fn cleanup(&mut self) {
+        self.items.clear();
+        let dir = artifact_dir();
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                tracing::warn!("Failed to remove artifact directory {}: {}", dir.display(), e);
+            }
+        }
+    }
+}
+
+/// Delete a file only if it lives in this session's artifact directory
+fn remove_if_owned(path: &Path) {
+    if path.starts_with(artifact_dir()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_path_keeps_name() {
+        let path = artifact_path("https://example.com/img/diagram.png?size=2");
+        assert_eq!(path.parent(), Some(artifact_dir().as_path()));
+        assert!(path.file_name().unwrap().to_string_lossy().ends_with("-diagram.png"));
+        assert_ne!(path, artifact_path("https://example.com/img/diagram.png?size=2"));
+        assert!(artifact_path("https://example.com/").parent().is_some());
+    }
+
+    #[test]
+    fn test_registry_bounded_and_cleaned_up() {
+        fs::create_dir_all(artifact_dir()).unwrap();
+        let mut artifacts = Artifacts::new();
+        let first = artifact_path("first.bin");
+        fs::write(&first, b"data").unwrap();
+        artifacts.add(Artifact { path: first.clone(), kind: "download".into() });
+        artifacts.add(Artifact { path: first.clone(), kind: "image".into() });
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts.get(1).unwrap().kind, "image");
+        assert!(artifacts.get(0).is_none());
+
+        for i in 0..MAX_ARTIFACTS {
+            artifacts.add(Artifact { path: PathBuf::from(format!("/nonexistent/{}.png", i)), kind: "image".into() });
+        }
+        assert_eq!(artifacts.len(), MAX_ARTIFACTS);
+        assert!(!first.exists(), "evicted artifact's file is deleted");
+        assert!(artifacts.list().starts_with("1. /nonexistent/0.png (image, missing)"));
+
+        artifacts.cleanup();
+        assert!(artifacts.is_empty());
+        assert!(!artifact_dir().exists());
+    }
+}
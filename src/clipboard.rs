@@ -0,0 +1,69 @@
+//! System clipboard access for the `/copy` command
+
+use anyhow::{Context, Result};
+
+/// Copy text to the system clipboard.
+/// Fails with a readable error when no clipboard is available (e.g. headless or SSH).
+pub fn copy(text: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Clipboard unavailable")?;
+    clipboard
+        .set_text(text)
+        .context("Failed to write to clipboard")
+}
+
+/// Contents of the last fenced code block in markdown text
+pub fn last_code_block(text: &str) -> Option<&str> {
+    let mut last = None;
+    let mut open: Option<(&str, usize)> = None; // (fence, content start)
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        match open {
+            None => {
+                if let Some(fence) = fence_of(trimmed) {
+                    open = Some((fence, offset + line.len()));
+                }
+            },
+            Some((fence, start)) => {
+                if trimmed.trim_end() == fence {
+                    last = Some(&text[start..offset]);
+                    open = None;
+                }
+            },
+        }
+        offset += line.len();
+    }
+
+    last.map(|block| block.strip_suffix('\n').unwrap_or(block))
+}
+
+/// The fence (``` or ~~~, possibly longer) opening a code block
+fn fence_of(line: &str) -> Option<&str> {
+    let ch = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then(|| &line[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_code_block() {
+        let text = "Try this:\n\n```rust\nfn a() {}\n```\n\nOr:\n\n```\nls -la\necho hi\n```\nDone.";
+        assert_eq!(last_code_block(text), Some("ls -la\necho hi"));
+    }
+
+    #[test]
+    fn test_last_code_block_nested_fences() {
+        let text = "````markdown\n```rust\nfn a() {}\n```\n````";
+        assert_eq!(last_code_block(text), Some("```rust\nfn a() {}\n```"));
+    }
+
+    #[test]
+    fn test_last_code_block_missing_or_unclosed() {
+        assert_eq!(last_code_block("no code here"), None);
+        assert_eq!(last_code_block("```\nunclosed"), None);
+    }
+}
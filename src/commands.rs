@@ -4,25 +4,79 @@ use anyhow::Result;
 const ALL_COMMANDS: &[&dyn CommandImpl] = &[
     &Help,
     &Compact,
+    &Uncompact,
+    &NoCompact,
+    &Plan,
+    &Execute,
+    &Init,
+    &Copy,
+    &Restore,
+    &Resume,
+    &New,
+    &Title,
+    &Stats,
+    &MaxTokens,
+    &Reasoning,
+    &Preset,
+    &IdeToggle,
+    &Vi,
+    &Pin,
+    &Unpin,
+    &Set,
+    &Unset,
+    &Vars,
+    &Artifacts,
+    &Annotations,
+    &Debug,
 ];
 
 pub struct Command;
 
 impl Command {
-    /// Parse input and return matching command, or None
-    pub fn parse(input: &str) -> Option<&'static dyn CommandImpl> {
+    /// Arguments following the command name in the input
+    pub fn args(input: &str) -> &str {
         let input = input.trim();
-        if !input.starts_with('/') {
-            return None;
-        }
+        input
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, args)| args.trim())
+    }
+
+    /// Name of the slash command in the input, whether or not it is registered.
+    /// Input like `/usr/bin/env` isn't treated as a command.
+    pub fn name(input: &str) -> Option<&str> {
+        let input = input.trim();
+        let name = input.strip_prefix('/')?.split_whitespace().next()?;
+        name.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            .then_some(name)
+    }
 
-        let cmd_name = input[1..].split_whitespace().next()?;
-        ALL_COMMANDS.iter().find(|cmd| cmd.name() == cmd_name).copied()
+    /// Parse input and return matching command, or None
+    pub fn parse(input: &str) -> Option<&'static dyn CommandImpl> {
+        Self::get(Self::name(input)?)
     }
 
     /// Get a command by name
     pub fn get(name: &str) -> Option<&'static dyn CommandImpl> {
-        ALL_COMMANDS.iter().find(|cmd| cmd.name() == name).copied()
+        Self::all().find(|cmd| cmd.name() == name)
+    }
+
+    /// All registered commands, in help order
+    pub fn all() -> impl Iterator<Item = &'static dyn CommandImpl> {
+        ALL_COMMANDS.iter().copied()
+    }
+
+    /// Commands and keybindings, as shown by `/help`
+    pub fn help_text() -> String {
+        let mut help_text = String::from("Available commands:");
+        for cmd in Self::all() {
+            help_text.push_str(&format!("\n  /{} - {}", cmd.name(), cmd.description()));
+        }
+        help_text.push_str("\n\nKeybindings:");
+        for (key, action) in crate::app::KEYBINDINGS {
+            help_text.push_str(&format!("\n  {} - {}", key, action));
+        }
+        help_text
     }
 
     /// Get completion for partial input, returns full command if unique match
@@ -33,8 +87,7 @@ impl Command {
         }
 
         let partial = &input[1..];
-        let matches: Vec<_> = ALL_COMMANDS
-            .iter()
+        let matches: Vec<_> = Self::all()
             .filter(|cmd| cmd.name().starts_with(partial))
             .collect();
 
@@ -54,8 +107,8 @@ pub trait CommandImpl: Send + Sync {
     /// Short description for help
     fn description(&self) -> &'static str;
 
-    /// Execute the command, optionally returning text to display
-    fn execute(&self, app: &mut crate::app::App) -> Result<Option<String>>;
+    /// Execute the command with its arguments, optionally returning text to display
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>>;
 }
 
 
@@ -67,15 +120,11 @@ impl CommandImpl for Help {
     }
 
     fn description(&self) -> &'static str {
-        "Show available commands"
+        "Show available commands and keybindings"
     }
 
-    fn execute(&self, _app: &mut crate::app::App) -> Result<Option<String>> {
-        let mut help_text = String::from("Available commands:");
-        for cmd in ALL_COMMANDS {
-            help_text.push_str(&format!("\n  /{} - {}", cmd.name(), cmd.description()));
-        }
-        Ok(Some(help_text))
+    fn execute(&self, _app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        Ok(Some(Command::help_text()))
     }
 }
 
@@ -91,8 +140,431 @@ impl CommandImpl for Compact {
         "Compact conversation history to reduce context size"
     }
 
-    fn execute(&self, app: &mut crate::app::App) -> Result<Option<String>> {
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
         app.queue_compaction();
         Ok(None)
     }
 }
+
+
+pub struct Uncompact;
+
+impl CommandImpl for Uncompact {
+    fn name(&self) -> &'static str {
+        "uncompact"
+    }
+
+    fn description(&self) -> &'static str {
+        "Restore the full context from before the last compaction (/uncompact <n> shows source n of the summary)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.uncompact(args)
+    }
+}
+
+
+pub struct NoCompact;
+
+impl CommandImpl for NoCompact {
+    fn name(&self) -> &'static str {
+        "nocompact"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pause automatic compaction (/nocompact on, /nocompact off, or /nocompact to toggle)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_compaction_paused_arg(args)
+    }
+}
+
+
+pub struct Plan;
+
+impl CommandImpl for Plan {
+    fn name(&self) -> &'static str {
+        "plan"
+    }
+
+    fn description(&self) -> &'static str {
+        "Plan before acting: block file modifications until /execute"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        app.enter_plan_mode()
+    }
+}
+
+
+pub struct Execute;
+
+impl CommandImpl for Execute {
+    fn name(&self) -> &'static str {
+        "execute"
+    }
+
+    fn description(&self) -> &'static str {
+        "Approve the plan and re-enable file modifications"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        app.approve_plan()
+    }
+}
+
+
+pub struct Init;
+
+impl CommandImpl for Init {
+    fn name(&self) -> &'static str {
+        "init"
+    }
+
+    fn description(&self) -> &'static str {
+        "Have the agent explore the project and write CODEY.md (/init update revises it)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.init_project(args)
+    }
+}
+
+
+pub struct Copy;
+
+impl CommandImpl for Copy {
+    fn name(&self) -> &'static str {
+        "copy"
+    }
+
+    fn description(&self) -> &'static str {
+        "Copy the last response to the clipboard (/copy code for its last code block)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        let text = app
+            .last_assistant_text()
+            .ok_or_else(|| anyhow::anyhow!("No assistant message to copy"))?;
+
+        let (text, what) = match args {
+            "" => (text.as_str(), "last response"),
+            "code" => (
+                crate::clipboard::last_code_block(&text)
+                    .ok_or_else(|| anyhow::anyhow!("No code block in the last response"))?,
+                "code block",
+            ),
+            other => anyhow::bail!("Unknown argument '{}' (expected nothing or 'code')", other),
+        };
+
+        crate::clipboard::copy(text)?;
+        Ok(Some(format!("Copied {} ({} chars) to clipboard", what, text.chars().count())))
+    }
+}
+
+
+pub struct Restore;
+
+impl CommandImpl for Restore {
+    fn name(&self) -> &'static str {
+        "restore"
+    }
+
+    fn description(&self) -> &'static str {
+        "Restore a file from its most recent backup (/restore <file>)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.restore_backup(args)
+    }
+}
+
+
+pub struct Resume;
+
+impl CommandImpl for Resume {
+    fn name(&self) -> &'static str {
+        "resume"
+    }
+
+    fn description(&self) -> &'static str {
+        "Switch to a saved transcript (/resume <number>, or /resume to list)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.resume(args)
+    }
+}
+
+
+pub struct New;
+
+impl CommandImpl for New {
+    fn name(&self) -> &'static str {
+        "new"
+    }
+
+    fn description(&self) -> &'static str {
+        "Save this session and start a fresh one, as if relaunched"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        app.new_session()
+    }
+}
+
+
+pub struct Title;
+
+impl CommandImpl for Title {
+    fn name(&self) -> &'static str {
+        "title"
+    }
+
+    fn description(&self) -> &'static str {
+        "Name this session for /resume (/title <text>, or /title to show it)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_title(args)
+    }
+}
+
+
+pub struct Stats;
+
+impl CommandImpl for Stats {
+    fn name(&self) -> &'static str {
+        "stats"
+    }
+
+    fn description(&self) -> &'static str {
+        "Show session duration and how much of it the agent was active"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        Ok(Some(app.session_stats().report()))
+    }
+}
+
+
+pub struct MaxTokens;
+
+impl CommandImpl for MaxTokens {
+    fn name(&self) -> &'static str {
+        "maxtokens"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set max output tokens for the next message only (/maxtokens <n>, or reset)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_next_max_tokens(args)
+    }
+}
+
+
+pub struct Reasoning;
+
+impl CommandImpl for Reasoning {
+    fn name(&self) -> &'static str {
+        "reasoning"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set how much the model thinks (/reasoning off|low|medium|high, default, or show)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_reasoning(args)
+    }
+}
+
+
+pub struct Preset;
+
+impl CommandImpl for Preset {
+    fn name(&self) -> &'static str {
+        "preset"
+    }
+
+    fn description(&self) -> &'static str {
+        "Switch tool filter presets (/preset <name>, /preset none, or /preset to list)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_filter_preset(args)
+    }
+}
+
+
+pub struct IdeToggle;
+
+impl CommandImpl for IdeToggle {
+    fn name(&self) -> &'static str {
+        "ide"
+    }
+
+    fn description(&self) -> &'static str {
+        "Connect to or disconnect from the editor (/ide on, /ide off, or /ide for status)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_ide(args)
+    }
+}
+
+
+pub struct Vi;
+
+impl CommandImpl for Vi {
+    fn name(&self) -> &'static str {
+        "vi"
+    }
+
+    fn description(&self) -> &'static str {
+        "Toggle vi-style modal input (/vi on, /vi off, or /vi to toggle)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_vi_mode(args)
+    }
+}
+
+
+pub struct Pin;
+
+impl CommandImpl for Pin {
+    fn name(&self) -> &'static str {
+        "pin"
+    }
+
+    fn description(&self) -> &'static str {
+        "Keep a file's current contents in context (/pin <file>, or /pin to list)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.pin(args)
+    }
+}
+
+
+pub struct Unpin;
+
+impl CommandImpl for Unpin {
+    fn name(&self) -> &'static str {
+        "unpin"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stop including a pinned file in context (/unpin <file>)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.unpin(args)
+    }
+}
+
+
+pub struct Artifacts;
+
+impl CommandImpl for Artifacts {
+    fn name(&self) -> &'static str {
+        "artifacts"
+    }
+
+    fn description(&self) -> &'static str {
+        "List files tools produced (/artifacts attach <n> or /artifacts open <n> to use one)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.artifacts_command(args)
+    }
+}
+
+
+pub struct Annotations;
+
+impl CommandImpl for Annotations {
+    fn name(&self) -> &'static str {
+        "annotations"
+    }
+
+    fn description(&self) -> &'static str {
+        "Clear the notes the agent left in the editor"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        app.request_clear_annotations()
+    }
+}
+
+
+pub struct Set;
+
+impl CommandImpl for Set {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set a session variable for custom tools and pinned files (/set <name>=<value>)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.set_variable(args)
+    }
+}
+
+
+pub struct Unset;
+
+impl CommandImpl for Unset {
+    fn name(&self) -> &'static str {
+        "unset"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove a session variable (/unset <name>)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.unset_variable(args)
+    }
+}
+
+
+pub struct Vars;
+
+impl CommandImpl for Vars {
+    fn name(&self) -> &'static str {
+        "vars"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the session variables"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, _args: &str) -> Result<Option<String>> {
+        app.list_variables()
+    }
+}
+
+
+pub struct Debug;
+
+impl CommandImpl for Debug {
+    fn name(&self) -> &'static str {
+        "debug"
+    }
+
+    fn description(&self) -> &'static str {
+        "Write the agent's raw message history to .codey/messages.txt (/debug messages)"
+    }
+
+    fn execute(&self, app: &mut crate::app::App, args: &str) -> Result<Option<String>> {
+        app.debug(args)
+    }
+}
@@ -3,19 +3,111 @@
 //! When the conversation context exceeds a threshold, this module handles
 //! asking the agent to summarize the conversation for continuation in a
 //! new transcript.
+//!
+//! The compaction request includes an index of the transcript's turns, and
+//! the summary ends with a `<sources>` list citing the turn each key fact
+//! came from. The list is kept on the `CompactionBlock`, so a claim in the
+//! summary can be traced back to the full transcript (`/uncompact <n>`).
+
+use std::path::{Path, PathBuf};
 
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use serde::{Deserialize, Serialize};
 
 use crate::impl_tool_block;
-use crate::transcript::{Block, BlockType, Status};
+use crate::transcript::{render_markdown, Block, BlockType, Role, Status, Transcript};
+
+/// Tag around the list of sources at the end of a summary
+const SOURCES_TAG: &str = "sources";
+
+/// Longest turn preview in the index sent with a compaction request
+const TURN_PREVIEW_LEN: usize = 80;
+
+/// A key fact in a compaction summary and the turn it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionSource {
+    pub fact: String,
+    /// Id of the turn in the transcript the summary replaced
+    pub turn: usize,
+}
+
+/// Appended to the compaction prompt: how to cite sources, and an index of
+/// the turns that can be cited
+pub fn sources_request(transcript: &Transcript) -> String {
+    let mut index = String::new();
+    for turn in transcript.turns() {
+        let role = match turn.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => continue,
+        };
+        let mut preview: String = turn
+            .content
+            .iter()
+            .find_map(|block| match block.kind() {
+                BlockType::Text => block.text().and_then(|t| t.lines().find(|l| !l.trim().is_empty())),
+                _ => block.tool_name(),
+            })
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if let Some((cut, _)) = preview.char_indices().nth(TURN_PREVIEW_LEN) {
+            preview.truncate(cut);
+            preview.push_str("...");
+        }
+        index.push_str(&format!("turn {} ({}): {}\n", turn.id, role, preview));
+    }
+    let transcript = transcript
+        .path()
+        .map(|path| format!(" transcript=\"{}\"", path.display()))
+        .unwrap_or_default();
+    format!(
+        "\n\nEnd the summary with the key facts it relies on and the turn each one came from, \
+         one per line, so they can be traced back later:\n\n\
+         <{tag}{transcript}>\n- turn 12: The API client retries on HTTP 529\n</{tag}>\n\n\
+         Turns in this conversation:\n{index}",
+        tag = SOURCES_TAG,
+    )
+}
+
+/// Split a summary into its prose and the sources listed at its end.
+/// Lines of the list that don't read `turn <id>: <fact>` are skipped.
+pub fn parse_sources(text: &str) -> (&str, Vec<CompactionSource>) {
+    let open = format!("<{}", SOURCES_TAG);
+    let Some(start) = text.rfind(&open) else {
+        return (text, Vec::new());
+    };
+    let body = &text[start..];
+    let body = body.split_once('>').map_or("", |(_, rest)| rest);
+    let body = body.split(&format!("</{}>", SOURCES_TAG)).next().unwrap_or_default();
+
+    let sources = body
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+            let (turn, fact) = line.strip_prefix("turn ")?.split_once(':')?;
+            Some(CompactionSource {
+                fact: fact.trim().to_string(),
+                turn: turn.trim().parse().ok()?,
+            })
+        })
+        .filter(|source| !source.fact.is_empty())
+        .collect();
+    (text[..start].trim_end(), sources)
+}
 
 /// Compaction summary block - shown when context was compacted
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompactionBlock {
     pub text: String,
     pub status: Status,
+    /// Transcript holding the full context this summary replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_transcript: Option<PathBuf>,
+    /// Facts the summary cites, with their turns in `previous_transcript`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<CompactionSource>,
 }
 
 impl CompactionBlock {
@@ -23,6 +115,8 @@ impl CompactionBlock {
         Self {
             text: text.into(),
             status: Status::Running,
+            previous_transcript: None,
+            sources: Vec::new(),
         }
     }
 }
@@ -31,6 +125,14 @@ impl CompactionBlock {
 impl Block for CompactionBlock {
     impl_tool_block!(BlockType::Compaction);
 
+    fn linked_transcript(&self) -> Option<&Path> {
+        self.previous_transcript.as_deref()
+    }
+
+    fn compaction_sources(&self) -> &[CompactionSource] {
+        &self.sources
+    }
+
     fn render(&self, width: u16) -> Vec<Line<'_>> {
         let mut lines = Vec::new();
 
@@ -60,13 +162,53 @@ impl Block for CompactionBlock {
         // Only render text content when complete
         if self.status == Status::Complete && !self.text.is_empty() {
             lines.push(Line::from(""));
-            let skin = ratskin::RatSkin::default();
-            let parsed = ratskin::RatSkin::parse_text(&self.text);
-            for line in skin.parse(parsed, width) {
-                lines.push(line);
+            lines.extend(render_markdown(parse_sources(&self.text).0, width));
+        }
+
+        if !self.sources.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Sources", Style::default().fg(Color::DarkGray))));
+            for (i, source) in self.sources.iter().enumerate() {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}. {} (turn {})", i + 1, source.fact, source.turn),
+                    Style::default().fg(Color::DarkGray),
+                )));
             }
         }
 
+        if let Some(ref path) = self.previous_transcript {
+            lines.push(Line::from(Span::styled(
+                format!("Full context: {} (/uncompact to restore)", path.display()),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
         lines
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sources() {
+        let summary = "## Done\n- Added retries\n\n<sources transcript=\"/tmp/t.json\">\n\
+                       - turn 4: Retries back off on HTTP 529\n\
+                       - turn x: not a turn\n\
+                       * turn 9: Config lives in ~/.config/codey\n</sources>\n";
+        let (prose, sources) = parse_sources(summary);
+        assert_eq!(prose, "## Done\n- Added retries");
+        assert_eq!(
+            sources,
+            vec![
+                CompactionSource { fact: "Retries back off on HTTP 529".into(), turn: 4 },
+                CompactionSource { fact: "Config lives in ~/.config/codey".into(), turn: 9 },
+            ]
+        );
+
+        let (prose, sources) = parse_sources("Just prose");
+        assert_eq!(prose, "Just prose");
+        assert!(sources.is_empty());
+    }
+}
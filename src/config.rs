@@ -9,15 +9,29 @@ use std::collections::HashMap;
 #[cfg(feature = "cli")]
 use std::path::PathBuf;
 
+use crate::llm::{ModelCapabilities, ReasoningLevel};
+
 #[cfg(feature = "cli")]
 use anyhow::{Context, Result};
 #[cfg(feature = "cli")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "cli")]
-use crate::tool_filter::ToolFilterConfig;
+use crate::tool_filter::{apply_preset, builtin_preset, FilterPreset, ToolFilterConfig, ToolFilters, BUILTIN_PRESETS};
 #[cfg(feature = "cli")]
 use crate::tools::names;
+#[cfg(feature = "cli")]
+use crate::tools::{ToolCategory, ToolDecision};
+#[cfg(feature = "cli")]
+use crate::tools::io::SHELL_MAX_OUTPUT_BYTES;
+#[cfg(feature = "cli")]
+use crate::tools::{DownloadTool, ShellTool, WebSearchTool, DEFAULT_MEMORY_MAX_BYTES};
+#[cfg(feature = "cli")]
+use crate::llm::ModelPricing;
+#[cfg(feature = "cli")]
+use crate::pinned::DEFAULT_PINNED_MAX_BYTES;
+#[cfg(feature = "cli")]
+use crate::transcript::DEFAULT_MAX_LINE_LENGTH;
 
 // =============================================================================
 // Library-public types (always available)
@@ -32,6 +46,15 @@ pub const TRANSCRIPTS_DIR: &str = "transcripts";
 /// Filename for correction memory
 pub const CORRECTIONS_FILENAME: &str = "corrections.json";
 
+/// Filename for the agent's project memory notes
+pub const MEMORY_FILENAME: &str = "memory.json";
+
+/// Filename for the tool call audit log
+pub const AUDIT_FILENAME: &str = "audit.jsonl";
+
+/// Directory name for file backups taken before edits
+pub const BACKUPS_DIR: &str = "backups";
+
 /// Runtime configuration for an Agent instance.
 ///
 /// This is the public API for library users to configure agents.
@@ -47,9 +70,23 @@ pub const CORRECTIONS_FILENAME: &str = "corrections.json";
 ///     model: "claude-sonnet-4-5-20250929".to_string(),
 ///     max_tokens: 8192,
 ///     thinking_budget: 2_000,
+///     reasoning: None,
 ///     max_retries: 5,
 ///     compaction_thinking_budget: 8_000,
 ///     fast_mode: false,
+///     anthropic_headers: Default::default(),
+///     api_base_url: None,
+///     interleaved_thinking_rounds: None,
+///     fallback_models: Vec::new(),
+///     context_window: None,
+///     overload_backoff_secs: 5,
+///     overload_backoff_max_secs: 30,
+///     max_overload_retries: 8,
+///     length_continuations: 0,
+///     restore_context_fraction: 0.5,
+///     request_timeout_secs: None,
+///     stream_idle_timeout_secs: Some(120),
+///     compaction_threshold: None,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -57,11 +94,64 @@ pub struct AgentRuntimeConfig {
     pub model: String,
     pub max_tokens: u32,
     pub thinking_budget: u32,
+    /// Named reasoning level, translated for each model; replaces
+    /// `thinking_budget` when set
+    pub reasoning: Option<ReasoningLevel>,
     pub max_retries: u32,
     pub compaction_thinking_budget: u32,
     /// Enable fast mode (research preview) for lower-latency responses.
     /// Only effective with opus-4-6 models.
     pub fast_mode: bool,
+    /// Overrides for the headers sent with Anthropic requests
+    pub anthropic_headers: AnthropicHeaders,
+    /// Send requests to this base URL instead of the provider's default
+    /// (ignored for OpenRouter models)
+    pub api_base_url: Option<String>,
+    /// Tool-call rounds per turn, after the first request, that may use
+    /// extended thinking (None = every round). Later rounds are sent without
+    /// thinking or the interleaved-thinking beta.
+    pub interleaved_thinking_rounds: Option<u32>,
+    /// Models to try in order once retries on `model` are exhausted
+    pub fallback_models: Vec<String>,
+    /// Context window in tokens (None = the model's known window, if any).
+    /// `max_tokens` is lowered for requests whose estimated input would leave
+    /// less than that much room.
+    pub context_window: Option<u32>,
+    /// Wait before the first retry of an overloaded (529) request; doubles
+    /// with each attempt up to `overload_backoff_max_secs`, with jitter
+    pub overload_backoff_secs: u64,
+    pub overload_backoff_max_secs: u64,
+    /// Retries for overloaded requests, counted apart from `max_retries`
+    pub max_overload_retries: u32,
+    /// Follow-up requests per turn that continue a response cut off at
+    /// `max_tokens` (0 = leave it cut off)
+    pub length_continuations: u32,
+    /// Share of the context window a restored transcript may fill; older
+    /// messages beyond it are left out (ignored when the window is unknown)
+    pub restore_context_fraction: f32,
+    /// Abort a request still streaming this long after it was sent (None = no limit)
+    pub request_timeout_secs: Option<u64>,
+    /// Abort a request that receives no data for this long (None = wait forever).
+    /// Both timeouts are retried like other network errors.
+    pub stream_idle_timeout_secs: Option<u64>,
+    /// Context size at which the caller compacts the conversation. When set,
+    /// the agent reports `AgentStep::ContextWarning` as the context nears it.
+    pub compaction_threshold: Option<u32>,
+}
+
+/// Overrides for Anthropic request headers.
+///
+/// By default OAuth requests identify as the Claude Code client with its beta
+/// flags, and API-key requests only enable interleaved thinking. API gateways
+/// and proxies may reject those, so both can be replaced.
+#[derive(Debug, Clone, Default)]
+pub struct AnthropicHeaders {
+    /// `anthropic-beta` values (None uses the built-in list for the auth method;
+    /// an empty list sends no beta header)
+    pub beta: Option<Vec<String>>,
+    /// `user-agent` value (None uses the built-in value for OAuth and nothing
+    /// for API keys; an empty string sends no override)
+    pub user_agent: Option<String>,
 }
 
 impl Default for AgentRuntimeConfig {
@@ -70,9 +160,34 @@ impl Default for AgentRuntimeConfig {
             model: "claude-sonnet-4-5-20250929".to_string(),
             max_tokens: 8192,
             thinking_budget: 2_000,
+            reasoning: None,
             max_retries: 5,
             compaction_thinking_budget: 8_000,
             fast_mode: false,
+            anthropic_headers: AnthropicHeaders::default(),
+            api_base_url: None,
+            interleaved_thinking_rounds: None,
+            fallback_models: Vec::new(),
+            context_window: None,
+            overload_backoff_secs: 5,
+            overload_backoff_max_secs: 30,
+            max_overload_retries: 8,
+            length_continuations: 0,
+            restore_context_fraction: 0.5,
+            request_timeout_secs: None,
+            stream_idle_timeout_secs: Some(120),
+            compaction_threshold: None,
+        }
+    }
+}
+
+impl AgentRuntimeConfig {
+    /// Thinking budget for a `max_tokens` request to `model`: the
+    /// `reasoning` level's budget for it when set, otherwise `thinking_budget`
+    pub fn thinking_budget_for(&self, model: &str, max_tokens: u32) -> u32 {
+        match self.reasoning {
+            Some(level) => ModelCapabilities::for_model(model).reasoning_budget(level, max_tokens),
+            None => self.thinking_budget,
         }
     }
 }
@@ -89,9 +204,23 @@ impl AgentRuntimeConfig {
             model: config.agents.foreground.model.clone(),
             max_tokens: config.agents.foreground.max_tokens,
             thinking_budget: config.agents.foreground.thinking_budget,
+            reasoning: config.general.reasoning,
             max_retries: config.general.max_retries,
             compaction_thinking_budget: config.general.compaction_thinking_budget,
             fast_mode: config.agents.foreground.fast_mode,
+            anthropic_headers: config.auth.anthropic.headers(),
+            api_base_url: config.general.api_base_url.clone(),
+            interleaved_thinking_rounds: config.general.interleaved_thinking_rounds,
+            fallback_models: config.general.fallback_models.clone(),
+            context_window: config.general.context_window,
+            overload_backoff_secs: config.general.overload_backoff_secs,
+            overload_backoff_max_secs: config.general.overload_backoff_max_secs,
+            max_overload_retries: config.general.max_overload_retries,
+            length_continuations: config.general.length_continuations(),
+            restore_context_fraction: config.general.restore_context_fraction,
+            request_timeout_secs: config.general.request_timeout_secs,
+            stream_idle_timeout_secs: config.general.stream_idle_timeout(),
+            compaction_threshold: Some(config.general.compaction_threshold),
         }
     }
 
@@ -101,13 +230,60 @@ impl AgentRuntimeConfig {
             model: config.agents.background.model.clone(),
             max_tokens: config.agents.background.max_tokens,
             thinking_budget: config.agents.background.thinking_budget,
+            reasoning: config.general.reasoning,
             max_retries: config.general.max_retries,
             compaction_thinking_budget: config.general.compaction_thinking_budget,
             fast_mode: config.agents.background.fast_mode,
+            anthropic_headers: config.auth.anthropic.headers(),
+            api_base_url: config.general.api_base_url.clone(),
+            interleaved_thinking_rounds: config.general.interleaved_thinking_rounds,
+            fallback_models: config.general.fallback_models.clone(),
+            context_window: config.general.context_window,
+            overload_backoff_secs: config.general.overload_backoff_secs,
+            overload_backoff_max_secs: config.general.overload_backoff_max_secs,
+            max_overload_retries: config.general.max_overload_retries,
+            length_continuations: config.general.length_continuations(),
+            restore_context_fraction: config.general.restore_context_fraction,
+            request_timeout_secs: config.general.request_timeout_secs,
+            stream_idle_timeout_secs: config.general.stream_idle_timeout(),
+            // Background agents aren't compacted
+            compaction_threshold: None,
         }
     }
 }
 
+/// Placeholder for secret values in printed config
+#[cfg(feature = "cli")]
+const REDACTED: &str = "<redacted>";
+
+/// Replace secret string values in a serialized config. Every value under an
+/// `env` or `headers` table is treated as secret, as is any string whose key
+/// names a key, token, secret or password.
+#[cfg(feature = "cli")]
+fn redact_secrets(value: &mut toml::Value, secret: bool) {
+    match value {
+        toml::Value::String(s) if secret => *s = REDACTED.to_string(),
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                let key = key.to_lowercase();
+                let secret = secret
+                    || key == "env"
+                    || key == "headers"
+                    || ["key", "token", "secret", "password"]
+                        .iter()
+                        .any(|word| key.contains(word));
+                redact_secrets(value, secret);
+            }
+        },
+        toml::Value::Array(items) => {
+            for item in items {
+                redact_secrets(item, secret);
+            }
+        },
+        _ => {},
+    }
+}
+
 /// Main configuration structure loaded from config.toml
 #[cfg(feature = "cli")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +297,7 @@ pub struct Config {
     pub tools: ToolsConfig,
     pub ide: IdeConfig,
     pub browser: BrowserConfig,
+    pub mcp: McpConfig,
 }
 
 #[cfg(feature = "cli")]
@@ -135,6 +312,7 @@ impl Default for Config {
             tools: ToolsConfig::default(),
             ide: IdeConfig::default(),
             browser: BrowserConfig::default(),
+            mcp: McpConfig::default(),
         }
     }
 }
@@ -149,12 +327,31 @@ impl Config {
                     .with_context(|| format!("Failed to read config file: {}", path.display()))?;
                 let config: Config = toml::from_str(&content)
                     .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+                config.validate()?;
                 return Ok(config);
             }
         }
         Ok(Config::default())
     }
 
+    /// The configuration as TOML, with secrets (API keys, tokens, MCP env vars
+    /// and headers) replaced by a placeholder. Used by `--print-config`.
+    pub fn to_redacted_toml(&self) -> Result<String> {
+        let mut value = toml::Value::try_from(self).context("Failed to serialize config")?;
+        redact_secrets(&mut value, false);
+        toml::to_string(&value).context("Failed to serialize config")
+    }
+
+    /// Check settings that reference files, so mistakes surface at startup
+    pub fn validate(&self) -> Result<()> {
+        if let Some(path) = self.general.compaction_prompt_path() {
+            if !path.is_file() {
+                anyhow::bail!("compaction_prompt_file not found: {}", path.display());
+            }
+        }
+        Ok(())
+    }
+
     /// Get the config directory path (~/.config/codey)
     pub fn config_dir() -> Option<PathBuf> {
         dirs::home_dir().map(|p| p.join(".config").join("codey"))
@@ -221,10 +418,87 @@ impl Default for AgentConfig {
 pub struct GeneralConfig {
     pub working_dir: Option<PathBuf>,
     pub max_retries: u32,
+    /// Seconds before the first retry of an overloaded (529) request; doubles
+    /// per attempt up to `overload_backoff_max_secs`
+    pub overload_backoff_secs: u64,
+    pub overload_backoff_max_secs: u64,
+    /// Retries for overloaded requests, separate from `max_retries`
+    pub max_overload_retries: u32,
+    /// Give up on a request still streaming this long after it was sent, and
+    /// retry it (None = no limit)
+    pub request_timeout_secs: Option<u64>,
+    /// Give up on a request that receives nothing for this long, and retry it
+    /// (0 = wait forever)
+    pub stream_idle_timeout_secs: u64,
+    /// Models to switch to, in order, when a request still fails after
+    /// `max_retries` (overloaded or unavailable provider)
+    pub fallback_models: Vec<String>,
+    /// When a response is cut off at `max_tokens`, ask the model to carry on
+    /// in the same turn instead of stopping mid-sentence
+    pub auto_continue_on_length: bool,
+    /// Most continuations per turn with `auto_continue_on_length`
+    pub max_auto_continues: u32,
     /// Token threshold at which to trigger context compaction (default: 100,000)
     pub compaction_threshold: u32,
+    /// Context window of the configured models in tokens, overriding the
+    /// built-in table (for proxies and models it doesn't know)
+    pub context_window: Option<u32>,
+    /// Share of the context window that `--continue` and `/resume` restore
+    /// from a transcript; older messages are left out (default: 0.5)
+    pub restore_context_fraction: f32,
     /// Thinking budget for compaction requests (default: 8,000)
     pub compaction_thinking_budget: u32,
+    /// Tool-call rounds per turn that may think, after the first request
+    /// (None = no limit, 0 = think only before the first response)
+    pub interleaved_thinking_rounds: Option<u32>,
+    /// Reasoning level (`off`, `low`, `medium`, `high`) translated to a
+    /// suitable budget or effort for each model. Unset uses the agents'
+    /// `thinking_budget`. Switch at runtime with `/reasoning`.
+    pub reasoning: Option<ReasoningLevel>,
+    /// Token threshold at which old tool results are replaced by short excerpts,
+    /// keeping the conversation itself intact (None = disabled). Set it below
+    /// `compaction_threshold` so it runs first.
+    pub tool_result_compaction_threshold: Option<u32>,
+    /// Most recent tool results that tool-result compaction leaves untouched
+    pub tool_result_keep_recent: usize,
+    /// Append every tool call and its approval decision to .codey/audit.jsonl
+    pub audit_log: bool,
+    /// Replaces the built-in compaction prompt
+    pub compaction_prompt: Option<String>,
+    /// File to read the compaction prompt from (relative to the config directory).
+    /// Ignored when `compaction_prompt` is set.
+    pub compaction_prompt_file: Option<PathBuf>,
+    /// Truncate tool output in saved transcripts to this many characters.
+    /// The live session keeps full output; `--continue` sees the truncated version.
+    pub transcript_tool_output_limit: Option<usize>,
+    /// Truncate thinking blocks in saved transcripts to this many characters,
+    /// keeping the head and tail. The live session and the API history
+    /// (including thinking signatures) keep the full reasoning.
+    pub transcript_thinking_limit: Option<usize>,
+    /// Truncate each string in a tool call's params (a `write_file` content)
+    /// in saved transcripts to this many characters. Tools run with the full
+    /// params; `--continue` shows the model a note in place of a shortened
+    /// one. 0 keeps everything.
+    pub transcript_param_limit: usize,
+    /// Base URL replacing the provider's default endpoint (proxies, gateways,
+    /// local OpenAI-compatible servers). OpenRouter models ignore it.
+    pub api_base_url: Option<String>,
+    /// Files whose current contents are added to the system prompt on every request
+    pub pinned_files: Vec<PathBuf>,
+    /// Upper bound on the total size of pinned file contents
+    pub pinned_max_bytes: usize,
+    /// Greeting shown at the start of a new session
+    pub welcome_message: WelcomeMessage,
+    /// Show the first heading of the project's CODEY.md above the greeting
+    pub project_banner: bool,
+    /// Vi-style modal input: Esc switches to a normal mode with motions and
+    /// editing commands instead of clearing the input
+    pub vi_mode: bool,
+    /// Show token usage and estimated cost under each assistant turn
+    pub show_per_turn_cost: bool,
+    /// Prices (dollars per million tokens) replacing the built-in list prices,
+    /// for discounted plans or models the table doesn't know
+    pub pricing: Option<ModelPricing>,
 }
 
 #[cfg(feature = "cli")]
@@ -233,8 +507,70 @@ impl Default for GeneralConfig {
         Self {
             working_dir: None,
             max_retries: 5,
+            overload_backoff_secs: 5,
+            overload_backoff_max_secs: 30,
+            max_overload_retries: 8,
+            request_timeout_secs: None,
+            stream_idle_timeout_secs: 120,
+            fallback_models: Vec::new(),
+            auto_continue_on_length: false,
+            max_auto_continues: 3,
             compaction_threshold: 192_000,
+            context_window: None,
+            restore_context_fraction: 0.5,
             compaction_thinking_budget: 8_000,
+            interleaved_thinking_rounds: None,
+            reasoning: None,
+            tool_result_compaction_threshold: None,
+            tool_result_keep_recent: 10,
+            audit_log: false,
+            compaction_prompt: None,
+            compaction_prompt_file: None,
+            transcript_tool_output_limit: None,
+            transcript_thinking_limit: None,
+            transcript_param_limit: 8000,
+            api_base_url: None,
+            pinned_files: Vec::new(),
+            pinned_max_bytes: DEFAULT_PINNED_MAX_BYTES,
+            welcome_message: WelcomeMessage::default(),
+            project_banner: false,
+            vi_mode: false,
+            show_per_turn_cost: false,
+            pricing: None,
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl GeneralConfig {
+    /// Stream idle timeout, None when disabled
+    pub fn stream_idle_timeout(&self) -> Option<u64> {
+        Some(self.stream_idle_timeout_secs).filter(|&secs| secs > 0)
+    }
+
+    /// Transcript param limit, None when disabled
+    pub fn param_limit(&self) -> Option<usize> {
+        Some(self.transcript_param_limit).filter(|&limit| limit > 0)
+    }
+
+    /// Continuations the agent may request per turn (0 when disabled)
+    pub fn length_continuations(&self) -> u32 {
+        if self.auto_continue_on_length {
+            self.max_auto_continues
+        } else {
+            0
+        }
+    }
+
+    /// Resolved path of `compaction_prompt_file`, if one applies
+    pub fn compaction_prompt_path(&self) -> Option<PathBuf> {
+        if self.compaction_prompt.is_some() {
+            return None;
+        }
+        let path = self.compaction_prompt_file.as_ref()?;
+        match Config::config_dir() {
+            Some(dir) if path.is_relative() => Some(dir.join(path)),
+            _ => Some(path.clone()),
         }
     }
 }
@@ -264,6 +600,8 @@ impl AgentPersonaConfig {
 pub struct AuthConfig {
     pub method: AuthMethod,
     pub api_key: Option<String>,
+    /// Header overrides for Anthropic requests (`[auth.anthropic]`)
+    pub anthropic: AnthropicAuthConfig,
 }
 
 #[cfg(feature = "cli")]
@@ -272,10 +610,50 @@ impl Default for AuthConfig {
         Self {
             method: AuthMethod::ApiKey,
             api_key: None,
+            anthropic: AnthropicAuthConfig::default(),
+        }
+    }
+}
+
+/// Anthropic header overrides, for API gateways and proxies that reject the
+/// defaults. Unset values keep the built-in headers for the auth method.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnthropicAuthConfig {
+    /// `anthropic-beta` values; an empty list sends none
+    pub beta_headers: Option<Vec<String>>,
+    /// `user-agent` value; an empty string sends none
+    pub user_agent: Option<String>,
+}
+
+#[cfg(feature = "cli")]
+impl AnthropicAuthConfig {
+    pub fn headers(&self) -> AnthropicHeaders {
+        AnthropicHeaders {
+            beta: self.beta_headers.clone(),
+            user_agent: self.user_agent.clone(),
         }
     }
 }
 
+/// `general.welcome_message`: `true` for the built-in greeting, `false` for
+/// none, or the text to show instead
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WelcomeMessage {
+    Enabled(bool),
+    Custom(String),
+}
+
+#[cfg(feature = "cli")]
+impl Default for WelcomeMessage {
+    fn default() -> Self {
+        Self::Enabled(true)
+    }
+}
+
 #[cfg(feature = "cli")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -291,6 +669,18 @@ pub struct UiConfig {
     pub theme: String,
     pub auto_scroll: bool,
     pub show_tokens: bool,
+    /// Animate a spinner while waiting for the first token or a running tool
+    pub spinner: bool,
+    /// Require a second Ctrl+C to quit; the first one interrupts the agent
+    pub confirm_quit: bool,
+    /// Show edit_file calls as word-level diffs in the transcript
+    pub inline_diffs: bool,
+    /// Longest line, in characters, shown in the transcript; longer lines
+    /// are cut with a "[long line truncated]" marker
+    pub max_line_length: usize,
+    /// Pastes longer than this many characters become a collapsed
+    /// attachment; shorter ones are inserted as editable text (0 = attach all)
+    pub paste_attach_threshold: usize,
 }
 
 #[cfg(feature = "cli")]
@@ -300,6 +690,11 @@ impl Default for UiConfig {
             theme: "base16-ocean.dark".to_string(),
             auto_scroll: true,
             show_tokens: true,
+            spinner: true,
+            confirm_quit: true,
+            inline_diffs: false,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            paste_attach_threshold: 500,
         }
     }
 }
@@ -309,18 +704,24 @@ impl Default for UiConfig {
 #[serde(default)]
 pub struct ToolsConfig {
     pub enabled: Vec<String>,
-    /// Filter patterns for shell tool (matches against command)
-    pub shell: ToolFilterConfig,
+    /// Filter patterns (matches against command) and sandbox for the shell tool
+    pub shell: ShellConfig,
     /// Filter patterns for read_file tool (matches against path)
     pub read_file: ToolFilterConfig,
+    /// Filter patterns for read_symbol tool (matches against path)
+    pub read_symbol: ToolFilterConfig,
     /// Filter patterns for write_file tool (matches against path)
     pub write_file: ToolFilterConfig,
-    /// Filter patterns for edit_file tool (matches against path)
-    pub edit_file: ToolFilterConfig,
+    /// Filter patterns (matches against path) and matching options for edit_file
+    pub edit_file: EditFileConfig,
     /// Filter patterns for fetch_url tool (matches against url)
     pub fetch_url: ToolFilterConfig,
-    /// Filter patterns for web_search tool (matches against query)
-    pub web_search: ToolFilterConfig,
+    /// Filter patterns for http_request tool (matches against url)
+    pub http_request: ToolFilterConfig,
+    /// Filter patterns for download tool (matches against url), plus its size limit
+    pub download: DownloadConfig,
+    /// Filter patterns for web_search tool (matches against query), plus its timeout
+    pub web_search: WebSearchConfig,
     /// Filter patterns for list_background_tasks tool (no params - use ".*" to auto-approve)
     pub list_background_tasks: ToolFilterConfig,
     /// Filter patterns for get_background_task tool (matches against task_id)
@@ -333,6 +734,57 @@ pub struct ToolsConfig {
     pub get_agent: ToolFilterConfig,
     /// Filter patterns for record_correction tool (matches against goal)
     pub record_correction: ToolFilterConfig,
+    /// Filter patterns for ide_open_files tool (no params - use ".*" to auto-approve)
+    pub ide_open_files: ToolFilterConfig,
+    /// Filter patterns for annotate tool (matches against path)
+    pub annotate: ToolFilterConfig,
+    /// Filter patterns for memory_read tool (matches against topic)
+    pub memory_read: ToolFilterConfig,
+    /// Filter patterns for memory_write tool (matches against topic)
+    pub memory_write: ToolFilterConfig,
+    /// Filter patterns for git_status tool (no path params - use ".*" to auto-approve)
+    pub git_status: ToolFilterConfig,
+    /// Filter patterns for git_stage tool (matches against paths)
+    pub git_stage: ToolFilterConfig,
+    /// Filter patterns for replace_in_files tool (matches against pattern)
+    pub replace_in_files: ToolFilterConfig,
+    /// Filter preset added to the rules above: built-in (`strict`,
+    /// `trusted-repo`, `readonly`) or from `presets`. Switch with `/preset`.
+    pub filter_preset: Option<String>,
+    /// User-defined filter presets (`[tools.presets.<name>.<tool>]`)
+    pub presets: HashMap<String, FilterPreset>,
+    /// Directories (paths or globs) where write_file and edit_file are
+    /// approved without asking, unless a filter pattern decides otherwise
+    pub auto_approve_write_dirs: Vec<String>,
+    /// Project memory settings (`[tools.memory]`)
+    pub memory: MemoryConfig,
+    /// User-defined tools backed by a command template (`[[tools.custom]]`)
+    pub custom: Vec<CustomToolConfig>,
+    /// Regex patterns for shell commands that need a terminal (editors, REPLs, ...).
+    /// Matching commands are refused with guidance instead of hanging.
+    pub interactive_commands: Vec<String>,
+    /// Combined stdout/stderr size in bytes at which a shell command is killed
+    pub shell_max_output_bytes: usize,
+    /// Shell command run after each batch of edit_file/write_file calls (e.g.
+    /// `cargo check --message-format short`); its result goes back to the agent
+    pub post_edit_check: Option<String>,
+    /// Give up on the post-edit check after this many seconds
+    pub post_edit_check_timeout_secs: u64,
+    /// What to do with tool calls no filter pattern matched, by tool category
+    pub approval: ApprovalConfig,
+    /// Seconds an approval prompt waits before deciding on its own (None = wait forever)
+    pub approval_timeout_secs: Option<u64>,
+    /// Decision made when `approval_timeout_secs` runs out
+    pub approval_timeout_action: ApprovalTimeoutAction,
+    /// Back up files to `.codey/backups` before edit_file modifies them
+    pub backup_on_write: bool,
+    /// Backups kept per file when `backup_on_write` is enabled
+    pub backup_retention: usize,
+    /// Make edit_file and replace_in_files fail on files the agent hasn't
+    /// read with read_file (or that changed on disk since it did)
+    pub require_read_before_edit: bool,
+    /// Remote file access for read_file (`[tools.ssh]`)
+    pub ssh: SshConfig,
 }
 
 #[cfg(feature = "cli")]
@@ -341,25 +793,60 @@ impl Default for ToolsConfig {
         Self {
             enabled: vec![
                 names::READ_FILE.to_string(),
+                names::READ_SYMBOL.to_string(),
                 names::WRITE_FILE.to_string(),
                 names::EDIT_FILE.to_string(),
                 names::SHELL.to_string(),
                 names::FETCH_URL.to_string(),
                 names::WEB_SEARCH.to_string(),
                 names::RECORD_CORRECTION.to_string(),
+                names::MEMORY_READ.to_string(),
+                names::MEMORY_WRITE.to_string(),
+                names::GIT_STATUS.to_string(),
+                names::GIT_STAGE.to_string(),
+                names::REPLACE_IN_FILES.to_string(),
             ],
-            shell: ToolFilterConfig::default(),
+            shell: ShellConfig::default(),
             read_file: ToolFilterConfig::default(),
+            read_symbol: ToolFilterConfig::default(),
             write_file: ToolFilterConfig::default(),
-            edit_file: ToolFilterConfig::default(),
+            edit_file: EditFileConfig::default(),
             fetch_url: ToolFilterConfig::default(),
-            web_search: ToolFilterConfig::default(),
+            http_request: ToolFilterConfig::default(),
+            download: DownloadConfig::default(),
+            web_search: WebSearchConfig::default(),
             list_background_tasks: ToolFilterConfig::default(),
             get_background_task: ToolFilterConfig::default(),
             spawn_agent: ToolFilterConfig::default(),
             list_agents: ToolFilterConfig::default(),
             get_agent: ToolFilterConfig::default(),
             record_correction: ToolFilterConfig::default(),
+            ide_open_files: ToolFilterConfig::default(),
+            annotate: ToolFilterConfig::default(),
+            memory_read: ToolFilterConfig::default(),
+            memory_write: ToolFilterConfig::default(),
+            git_status: ToolFilterConfig::default(),
+            git_stage: ToolFilterConfig::default(),
+            replace_in_files: ToolFilterConfig::default(),
+            filter_preset: None,
+            presets: HashMap::new(),
+            auto_approve_write_dirs: Vec::new(),
+            memory: MemoryConfig::default(),
+            custom: Vec::new(),
+            interactive_commands: ShellTool::INTERACTIVE_COMMANDS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            shell_max_output_bytes: SHELL_MAX_OUTPUT_BYTES,
+            post_edit_check: None,
+            post_edit_check_timeout_secs: 120,
+            approval: ApprovalConfig::default(),
+            approval_timeout_secs: None,
+            approval_timeout_action: ApprovalTimeoutAction::Deny,
+            backup_on_write: false,
+            backup_retention: 10,
+            require_read_before_edit: false,
+            ssh: SshConfig::default(),
         }
     }
 }
@@ -369,20 +856,328 @@ impl ToolsConfig {
     /// Build a HashMap of tool filters for compilation
     pub fn filters(&self) -> HashMap<String, ToolFilterConfig> {
         let mut map = HashMap::new();
-        map.insert(names::SHELL.to_string(), self.shell.clone());
+        map.insert(names::SHELL.to_string(), self.shell.filter.clone());
         map.insert(names::READ_FILE.to_string(), self.read_file.clone());
+        map.insert(names::READ_SYMBOL.to_string(), self.read_symbol.clone());
         map.insert(names::WRITE_FILE.to_string(), self.write_file.clone());
-        map.insert(names::EDIT_FILE.to_string(), self.edit_file.clone());
+        map.insert(names::EDIT_FILE.to_string(), self.edit_file.filter.clone());
         map.insert(names::FETCH_URL.to_string(), self.fetch_url.clone());
-        map.insert(names::WEB_SEARCH.to_string(), self.web_search.clone());
+        map.insert(names::HTTP_REQUEST.to_string(), self.http_request.clone());
+        map.insert(names::DOWNLOAD.to_string(), self.download.filter.clone());
+        map.insert(names::WEB_SEARCH.to_string(), self.web_search.filter.clone());
         map.insert(names::LIST_BACKGROUND_TASKS.to_string(), self.list_background_tasks.clone());
         map.insert(names::GET_BACKGROUND_TASK.to_string(), self.get_background_task.clone());
         map.insert(names::SPAWN_AGENT.to_string(), self.spawn_agent.clone());
         map.insert(names::LIST_AGENTS.to_string(), self.list_agents.clone());
         map.insert(names::GET_AGENT.to_string(), self.get_agent.clone());
         map.insert(names::RECORD_CORRECTION.to_string(), self.record_correction.clone());
+        map.insert(names::IDE_OPEN_FILES.to_string(), self.ide_open_files.clone());
+        map.insert(names::ANNOTATE.to_string(), self.annotate.clone());
+        map.insert(names::MEMORY_READ.to_string(), self.memory_read.clone());
+        map.insert(names::MEMORY_WRITE.to_string(), self.memory_write.clone());
+        map.insert(names::GIT_STATUS.to_string(), self.git_status.clone());
+        map.insert(names::GIT_STAGE.to_string(), self.git_stage.clone());
+        map.insert(names::REPLACE_IN_FILES.to_string(), self.replace_in_files.clone());
         map
     }
+
+    /// Tool filters with a preset's rules added. User presets shadow built-in
+    /// ones of the same name.
+    pub fn filters_with_preset(&self, preset: Option<&str>) -> Result<HashMap<String, ToolFilterConfig>> {
+        let mut filters = self.filters();
+        if let Some(name) = preset {
+            let preset = self
+                .presets
+                .get(name)
+                .cloned()
+                .or_else(|| builtin_preset(name))
+                .with_context(|| {
+                    format!("Unknown filter preset '{}' (available: {})", name, self.preset_names().join(", "))
+                })?;
+            apply_preset(&mut filters, &preset);
+        }
+        Ok(filters)
+    }
+
+    /// Compiled filters with a preset's rules and the write directories
+    pub fn compile_filters(&self, preset: Option<&str>) -> Result<ToolFilters> {
+        ToolFilters::compile(&self.filters_with_preset(preset)?)?.with_write_dirs(&self.auto_approve_write_dirs)
+    }
+
+    /// Built-in and user-defined preset names, sorted
+    pub fn preset_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_PRESETS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(self.presets.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// Project memory (`[tools.memory]`): notes the agent keeps in `.codey/memory.json`
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryConfig {
+    /// Add the saved notes to the system prompt at session start
+    pub inject: bool,
+    /// Upper bound on the total size of all notes, in bytes
+    pub max_bytes: usize,
+}
+
+#[cfg(feature = "cli")]
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            inject: true,
+            max_bytes: DEFAULT_MEMORY_MAX_BYTES,
+        }
+    }
+}
+
+/// Remote file access (`[tools.ssh]`): lets read_file read `ssh://host/path`
+/// and `host:/path` through the system ssh client. Remote paths are read-only.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    pub enabled: bool,
+    /// Extra arguments for every ssh invocation, e.g. `["-i", "~/.ssh/dev_key"]`
+    pub options: Vec<String>,
+    /// Give up on a remote read after this many seconds
+    pub timeout_secs: u64,
+}
+
+#[cfg(feature = "cli")]
+impl Default for SshConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            options: Vec::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// edit_file settings: the usual allow/deny filters plus a matching fallback
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditFileConfig {
+    #[serde(flatten)]
+    pub filter: ToolFilterConfig,
+    /// When old_string isn't found exactly, retry ignoring leading/trailing
+    /// whitespace on each line and apply the edit if that match is unique
+    pub fuzzy: bool,
+    /// With `fuzzy`, also treat runs of whitespace within a line as equal
+    pub fuzzy_collapse_whitespace: bool,
+}
+
+/// web_search settings: the usual allow/deny filters plus a timeout
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSearchConfig {
+    #[serde(flatten)]
+    pub filter: ToolFilterConfig,
+    /// Give up on the search provider after this many seconds, so a slow
+    /// provider fails the search instead of stalling the turn
+    pub timeout_secs: u64,
+}
+
+#[cfg(feature = "cli")]
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            filter: ToolFilterConfig::default(),
+            timeout_secs: WebSearchTool::DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// download settings: allow/deny filters on the URL plus size and stall limits
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadConfig {
+    #[serde(flatten)]
+    pub filter: ToolFilterConfig,
+    /// Refuse (or stop) downloads larger than this many bytes
+    pub max_bytes: u64,
+    /// Retry a connection or read that stalls for this many seconds
+    pub timeout_secs: u64,
+}
+
+#[cfg(feature = "cli")]
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            filter: ToolFilterConfig::default(),
+            max_bytes: DownloadTool::DEFAULT_MAX_BYTES,
+            timeout_secs: DownloadTool::DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// shell settings: the usual allow/deny filters plus an optional sandbox
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    #[serde(flatten)]
+    pub filter: ToolFilterConfig,
+    /// Restricted environment for commands (`[tools.shell.sandbox]`)
+    pub sandbox: ShellSandboxConfig,
+}
+
+/// Run shell commands, custom tools and the post-edit check with a scrubbed
+/// environment, optionally under a wrapper such as `firejail`, `bwrap` or `unshare`
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellSandboxConfig {
+    pub enabled: bool,
+    /// Command that shell commands run under, e.g. `["firejail", "--quiet"]`.
+    /// Empty runs commands directly, with only the environment restricted.
+    pub wrapper: Vec<String>,
+    /// Environment variables passed through; everything else is dropped
+    pub env: Vec<String>,
+    /// PATH for sandboxed commands
+    pub path: String,
+    /// Cut off network access, for wrappers that support it
+    pub no_network: bool,
+}
+
+#[cfg(feature = "cli")]
+impl Default for ShellSandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wrapper: Vec::new(),
+            env: ["HOME", "USER", "LANG", "TERM", "TMPDIR"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            path: "/usr/local/bin:/usr/bin:/bin".to_string(),
+            no_network: false,
+        }
+    }
+}
+
+/// Default handling for a tool call that no filter pattern decided
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalDefault {
+    /// Run without asking
+    Auto,
+    /// Ask the user
+    Prompt,
+    /// Refuse without asking
+    Deny,
+}
+
+/// Per-category approval defaults (`[tools.approval]`).
+/// Per-tool filter patterns take precedence over these.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// Tools that only read local state (read_file, read_symbol, open_file, task/agent status)
+    pub read: ApprovalDefault,
+    /// Tools that modify files
+    pub write: ApprovalDefault,
+    /// Tools that run commands (shell, custom tools, MCP tools, sub-agents)
+    pub exec: ApprovalDefault,
+    /// Tools that access the network
+    pub network: ApprovalDefault,
+}
+
+#[cfg(feature = "cli")]
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            read: ApprovalDefault::Auto,
+            write: ApprovalDefault::Prompt,
+            exec: ApprovalDefault::Prompt,
+            network: ApprovalDefault::Prompt,
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ApprovalConfig {
+    /// Decision for a category, or None if the user should be asked
+    pub fn decision(&self, category: ToolCategory) -> Option<ToolDecision> {
+        let default = match category {
+            ToolCategory::Read => self.read,
+            ToolCategory::Write => self.write,
+            ToolCategory::Exec => self.exec,
+            ToolCategory::Network => self.network,
+        };
+        match default {
+            ApprovalDefault::Auto => Some(ToolDecision::Approve),
+            ApprovalDefault::Prompt => None,
+            ApprovalDefault::Deny => Some(ToolDecision::Deny),
+        }
+    }
+}
+
+/// Decision for a tool whose approval prompt timed out
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalTimeoutAction {
+    #[default]
+    Deny,
+    Approve,
+}
+
+#[cfg(feature = "cli")]
+impl ApprovalTimeoutAction {
+    pub fn decision(self) -> ToolDecision {
+        match self {
+            ApprovalTimeoutAction::Deny => ToolDecision::Deny,
+            ApprovalTimeoutAction::Approve => ToolDecision::Approve,
+        }
+    }
+}
+
+/// A tool that runs a command template with the agent's params substituted in.
+/// Placeholders look like `{param}`; the command is run without a shell.
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomToolConfig {
+    /// Tool name exposed to the model
+    pub name: String,
+    /// Description shown to the model
+    pub description: String,
+    /// Command template, e.g. `./scripts/deploy.sh --env {env}`
+    pub command: String,
+    /// JSON schema for the params (defaults to one required string per placeholder)
+    pub schema: Option<serde_json::Value>,
+    /// Directory to run the command in (defaults to the current directory)
+    pub working_dir: Option<String>,
+    /// Seconds before the command is killed
+    pub timeout_secs: u64,
+}
+
+#[cfg(feature = "cli")]
+impl Default for CustomToolConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: String::new(),
+            command: String::new(),
+            schema: None,
+            working_dir: None,
+            timeout_secs: 120,
+        }
+    }
 }
 
 /// IDE integration configuration
@@ -391,6 +1186,9 @@ impl ToolsConfig {
 #[serde(default)]
 pub struct IdeConfig {
     pub nvim: NvimConfig,
+    /// Milliseconds to wait for the IDE to show or close a preview before
+    /// giving up. Repeated timeouts disconnect the IDE for the session.
+    pub timeout_ms: u64,
 }
 
 #[cfg(feature = "cli")]
@@ -398,6 +1196,7 @@ impl Default for IdeConfig {
     fn default() -> Self {
         Self {
             nvim: NvimConfig::default(),
+            timeout_ms: 2000,
         }
     }
 }
@@ -415,6 +1214,9 @@ pub struct NvimConfig {
     pub show_diffs: bool,
     /// Auto-reload buffers after file edits
     pub auto_reload: bool,
+    /// Extension → nvim filetype mappings for previews, over the built-in
+    /// table. Extensions may span dots (`"tsx.snap" = "typescriptreact"`).
+    pub filetypes: HashMap<String, String>,
 }
 
 #[cfg(feature = "cli")]
@@ -425,6 +1227,7 @@ impl Default for NvimConfig {
             socket: None,
             show_diffs: true,
             auto_reload: true,
+            filetypes: HashMap::new(),
         }
     }
 }
@@ -471,6 +1274,48 @@ impl Default for BrowserConfig {
     }
 }
 
+/// MCP (Model Context Protocol) server configuration
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Servers keyed by name; the name prefixes each discovered tool
+    pub servers: HashMap<String, McpServerConfig>,
+}
+
+/// A single MCP server, reached either over stdio (`command`) or HTTP (`url`)
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpServerConfig {
+    /// Executable to spawn for the stdio transport
+    pub command: Option<String>,
+    /// Arguments passed to `command`
+    pub args: Vec<String>,
+    /// Extra environment variables for `command`
+    pub env: HashMap<String, String>,
+    /// Endpoint for the streamable HTTP transport (used when `command` is not set)
+    pub url: Option<String>,
+    /// Extra HTTP headers sent with every request (e.g. Authorization)
+    pub headers: HashMap<String, String>,
+    /// Set to false to keep a server configured without connecting to it
+    pub enabled: bool,
+}
+
+#[cfg(feature = "cli")]
+impl Default for McpServerConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            args: Vec::new(),
+            env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
+            enabled: true,
+        }
+    }
+}
+
 #[cfg(all(test, feature = "cli"))]
 mod tests {
     use super::*;
@@ -533,12 +1378,380 @@ allow = ["\\.rs$"]
 deny = ["\\.env$"]
 "#;
         let config: Config = toml::from_str(toml).unwrap();
-        assert_eq!(config.tools.shell.allow, vec!["^ls\\b", "^cat\\b"]);
-        assert_eq!(config.tools.shell.deny, vec!["rm -rf"]);
+        assert_eq!(config.tools.shell.filter.allow, vec!["^ls\\b", "^cat\\b"]);
+        assert_eq!(config.tools.shell.filter.deny, vec!["rm -rf"]);
         assert_eq!(config.tools.read_file.allow, vec!["\\.rs$"]);
         assert_eq!(config.tools.read_file.deny, vec!["\\.env$"]);
     }
 
+    #[test]
+    fn test_parse_shell_sandbox() {
+        let toml = r#"
+[tools.shell]
+deny = ["rm -rf"]
+
+[tools.shell.sandbox]
+enabled = true
+wrapper = ["firejail", "--quiet"]
+env = ["HOME"]
+no_network = true
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let sandbox = &config.tools.shell.sandbox;
+        assert_eq!(config.tools.shell.filter.deny, vec!["rm -rf"]);
+        assert!(sandbox.enabled && sandbox.no_network);
+        assert_eq!(sandbox.wrapper, vec!["firejail", "--quiet"]);
+        assert_eq!(sandbox.env, vec!["HOME"]);
+        assert_eq!(sandbox.path, "/usr/local/bin:/usr/bin:/bin");
+        assert!(!Config::default().tools.shell.sandbox.enabled);
+    }
+
+    #[test]
+    fn test_parse_web_search_timeout() {
+        assert_eq!(Config::default().tools.web_search.timeout_secs, 20);
+
+        let toml = r#"
+[tools.web_search]
+allow = ["rust"]
+timeout_secs = 5
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.web_search.timeout_secs, 5);
+        assert_eq!(config.tools.filters()[names::WEB_SEARCH].allow, vec!["rust"]);
+    }
+
+    #[test]
+    fn test_parse_filter_presets() {
+        let toml = r#"
+[tools]
+filter_preset = "ci"
+
+[tools.presets.ci.shell]
+allow = ["^cargo test\\b"]
+deny = ["git push"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.filter_preset.as_deref(), Some("ci"));
+        assert_eq!(config.tools.presets["ci"]["shell"].deny, vec!["git push"]);
+
+        let filters = config.tools.filters_with_preset(Some("ci")).unwrap();
+        assert_eq!(filters[names::SHELL].allow, vec!["^cargo test\\b"]);
+        assert!(config.tools.filters_with_preset(Some("readonly")).is_ok());
+        assert!(config.tools.filters_with_preset(Some("nonexistent")).is_err());
+        assert_eq!(config.tools.preset_names(), vec!["ci", "readonly", "strict", "trusted-repo"]);
+    }
+
+    #[test]
+    fn test_parse_post_edit_check() {
+        assert!(Config::default().tools.post_edit_check.is_none());
+
+        let toml = r#"
+[tools]
+post_edit_check = "cargo check --message-format short"
+post_edit_check_timeout_secs = 300
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.post_edit_check.as_deref(), Some("cargo check --message-format short"));
+        assert_eq!(config.tools.post_edit_check_timeout_secs, 300);
+    }
+
+    #[test]
+    fn test_parse_welcome_message() {
+        assert_eq!(Config::default().general.welcome_message, WelcomeMessage::Enabled(true));
+
+        let config: Config = toml::from_str("[general]\nwelcome_message = false\n").unwrap();
+        assert_eq!(config.general.welcome_message, WelcomeMessage::Enabled(false));
+
+        let config: Config = toml::from_str("[general]\nwelcome_message = \"Back at it.\"\n").unwrap();
+        assert_eq!(config.general.welcome_message, WelcomeMessage::Custom("Back at it.".into()));
+    }
+
+    #[test]
+    fn test_parse_vi_mode() {
+        assert!(!Config::default().general.vi_mode);
+        let config: Config = toml::from_str("[general]\nvi_mode = true\n").unwrap();
+        assert!(config.general.vi_mode);
+    }
+
+    #[test]
+    fn test_parse_per_turn_cost() {
+        assert!(!Config::default().general.show_per_turn_cost);
+
+        let toml = r#"
+[general]
+show_per_turn_cost = true
+
+[general.pricing]
+input = 2.0
+output = 10.0
+cache_write = 2.5
+cache_read = 0.2
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.general.show_per_turn_cost);
+        assert_eq!(config.general.pricing.map(|p| p.output), Some(10.0));
+    }
+
+    #[test]
+    fn test_parse_audit_log() {
+        assert!(!Config::default().general.audit_log);
+
+        let toml = r#"
+[general]
+audit_log = true
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.general.audit_log);
+    }
+
+    #[test]
+    fn test_parse_mcp_servers() {
+        let toml = r#"
+[mcp.servers.github]
+command = "github-mcp-server"
+args = ["stdio"]
+env = { GITHUB_TOKEN = "token" }
+
+[mcp.servers.docs]
+url = "https://example.com/mcp"
+enabled = false
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let github = &config.mcp.servers["github"];
+        assert_eq!(github.command.as_deref(), Some("github-mcp-server"));
+        assert_eq!(github.args, vec!["stdio"]);
+        assert_eq!(github.env["GITHUB_TOKEN"], "token");
+        assert!(github.enabled);
+
+        let docs = &config.mcp.servers["docs"];
+        assert_eq!(docs.url.as_deref(), Some("https://example.com/mcp"));
+        assert!(!docs.enabled);
+    }
+
+    #[test]
+    fn test_parse_interactive_commands() {
+        let config = Config::default();
+        assert_eq!(
+            config.tools.interactive_commands.len(),
+            ShellTool::INTERACTIVE_COMMANDS.len()
+        );
+
+        let toml = r#"
+[tools]
+interactive_commands = ["^my-tui\\b"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.interactive_commands, vec!["^my-tui\\b"]);
+    }
+
+    #[test]
+    fn test_parse_shell_max_output_bytes() {
+        assert_eq!(Config::default().tools.shell_max_output_bytes, 1024 * 1024);
+
+        let toml = r#"
+[tools]
+shell_max_output_bytes = 200000
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.shell_max_output_bytes, 200000);
+    }
+
+    #[test]
+    fn test_parse_custom_tools() {
+        let toml = r#"
+[[tools.custom]]
+name = "deploy"
+description = "Deploy to an environment"
+command = "./scripts/deploy.sh --env {env}"
+
+[[tools.custom]]
+name = "count_lines"
+description = "Count lines in a file"
+command = "wc -l {path}"
+timeout_secs = 10
+schema = { type = "object", properties = { path = { type = "string" } }, required = ["path"] }
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.custom.len(), 2);
+        assert_eq!(config.tools.custom[0].name, "deploy");
+        assert_eq!(config.tools.custom[0].timeout_secs, 120);
+        assert!(config.tools.custom[0].schema.is_none());
+        assert_eq!(config.tools.custom[1].timeout_secs, 10);
+        let schema = config.tools.custom[1].schema.as_ref().unwrap();
+        assert_eq!(schema["required"][0], "path");
+        // Built-in tool defaults are untouched
+        assert!(config.tools.enabled.contains(&names::SHELL.to_string()));
+    }
+
+    #[test]
+    fn test_parse_transcript_tool_output_limit() {
+        assert!(Config::default().general.transcript_tool_output_limit.is_none());
+        assert_eq!(Config::default().general.param_limit(), Some(8000));
+
+        let toml = r#"
+[general]
+transcript_tool_output_limit = 2000
+transcript_thinking_limit = 4000
+transcript_param_limit = 500
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.general.transcript_tool_output_limit, Some(2000));
+        assert_eq!(config.general.transcript_thinking_limit, Some(4000));
+        assert_eq!(config.general.param_limit(), Some(500));
+    }
+
+    #[test]
+    fn test_parse_tool_result_compaction() {
+        let general = Config::default().general;
+        assert!(general.tool_result_compaction_threshold.is_none());
+        assert_eq!(general.tool_result_keep_recent, 10);
+
+        let toml = r#"
+[general]
+tool_result_compaction_threshold = 120000
+tool_result_keep_recent = 4
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.general.tool_result_compaction_threshold, Some(120_000));
+        assert_eq!(config.general.tool_result_keep_recent, 4);
+    }
+
+    #[test]
+    fn test_parse_fallback_models() {
+        assert!(Config::default().general.fallback_models.is_empty());
+
+        let toml = r#"
+[general]
+fallback_models = ["claude-sonnet-4-5", "openrouter::openai/gpt-5"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let runtime = AgentRuntimeConfig::foreground(&config);
+        assert_eq!(
+            runtime.fallback_models,
+            vec!["claude-sonnet-4-5", "openrouter::openai/gpt-5"]
+        );
+    }
+
+    #[test]
+    fn test_parse_reasoning() {
+        let runtime = AgentRuntimeConfig::foreground(&Config::default());
+        assert_eq!(runtime.reasoning, None);
+        assert_eq!(runtime.thinking_budget_for("claude-opus-4-6", runtime.max_tokens), 2_000);
+
+        let toml = r#"
+[general]
+reasoning = "high"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let runtime = AgentRuntimeConfig::foreground(&config);
+        assert_eq!(runtime.reasoning, Some(ReasoningLevel::High));
+        // Half of the default max_tokens, leaving the rest for the response
+        assert_eq!(runtime.thinking_budget_for("claude-opus-4-6", runtime.max_tokens), 4_096);
+        assert_eq!(runtime.thinking_budget_for("claude-opus-4-6", 32_000), 16_384);
+        assert_eq!(runtime.thinking_budget_for("gpt-4o", runtime.max_tokens), 0);
+
+        assert!(toml::from_str::<Config>("[general]\nreasoning = \"extreme\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_auto_continue_on_length() {
+        assert_eq!(AgentRuntimeConfig::foreground(&Config::default()).length_continuations, 0);
+        let toml = r#"
+[general]
+auto_continue_on_length = true
+max_auto_continues = 2
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(AgentRuntimeConfig::foreground(&config).length_continuations, 2);
+    }
+
+    #[test]
+    fn test_parse_interleaved_thinking_rounds() {
+        assert!(Config::default().general.interleaved_thinking_rounds.is_none());
+
+        let toml = r#"
+[general]
+interleaved_thinking_rounds = 2
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.general.interleaved_thinking_rounds, Some(2));
+        let runtime = AgentRuntimeConfig::foreground(&config);
+        assert_eq!(runtime.interleaved_thinking_rounds, Some(2));
+    }
+
+    #[test]
+    fn test_parse_context_window() {
+        assert!(Config::default().general.context_window.is_none());
+        let config: Config = toml::from_str("[general]\ncontext_window = 128000\n").unwrap();
+        assert_eq!(config.general.context_window, Some(128_000));
+        assert_eq!(AgentRuntimeConfig::foreground(&config).context_window, Some(128_000));
+    }
+
+    #[test]
+    fn test_parse_request_timeouts() {
+        let runtime = AgentRuntimeConfig::foreground(&Config::default());
+        assert_eq!(runtime.request_timeout_secs, None);
+        assert_eq!(runtime.stream_idle_timeout_secs, Some(120));
+
+        let config: Config =
+            toml::from_str("[general]\nrequest_timeout_secs = 600\nstream_idle_timeout_secs = 0\n").unwrap();
+        let runtime = AgentRuntimeConfig::foreground(&config);
+        assert_eq!(runtime.request_timeout_secs, Some(600));
+        assert_eq!(runtime.stream_idle_timeout_secs, None);
+    }
+
+    #[test]
+    fn test_parse_restore_context_fraction() {
+        assert_eq!(Config::default().general.restore_context_fraction, 0.5);
+        let config: Config = toml::from_str("[general]\nrestore_context_fraction = 0.3\n").unwrap();
+        assert_eq!(AgentRuntimeConfig::foreground(&config).restore_context_fraction, 0.3);
+    }
+
+    #[test]
+    fn test_parse_pinned_files() {
+        assert!(Config::default().general.pinned_files.is_empty());
+
+        let toml = r#"
+[general]
+pinned_files = ["docs/DESIGN.md", "api/openapi.yaml"]
+pinned_max_bytes = 32768
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.general.pinned_files,
+            vec![PathBuf::from("docs/DESIGN.md"), PathBuf::from("api/openapi.yaml")]
+        );
+        assert_eq!(config.general.pinned_max_bytes, 32768);
+    }
+
+    #[test]
+    fn test_compaction_prompt_overrides() {
+        let config = Config::default();
+        assert!(config.general.compaction_prompt_path().is_none());
+        assert!(config.validate().is_ok());
+
+        let toml = r#"
+[general]
+compaction_prompt_file = "/nonexistent/compaction.md"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.general.compaction_prompt_path(),
+            Some(PathBuf::from("/nonexistent/compaction.md"))
+        );
+        assert!(config.validate().is_err());
+
+        // Inline prompt takes precedence over the file
+        let toml = r#"
+[general]
+compaction_prompt = "Summarize the open tasks."
+compaction_prompt_file = "/nonexistent/compaction.md"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.general.compaction_prompt_path().is_none());
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_agent_persona_defaults() {
         let config = Config::default();
@@ -574,4 +1787,188 @@ name = "Assistant"
         assert!(config.agent.system_prompt.is_none());
         assert_eq!(config.agent.name(), "Assistant");
     }
+
+    #[test]
+    fn test_parse_approval_defaults() {
+        let defaults = ApprovalConfig::default();
+        assert_eq!(defaults.decision(ToolCategory::Read), Some(ToolDecision::Approve));
+        assert_eq!(defaults.decision(ToolCategory::Exec), None);
+
+        let toml = r#"
+[tools.approval]
+write = "auto"
+network = "deny"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let approval = &config.tools.approval;
+        assert_eq!(approval.read, ApprovalDefault::Auto);
+        assert_eq!(approval.decision(ToolCategory::Write), Some(ToolDecision::Approve));
+        assert_eq!(approval.decision(ToolCategory::Exec), None);
+        assert_eq!(approval.decision(ToolCategory::Network), Some(ToolDecision::Deny));
+    }
+
+    #[test]
+    fn test_parse_approval_timeout() {
+        let defaults = Config::default();
+        assert_eq!(defaults.tools.approval_timeout_secs, None);
+        assert_eq!(defaults.tools.approval_timeout_action.decision(), ToolDecision::Deny);
+
+        let toml = r#"
+[tools]
+approval_timeout_secs = 30
+approval_timeout_action = "approve"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.tools.approval_timeout_secs, Some(30));
+        assert_eq!(config.tools.approval_timeout_action, ApprovalTimeoutAction::Approve);
+    }
+
+    #[test]
+    fn test_redacted_toml() {
+        let toml = r#"
+[auth]
+api_key = "sk-ant-secret"
+
+[mcp.servers.github]
+command = "github-mcp"
+env = { GITHUB_TOKEN = "ghp_secret" }
+headers = { Authorization = "Bearer secret" }
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let printed = config.to_redacted_toml().unwrap();
+        assert!(!printed.contains("secret"), "{}", printed);
+        assert!(printed.contains("github-mcp"));
+
+        // The output is itself a valid config
+        let reparsed: Config = toml::from_str(&printed).unwrap();
+        assert_eq!(reparsed.auth.api_key.as_deref(), Some(REDACTED));
+        assert_eq!(reparsed.agents.foreground.max_tokens, config.agents.foreground.max_tokens);
+    }
+
+    #[test]
+    fn test_parse_confirm_quit() {
+        assert!(Config::default().ui.confirm_quit);
+        let config: Config = toml::from_str("[ui]\nconfirm_quit = false\n").unwrap();
+        assert!(!config.ui.confirm_quit);
+    }
+
+    #[test]
+    fn test_parse_ssh_config() {
+        assert!(!Config::default().tools.ssh.enabled);
+        let toml = r#"
+[tools.ssh]
+enabled = true
+options = ["-i", "~/.ssh/dev_key"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.tools.ssh.enabled);
+        assert_eq!(config.tools.ssh.options, vec!["-i", "~/.ssh/dev_key"]);
+        assert_eq!(config.tools.ssh.timeout_secs, 30);
+    }
+
+    #[test]
+    fn test_parse_inline_diffs() {
+        assert!(!Config::default().ui.inline_diffs);
+        let config: Config = toml::from_str("[ui]\ninline_diffs = true\n").unwrap();
+        assert!(config.ui.inline_diffs);
+    }
+
+    #[test]
+    fn test_parse_max_line_length() {
+        assert_eq!(Config::default().ui.max_line_length, DEFAULT_MAX_LINE_LENGTH);
+        let config: Config = toml::from_str("[ui]\nmax_line_length = 500\n").unwrap();
+        assert_eq!(config.ui.max_line_length, 500);
+    }
+
+    #[test]
+    fn test_parse_paste_attach_threshold() {
+        assert_eq!(Config::default().ui.paste_attach_threshold, 500);
+        let config: Config = toml::from_str("[ui]\npaste_attach_threshold = 0\n").unwrap();
+        assert_eq!(config.ui.paste_attach_threshold, 0);
+    }
+
+    #[test]
+    fn test_parse_memory_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.tools.memory.inject);
+
+        let toml = r#"
+[tools.memory]
+inject = false
+max_bytes = 4096
+
+[tools.memory_write]
+allow = ["^build$"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(!config.tools.memory.inject);
+        assert_eq!(config.tools.memory.max_bytes, 4096);
+        assert_eq!(config.tools.memory_write.allow, vec!["^build$"]);
+    }
+
+    #[test]
+    fn test_parse_ide_timeout() {
+        assert_eq!(Config::default().ide.timeout_ms, 2000);
+
+        let toml = r#"
+[ide]
+timeout_ms = 500
+
+[ide.nvim]
+show_diffs = false
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.ide.timeout_ms, 500);
+        assert!(!config.ide.nvim.show_diffs);
+    }
+
+    #[test]
+    fn test_parse_nvim_filetypes() {
+        let toml = r#"
+[ide.nvim.filetypes]
+astro = "astro"
+"tsx.snap" = "typescriptreact"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.ide.nvim.filetypes["tsx.snap"], "typescriptreact");
+        assert_eq!(config.ide.nvim.filetypes.len(), 2);
+        assert!(config.ide.nvim.show_diffs);
+    }
+
+    #[test]
+    fn test_parse_edit_file_fuzzy() {
+        let toml = r#"
+[tools.edit_file]
+deny = ["\\.env$"]
+fuzzy = true
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.tools.edit_file.fuzzy);
+        assert!(!config.tools.edit_file.fuzzy_collapse_whitespace);
+        assert_eq!(config.tools.edit_file.filter.deny, vec!["\\.env$"]);
+        assert!(config.tools.filters()[names::EDIT_FILE].allow.is_empty());
+    }
+
+    #[test]
+    fn test_parse_anthropic_header_overrides() {
+        let headers = Config::default().auth.anthropic.headers();
+        assert!(headers.beta.is_none());
+        assert!(headers.user_agent.is_none());
+
+        let toml = r#"
+[auth]
+method = "api_key"
+
+[auth.anthropic]
+beta_headers = []
+user_agent = "my-gateway-client/1.0"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let runtime = AgentRuntimeConfig::foreground(&config);
+        assert_eq!(runtime.anthropic_headers.beta, Some(Vec::new()));
+        assert_eq!(
+            runtime.anthropic_headers.user_agent.as_deref(),
+            Some("my-gateway-client/1.0")
+        );
+    }
 }
@@ -0,0 +1,165 @@
+//! Inline diff rendering
+//!
+//! Renders the difference between two texts as terminal lines: removed lines
+//! in red, added lines in green, and within a changed line only the words that
+//! actually changed highlighted, so a one-word edit in a long line stands out.
+//! Also produces plain unified diffs, for showing changes to many files in the
+//! IDE.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+use crate::ide::FileDiff;
+use crate::tools::io::{format_for_user, DEFAULT_TAB_WIDTH};
+
+/// Line diff of `old` against `new`, with word-level highlighting of changed lines
+pub fn render_diff(old: &str, new: &str) -> Vec<Line<'static>> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+    for op in diff.ops() {
+        render_op(&diff, op, &mut lines);
+    }
+    lines
+}
+
+/// Like [`render_diff`], but only the changed lines and `context` lines around
+/// them, with a `...` line between hunks. For whole files, where most lines
+/// haven't changed.
+pub fn render_hunks(old: &str, new: &str, context: usize) -> Vec<Line<'static>> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+    for (i, group) in diff.grouped_ops(context).iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::from(Span::styled("  ...", Style::default().fg(Color::DarkGray))));
+        }
+        for op in group {
+            render_op(&diff, op, &mut lines);
+        }
+    }
+    lines
+}
+
+fn render_op(diff: &TextDiff<'_, '_, '_, str>, op: &DiffOp, lines: &mut Vec<Line<'static>>) {
+    for change in diff.iter_inline_changes(op) {
+        let (sign, color) = match change.tag() {
+            ChangeTag::Delete => ("- ", Color::Red),
+            ChangeTag::Insert => ("+ ", Color::Green),
+            ChangeTag::Equal => ("  ", Color::DarkGray),
+        };
+        let style = Style::default().fg(color);
+        let changed = Style::default().fg(Color::Black).bg(color);
+
+        let mut spans = vec![Span::styled(format!("  {}", sign), style)];
+        for (emphasized, value) in change.iter_strings_lossy() {
+            let value = format_for_user(value.trim_end_matches('\n'), DEFAULT_TAB_WIDTH);
+            if value.is_empty() {
+                continue;
+            }
+            spans.push(Span::styled(value, if emphasized { changed } else { style }));
+        }
+        lines.push(Line::from(spans));
+    }
+}
+
+/// Number of lines added and removed going from `old` to `new`
+pub fn line_counts(old: &str, new: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+/// Unified diff of several files, in the format of `git diff`
+pub fn unified(files: &[FileDiff]) -> String {
+    files
+        .iter()
+        .map(|file| {
+            TextDiff::from_lines(&file.original, &file.modified)
+                .unified_diff()
+                .context_radius(3)
+                .header(&format!("a/{}", file.path), &format!("b/{}", file.path))
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn highlighted(line: &Line) -> String {
+        line.spans
+            .iter()
+            .filter(|s| s.style.bg.is_some())
+            .map(|s| s.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_diff_highlights_changed_words() {
+        let old = "fn main() {\n    let total = price * quantity;\n}\n";
+        let new = "fn main() {\n    let total = price * count;\n}\n";
+        let lines = render_diff(old, new);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(text(&lines[0]), "    fn main() {");
+        assert_eq!(text(&lines[1]), "  -     let total = price * quantity;");
+        assert_eq!(text(&lines[2]), "  +     let total = price * count;");
+        assert!(highlighted(&lines[1]).contains("quantity"));
+        assert!(!highlighted(&lines[1]).contains("price"));
+        assert!(highlighted(&lines[2]).contains("count"));
+        assert_eq!(highlighted(&lines[0]), "");
+    }
+
+    #[test]
+    fn test_render_diff_added_lines() {
+        let lines = render_diff("", "one\ntwo\n");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(text(&lines[1]), "  + two");
+    }
+
+    #[test]
+    fn test_render_hunks_and_counts() {
+        let old: String = (1..=20).map(|n| format!("line {:02}\n", n)).collect();
+        let new = old.replace("line 02\n", "line two\n").replace("line 18\n", "");
+        let lines = render_hunks(&old, &new, 1);
+
+        let rendered: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "    line 01",
+                "  - line 02",
+                "  + line two",
+                "    line 03",
+                "  ...",
+                "    line 17",
+                "  - line 18",
+                "    line 19",
+            ]
+        );
+        assert_eq!(line_counts(&old, &new), (1, 2));
+    }
+
+    #[test]
+    fn test_unified() {
+        let files = vec![FileDiff {
+            path: "src/a.rs".to_string(),
+            original: "fn old() {}\n".to_string(),
+            modified: "fn new() {}\n".to_string(),
+        }];
+        let diff = unified(&files);
+        assert!(diff.starts_with("--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1"), "{}", diff);
+        assert!(diff.contains("-fn old() {}\n+fn new() {}\n"));
+    }
+}
@@ -3,6 +3,8 @@
 //! Effects are actions that tools delegate to the app layer, such as:
 //! - Requesting user approval for a tool call
 //! - Opening files or showing previews in the IDE
+//! - Running follow-up shell commands through the approval flow
+//! - Registering files a tool produced as session artifacts
 //! - Spawning sub-agents
 //!
 //! The `EffectQueue` (CLI-only) manages pending effects with resource exclusivity:
@@ -59,6 +61,14 @@ pub enum Effect {
     IdeCheckUnsavedEdits {
         path: PathBuf,
     },
+    /// List files open in the IDE
+    IdeOpenFiles,
+    /// Leave an inline note at a line in the IDE
+    IdeAnnotate {
+        path: PathBuf,
+        line: u32,
+        text: String,
+    },
 
     // === Background Tasks ===
     ListBackgroundTasks,
@@ -66,6 +76,23 @@ pub enum Effect {
         task_id: String,
     },
 
+    // === Commands ===
+    /// Run a shell command on behalf of the tool, as its own `shell` call with
+    /// the usual approval and streaming. The command's output (or error)
+    /// becomes the effect's result.
+    RunCommand {
+        command: String,
+        working_dir: Option<String>,
+    },
+
+    // === Artifacts ===
+    /// Register a file the tool produced, so the user can open it or attach
+    /// it to a later message
+    Artifact {
+        path: PathBuf,
+        kind: String,
+    },
+
     // === Sub-Agents ===
     /// Spawn a sub-agent. App registers it and polls through main loop.
     #[cfg(feature = "cli")]
@@ -111,11 +138,27 @@ impl std::fmt::Debug for Effect {
                 .debug_struct("IdeCheckUnsavedEdits")
                 .field("path", path)
                 .finish(),
+            Effect::IdeOpenFiles => f.write_str("IdeOpenFiles"),
+            Effect::IdeAnnotate { path, line, .. } => f
+                .debug_struct("IdeAnnotate")
+                .field("path", path)
+                .field("line", line)
+                .finish_non_exhaustive(),
             Effect::ListBackgroundTasks => f.write_str("ListBackgroundTasks"),
             Effect::GetBackgroundTask { task_id } => f
                 .debug_struct("GetBackgroundTask")
                 .field("task_id", task_id)
                 .finish(),
+            Effect::RunCommand { command, working_dir } => f
+                .debug_struct("RunCommand")
+                .field("command", command)
+                .field("working_dir", working_dir)
+                .finish(),
+            Effect::Artifact { path, kind } => f
+                .debug_struct("Artifact")
+                .field("path", path)
+                .field("kind", kind)
+                .finish(),
             #[cfg(feature = "cli")]
             Effect::SpawnAgent { label, .. } => f
                 .debug_struct("SpawnAgent")
@@ -320,6 +363,11 @@ impl EffectQueue {
         self.pending.remove(idx)
     }
 
+    /// The active (acknowledged) approval, left in the queue
+    pub fn active_approval(&self) -> Option<&PendingEffect> {
+        self.pending.iter().find(|p| p.is_approval() && p.acknowledged)
+    }
+
     /// Check if there's an active approval being shown to the user
     pub fn has_active_approval(&self) -> bool {
         self.pending
@@ -6,8 +6,9 @@
 //! # Architecture
 //!
 //! The [`Ide`] trait defines a bidirectional interface:
-//! - **Output**: Show previews, close previews, reload buffers, navigate to files
-//! - **Input**: Check for unsaved changes
+//! - **Output**: Show previews, close previews, reload buffers, navigate to files,
+//!   annotate lines
+//! - **Input**: Check for unsaved changes, list open files
 //! - **Events**: Selection changes streamed from the IDE
 //!
 //! The app holds an `Option<Box<dyn Ide>>` and calls these methods at appropriate
@@ -36,11 +37,23 @@ pub struct Edit {
     pub new_string: String,
 }
 
+/// A whole file before and after a change
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub original: String,
+    pub modified: String,
+}
+
 /// A preview to show in the IDE before tool execution
 #[derive(Debug, Clone)]
 pub enum ToolPreview {
     /// Show file content (for write_file, showing what will be created)
     File { path: String, content: String },
+    /// Show edits to an existing file as hunks with context (for edit_file)
+    Edits { path: String, edits: Vec<Edit> },
+    /// Show changes to several files at once (for replace_in_files)
+    MultiDiff(Vec<FileDiff>),
 }
 
 /// A text selection from the IDE
@@ -100,9 +113,19 @@ pub trait Ide: Send + Sync {
         column: Option<u32>,
     ) -> Result<()>;
 
+    /// Show an ephemeral note at a line (1-indexed), e.g. as virtual text.
+    /// Notes stay until [`Ide::clear_annotations`] is called.
+    async fn annotate(&self, path: &str, line: u32, text: &str) -> Result<()>;
+
+    /// Remove all notes left by [`Ide::annotate`]
+    async fn clear_annotations(&self) -> Result<()>;
+
     /// Check if a file has unsaved changes
     async fn has_unsaved_changes(&self, path: &str) -> Result<bool>;
 
+    /// Paths of files currently open in the editor
+    async fn open_files(&self) -> Result<Vec<String>>;
+
     // === Events: IDE → App (streaming) ===
 
     /// Poll for the next event from the IDE
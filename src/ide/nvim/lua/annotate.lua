@@ -0,0 +1,26 @@
+-- Show an inline note at the end of a line
+-- Args: path (string), line (number), text (string)
+
+local path, line, text = ...
+
+-- Expand to absolute path if relative
+if not path:match('^/') then
+    local cwd = vim.fn.getcwd()
+    path = cwd .. '/' .. path
+end
+
+-- Normalize the path (resolve . and ..)
+path = vim.fn.fnamemodify(path, ':p')
+
+local ns = vim.api.nvim_create_namespace('codey_annotations')
+local buf = vim.fn.bufadd(path)
+vim.fn.bufload(buf)
+
+local line_count = vim.api.nvim_buf_line_count(buf)
+line = math.max(1, math.min(line, line_count))
+
+vim.api.nvim_buf_set_extmark(buf, ns, line - 1, 0, {
+    virt_text = { { '◆ ' .. text, 'DiagnosticVirtualTextInfo' } },
+    virt_text_pos = 'eol',
+    hl_mode = 'combine',
+})
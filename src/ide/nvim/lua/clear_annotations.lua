@@ -0,0 +1,10 @@
+-- Remove all inline notes left by codey
+-- Args: none
+
+local ns = vim.api.nvim_create_namespace('codey_annotations')
+
+for _, buf in ipairs(vim.api.nvim_list_bufs()) do
+    if vim.api.nvim_buf_is_valid(buf) then
+        vim.api.nvim_buf_clear_namespace(buf, ns, 0, -1)
+    end
+end
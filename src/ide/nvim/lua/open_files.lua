@@ -0,0 +1,16 @@
+-- List files open in listed, loaded buffers
+-- Returns: array of absolute paths
+
+local files = {}
+for _, buf in ipairs(vim.api.nvim_list_bufs()) do
+    if vim.api.nvim_buf_is_loaded(buf)
+        and vim.bo[buf].buflisted
+        and vim.bo[buf].buftype == ''
+    then
+        local name = vim.api.nvim_buf_get_name(buf)
+        if name ~= '' and vim.fn.filereadable(name) == 1 then
+            table.insert(files, vim.fn.fnamemodify(name, ':p'))
+        end
+    end
+end
+return files
@@ -11,6 +11,7 @@
 //! 3. Set via `$NVIM_LISTEN_ADDRESS` environment variable
 #![allow(dead_code)]
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -23,12 +24,30 @@ use tokio::sync::{mpsc, Mutex};
 use tracing::{debug, info, warn};
 
 use super::{Edit, Ide, IdeEvent, Selection, ToolPreview};
+use crate::diff;
 
 /// Type alias for the writer half of the nvim connection
 type NvimWriter = Compat<WriteHalf<UnixStream>>;
 
-/// Detect nvim filetype from file path extension
-fn detect_filetype(path: &str) -> Option<&'static str> {
+/// Detect nvim filetype from a file path. `overrides` (`[ide.nvim.filetypes]`)
+/// take precedence over the built-in table and may span dots, so `tsx.snap`
+/// matches `App.tsx.snap`; the longest matching extension wins.
+fn detect_filetype(path: &str, overrides: &HashMap<String, String>) -> Option<String> {
+    let name = Path::new(path).file_name()?.to_str()?;
+    overrides
+        .iter()
+        .map(|(ext, filetype)| (ext.trim_start_matches('.'), filetype))
+        .filter(|(ext, _)| {
+            name.strip_suffix(*ext)
+                .is_some_and(|stem| stem.ends_with('.'))
+        })
+        .max_by_key(|(ext, _)| ext.len())
+        .map(|(_, filetype)| filetype.clone())
+        .or_else(|| builtin_filetype(path).map(String::from))
+}
+
+/// Built-in filetype for a file path's extension
+fn builtin_filetype(path: &str) -> Option<&'static str> {
     Path::new(path)
         .extension()
         .and_then(|e| e.to_str())
@@ -142,6 +161,8 @@ pub struct Nvim {
     socket_path: PathBuf,
     show_diffs: bool,
     auto_reload: bool,
+    /// Extension → filetype mappings layered over the built-in table
+    filetypes: HashMap<String, String>,
     event_rx: mpsc::Receiver<IdeEvent>,
     /// Our RPC channel ID (for multi-instance coordination)
     channel_id: i64,
@@ -149,11 +170,7 @@ pub struct Nvim {
 
 impl Nvim {
     /// Connect to a Neovim instance at the given socket path
-    async fn connect(
-        socket_path: impl Into<PathBuf>,
-        show_diffs: bool,
-        auto_reload: bool,
-    ) -> Result<Self> {
+    async fn connect(socket_path: impl Into<PathBuf>, config: &crate::config::NvimConfig) -> Result<Self> {
         let socket_path = socket_path.into();
         info!("Connecting to nvim at {:?}", socket_path);
 
@@ -193,8 +210,9 @@ impl Nvim {
         let nvim = Self {
             client: Arc::new(Mutex::new(client)),
             socket_path,
-            show_diffs,
-            auto_reload,
+            show_diffs: config.show_diffs,
+            auto_reload: config.auto_reload,
+            filetypes: config.filetypes.clone(),
             event_rx,
             channel_id,
         };
@@ -212,13 +230,10 @@ impl Nvim {
     /// 2. Tmux session-based socket: /tmp/nvim-{session}.sock
     /// 3. $NVIM_LISTEN_ADDRESS environment variable
     pub async fn discover(config: &crate::config::NvimConfig) -> Result<Option<Self>> {
-        let show_diffs = config.show_diffs;
-        let auto_reload = config.auto_reload;
-
         // 1. Explicit socket path
         if let Some(path) = &config.socket {
             if path.exists() {
-                return Ok(Some(Self::connect(path, show_diffs, auto_reload).await?));
+                return Ok(Some(Self::connect(path, config).await?));
             }
             warn!("Configured nvim socket does not exist: {:?}", path);
         }
@@ -241,9 +256,7 @@ impl Nvim {
                                 "Discovered nvim socket from tmux session: {:?}",
                                 socket_path
                             );
-                            return Ok(Some(
-                                Self::connect(socket_path, show_diffs, auto_reload).await?,
-                            ));
+                            return Ok(Some(Self::connect(socket_path, config).await?));
                         }
                     }
                 }
@@ -258,7 +271,7 @@ impl Nvim {
                     "Discovered nvim socket from NVIM_LISTEN_ADDRESS: {:?}",
                     path
                 );
-                return Ok(Some(Self::connect(path, show_diffs, auto_reload).await?));
+                return Ok(Some(Self::connect(path, config).await?));
             }
         }
 
@@ -427,11 +440,20 @@ impl Ide for Nvim {
         }
         match preview {
             ToolPreview::File { path, content } => {
-                let lang = detect_filetype(path);
-                self.show_file_preview(content, path, lang)
+                let lang = detect_filetype(path, &self.filetypes);
+                self.show_file_preview(content, path, lang.as_deref())
                     .await
                     .context("nvim: failed to show preview")
             },
+            ToolPreview::Edits { path, edits } => self.show_diff_preview(path, edits).await,
+            // One side-by-side diff per file would need a tab each, so show
+            // them all as a single unified diff instead
+            ToolPreview::MultiDiff(files) => {
+                let title = format!("{} files", files.len());
+                self.show_file_preview(&diff::unified(files), &title, Some("diff"))
+                    .await
+                    .context("nvim: failed to show multi-file preview")
+            },
         }
     }
 
@@ -439,8 +461,8 @@ impl Ide for Nvim {
         if !self.show_diffs {
             return Ok(());
         }
-        let lang = detect_filetype(path);
-        self.show_diff_hunks(path, edits, lang)
+        let lang = detect_filetype(path, &self.filetypes);
+        self.show_diff_hunks(path, edits, lang.as_deref())
             .await
             .with_context(|| format!("nvim: failed to show diff preview for {}", path))
     }
@@ -472,6 +494,21 @@ impl Ide for Nvim {
         Ok(())
     }
 
+    async fn annotate(&self, path: &str, line: u32, text: &str) -> Result<()> {
+        let args = vec![Value::from(path), Value::from(line as i64), Value::from(text)];
+        self.exec_lua(include_str!("lua/annotate.lua"), args)
+            .await
+            .with_context(|| format!("nvim: failed to annotate {}:{}", path, line))?;
+        Ok(())
+    }
+
+    async fn clear_annotations(&self) -> Result<()> {
+        self.exec_lua(include_str!("lua/clear_annotations.lua"), vec![])
+            .await
+            .context("nvim: failed to clear annotations")?;
+        Ok(())
+    }
+
     async fn has_unsaved_changes(&self, path: &str) -> Result<bool> {
         let args = vec![Value::from(path)];
         let result = self
@@ -481,6 +518,22 @@ impl Ide for Nvim {
         Ok(result.as_bool().unwrap_or(false))
     }
 
+    async fn open_files(&self) -> Result<Vec<String>> {
+        let result = self
+            .exec_lua(include_str!("lua/open_files.lua"), vec![])
+            .await
+            .context("nvim: failed to list open buffers")?;
+        Ok(result
+            .as_array()
+            .map(|files| {
+                files
+                    .iter()
+                    .filter_map(|f| f.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     async fn next(&mut self) -> Option<IdeEvent> {
         self.event_rx.recv().await
     }
@@ -495,6 +548,24 @@ mod tests {
     use super::*;
     use crate::config::NvimConfig;
 
+    #[test]
+    fn test_detect_filetype_overrides() {
+        let overrides = HashMap::from([
+            ("astro".to_string(), "astro".to_string()),
+            (".tsx.snap".to_string(), "typescriptreact".to_string()),
+            ("rs".to_string(), "rustish".to_string()),
+        ]);
+        assert_eq!(detect_filetype("src/Page.astro", &overrides).as_deref(), Some("astro"));
+        assert_eq!(
+            detect_filetype("__snapshots__/App.tsx.snap", &overrides).as_deref(),
+            Some("typescriptreact")
+        );
+        assert_eq!(detect_filetype("main.rs", &overrides).as_deref(), Some("rustish"));
+        assert_eq!(detect_filetype("main.py", &overrides).as_deref(), Some("python"));
+        assert_eq!(detect_filetype("notes.snap", &overrides), None);
+        assert_eq!(detect_filetype("astro", &overrides), None);
+    }
+
     #[tokio::test]
     #[ignore] // Requires running nvim instance
     async fn test_discover_nvim() {
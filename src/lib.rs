@@ -5,7 +5,7 @@
 //! # Example
 //!
 //! ```no_run
-//! use codey::{Agent, AgentRuntimeConfig, AgentStep, RequestMode, ToolRegistry};
+//! use codey::{Agent, AgentError, AgentRuntimeConfig, AgentStep, RequestMode, ToolRegistry};
 //!
 //! #[tokio::main]
 //! async fn main() {
@@ -29,6 +29,10 @@
 //!                 println!("\n\nTokens used: {}", usage.output_tokens);
 //!                 break;
 //!             }
+//!             AgentStep::Error(AgentError::Auth(_)) => {
+//!                 eprintln!("Check your API key");
+//!                 break;
+//!             }
 //!             AgentStep::Error(e) => {
 //!                 eprintln!("Error: {}", e);
 //!                 break;
@@ -38,6 +42,22 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Tool previews
+//!
+//! When the agent asks to run tools ([`AgentStep::ToolRequest`]),
+//! [`Agent::preview_tool`] returns a [`ToolPreview`] of what a call would do
+//! (the file `write_file` would create, the edits `edit_file` would make, the
+//! files `replace_in_files` would change), so custom approval UIs can show it
+//! before the call runs. [`SimpleTool`]s provide one with
+//! [`SimpleTool::with_preview`].
+//!
+//! # Tool definitions
+//!
+//! [`ToolRegistry::schemas`] returns the name, description and parameter
+//! schema of every tool in a registry, the same definitions the agent sends to
+//! the model, for generating docs, building UIs, or checking configs against
+//! the available tools.
 
 // Core modules (always available)
 mod auth;
@@ -50,15 +70,26 @@ mod transcript;
 
 // CLI-only modules
 #[cfg(feature = "cli")]
+mod artifacts;
+#[cfg(feature = "cli")]
 mod compaction;
 #[cfg(feature = "cli")]
+mod diff;
+#[cfg(feature = "cli")]
 mod notifications;
 #[cfg(feature = "cli")]
+mod pinned;
+#[cfg(feature = "cli")]
 mod prompts;
 #[cfg(feature = "cli")]
 mod tool_filter;
+#[cfg(feature = "cli")]
+mod variables;
 
 // Re-export the public API
-pub use config::AgentRuntimeConfig;
-pub use llm::{Agent, AgentStep, RequestMode, Usage};
+pub use config::{AgentRuntimeConfig, AnthropicHeaders};
+pub use ide::{Edit, FileDiff, ToolPreview};
+pub use llm::{
+    Agent, AgentError, AgentStep, ContextLevel, ModelCapabilities, ModelPricing, ReasoningLevel, RequestMode, Usage,
+};
 pub use tools::{SimpleTool, ToolCall, ToolRegistry};
@@ -0,0 +1,254 @@
+//! File paths and URLs in the transcript
+//!
+//! Assistant text and tool output are full of references: `src/app.rs:120`,
+//! `https://docs.rs/...`, the `path` a tool just edited. `find_links` picks
+//! them out of plain text so the chat view can underline them, and
+//! `transcript_links` lists them newest first so the user can cycle focus
+//! through them and open one (files in the IDE, URLs in the browser).
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+use ratatui::{
+    style::Modifier,
+    text::{Line, Span},
+};
+
+use crate::transcript::{BlockType, Transcript};
+
+/// Tool params whose value is a reference worth opening
+const LINK_PARAMS: &[&str] = &["path", "file_path", "url"];
+
+/// Quoting and markup stripped from the start of a candidate token
+const LEADING: &[char] = &['"', '\'', '`', '*', '(', '[', '<', '{'];
+
+/// Quoting, markup and punctuation stripped from the end of a candidate token
+const TRAILING: &[char] = &['"', '\'', '`', '*', ')', ']', '>', '}', ',', '.', ';', ':', '!', '?'];
+
+fn trim_token(token: &str) -> &str {
+    token.trim_start_matches(LEADING).trim_end_matches(TRAILING)
+}
+
+/// Something in the transcript that can be opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    Url(String),
+    File {
+        path: PathBuf,
+        line: Option<u32>,
+        column: Option<u32>,
+    },
+}
+
+impl Link {
+    /// Parse a single token (already split on whitespace) as a link
+    pub fn parse(token: &str) -> Option<Self> {
+        let token = trim_token(token);
+        if let Some(start) = token.find("https://").or_else(|| token.find("http://")) {
+            let url = token[start..].trim_end_matches(TRAILING);
+            let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or_default();
+            return (!rest.is_empty()).then(|| Self::Url(url.to_string()));
+        }
+        if token.contains("://") {
+            return None;
+        }
+
+        // path[:line[:column]]
+        let mut parts = token.splitn(3, ':');
+        let path = parts.next()?;
+        let line = parts.next().map(|p| p.parse::<u32>().ok());
+        let column = parts.next().map(|p| p.parse::<u32>().ok());
+        if line == Some(None) || column == Some(None) {
+            return None;
+        }
+        let (line, column) = (line.flatten(), column.flatten());
+
+        let allowed = |c: char| c.is_alphanumeric() || "/._-~+@".contains(c);
+        if path.is_empty() || !path.chars().all(allowed) {
+            return None;
+        }
+        // Bare file names (`main.rs`) only count with a line number, so that
+        // prose like "e.g" or "v1.2" isn't underlined
+        if !path.contains('/') && line.is_none() {
+            return None;
+        }
+        let name = path.rsplit('/').next().unwrap_or_default();
+        let (_, ext) = name.rsplit_once('.')?;
+        if ext.is_empty() || ext.len() > 10 || !ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        Some(Self::File {
+            path: PathBuf::from(path),
+            line,
+            column,
+        })
+    }
+
+    /// Short description for the status line
+    pub fn label(&self) -> String {
+        match self {
+            Self::Url(url) => url.clone(),
+            Self::File { path, line, column } => {
+                let mut label = path.display().to_string();
+                if let Some(line) = line {
+                    label.push_str(&format!(":{}", line));
+                    if let Some(column) = column {
+                        label.push_str(&format!(":{}", column));
+                    }
+                }
+                label
+            },
+        }
+    }
+}
+
+/// Links in `text`, with their byte ranges
+pub fn find_links(text: &str) -> Vec<(Range<usize>, Link)> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    for token in text.split_inclusive(char::is_whitespace) {
+        let start = offset;
+        offset += token.len();
+        let token = token.trim_end();
+        let Some(link) = Link::parse(token) else {
+            continue;
+        };
+        // Locate the link text inside the token, for the byte range
+        let needle = match &link {
+            Link::Url(url) => url.clone(),
+            Link::File { .. } => trim_token(token).to_string(),
+        };
+        if let Some(pos) = token.find(&needle) {
+            links.push((start + pos..start + pos + needle.len(), link));
+        }
+    }
+    links
+}
+
+/// Links in the transcript's text and tool blocks, newest first, without
+/// duplicates
+pub fn transcript_links(transcript: &Transcript) -> Vec<Link> {
+    let mut links: Vec<Link> = Vec::new();
+    for turn in transcript.turns().iter().rev() {
+        for block in turn.content.iter().rev() {
+            if !matches!(block.kind(), BlockType::Text | BlockType::Tool) {
+                continue;
+            }
+            let mut found: Vec<Link> = block
+                .text()
+                .map(|text| find_links(text).into_iter().map(|(_, link)| link).collect())
+                .unwrap_or_default();
+            if let Some(params) = block.params() {
+                found.extend(
+                    LINK_PARAMS
+                        .iter()
+                        .filter_map(|key| params.get(*key)?.as_str())
+                        .filter_map(param_link),
+                );
+            }
+            for link in found.into_iter().rev() {
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+    }
+    links
+}
+
+/// A tool param names its file or URL directly, so it needs no heuristics
+fn param_link(value: &str) -> Option<Link> {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        Some(Link::Url(value.to_string()))
+    } else if value.is_empty() || value.contains(char::is_whitespace) {
+        None
+    } else {
+        Some(Link::File {
+            path: PathBuf::from(value),
+            line: None,
+            column: None,
+        })
+    }
+}
+
+/// Underline the links in a rendered line; the focused one is also reversed
+pub fn style_links(line: Line<'static>, focused: Option<&Link>) -> Line<'static> {
+    if !line.spans.iter().any(|span| span.content.contains(['/', ':'])) {
+        return line;
+    }
+    let Line { spans: original, style, alignment } = line;
+    let mut spans = Vec::with_capacity(original.len());
+    for span in original {
+        let links = find_links(&span.content);
+        if links.is_empty() {
+            spans.push(span);
+            continue;
+        }
+        let mut last = 0;
+        for (range, link) in links {
+            if range.start > last {
+                spans.push(Span::styled(span.content[last..range.start].to_string(), span.style));
+            }
+            let mut style = span.style.add_modifier(Modifier::UNDERLINED);
+            if focused == Some(&link) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            spans.push(Span::styled(span.content[range.clone()].to_string(), style));
+            last = range.end;
+        }
+        if last < span.content.len() {
+            spans.push(Span::styled(span.content[last..].to_string(), span.style));
+        }
+    }
+    Line { spans, style, alignment }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, line: Option<u32>) -> Link {
+        Link::File {
+            path: PathBuf::from(path),
+            line,
+            column: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_links() {
+        assert_eq!(Link::parse("https://docs.rs/open."), Some(Link::Url("https://docs.rs/open".into())));
+        assert_eq!(
+            Link::parse("[docs](https://example.com/a)"),
+            Some(Link::Url("https://example.com/a".into()))
+        );
+        assert_eq!(Link::parse("`src/app.rs:120`,"), Some(file("src/app.rs", Some(120))));
+        assert_eq!(Link::parse("main.rs:7"), Some(file("main.rs", Some(7))));
+        assert_eq!(Link::parse("./.codey/config.toml"), Some(file("./.codey/config.toml", None)));
+
+        assert_eq!(Link::parse("main.rs"), None);
+        assert_eq!(Link::parse("e.g."), None);
+        assert_eq!(Link::parse("and/or"), None);
+        assert_eq!(Link::parse("https://"), None);
+        assert_eq!(Link::parse("ssh://host/x.rs"), None);
+    }
+
+    #[test]
+    fn test_find_links_ranges() {
+        let text = "See src/lib.rs:4 and https://example.com.";
+        let links = find_links(text);
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text[links[0].0.clone()], "src/lib.rs:4");
+        assert_eq!(&text[links[1].0.clone()], "https://example.com");
+    }
+
+    #[test]
+    fn test_style_links() {
+        let line = Line::from("open src/app.rs now");
+        let styled = style_links(line, Some(&file("src/app.rs", None)));
+        assert_eq!(styled.spans.len(), 3);
+        assert_eq!(styled.spans[1].content, "src/app.rs");
+        assert!(styled.spans[1].style.add_modifier.contains(Modifier::UNDERLINED | Modifier::REVERSED));
+    }
+}
@@ -10,15 +10,23 @@ use genai::chat::{
     ToolCall as GenaiToolCall, ToolResponse,
 };
 use genai::{Client, Headers};
+use serde::{Deserialize, Serialize};
 
-use super::client::build_client;
+use super::capabilities::{ModelCapabilities, ReasoningLevel};
+use super::client::build_client_with_base_url;
 use super::client::is_openrouter_model;
+use super::error::AgentError;
 
 use crate::auth::OAuthCredentials;
 use crate::config::AgentRuntimeConfig;
-use crate::transcript::{BlockType, Role, Transcript};
-use crate::tools::{ToolCall, ToolDecision, ToolRegistry};
+use crate::ide::ToolPreview;
+use crate::transcript::{replay_params, BlockType, Role, Transcript};
+use crate::tools::{names, ToolCall, ToolDecision, ToolRegistry};
 
+/// Beta value that lets the model think between tool calls
+const INTERLEAVED_THINKING_BETA: &str = "interleaved-thinking-2025-05-14";
+
+/// Default `anthropic-beta` values for OAuth requests (see `AnthropicHeaders`)
 const ANTHROPIC_BETA_HEADER: &str = concat!(
     "oauth-2025-04-20,",
     "claude-code-20250219,",
@@ -26,14 +34,145 @@ const ANTHROPIC_BETA_HEADER: &str = concat!(
     // Removed: causes OAuth rejection before tool calls are processed
     // "fine-grained-tool-streaming-2025-05-14",
 );
+/// Default `anthropic-beta` value for API-key requests
+const ANTHROPIC_API_KEY_BETA_HEADER: &str = INTERLEAVED_THINKING_BETA;
 const ANTHROPIC_USER_AGENT: &str = "claude-code/2.1.37 (external, cli)";
 
 /// Beta header value that activates fast mode (research preview).
 const FAST_MODE_BETA: &str = "research-preview-2026-02-01";
 
+/// Backoff before retry `attempt` (1-based) of an overloaded request:
+/// `base_secs` doubling per attempt, capped at `max_secs`, then scaled by
+/// ±20% using `jitter` (a sample in [0, 1)) so clients don't retry in step.
+fn overload_backoff(attempt: u32, base_secs: u64, max_secs: u64, jitter: f64) -> Duration {
+    let exponential = base_secs.saturating_mul(1u64 << attempt.saturating_sub(1).min(16));
+    let capped = exponential.min(max_secs).max(1) as f64;
+    Duration::from_secs_f64((capped * (0.8 + 0.4 * jitter)).min(max_secs.max(1) as f64))
+}
+
+/// Await `future`, giving up at the deadline with its message
+async fn with_deadline<T>(
+    future: impl std::future::Future<Output = T>,
+    deadline: Option<(tokio::time::Instant, String)>,
+) -> Result<T, String> {
+    match deadline {
+        Some((at, message)) => tokio::time::timeout_at(at, future).await.map_err(|_| message),
+        None => Ok(future.await),
+    }
+}
+
 /// Duration to cool down fast mode after a rate limit, before re-enabling.
 const FAST_MODE_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(20 * 60);
 
+/// Tool results shorter than this are left alone by `compact_tool_results`
+const TOOL_RESULT_ELIDE_MIN_CHARS: usize = 1_000;
+/// Lines of an elided tool result kept as an excerpt
+const TOOL_RESULT_EXCERPT_LINES: usize = 5;
+/// Marks a tool result already replaced by `compact_tool_results`
+const ELIDED_TOOL_RESULT_PREFIX: &str = "[Output of ";
+
+/// Short stand-in for a bulky tool result: its size and first few lines
+fn elide_tool_result(tool_name: &str, output: &str) -> String {
+    let excerpt: Vec<String> = output
+        .lines()
+        .take(TOOL_RESULT_EXCERPT_LINES)
+        .map(|line| line.chars().take(120).collect())
+        .collect();
+    format!(
+        "{}{} elided to save context: {} lines, {} chars. It began:\n{}\nCall the tool again if you need the full output.]",
+        ELIDED_TOOL_RESULT_PREFIX,
+        tool_name,
+        output.lines().count(),
+        output.chars().count(),
+        excerpt.join("\n")
+    )
+}
+
+/// Rough characters per token, for sizing a request before it is sent
+const CHARS_PER_TOKEN: usize = 4;
+/// Token estimate for an image or other non-text content part
+const BINARY_PART_TOKENS: u32 = 1_600;
+/// Floor for `max_tokens` when a request's input nearly fills the context window
+const MIN_OUTPUT_TOKENS: u32 = 1_024;
+/// Sent after a response cut off at `max_tokens` to have the model carry on
+const CONTINUE_PROMPT: &str = "Your last response was cut off by the output token limit. \
+Continue exactly where it stopped, without repeating anything or adding a preamble.";
+
+/// Whether a provider's stop/finish reason means the response hit `max_tokens`
+/// (Anthropic `max_tokens`, OpenAI `length`, Gemini `MAX_TOKENS`)
+fn is_length_stop(reason: &str) -> bool {
+    matches!(reason, "max_tokens" | "length" | "MAX_TOKENS")
+}
+
+/// Whether a message is a `CONTINUE_PROMPT` the agent sent, not the user
+fn is_continue_prompt(message: &ChatMessage) -> bool {
+    matches!(message.role, ChatRole::User)
+        && matches!(&message.content.parts()[..], [ContentPart::Text(text)] if text == CONTINUE_PROMPT)
+}
+
+/// Rough token count of a conversation's messages
+fn estimate_message_tokens(messages: &[ChatMessage]) -> u32 {
+    let mut chars = 0;
+    let mut binary_tokens = 0;
+    for message in messages {
+        for part in message.content.parts() {
+            match part {
+                ContentPart::Text(text) => chars += text.len(),
+                ContentPart::ToolCall(call) => {
+                    chars += call.fn_name.len() + call.fn_arguments.to_string().len()
+                },
+                ContentPart::ToolResponse(response) => chars += response.content.len(),
+                _ => binary_tokens += BINARY_PART_TOKENS,
+            }
+        }
+    }
+    (chars / CHARS_PER_TOKEN) as u32 + binary_tokens
+}
+
+/// Stands in for the history `restore_from_transcript` leaves out
+fn omitted_history_note(omitted: usize) -> String {
+    format!(
+        "[{} earlier messages of this session were left out when it was restored, to fit the \
+context window. Ask the user if you need something from before this point.]",
+        omitted
+    )
+}
+
+/// Characters of each content part kept at either end by `dump_messages`
+const DUMP_PART_EDGE_CHARS: usize = 200;
+
+/// `text` with everything but its first and last `DUMP_PART_EDGE_CHARS`
+/// characters replaced by a count
+fn dump_excerpt(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= DUMP_PART_EDGE_CHARS * 2 {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(DUMP_PART_EDGE_CHARS).collect();
+    let tail: String = text.chars().skip(count - DUMP_PART_EDGE_CHARS).collect();
+    format!("{}\n... [{} chars omitted] ...\n{}", head, count - DUMP_PART_EDGE_CHARS * 2, tail)
+}
+
+/// Some providers and streaming modes deliver tool arguments as a JSON-encoded
+/// string instead of an object. Decode those in place so tools (and the message
+/// history sent back to the API) always see structured arguments.
+fn normalize_tool_arguments(args: &mut serde_json::Value) {
+    let serde_json::Value::String(raw) = args else {
+        return;
+    };
+    if raw.trim().is_empty() {
+        *args = serde_json::json!({});
+        return;
+    }
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(parsed @ serde_json::Value::Object(_)) => *args = parsed,
+        Ok(_) | Err(_) => {
+            // Leave it as-is; the tool reports the invalid params
+            debug!("Tool arguments are a string but not a JSON object: {}", raw);
+        },
+    }
+}
+
 // Only expose internal ToolCall
 // Note: agent_id is set to 0 here - the caller (App) should set the correct ID
 // after receiving the ToolRequest from the registry
@@ -59,7 +198,7 @@ impl From<&GenaiToolCall> for ToolCall {
 }
 
 /// Token usage tracking
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Usage {
     /// Cumulative output tokens across the session
     pub output_tokens: u32,
@@ -89,6 +228,103 @@ impl Usage {
 
         details
     }
+
+    /// Estimated cost in dollars for this usage.
+    ///
+    /// Input is priced from the last request's context (uncached input, cache
+    /// writes and cache reads at their own rates); output uses `output_tokens`.
+    /// Pass a per-turn `Usage` to get the cost of a single request.
+    pub fn cost(&self, pricing: &ModelPricing) -> f64 {
+        let uncached_input = self
+            .context_tokens
+            .saturating_sub(self.cache_creation_tokens)
+            .saturating_sub(self.cache_read_tokens);
+
+        let per_token = |tokens: u32, per_million: f64| tokens as f64 * per_million / 1_000_000.0;
+        per_token(uncached_input, pricing.input)
+            + per_token(self.cache_creation_tokens, pricing.cache_write)
+            + per_token(self.cache_read_tokens, pricing.cache_read)
+            + per_token(self.output_tokens, pricing.output)
+    }
+}
+
+/// Share of the compaction threshold at which context counts as nearly full
+pub const CONTEXT_WARNING_RATIO: f32 = 0.8;
+
+/// How close the context is to the compaction threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContextLevel {
+    /// Below [`CONTEXT_WARNING_RATIO`] of the threshold
+    #[default]
+    Normal,
+    /// Past [`CONTEXT_WARNING_RATIO`] of the threshold
+    Warning,
+    /// At or over the threshold, where compaction is due
+    Critical,
+}
+
+impl ContextLevel {
+    /// The level of `context_tokens` against a compaction `threshold`
+    pub fn of(context_tokens: u32, threshold: u32) -> Self {
+        if context_tokens >= threshold {
+            Self::Critical
+        } else if context_tokens as f32 >= threshold as f32 * CONTEXT_WARNING_RATIO {
+            Self::Warning
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Per-token model prices, in dollars per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    /// List prices for known Claude models, None for anything else. Cache
+    /// writes cost 1.25x input and cache reads 0.1x.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = model.to_lowercase();
+        let legacy_opus = ["opus-4-0", "opus-4-1", "opus-4-2025", "3-opus"];
+        let (input, output) = if model.contains("opus") {
+            if legacy_opus.iter().any(|v| model.contains(v)) {
+                (15.0, 75.0)
+            } else {
+                (5.0, 25.0)
+            }
+        } else if model.contains("sonnet") {
+            (3.0, 15.0)
+        } else if model.contains("haiku-4") {
+            (1.0, 5.0)
+        } else if model.contains("haiku") {
+            (0.8, 4.0)
+        } else {
+            return None;
+        };
+        Some(Self {
+            input,
+            output,
+            cache_write: input * 1.25,
+            cache_read: input * 0.1,
+        })
+    }
+}
+
+impl Usage {
+    /// Add another request's usage to a multi-request total. Unlike `+=`,
+    /// which tracks the current context, every field is summed, so `cost`
+    /// of the total is the cost of all the requests.
+    pub fn add_request(&mut self, request: Usage) {
+        self.output_tokens += request.output_tokens;
+        self.context_tokens += request.context_tokens;
+        self.cache_creation_tokens += request.cache_creation_tokens;
+        self.cache_read_tokens += request.cache_read_tokens;
+    }
 }
 
 impl std::ops::AddAssign for Usage {
@@ -111,12 +347,34 @@ pub enum AgentStep {
     CompactionDelta(String),
     /// Agent wants to execute tools, needs approval
     ToolRequest(Vec<ToolCall>),
-    /// Retrying after error
-    Retrying { attempt: u32, error: String },
+    /// Retrying after error, once `delay_secs` have passed. `overloaded`
+    /// retries (the provider is at capacity) back off longer and are counted
+    /// against `max_overload_retries` instead of `max_retries`.
+    Retrying {
+        attempt: u32,
+        error: String,
+        delay_secs: u64,
+        overloaded: bool,
+    },
+    /// Retries on `from` are exhausted; retrying the request on the next
+    /// model in `fallback_models`
+    FallbackModel { from: String, to: String, error: String },
+    /// The response stopped at `max_tokens`; continuation `continuation` of
+    /// `max` is being requested and will stream into the same turn
+    Continuing { continuation: u32, max: u32 },
+    /// The context grew into a higher [`ContextLevel`] against
+    /// `compaction_threshold`. Reported once per crossing, just before the
+    /// `ToolRequest` or `Finished` of the request that crossed it; only when
+    /// the threshold is configured.
+    ContextWarning {
+        level: ContextLevel,
+        context_tokens: u32,
+        threshold: u32,
+    },
     /// Agent finished processing this message
     Finished { usage: Usage },
-    /// Error occurred
-    Error(String),
+    /// The request failed and won't be retried
+    Error(AgentError),
 }
 
 /// Internal state for the agent stream
@@ -137,6 +395,9 @@ pub enum RequestMode {
     Normal,
     /// Compaction mode: no tools, focused on summarization
     Compaction,
+    /// Planning mode: read-only tools, thinking enabled, no file modifications.
+    /// Callers keep sending requests in this mode until the plan is approved.
+    Plan,
 }
 
 /// Options derived from a RequestMode
@@ -144,6 +405,8 @@ pub struct RequestOptions {
     pub tools_enabled: bool,
     pub thinking_budget: u32,
     pub capture_tool_calls: bool,
+    /// Offer only tools that cannot modify the workspace (see `names::READ_ONLY`)
+    pub read_only_tools: bool,
 }
 
 impl RequestMode {
@@ -151,13 +414,21 @@ impl RequestMode {
         match self {
             Self::Normal => RequestOptions {
                 tools_enabled: true,
-                thinking_budget: config.thinking_budget,
+                thinking_budget: config.thinking_budget_for(&config.model, config.max_tokens),
                 capture_tool_calls: true,
+                read_only_tools: false,
             },
             Self::Compaction => RequestOptions {
                 tools_enabled: false,
                 thinking_budget: config.compaction_thinking_budget,
                 capture_tool_calls: false,
+                read_only_tools: false,
+            },
+            Self::Plan => RequestOptions {
+                tools_enabled: true,
+                thinking_budget: config.thinking_budget_for(&config.model, config.max_tokens),
+                capture_tool_calls: true,
+                read_only_tools: true,
             },
         }
     }
@@ -175,6 +446,8 @@ pub struct Agent {
     messages: Vec<ChatMessage>,
     system_prompt: String,
     total_usage: Usage,
+    /// Usage of every request since the last `send_request`
+    turn_usage: Usage,
     /// OAuth credentials for Claude Max (if available)
     oauth: Option<OAuthCredentials>,
     /// Optional dynamic prompt builder - called before each request
@@ -192,6 +465,10 @@ pub struct Agent {
     streaming_tool_calls: Vec<GenaiToolCall>,
     streaming_thinking: Vec<Thinking>,
     tool_responses: Vec<ToolResponse>,
+    /// When the request in flight was sent (for `request_timeout_secs`)
+    request_started: Option<tokio::time::Instant>,
+    /// When the request in flight last received data (for `stream_idle_timeout_secs`)
+    stream_activity: Option<tokio::time::Instant>,
 
     /// When set, fast mode is cooling down until this instant.
     /// During cooldown, the fast mode beta header is omitted from requests.
@@ -200,6 +477,32 @@ pub struct Agent {
     /// Retry attempt counter, persists across calls to exec_chat_with_retry.
     /// Reset on successful request or new user message.
     retry_attempt: u32,
+    /// Like `retry_attempt`, for overloaded errors
+    overload_attempt: u32,
+    /// Backoff before the next request, set when a retry is scheduled
+    retry_delay: Option<Duration>,
+
+    /// `max_tokens` override for the next user request (set by `set_next_max_tokens`)
+    next_max_tokens: Option<u32>,
+    /// `max_tokens` override for the request in progress, including its tool-call rounds
+    request_max_tokens: Option<u32>,
+    /// Thinking was left off the current request by `interleaved_thinking_rounds`
+    thinking_skipped: bool,
+    /// `max_tokens` of the current request, when it was lowered to fit the context window
+    fitted_max_tokens: Option<u32>,
+    /// Model serving the current request: 0 is `config.model`, n is
+    /// `config.fallback_models[n - 1]`. Reset for each user message.
+    model_index: usize,
+    /// The response being streamed stopped at `max_tokens`
+    stopped_at_length: bool,
+    /// Continuations requested this turn (capped by `length_continuations`)
+    continuations: u32,
+    /// Where the context stood against `compaction_threshold` after the last request
+    context_level: ContextLevel,
+    /// `ContextWarning` waiting to go out before the next `ToolRequest` or `Finished`
+    context_warning: Option<AgentStep>,
+    /// Step held back while a `ContextWarning` is returned ahead of it
+    queued_step: Option<AgentStep>,
 }
 
 impl Agent {
@@ -211,12 +514,13 @@ impl Agent {
         tools: ToolRegistry,
     ) -> Self {
         Self {
-            client: build_client(),
+            client: build_client_with_base_url(config.api_base_url.as_deref()),
             config,
             tools,
             messages: vec![ChatMessage::system(system_prompt)],
             system_prompt: system_prompt.to_string(),
             total_usage: Usage::default(),
+            turn_usage: Usage::default(),
             oauth,
             system_prompt_builder: None,
 
@@ -230,9 +534,24 @@ impl Agent {
             streaming_tool_calls: Vec::new(),
             streaming_thinking: Vec::new(),
             tool_responses: Vec::new(),
+            request_started: None,
+            stream_activity: None,
 
             fast_mode_cooldown_until: None,
             retry_attempt: 0,
+            overload_attempt: 0,
+            retry_delay: None,
+
+            next_max_tokens: None,
+            request_max_tokens: None,
+            thinking_skipped: false,
+            fitted_max_tokens: None,
+            model_index: 0,
+            stopped_at_length: false,
+            continuations: 0,
+            context_level: ContextLevel::Normal,
+            context_warning: None,
+            queued_step: None,
         }
     }
 
@@ -248,40 +567,164 @@ impl Agent {
     ) -> Self {
         let system_prompt = prompt_builder();
         Self {
-            client: build_client(),
-            config,
-            tools,
-            messages: vec![ChatMessage::system(&system_prompt)],
-            system_prompt,
-            total_usage: Usage::default(),
-            oauth,
             system_prompt_builder: Some(prompt_builder),
-
-            // Streaming state starts empty
-            state: None,
-            active_stream: None,
-            mode: RequestMode::Normal,
-
-            // Accumulated during streaming
-            streaming_text: String::new(),
-            streaming_tool_calls: Vec::new(),
-            streaming_thinking: Vec::new(),
-            tool_responses: Vec::new(),
-
-            fast_mode_cooldown_until: None,
-            retry_attempt: 0,
+            ..Self::new(config, &system_prompt, oauth, tools)
         }
     }
 
     /// Restore agent message history from a transcript
-    /// Preserves the existing system prompt (first message if it's a system message)
+    /// Preserves the existing system prompt (first message if it's a system message).
+    /// Usage and the context level start over with the restored history.
     pub fn restore_from_transcript(&mut self, transcript: &Transcript) {
         self.messages.clear();
+        self.total_usage = Usage::default();
+        self.turn_usage = Usage::default();
+        self.context_level = ContextLevel::Normal;
+        self.context_warning = None;
 
         // Restore system prompt first
         self.messages
             .push(ChatMessage::system(self.system_prompt.clone()));
 
+        self.append_transcript(transcript, true);
+        let summarized = transcript
+            .turns()
+            .iter()
+            .any(|turn| turn.content.iter().any(|block| block.kind() == BlockType::Compaction));
+        self.fit_restored_history(summarized);
+
+        info!("Restored {} messages from transcript", self.messages.len());
+    }
+
+    /// Drop the oldest restored messages until the history fits in
+    /// `restore_context_fraction` of the context window, noting the gap at
+    /// the start of the first message kept. Cuts only before a user message,
+    /// so tool calls stay paired with their results; the last user message and
+    /// what follows are always kept, as is the compaction summary when the
+    /// session was `summarized` (it's the first model message).
+    fn fit_restored_history(&mut self, summarized: bool) {
+        let capabilities = ModelCapabilities::for_model(&self.config.model);
+        let Some(window) = self.config.context_window.or(capabilities.context_window) else {
+            return;
+        };
+        let mut budget = (window as f64 * self.config.restore_context_fraction.clamp(0.0, 1.0) as f64) as u32;
+        let Some(last_user) = self
+            .messages
+            .iter()
+            .rposition(|msg| matches!(msg.role, ChatRole::User))
+        else {
+            return;
+        };
+        // Messages before `first_droppable` are always kept
+        let first_droppable = match summarized {
+            true => self
+                .messages
+                .iter()
+                .position(|msg| matches!(msg.role, ChatRole::Assistant))
+                .map_or(1, |summary| summary + 1),
+            false => 1,
+        };
+        budget = budget.saturating_sub(estimate_message_tokens(&self.messages[1..first_droppable]));
+
+        let mut keep_from = last_user;
+        let mut tokens = 0;
+        for idx in (first_droppable..self.messages.len()).rev() {
+            tokens += estimate_message_tokens(std::slice::from_ref(&self.messages[idx]));
+            if tokens > budget {
+                break;
+            }
+            if matches!(self.messages[idx].role, ChatRole::User) {
+                keep_from = idx;
+            }
+        }
+        if keep_from <= first_droppable {
+            return;
+        }
+
+        let omitted = keep_from - first_droppable;
+        self.messages.drain(first_droppable..keep_from);
+        // Part of the kept user message, so user and model messages still alternate
+        let first_kept = &mut self.messages[first_droppable];
+        let mut parts = vec![ContentPart::Text(format!("{}\n\n", omitted_history_note(omitted)))];
+        parts.extend(first_kept.content.parts().iter().cloned());
+        first_kept.content = parts
+            .into_iter()
+            .fold(MessageContent::default(), |content, part| content.append(part));
+        info!(
+            "Left out {} older messages to fit the restored history in {} tokens",
+            omitted, budget
+        );
+    }
+
+    /// Undo a compaction by restoring the full pre-compaction transcript,
+    /// followed by everything in the current transcript except its summary.
+    pub fn restore_uncompacted(&mut self, previous: &Transcript, current: &Transcript) {
+        self.messages.clear();
+        self.messages
+            .push(ChatMessage::system(self.system_prompt.clone()));
+
+        self.append_transcript(previous, true);
+        self.append_transcript(current, false);
+
+        info!("Restored {} uncompacted messages", self.messages.len());
+    }
+
+    /// Number of messages in the agent's context (including the system prompt)
+    pub fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// The raw message history sent to the API, system prompt first
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Mutable access to the raw message history, for debugging. Edits must
+    /// keep the history valid for the API (e.g. every tool call followed by
+    /// its response) or the next request will fail.
+    pub fn messages_mut(&mut self) -> &mut Vec<ChatMessage> {
+        &mut self.messages
+    }
+
+    /// Human-readable dump of the message history: each message's role and
+    /// its content parts by kind, with long bodies cut to their head and tail.
+    /// For diagnosing restore, compaction and cache-control problems.
+    pub fn dump_messages(&self) -> String {
+        let mut out = String::new();
+        for (idx, msg) in self.messages.iter().enumerate() {
+            out.push_str(&format!("#{} {:?}", idx, msg.role));
+            if let Some(ref options) = msg.options {
+                out.push_str(&format!(" {:?}", options));
+            }
+            out.push('\n');
+            for part in msg.content.parts() {
+                let (kind, body) = match part {
+                    ContentPart::Text(text) => ("text".to_string(), text.clone()),
+                    ContentPart::ToolCall(call) => (
+                        format!("tool_call {} {}", call.fn_name, call.call_id),
+                        call.fn_arguments.to_string(),
+                    ),
+                    ContentPart::ToolResponse(response) => {
+                        (format!("tool_response {}", response.call_id), response.content.clone())
+                    },
+                    other => {
+                        let debug = format!("{:?}", other);
+                        let kind = debug.split(['(', ' ', '{']).next().unwrap_or("part").to_lowercase();
+                        (kind, debug)
+                    },
+                };
+                out.push_str(&format!("  [{}]\n", kind));
+                for line in dump_excerpt(&body).lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+        }
+        out
+    }
+
+    /// Append the messages represented by a transcript's turns.
+    /// `include_compaction` controls whether compaction summaries are replayed.
+    fn append_transcript(&mut self, transcript: &Transcript, include_compaction: bool) {
         for turn in transcript.turns() {
             match turn.role {
                 // Skip system turns - we use our predefined system prompt
@@ -310,11 +753,16 @@ impl Agent {
                     // Process blocks by kind
                     for block in &turn.content {
                         match block.kind() {
-                            BlockType::Text | BlockType::Compaction => {
+                            BlockType::Text => {
                                 if let Some(text) = block.text() {
                                     text_parts.push(text);
                                 }
                             },
+                            BlockType::Compaction => {
+                                if let Some(text) = block.text().filter(|_| include_compaction) {
+                                    text_parts.push(text);
+                                }
+                            },
                             BlockType::Tool => {
                                 // Only add tool call if it has a result (text)
                                 // Skip incomplete tools (e.g., quit while awaiting approval)
@@ -332,7 +780,7 @@ impl Agent {
                                     tool_calls.push(GenaiToolCall {
                                         call_id: call_id.to_string(),
                                         fn_name: tool_name.to_string(),
-                                        fn_arguments: params.clone(),
+                                        fn_arguments: replay_params(params),
                                         thought_signatures: None,
                                     });
                                     tool_responses.push(ToolResponse::new(
@@ -368,18 +816,39 @@ impl Agent {
                 },
             }
         }
+    }
 
-        info!("Restored {} messages from transcript", self.messages.len());
+    /// Tool-call rounds already answered in the current turn: model
+    /// responses since the last user message. Continue prompts don't start
+    /// a new turn.
+    fn turn_round(&self) -> usize {
+        self.messages
+            .iter()
+            .rev()
+            .take_while(|msg| !matches!(msg.role, ChatRole::User) || is_continue_prompt(msg))
+            .filter(|msg| matches!(msg.role, ChatRole::Assistant))
+            .count()
+    }
+
+    /// Whether the next request may think, given `interleaved_thinking_rounds`.
+    /// The first round of a turn always may.
+    fn thinking_allowed(&self) -> bool {
+        match self.config.interleaved_thinking_rounds {
+            Some(limit) => self.turn_round() <= limit as usize,
+            None => true,
+        }
     }
 
     /// Get tool definitions in genai format
-    fn get_tools(&self) -> Vec<Tool> {
+    fn get_tools(&self, read_only: bool) -> Vec<Tool> {
         self.tools
-            .values()
-            .map(|tool| {
-                Tool::new(tool.name())
-                    .with_description(tool.description())
-                    .with_schema(tool.schema())
+            .schemas()
+            .into_iter()
+            .filter(|(name, _, _)| !read_only || names::READ_ONLY.contains(name))
+            .map(|(name, description, schema)| {
+                Tool::new(name)
+                    .with_description(description)
+                    .with_schema(schema)
             })
             .collect()
     }
@@ -387,17 +856,106 @@ impl Agent {
     /// Send a user message to the agent
     /// Call next() repeatedly to get AgentSteps until None
     pub fn send_request(&mut self, user_input: &str, mode: RequestMode) {
+        // A cancelled turn can leave continue prompts behind
+        self.fold_continuations();
         self.messages.push(ChatMessage::user(user_input));
         self.mode = mode;
         self.retry_attempt = 0;
+        self.overload_attempt = 0;
+        self.retry_delay = None;
+        self.turn_usage = Usage::default();
+        // Each message starts on the primary model again
+        self.model_index = 0;
+        self.continuations = 0;
+        // A pending override is meant for the user's next message, not a compaction
+        self.request_max_tokens = match mode {
+            RequestMode::Compaction => None,
+            _ => self.next_max_tokens.take(),
+        };
         self.state = Some(StreamState::NeedsChatRequest);
     }
 
+    /// Use `max_tokens` for the next request only (including its tool-call
+    /// rounds) instead of the configured value. Fails if the model can't
+    /// produce that many tokens or it leaves no room beyond the thinking budget.
+    pub fn set_next_max_tokens(&mut self, max_tokens: u32) -> Result<(), String> {
+        let capabilities = ModelCapabilities::for_model(&self.config.model);
+        if let Some(limit) = capabilities.max_output_tokens {
+            if max_tokens > limit {
+                return Err(format!(
+                    "{} allows at most {} output tokens",
+                    self.config.model, limit
+                ));
+            }
+        }
+        let thinking_budget = self.config.thinking_budget_for(&self.config.model, max_tokens);
+        if capabilities.thinking && thinking_budget > 0 && max_tokens <= thinking_budget {
+            return Err(format!(
+                "max tokens must be greater than the thinking budget ({})",
+                thinking_budget
+            ));
+        }
+        if max_tokens == 0 {
+            return Err("max tokens must be greater than 0".to_string());
+        }
+        self.next_max_tokens = Some(max_tokens);
+        Ok(())
+    }
+
+    /// Drop a pending `set_next_max_tokens` override
+    pub fn clear_next_max_tokens(&mut self) {
+        self.next_max_tokens = None;
+    }
+
+    /// The configured `max_tokens` and the pending override for the next request, if any
+    pub fn max_tokens(&self) -> (u32, Option<u32>) {
+        (self.config.max_tokens, self.next_max_tokens)
+    }
+
+    /// The reasoning level in use (None = the numeric `thinking_budget`)
+    pub fn reasoning(&self) -> Option<ReasoningLevel> {
+        self.config.reasoning
+    }
+
+    /// Use a named reasoning level from the next request on, or go back to
+    /// `thinking_budget` with None
+    pub fn set_reasoning(&mut self, reasoning: Option<ReasoningLevel>) {
+        self.config.reasoning = reasoning;
+    }
+
+    /// Thinking budget for the configured model at the next request's `max_tokens`
+    pub fn thinking_budget(&self) -> u32 {
+        let max_tokens = self.next_max_tokens.unwrap_or(self.config.max_tokens);
+        self.config.thinking_budget_for(&self.config.model, max_tokens)
+    }
+
     /// Cancel the current streaming operation
     pub fn cancel(&mut self) {
         debug!("Agent::cancel");
         self.state = None;
         self.active_stream = None;
+        self.queued_step = None;
+    }
+
+    /// Abandon pending tool calls and return to a clean idle state.
+    ///
+    /// Use this when tool results will never arrive, e.g. the future driving a
+    /// tool was dropped while the agent was in `AwaitingToolDecision`. The
+    /// pending calls are discarded without being added to the history, so the
+    /// next `send_request` starts from a consistent conversation.
+    /// Returns true if the agent was waiting on tool results.
+    pub fn abort_tool_wait(&mut self) -> bool {
+        let was_waiting = matches!(self.state, Some(StreamState::AwaitingToolDecision));
+        if was_waiting {
+            debug!(
+                "Agent: aborting tool wait, discarding {} tool call(s)",
+                self.streaming_tool_calls.len()
+            );
+        }
+        self.cancel();
+        self.streaming_tool_calls.clear();
+        self.tool_responses.clear();
+        was_waiting
     }
 
     /// Refresh OAuth token if expired. Returns true if refresh was needed and succeeded.
@@ -420,6 +978,12 @@ impl Agent {
         self.oauth = oauth;
     }
 
+    /// Swap the tools offered to the model from the next request on, keeping
+    /// the conversation. Returns the previous registry so it can be restored.
+    pub fn set_tools(&mut self, tools: ToolRegistry) -> ToolRegistry {
+        std::mem::replace(&mut self.tools, tools)
+    }
+
     /// Refresh the system prompt if a dynamic builder is configured.
     ///
     /// This is called before each LLM request to allow the prompt content
@@ -443,6 +1007,12 @@ impl Agent {
         self.total_usage
     }
 
+    /// Usage summed over every request of the current turn, including its
+    /// tool-call rounds
+    pub fn turn_usage(&self) -> Usage {
+        self.turn_usage
+    }
+
     /// Get the last assistant message text (for returning sub-agent results).
     /// Returns the accumulated streaming text if present.
     pub fn last_message(&self) -> Option<String> {
@@ -472,6 +1042,7 @@ impl Agent {
         // Add the compaction summary as a user message providing context
         self.messages.push(ChatMessage::user(summary));
         self.total_usage = Usage::default();
+        self.context_level = ContextLevel::Normal;
 
         info!(
             "Agent reset with compaction summary ({} chars)",
@@ -479,6 +1050,52 @@ impl Agent {
         );
     }
 
+    /// Replace the bodies of old, bulky tool results with their size and a
+    /// short excerpt, keeping the `keep_recent` most recent results and all
+    /// user and assistant text intact. A lighter alternative to full
+    /// compaction. Returns the number of results elided and the characters saved.
+    pub fn compact_tool_results(&mut self, keep_recent: usize) -> (usize, usize) {
+        let tool_names: std::collections::HashMap<String, String> = self
+            .messages
+            .iter()
+            .flat_map(|msg| msg.content.tool_calls())
+            .map(|call| (call.call_id.clone(), call.fn_name.clone()))
+            .collect();
+        let responses: Vec<usize> = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, msg)| !msg.content.tool_responses().is_empty())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let (mut elided, mut saved) = (0, 0);
+        let old = responses.len().saturating_sub(keep_recent);
+        for &idx in &responses[..old] {
+            let Some(response) = self.messages[idx].content.tool_responses().first().map(|r| (*r).clone())
+            else {
+                continue;
+            };
+            if response.content.len() < TOOL_RESULT_ELIDE_MIN_CHARS
+                || response.content.starts_with(ELIDED_TOOL_RESULT_PREFIX)
+            {
+                continue;
+            }
+            let name = tool_names
+                .get(&response.call_id)
+                .map_or("tool", String::as_str);
+            let summary = elide_tool_result(name, &response.content);
+            saved += response.content.len().saturating_sub(summary.len());
+            elided += 1;
+            self.messages[idx] = ChatMessage::from(ToolResponse::new(response.call_id, summary));
+        }
+
+        if elided > 0 {
+            info!("Elided {} old tool results ({} chars)", elided, saved);
+        }
+        (elided, saved)
+    }
+
     /// Convert genai Usage to our Usage struct (for a single turn, not cumulative)
     fn extract_turn_usage(genai_usage: &genai::chat::Usage) -> Usage {
         let input_tokens = genai_usage.prompt_tokens.unwrap_or(0) as u32;
@@ -506,6 +1123,34 @@ impl Agent {
         }
     }
 
+    /// Model serving the current request: the configured model, or a
+    /// fallback after it failed
+    pub fn active_model(&self) -> &str {
+        match self.model_index {
+            0 => &self.config.model,
+            n => &self.config.fallback_models[n - 1],
+        }
+    }
+
+    /// Move on to the next fallback model after retries on the current one
+    /// ran out. Returns the step announcing it, or None if none are left.
+    fn fall_back(&mut self, error: &str) -> Option<AgentStep> {
+        if self.model_index >= self.config.fallback_models.len() {
+            return None;
+        }
+        let from = self.active_model().to_string();
+        self.model_index += 1;
+        self.retry_attempt = 0;
+        self.overload_attempt = 0;
+        let to = self.active_model().to_string();
+        warn!("Retries exhausted on {}, falling back to {}", from, to);
+        Some(AgentStep::FallbackModel {
+            from,
+            to,
+            error: error.to_string(),
+        })
+    }
+
     /// Returns true if fast mode is currently active.
     ///
     /// Fast mode requires: config flag enabled, model is opus-4-6, and not in cooldown.
@@ -513,7 +1158,7 @@ impl Agent {
         if !self.config.fast_mode {
             return false;
         }
-        if !self.config.model.to_lowercase().contains("opus-4-6") {
+        if !self.active_model().to_lowercase().contains("opus-4-6") {
             return false;
         }
         if let Some(until) = self.fast_mode_cooldown_until {
@@ -533,13 +1178,75 @@ impl Agent {
         }
     }
 
-    /// Check if an error message indicates a rate limit (429) or overloaded (529) response.
-    fn is_rate_limit_error(&self, error: &str) -> bool {
-        let lower = error.to_lowercase();
-        lower.contains("429")
-            || lower.contains("rate limit")
-            || lower.contains("overloaded")
-            || lower.contains("529")
+    /// `max_tokens` for a request: the configured (or overridden) value,
+    /// lowered when the estimated input would leave less room than that in
+    /// the context window. Never goes below `MIN_OUTPUT_TOKENS`; at that point
+    /// the conversation needs compacting.
+    fn fit_max_tokens(&mut self, request: &ChatRequest, capabilities: &ModelCapabilities) -> u32 {
+        let max_tokens = self.request_max_tokens.unwrap_or(self.config.max_tokens);
+        self.fitted_max_tokens = None;
+        let Some(window) = self.config.context_window.or(capabilities.context_window) else {
+            return max_tokens;
+        };
+
+        let mut input = estimate_message_tokens(&request.messages);
+        if request.tools.is_some() {
+            let tool_chars: usize = self
+                .tools
+                .values()
+                .map(|tool| tool.name().len() + tool.description().len() + tool.schema().to_string().len())
+                .sum();
+            input += (tool_chars / CHARS_PER_TOKEN) as u32;
+        }
+        let available = window.saturating_sub(input);
+        if available >= max_tokens {
+            return max_tokens;
+        }
+        let fitted = available.max(MIN_OUTPUT_TOKENS);
+        if available < MIN_OUTPUT_TOKENS {
+            warn!(
+                "Request input (~{} tokens) nearly fills the {} token context window; compact the conversation",
+                input, window
+            );
+        }
+        info!(
+            "Lowering max_tokens from {} to {}: ~{} input tokens in a {} token context window",
+            max_tokens, fitted, input, window
+        );
+        self.fitted_max_tokens = Some(fitted);
+        fitted
+    }
+
+    /// Reasoning effort for a `max_tokens` request, or None to send the
+    /// request without thinking
+    fn reasoning_effort(
+        &self,
+        capabilities: &ModelCapabilities,
+        mode_opts: &RequestOptions,
+        max_tokens: u32,
+    ) -> Option<ReasoningEffort> {
+        if !capabilities.thinking || self.thinking_skipped {
+            return None;
+        }
+        // Compaction keeps its own numeric budget; a reasoning level is sized
+        // to the max_tokens this request actually sends
+        let reasoning = match self.mode {
+            RequestMode::Compaction => None,
+            _ => self.config.reasoning,
+        };
+        let thinking_budget = match reasoning {
+            Some(level) => capabilities.reasoning_budget(level, max_tokens),
+            None => mode_opts.thinking_budget,
+        };
+        let effort = capabilities.reasoning_effort(reasoning, thinking_budget)?;
+        if matches!(effort, ReasoningEffort::Budget(budget) if max_tokens <= budget) {
+            info!(
+                "Thinking off: max_tokens {} leaves no room for the {} token thinking budget",
+                max_tokens, thinking_budget
+            );
+            return None;
+        }
+        Some(effort)
     }
 
     /// Execute a chat request with retry and exponential backoff
@@ -549,21 +1256,36 @@ impl Agent {
     /// (which requires Agent: Sync). Agent is Send but not Sync due to the
     /// internal stream being `dyn Stream + Send` but not `+ Sync`.
     async fn exec_chat_with_retry(&mut self) -> Result<ChatStreamResponse, AgentStep> {
+        let model = self.active_model().to_string();
+        let capabilities = ModelCapabilities::for_model(&model);
+
         // Clone messages and add cache_control to the last message
         // Per Anthropic docs: mark the final message to enable incremental caching
         let mut messages = self.messages.clone();
-        if let Some(last_msg) = messages.last_mut() {
-            last_msg.options = Some(CacheControl::Ephemeral.into());
-            debug!(
-                "Added cache_control to last message (role: {})",
-                last_msg.role
-            );
+        if capabilities.prompt_caching {
+            if let Some(last_msg) = messages.last_mut() {
+                last_msg.options = Some(CacheControl::Ephemeral.into());
+                debug!(
+                    "Added cache_control to last message (role: {})",
+                    last_msg.role
+                );
+            }
         }
 
         let mut request = ChatRequest::new(messages);
         let mode_opts = self.mode.options(&self.config);
-        if mode_opts.tools_enabled {
-            request = request.with_tools(self.get_tools());
+        if mode_opts.tools_enabled && capabilities.tool_streaming {
+            request = request.with_tools(self.get_tools(mode_opts.read_only_tools));
+        }
+
+        // Past the interleaved thinking limit, later rounds of the turn skip thinking
+        self.thinking_skipped = capabilities.thinking && !self.thinking_allowed();
+        if self.thinking_skipped {
+            info!(
+                "Thinking off for tool round {} (interleaved_thinking_rounds = {:?})",
+                self.turn_round(),
+                self.config.interleaved_thinking_rounds
+            );
         }
 
         // Check fast mode status before building headers
@@ -573,94 +1295,272 @@ impl Agent {
         }
 
         // Build headers based on provider and OAuth availability
-        let headers = if is_openrouter_model(&self.config.model) {
+        let headers = if is_openrouter_model(&model) {
             // OpenRouter uses standard Bearer auth (handled by client resolver)
             // Add recommended headers for app attribution
             Headers::from([
                 ("HTTP-Referer".to_string(), "https://github.com/tcdent/codey".to_string()),
                 ("X-Title".to_string(), "Codey".to_string()),
             ])
-        } else if let Some(ref oauth) = self.oauth {
-            let mut beta = ANTHROPIC_BETA_HEADER.to_string();
+        } else {
+            let overrides = &self.config.anthropic_headers;
+            let (default_beta, default_user_agent) = match self.oauth {
+                Some(_) => (ANTHROPIC_BETA_HEADER, Some(ANTHROPIC_USER_AGENT)),
+                None => (ANTHROPIC_API_KEY_BETA_HEADER, None),
+            };
+
+            let mut beta: Vec<String> = match overrides.beta {
+                Some(ref values) => values.clone(),
+                None => default_beta.split(',').map(String::from).collect(),
+            };
+            if self.thinking_skipped {
+                beta.retain(|value| value != INTERLEAVED_THINKING_BETA);
+            }
             if fast_mode_active {
-                beta.push(',');
-                beta.push_str(FAST_MODE_BETA);
+                beta.push(FAST_MODE_BETA.to_string());
             }
-            Headers::from([
-                (
+            let user_agent = match overrides.user_agent {
+                Some(ref value) => Some(value.as_str()).filter(|v| !v.is_empty()),
+                None => default_user_agent,
+            };
+
+            let mut headers = Vec::new();
+            if let Some(ref oauth) = self.oauth {
+                headers.push((
                     "authorization".to_string(),
                     format!("Bearer {}", oauth.access_token),
-                ),
-                ("anthropic-beta".to_string(), beta),
-                ("user-agent".to_string(), ANTHROPIC_USER_AGENT.to_string()),
-            ])
-        } else {
-            let mut beta = "interleaved-thinking-2025-05-14".to_string();
-            if fast_mode_active {
-                beta.push(',');
-                beta.push_str(FAST_MODE_BETA);
+                ));
             }
-            Headers::from([("anthropic-beta".to_string(), beta)])
+            if !beta.is_empty() {
+                headers.push(("anthropic-beta".to_string(), beta.join(",")));
+            }
+            if let Some(user_agent) = user_agent {
+                headers.push(("user-agent".to_string(), user_agent.to_string()));
+            }
+            Headers::from(headers)
         };
 
-        // Build chat options - reasoning_effort is only for Anthropic models
+        // Leave room in the context window for the response
+        let max_tokens = self.fit_max_tokens(&request, &capabilities);
+        let effort = self.reasoning_effort(&capabilities, &mode_opts, max_tokens);
+
+        // Build chat options, enabling only what the model supports
         let mut chat_options = ChatOptions::default()
-            .with_max_tokens(self.config.max_tokens)
+            .with_max_tokens(max_tokens)
             .with_capture_usage(true)
-            .with_capture_tool_calls(mode_opts.capture_tool_calls)
+            .with_capture_tool_calls(mode_opts.capture_tool_calls && capabilities.tool_streaming)
             .with_extra_headers(headers);
-        
-        // Only add reasoning/thinking options for Anthropic models
-        if !is_openrouter_model(&self.config.model) {
+
+        if let Some(effort) = effort {
             chat_options = chat_options
                 .with_capture_reasoning_content(true)
-                .with_reasoning_effort(ReasoningEffort::Budget(mode_opts.thinking_budget));
+                .with_reasoning_effort(effort);
         }
 
-        self.retry_attempt += 1;
         match self
             .client
-            .exec_chat_stream(&self.config.model, request.clone(), Some(&chat_options))
+            .exec_chat_stream(&model, request.clone(), Some(&chat_options))
             .await
         {
             Ok(resp) => {
                 info!("Chat request successful");
                 self.retry_attempt = 0;
+                self.overload_attempt = 0;
                 Ok(resp)
             },
             Err(e) => {
                 let err = format!("{:#}", e);
-                error!("Chat request failed (attempt {}): {}", self.retry_attempt, err);
+                error!("Chat request failed (attempt {}): {}", self.retry_attempt + 1, err);
 
                 // If fast mode is active and we hit a rate limit or overloaded
                 // error, trigger cooldown and retry without the fast mode header.
-                if fast_mode_active && self.is_rate_limit_error(&err) {
+                // Not counted as a retry attempt.
+                if fast_mode_active && AgentError::classify(&err).is_rate_limit() {
                     warn!(
                         "Fast mode rate limited, entering {}s cooldown",
                         FAST_MODE_COOLDOWN.as_secs()
                     );
                     self.fast_mode_cooldown_until = Some(Instant::now() + FAST_MODE_COOLDOWN);
-                    // Don't count fast mode fallback as a retry attempt
-                    self.retry_attempt -= 1;
                     return Err(AgentStep::Retrying {
                         attempt: self.retry_attempt,
-                        error: format!("Fast mode rate limited, falling back to standard speed"),
+                        error: "Fast mode rate limited, falling back to standard speed".to_string(),
+                        delay_secs: 0,
+                        overloaded: false,
                     });
                 }
 
-                if self.retry_attempt >= self.config.max_retries {
-                    self.retry_attempt = 0;
-                    return Err(AgentStep::Error(format!(
-                        "API error ({}): {}",
-                        self.config.model, err
-                    )));
-                }
                 // Return retry step, caller should call next() again
-                Err(AgentStep::Retrying {
-                    attempt: self.retry_attempt,
-                    error: err,
-                })
+                Err(self.on_request_error(err))
+            },
+        }
+    }
+
+    /// Decide what follows a failed request or stream: a retry after a
+    /// backoff, the next fallback model, or giving up. Overloaded errors wait
+    /// longer (jittered, `overload_backoff_secs` doubling up to
+    /// `overload_backoff_max_secs`) and have their own retry budget.
+    fn on_request_error(&mut self, err: String) -> AgentStep {
+        let classified = AgentError::classify(&err);
+        let overloaded = matches!(classified, AgentError::Overloaded(_));
+        let exhausted = if overloaded {
+            self.overload_attempt += 1;
+            self.overload_attempt > self.config.max_overload_retries
+        } else {
+            self.retry_attempt += 1;
+            self.retry_attempt >= self.config.max_retries || !classified.is_retryable()
+        };
+        if exhausted {
+            if let Some(step) = self.fall_back(&err) {
+                return step;
+            }
+            self.retry_attempt = 0;
+            self.overload_attempt = 0;
+            return AgentStep::Error(classified);
+        }
+
+        let (attempt, delay) = if overloaded {
+            let delay = overload_backoff(
+                self.overload_attempt,
+                self.config.overload_backoff_secs,
+                self.config.overload_backoff_max_secs,
+                rand::random(),
+            );
+            (self.overload_attempt, delay)
+        } else {
+            // Exponential backoff: 2s, 4s, 8s, 16s, ...
+            (self.retry_attempt, Duration::from_secs(2u64.pow(self.retry_attempt)))
+        };
+        self.retry_delay = Some(delay);
+        AgentStep::Retrying {
+            attempt,
+            error: err,
+            delay_secs: delay.as_secs(),
+            overloaded,
+        }
+    }
+
+    /// When the request in flight times out, and the error to report:
+    /// `stream_idle_timeout_secs` after it last received data, or
+    /// `request_timeout_secs` after it was sent, whichever comes first
+    fn stream_deadline(&self) -> Option<(tokio::time::Instant, String)> {
+        let idle = self.config.stream_idle_timeout_secs.zip(self.stream_activity).map(|(secs, last)| {
+            (last + Duration::from_secs(secs), format!("Stream timed out: no data for {}s", secs))
+        });
+        let overall = self.config.request_timeout_secs.zip(self.request_started).map(|(secs, started)| {
+            (started + Duration::from_secs(secs), format!("Request timed out after {}s", secs))
+        });
+        match (idle, overall) {
+            (Some(idle), Some(overall)) => Some(if overall.0 < idle.0 { overall } else { idle }),
+            (idle, overall) => idle.or(overall),
+        }
+    }
+
+    /// Drop a stream that failed or stalled and decide what follows
+    fn on_stream_error(&mut self, err: String) -> AgentStep {
+        error!("Stream error (attempt {}): {}", self.retry_attempt + 1, err);
+        self.active_stream = None;
+
+        let step = self.on_request_error(err);
+        // Retries and fallbacks go back to NeedsChatRequest so
+        // the retry loop picks them up
+        self.state = match step {
+            AgentStep::Error(_) => None,
+            _ => Some(StreamState::NeedsChatRequest),
+        };
+        step
+    }
+
+    /// After a response cut off at `max_tokens`, ask the model to carry on
+    /// where it stopped, up to `length_continuations` times per turn
+    fn continue_after_length(&mut self) -> Option<AgentStep> {
+        if !self.stopped_at_length || self.continuations >= self.config.length_continuations {
+            return None;
+        }
+        self.continuations += 1;
+        info!(
+            "Response hit max_tokens, requesting continuation {}/{}",
+            self.continuations, self.config.length_continuations
+        );
+        self.messages.push(ChatMessage::user(CONTINUE_PROMPT));
+        self.state = Some(StreamState::NeedsChatRequest);
+        Some(AgentStep::Continuing {
+            continuation: self.continuations,
+            max: self.config.length_continuations,
+        })
+    }
+
+    /// Once the turn is over, merge each continuation into the response it
+    /// continued and drop the continue prompt between them, so the history
+    /// only holds what the user and the model actually said
+    fn fold_continuations(&mut self) {
+        let turn_start = self
+            .messages
+            .iter()
+            .rposition(|msg| matches!(msg.role, ChatRole::User) && !is_continue_prompt(msg))
+            .unwrap_or(0);
+        let mut idx = turn_start + 1;
+        while idx < self.messages.len() {
+            if !is_continue_prompt(&self.messages[idx]) {
+                idx += 1;
+                continue;
+            }
+            self.messages.remove(idx);
+            let merges = matches!(self.messages[idx - 1].role, ChatRole::Assistant)
+                && self.messages.get(idx).is_some_and(|msg| matches!(msg.role, ChatRole::Assistant));
+            if !merges {
+                continue;
+            }
+            let continuation = self.messages.remove(idx);
+
+            // Text carries straight on from the cut; the continuation's
+            // thinking was about finishing it and isn't kept
+            let mut parts: Vec<ContentPart> = self.messages[idx - 1].content.parts().to_vec();
+            for part in continuation.content.parts() {
+                match (parts.last_mut(), part) {
+                    (_, ContentPart::Thinking(_)) => {},
+                    (Some(ContentPart::Text(text)), ContentPart::Text(more)) => text.push_str(more),
+                    (_, part) => parts.push(part.clone()),
+                }
+            }
+            self.messages[idx - 1].content = parts
+                .into_iter()
+                .fold(MessageContent::default(), |content, part| content.append(part));
+        }
+    }
+
+    /// Where the context stood against `compaction_threshold` after the last
+    /// request (always `Normal` when no threshold is configured)
+    pub fn context_level(&self) -> ContextLevel {
+        self.context_level
+    }
+
+    /// Track the context level after a request's usage comes in, and line up
+    /// a `ContextWarning` if it went up
+    fn update_context_level(&mut self) {
+        let Some(threshold) = self.config.compaction_threshold else {
+            return;
+        };
+        let context_tokens = self.total_usage.context_tokens;
+        let level = ContextLevel::of(context_tokens, threshold);
+        if level > self.context_level {
+            self.context_warning = Some(AgentStep::ContextWarning {
+                level,
+                context_tokens,
+                threshold,
+            });
+        }
+        self.context_level = level;
+    }
+
+    /// Return a pending `ContextWarning` in place of `step`, which is held
+    /// for the following call to `next`
+    fn warn_before(&mut self, step: AgentStep) -> AgentStep {
+        match self.context_warning.take() {
+            Some(warning) => {
+                self.queued_step = Some(step);
+                warning
             },
+            None => step,
         }
     }
 
@@ -670,17 +1570,21 @@ impl Agent {
     /// This method is cancel-safe: if the future is dropped mid-poll,
     /// the agent remains in a valid state and can be polled again.
     pub async fn next(&mut self) -> Option<AgentStep> {
+        if let Some(step) = self.queued_step.take() {
+            return Some(step);
+        }
         loop {
             // Check state without taking it (cancel-safe)
             match self.state.as_ref()? {
                 StreamState::NeedsChatRequest => {
                     debug!("Agent state: NeedsChatRequest, clearing streaming data");
 
-                    // Exponential backoff before retrying: 2s, 4s, 8s, 16s, ...
-                    if self.retry_attempt > 0 {
-                        let delay = Duration::from_secs(2u64.pow(self.retry_attempt));
-                        info!("Backoff: waiting {}s before retry attempt {}", delay.as_secs(), self.retry_attempt + 1);
+                    // Backoff scheduled by the failed request. Cleared only after
+                    // the sleep, so a cancelled wait starts over.
+                    if let Some(delay) = self.retry_delay {
+                        info!("Backoff: waiting {:.1}s before retrying", delay.as_secs_f64());
                         tokio::time::sleep(delay).await;
+                        self.retry_delay = None;
                     }
 
                     // Refresh dynamic system prompt before each request
@@ -691,8 +1595,20 @@ impl Agent {
                     self.streaming_tool_calls.clear();
                     self.streaming_thinking.clear();
                     self.tool_responses.clear();
-
-                    match self.exec_chat_with_retry().await {
+                    self.stopped_at_length = false;
+
+                    let started = tokio::time::Instant::now();
+                    self.request_started = Some(started);
+                    self.stream_activity = Some(started);
+                    let deadline = self.stream_deadline();
+                    let result = match with_deadline(self.exec_chat_with_retry(), deadline).await {
+                        Ok(result) => result,
+                        Err(err) => {
+                            error!("{} (attempt {})", err, self.retry_attempt + 1);
+                            Err(self.on_request_error(err))
+                        },
+                    };
+                    match result {
                         Ok(response) => {
                             debug!("Agent state: NeedsChatRequest -> Streaming");
                             // Store stream separately and update state
@@ -701,8 +1617,8 @@ impl Agent {
                             // Continue to process streaming state
                         },
                         Err(step) => {
-                            // Retrying or Error - state stays NeedsChatRequest for retry
-                            if !matches!(step, AgentStep::Retrying { .. }) {
+                            // Retrying or falling back - state stays NeedsChatRequest for retry
+                            if !matches!(step, AgentStep::Retrying { .. } | AgentStep::FallbackModel { .. }) {
                                 self.state = None;
                             }
                             return Some(step);
@@ -712,9 +1628,16 @@ impl Agent {
 
                 StreamState::Streaming => {
                     // Get the stream (must exist if we're in Streaming state)
+                    let deadline = self.stream_deadline();
                     let stream = self.active_stream.as_mut()?;
 
-                    match stream.next().await {
+                    let item = match with_deadline(stream.next(), deadline).await {
+                        Ok(item) => item,
+                        // Stalled: abort it and retry like any other stream error
+                        Err(err) => return Some(self.on_stream_error(err)),
+                    };
+                    self.stream_activity = Some(tokio::time::Instant::now());
+                    match item {
                         Some(Ok(event)) => match event {
                             ChatStreamEvent::Start => {
                                 debug!("Agent: got ChatStreamEvent::Start");
@@ -727,7 +1650,9 @@ impl Agent {
                                     RequestMode::Compaction => {
                                         AgentStep::CompactionDelta(chunk.content)
                                     },
-                                    RequestMode::Normal => AgentStep::TextDelta(chunk.content),
+                                    RequestMode::Normal | RequestMode::Plan => {
+                                        AgentStep::TextDelta(chunk.content)
+                                    },
                                 });
                             },
                             ChatStreamEvent::ToolCallChunk(_) => {
@@ -746,39 +1671,38 @@ impl Agent {
                                 if let Some(ref genai_usage) = end.captured_usage {
                                     let turn_usage = Self::extract_turn_usage(genai_usage);
                                     self.total_usage += turn_usage;
-                                    info!("{}", turn_usage.format_log());
+                                    self.turn_usage.add_request(turn_usage);
+                                    self.update_context_level();
+                                    let mut notes = Vec::new();
+                                    if self.thinking_skipped {
+                                        notes.push("thinking skipped: interleaved_thinking_rounds".to_string());
+                                    }
+                                    if let Some(max_tokens) = self.fitted_max_tokens {
+                                        notes.push(format!("max_tokens lowered to {} to fit the context window", max_tokens));
+                                    }
+                                    if notes.is_empty() {
+                                        info!("{}", turn_usage.format_log());
+                                    } else {
+                                        info!("{} ({})", turn_usage.format_log(), notes.join("; "));
+                                    }
                                 } else {
                                     debug!("No captured_usage in End event");
                                 }
+                                self.stopped_at_length =
+                                    end.captured_stop_reason.as_deref().is_some_and(is_length_stop);
                                 if let Some(captured) = end.captured_thinking_blocks.take() {
                                     self.streaming_thinking = captured;
                                 }
-                                if let Some(captured) = end.captured_into_tool_calls() {
+                                if let Some(mut captured) = end.captured_into_tool_calls() {
+                                    for tc in &mut captured {
+                                        normalize_tool_arguments(&mut tc.fn_arguments);
+                                    }
                                     self.streaming_tool_calls = captured;
                                 }
                                 // Continue to process stream end
                             },
                         },
-                        Some(Err(e)) => {
-                            let err = format!("{:#}", e);
-                            error!("Stream error (attempt {}): {}", self.retry_attempt, err);
-                            self.active_stream = None;
-
-                            self.retry_attempt += 1;
-                            if self.retry_attempt >= self.config.max_retries {
-                                self.retry_attempt = 0;
-                                self.state = None;
-                                return Some(AgentStep::Error(format!(
-                                    "Stream error ({}): {}", self.config.model, err
-                                )));
-                            }
-                            // Go back to NeedsChatRequest so the retry loop picks it up
-                            self.state = Some(StreamState::NeedsChatRequest);
-                            return Some(AgentStep::Retrying {
-                                attempt: self.retry_attempt,
-                                error: err,
-                            });
-                        },
+                        Some(Err(e)) => return Some(self.on_stream_error(format!("{:#}", e))),
                         None => {
                             debug!("Agent: stream returned None (closed)");
                             // Stream ended, clean up stream
@@ -789,7 +1713,7 @@ impl Agent {
                                     RequestMode::Compaction => {
                                         self.reset_with_summary(&self.streaming_text.clone())
                                     },
-                                    RequestMode::Normal => {
+                                    RequestMode::Normal | RequestMode::Plan => {
                                         // Build message with thinking blocks + text (same pattern as tool use)
                                         // Only push if there's actual content
                                         let has_content = !self.streaming_thinking.is_empty()
@@ -816,6 +1740,9 @@ impl Agent {
                                                 content: msg_content,
                                                 options: None,
                                             });
+                                            if let Some(step) = self.continue_after_length() {
+                                                return Some(step);
+                                            }
                                         } else {
                                             debug!(
                                                 "Agent: no content to push (no thinking, no text)"
@@ -828,9 +1755,11 @@ impl Agent {
                                     self.messages.len()
                                 );
                                 self.state = None;
-                                return Some(AgentStep::Finished {
+                                self.fold_continuations();
+                                let finished = AgentStep::Finished {
                                     usage: self.total_usage,
-                                });
+                                };
+                                return Some(self.warn_before(finished));
                             }
 
                             let tool_calls: Vec<ToolCall> = self
@@ -839,7 +1768,7 @@ impl Agent {
                                 .map(ToolCall::from)
                                 .collect();
                             self.state = Some(StreamState::AwaitingToolDecision);
-                            return Some(AgentStep::ToolRequest(tool_calls));
+                            return Some(self.warn_before(AgentStep::ToolRequest(tool_calls)));
                         },
                     }
                 },
@@ -852,6 +1781,12 @@ impl Agent {
         }
     }
 
+    /// What a requested tool call will do, for showing the user before they
+    /// approve it (None if the tool has no preview or isn't registered)
+    pub fn preview_tool(&self, call: &ToolCall) -> Option<ToolPreview> {
+        self.tools.try_get(&call.name)?.preview(&call.params)
+    }
+
     /// Submit a tool execution result
     /// Called by App after ToolExecutor runs the tool
     pub fn submit_tool_result(&mut self, call_id: &str, content: String) {
@@ -870,16 +1805,18 @@ impl Agent {
             );
         }
 
+        // Guard: streaming_tool_calls must be non-empty (otherwise we shouldn't be receiving results,
+        // e.g. a late result after abort_tool_wait)
+        if self.streaming_tool_calls.is_empty() {
+            tracing::warn!("submit_tool_result called but no tool calls pending");
+            return;
+        }
+
         // Store the response
         self.tool_responses
             .push(ToolResponse::new(call_id.to_string(), content));
 
         // Check if all tools have been decided
-        // Guard: streaming_tool_calls must be non-empty (otherwise we shouldn't be receiving results)
-        if self.streaming_tool_calls.is_empty() {
-            tracing::warn!("submit_tool_result called but no tool calls pending");
-            return;
-        }
 
         debug!(
             "Agent: tool_responses={}/{}",
@@ -937,3 +1874,570 @@ impl Agent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_cost() {
+        let usage = Usage {
+            output_tokens: 1_000,
+            context_tokens: 10_000,
+            cache_creation_tokens: 2_000,
+            cache_read_tokens: 5_000,
+        };
+        let pricing = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_write: 3.75,
+            cache_read: 0.3,
+        };
+        // 3k uncached input + 2k cache writes + 5k cache reads + 1k output
+        let expected = 0.009 + 0.0075 + 0.0015 + 0.015;
+        assert!((usage.cost(&pricing) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overload_backoff() {
+        let secs = |attempt, jitter| overload_backoff(attempt, 5, 30, jitter).as_secs_f64();
+        assert_eq!(secs(1, 0.5), 5.0);
+        assert_eq!(secs(2, 0.5), 10.0);
+        assert_eq!(secs(3, 0.5), 20.0);
+        assert_eq!(secs(4, 0.5), 30.0);
+        assert_eq!(secs(40, 0.5), 30.0);
+        assert_eq!(secs(1, 0.0), 4.0);
+        assert!(secs(4, 0.99) <= 30.0);
+    }
+
+    #[test]
+    fn test_overloaded_errors_have_their_own_budget() {
+        let config = AgentRuntimeConfig {
+            max_retries: 2,
+            max_overload_retries: 3,
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "", None, ToolRegistry::empty());
+        let overloaded = "status 529: {\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}";
+
+        for attempt in 1..=3 {
+            match agent.on_request_error(overloaded.to_string()) {
+                AgentStep::Retrying { attempt: a, overloaded: true, delay_secs, .. } => {
+                    assert_eq!(a, attempt);
+                    assert!(delay_secs >= 4);
+                },
+                _ => panic!("expected an overload retry"),
+            }
+        }
+        assert_eq!(agent.retry_attempt, 0);
+        assert!(matches!(agent.on_request_error(overloaded.to_string()), AgentStep::Error(AgentError::Overloaded(_))));
+
+        assert!(matches!(
+            agent.on_request_error("error sending request".to_string()),
+            AgentStep::Retrying { attempt: 1, delay_secs: 2, overloaded: false, .. }
+        ));
+        assert!(matches!(agent.on_request_error("error sending request".to_string()), AgentStep::Error(_)));
+    }
+
+    #[test]
+    fn test_turn_usage_sums_requests() {
+        let request = |context_tokens, output_tokens| Usage {
+            output_tokens,
+            context_tokens,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+        };
+        let mut turn = Usage::default();
+        turn.add_request(request(1_000, 100));
+        turn.add_request(request(1_500, 200));
+        assert_eq!(turn.context_tokens, 2_500);
+        assert_eq!(turn.output_tokens, 300);
+
+        let sonnet = ModelPricing::for_model("claude-sonnet-4-5-20250929").unwrap();
+        assert_eq!((sonnet.input, sonnet.output, sonnet.cache_read), (3.0, 15.0, 0.3));
+        assert_eq!(ModelPricing::for_model("claude-opus-4-1").unwrap().input, 15.0);
+        assert_eq!(ModelPricing::for_model("claude-opus-4-5").unwrap().input, 5.0);
+        assert!(ModelPricing::for_model("gpt-4o").is_none());
+    }
+
+    #[test]
+    fn test_reasoning_level_request_options() {
+        let mut agent = Agent::new(AgentRuntimeConfig::default(), "", None, ToolRegistry::empty());
+        let capabilities = ModelCapabilities::for_model(&agent.config.model);
+        let mode_opts = RequestMode::Normal.options(&agent.config);
+        let max_tokens = agent.config.max_tokens;
+
+        // `high` fits under the default max_tokens instead of switching thinking off
+        agent.set_reasoning(Some(ReasoningLevel::High));
+        assert!(matches!(
+            agent.reasoning_effort(&capabilities, &mode_opts, max_tokens),
+            Some(ReasoningEffort::Budget(4_096))
+        ));
+        assert!(matches!(
+            agent.reasoning_effort(&capabilities, &mode_opts, 32_000),
+            Some(ReasoningEffort::Budget(16_384))
+        ));
+        assert!(agent.set_next_max_tokens(10_000).is_ok());
+        assert_eq!(agent.thinking_budget(), 5_000);
+
+        agent.set_reasoning(Some(ReasoningLevel::Off));
+        assert!(agent.reasoning_effort(&capabilities, &mode_opts, max_tokens).is_none());
+
+        // Effort models can't turn reasoning off, so `off` asks for the least
+        let o3 = ModelCapabilities::for_model("o3");
+        assert!(matches!(
+            agent.reasoning_effort(&o3, &mode_opts, max_tokens),
+            Some(ReasoningEffort::Low)
+        ));
+    }
+
+    #[test]
+    fn test_next_max_tokens_applies_once() {
+        let mut agent = Agent::new(AgentRuntimeConfig::default(), "", None, ToolRegistry::empty());
+        let (default, _) = agent.max_tokens();
+
+        assert!(agent.set_next_max_tokens(1_000_000).is_err());
+        assert!(agent.set_next_max_tokens(agent.config.thinking_budget).is_err());
+        agent.set_next_max_tokens(32_000).unwrap();
+        assert_eq!(agent.max_tokens(), (default, Some(32_000)));
+
+        // Compaction leaves the override for the next user message
+        agent.send_request("summarize", RequestMode::Compaction);
+        assert_eq!(agent.request_max_tokens, None);
+        agent.send_request("write the module", RequestMode::Normal);
+        assert_eq!(agent.request_max_tokens, Some(32_000));
+        assert_eq!(agent.max_tokens(), (default, None));
+
+        agent.send_request("thanks", RequestMode::Normal);
+        assert_eq!(agent.request_max_tokens, None);
+    }
+
+    #[test]
+    fn test_preview_tool() {
+        let tool = crate::tools::SimpleTool::new("touch", "Create a file", serde_json::json!({}))
+            .with_preview(|params| {
+                Some(ToolPreview::File {
+                    path: params["path"].as_str()?.to_string(),
+                    content: String::new(),
+                })
+            });
+        let mut tools = ToolRegistry::empty();
+        tools.register(std::sync::Arc::new(tool));
+        let agent = Agent::new(AgentRuntimeConfig::default(), "", None, tools);
+
+        let call = ToolCall {
+            agent_id: 0,
+            call_id: "call_1".to_string(),
+            name: "touch".to_string(),
+            params: serde_json::json!({"path": "notes.md"}),
+            decision: ToolDecision::Pending,
+            background: false,
+        };
+        assert!(matches!(
+            agent.preview_tool(&call),
+            Some(ToolPreview::File { ref path, .. }) if path == "notes.md"
+        ));
+        let unknown = ToolCall { name: "missing".to_string(), ..call };
+        assert!(agent.preview_tool(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_context_warning_once_per_crossing() {
+        assert_eq!(ContextLevel::of(799, 1000), ContextLevel::Normal);
+        assert_eq!(ContextLevel::of(800, 1000), ContextLevel::Warning);
+        assert_eq!(ContextLevel::of(1000, 1000), ContextLevel::Critical);
+
+        let config = AgentRuntimeConfig {
+            compaction_threshold: Some(1000),
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        let warning_at = |agent: &mut Agent, context_tokens: u32| {
+            agent.total_usage.context_tokens = context_tokens;
+            agent.update_context_level();
+            match agent.warn_before(AgentStep::Finished { usage: agent.total_usage }) {
+                AgentStep::ContextWarning { level, .. } => {
+                    assert!(matches!(agent.queued_step.take(), Some(AgentStep::Finished { .. })));
+                    Some(level)
+                },
+                _ => None,
+            }
+        };
+
+        assert_eq!(warning_at(&mut agent, 500), None);
+        assert_eq!(warning_at(&mut agent, 850), Some(ContextLevel::Warning));
+        assert_eq!(warning_at(&mut agent, 900), None);
+        assert_eq!(warning_at(&mut agent, 1200), Some(ContextLevel::Critical));
+
+        // Compaction brings it back down, so the next crossing is reported again
+        agent.reset_with_summary("summary");
+        assert_eq!(agent.context_level(), ContextLevel::Normal);
+        assert_eq!(warning_at(&mut agent, 850), Some(ContextLevel::Warning));
+
+        // As does switching to another conversation
+        agent.restore_from_transcript(&Transcript::with_path(std::path::PathBuf::from("other.md")));
+        assert_eq!(agent.context_level(), ContextLevel::Normal);
+        assert_eq!(agent.total_usage().context_tokens, 0);
+    }
+
+    #[test]
+    fn test_length_continuations_capped_per_turn() {
+        assert!(is_length_stop("max_tokens") && is_length_stop("length"));
+        assert!(!is_length_stop("end_turn"));
+
+        let config = AgentRuntimeConfig {
+            length_continuations: 1,
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.send_request("write a long essay", RequestMode::Normal);
+        assert!(agent.continue_after_length().is_none());
+
+        agent.stopped_at_length = true;
+        assert!(matches!(
+            agent.continue_after_length(),
+            Some(AgentStep::Continuing { continuation: 1, max: 1 })
+        ));
+        assert!(matches!(agent.state, Some(StreamState::NeedsChatRequest)));
+        assert!(agent.continue_after_length().is_none());
+
+        agent.send_request("another one", RequestMode::Normal);
+        agent.stopped_at_length = true;
+        assert!(agent.continue_after_length().is_some());
+    }
+
+    #[test]
+    fn test_continuations_fold_into_one_response() {
+        let config = AgentRuntimeConfig {
+            length_continuations: 2,
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.send_request("write a long essay", RequestMode::Normal);
+        let before = agent.messages.len();
+
+        agent.messages.push(ChatMessage::assistant("The first half"));
+        agent.stopped_at_length = true;
+        assert!(agent.continue_after_length().is_some());
+        // The continue prompt doesn't start a new turn
+        assert_eq!(agent.turn_round(), 1);
+        agent.messages.push(ChatMessage::assistant(" and the second."));
+        assert_eq!(agent.turn_round(), 2);
+
+        agent.fold_continuations();
+        assert_eq!(agent.messages.len(), before + 1);
+        let parts = agent.messages.last().unwrap().content.parts();
+        assert!(matches!(&parts[..], [ContentPart::Text(text)] if text == "The first half and the second."));
+
+        // A prompt whose continuation never arrived is dropped
+        assert!(agent.continue_after_length().is_some());
+        agent.send_request("thanks", RequestMode::Normal);
+        assert!(!agent.messages.iter().any(is_continue_prompt));
+    }
+
+    #[test]
+    fn test_interleaved_thinking_rounds() {
+        let config = AgentRuntimeConfig {
+            interleaved_thinking_rounds: Some(1),
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.send_request("look around", RequestMode::Normal);
+        assert!(agent.thinking_allowed());
+
+        for (i, allowed) in [true, false].into_iter().enumerate() {
+            let call_id = format!("call_{}", i);
+            agent.state = Some(StreamState::AwaitingToolDecision);
+            agent.streaming_tool_calls = vec![GenaiToolCall {
+                call_id: call_id.clone(),
+                fn_name: "mcp_read_file".to_string(),
+                fn_arguments: serde_json::json!({ "path": "log.txt" }),
+                thought_signatures: None,
+            }];
+            agent.submit_tool_result(&call_id, "ok".to_string());
+            assert_eq!(agent.turn_round(), i + 1);
+            assert_eq!(agent.thinking_allowed(), allowed);
+        }
+
+        // A new user message starts a new turn
+        agent.send_request("next", RequestMode::Normal);
+        assert!(agent.thinking_allowed());
+    }
+
+    #[test]
+    fn test_dump_messages() {
+        let mut agent = Agent::new(AgentRuntimeConfig::default(), "system", None, ToolRegistry::empty());
+        agent.send_request(&"x".repeat(1_000), RequestMode::Normal);
+        agent.state = Some(StreamState::AwaitingToolDecision);
+        agent.streaming_tool_calls = vec![GenaiToolCall {
+            call_id: "call_0".to_string(),
+            fn_name: "mcp_read_file".to_string(),
+            fn_arguments: serde_json::json!({ "path": "log.txt" }),
+            thought_signatures: None,
+        }];
+        agent.submit_tool_result("call_0", "ok".to_string());
+
+        let dump = agent.dump_messages();
+        assert!(dump.starts_with("#0 System\n  [text]\n    system\n#1 User\n"));
+        assert!(dump.contains("[600 chars omitted]"));
+        assert!(dump.contains("  [tool_call mcp_read_file call_0]\n    {\"path\":\"log.txt\"}\n"));
+        assert!(dump.contains("  [tool_response call_0]\n    ok\n"));
+    }
+
+    #[test]
+    fn test_fit_max_tokens() {
+        let config = AgentRuntimeConfig {
+            max_tokens: 8_000,
+            context_window: Some(20_000),
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        let capabilities = ModelCapabilities::for_model(&agent.config.model);
+
+        agent.send_request("hello", RequestMode::Normal);
+        let request = ChatRequest::new(agent.messages.clone());
+        assert_eq!(agent.fit_max_tokens(&request, &capabilities), 8_000);
+        assert_eq!(agent.fitted_max_tokens, None);
+
+        // ~15k tokens of input leaves ~5k for the response
+        agent.send_request(&"x".repeat(60_000), RequestMode::Normal);
+        let request = ChatRequest::new(agent.messages.clone());
+        let fitted = agent.fit_max_tokens(&request, &capabilities);
+        assert!((4_990..=5_000).contains(&fitted), "fitted to {}", fitted);
+        assert_eq!(agent.fitted_max_tokens, Some(fitted));
+
+        // A full window still leaves the minimum
+        agent.send_request(&"x".repeat(100_000), RequestMode::Normal);
+        let request = ChatRequest::new(agent.messages.clone());
+        assert_eq!(agent.fit_max_tokens(&request, &capabilities), MIN_OUTPUT_TOKENS);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_times_out() {
+        let config = AgentRuntimeConfig {
+            stream_idle_timeout_secs: Some(1),
+            request_timeout_secs: Some(600),
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.send_request("hello", RequestMode::Normal);
+        agent.state = Some(StreamState::Streaming);
+        agent.active_stream = Some(futures::stream::pending().boxed());
+        agent.stream_activity = Some(tokio::time::Instant::now());
+        agent.request_started = agent.stream_activity;
+
+        match agent.next().await {
+            Some(AgentStep::Retrying { error, .. }) => assert_eq!(error, "Stream timed out: no data for 1s"),
+            _ => panic!("expected a retry"),
+        }
+        assert!(agent.active_stream.is_none());
+        assert!(matches!(agent.state, Some(StreamState::NeedsChatRequest)));
+    }
+
+    #[test]
+    fn test_fallback_models() {
+        let config = AgentRuntimeConfig {
+            fallback_models: vec!["claude-sonnet-4-5".to_string(), "gpt-5".to_string()],
+            ..Default::default()
+        };
+        let primary = config.model.clone();
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.send_request("hello", RequestMode::Normal);
+        assert_eq!(agent.active_model(), primary);
+
+        match agent.fall_back("overloaded") {
+            Some(AgentStep::FallbackModel { from, to, .. }) => {
+                assert_eq!(from, primary);
+                assert_eq!(to, "claude-sonnet-4-5");
+            },
+            _ => panic!("Expected FallbackModel"),
+        }
+        assert!(agent.fall_back("overloaded").is_some());
+        assert_eq!(agent.active_model(), "gpt-5");
+        assert!(agent.fall_back("overloaded").is_none());
+
+        // The next message starts on the primary model again
+        agent.send_request("again", RequestMode::Normal);
+        assert_eq!(agent.active_model(), primary);
+    }
+
+    #[test]
+    fn test_compact_tool_results() {
+        let mut agent = Agent::new(AgentRuntimeConfig::default(), "system", None, ToolRegistry::empty());
+        agent.send_request("look around", RequestMode::Normal);
+
+        // Three rounds of tool calls: two bulky results and one small one
+        let outputs = ["line\n".repeat(500), "tiny".to_string(), "row\n".repeat(400)];
+        for (i, output) in outputs.iter().enumerate() {
+            let call_id = format!("call_{}", i);
+            agent.state = Some(StreamState::AwaitingToolDecision);
+            agent.streaming_tool_calls = vec![GenaiToolCall {
+                call_id: call_id.clone(),
+                fn_name: "mcp_read_file".to_string(),
+                fn_arguments: serde_json::json!({ "path": "log.txt" }),
+                thought_signatures: None,
+            }];
+            agent.submit_tool_result(&call_id, output.clone());
+        }
+        let count = agent.message_count();
+
+        // The newest result is kept, the small one is skipped
+        let (elided, saved) = agent.compact_tool_results(1);
+        assert_eq!(elided, 1);
+        assert!(saved > 2_000);
+        assert_eq!(agent.message_count(), count);
+
+        let results: Vec<String> = agent
+            .messages
+            .iter()
+            .flat_map(|msg| msg.content.tool_responses())
+            .map(|r| r.content.clone())
+            .collect();
+        assert!(results[0].starts_with("[Output of mcp_read_file elided to save context: 500 lines"));
+        assert_eq!(results[1], "tiny");
+        assert_eq!(results[2], outputs[2]);
+
+        // Already elided results aren't elided again
+        assert_eq!(agent.compact_tool_results(0).0, 1);
+    }
+
+    #[test]
+    fn test_fit_restored_history() {
+        let config = AgentRuntimeConfig {
+            context_window: Some(10_000),
+            restore_context_fraction: 0.5,
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        // Ten exchanges of ~1k tokens each; the last four fit in 5k
+        for i in 0..10 {
+            agent.messages.push(ChatMessage::user(format!("question {} {}", i, "x".repeat(2_000))));
+            agent.messages.push(ChatMessage::assistant("y".repeat(2_000)));
+        }
+        agent.fit_restored_history(false);
+
+        assert_eq!(agent.message_count(), 1 + 8);
+        assert!(matches!(agent.messages[0].role, ChatRole::System));
+        let text = |msg: &ChatMessage| {
+            msg.content
+                .parts()
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<String>()
+        };
+        // The note opens the first message kept rather than adding a second user message
+        assert!(text(&agent.messages[1]).starts_with("[12 earlier messages"));
+        assert!(text(&agent.messages[1]).contains("]\n\nquestion 6 "));
+        assert!(matches!(agent.messages[2].role, ChatRole::Assistant));
+
+        // A last exchange larger than the budget is still kept
+        agent.messages.push(ChatMessage::user("z".repeat(40_000)));
+        agent.fit_restored_history(false);
+        assert_eq!(agent.message_count(), 2);
+        assert!(text(&agent.messages[1]).starts_with("[8 earlier messages"));
+    }
+
+    #[test]
+    fn test_fit_restored_history_keeps_summary() {
+        let config = AgentRuntimeConfig {
+            context_window: Some(10_000),
+            restore_context_fraction: 0.5,
+            ..Default::default()
+        };
+        let mut agent = Agent::new(config, "system", None, ToolRegistry::empty());
+        agent.messages.push(ChatMessage::assistant("summary of the session"));
+        for i in 0..10 {
+            agent.messages.push(ChatMessage::user(format!("question {} {}", i, "x".repeat(2_000))));
+            agent.messages.push(ChatMessage::assistant("y".repeat(2_000)));
+        }
+        agent.fit_restored_history(true);
+
+        assert_eq!(agent.message_count(), 1 + 1 + 8);
+        let first_text = |msg: &ChatMessage| match msg.content.parts().first() {
+            Some(ContentPart::Text(text)) => text.clone(),
+            _ => String::new(),
+        };
+        assert_eq!(first_text(&agent.messages[1]), "summary of the session");
+        assert!(first_text(&agent.messages[2]).starts_with("[12 earlier messages"));
+    }
+
+    #[test]
+    fn test_usage_serde_roundtrip() {
+        let usage = Usage {
+            output_tokens: 12,
+            context_tokens: 340,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 300,
+        };
+        let json = serde_json::to_value(usage).unwrap();
+        assert_eq!(json["context_tokens"], 340);
+        assert_eq!(serde_json::from_value::<Usage>(json).unwrap(), usage);
+    }
+
+    #[test]
+    fn test_normalize_tool_arguments() {
+        let call = |fn_arguments| GenaiToolCall {
+            call_id: "call_1".to_string(),
+            fn_name: "mcp_read_file".to_string(),
+            fn_arguments,
+            thought_signatures: None,
+        };
+
+        // Object arguments pass through unchanged
+        let mut object = call(serde_json::json!({ "path": "src/main.rs", "background": true }));
+        normalize_tool_arguments(&mut object.fn_arguments);
+        let tool_call = ToolCall::from(&object);
+        assert_eq!(tool_call.params, serde_json::json!({ "path": "src/main.rs" }));
+        assert!(tool_call.background);
+
+        // String arguments are decoded, including the background flag
+        let mut string = call(serde_json::json!(r#"{"path": "src/main.rs", "background": true}"#));
+        normalize_tool_arguments(&mut string.fn_arguments);
+        let tool_call = ToolCall::from(&string);
+        assert_eq!(tool_call.params, serde_json::json!({ "path": "src/main.rs" }));
+        assert!(tool_call.background);
+
+        let mut empty = serde_json::json!("");
+        normalize_tool_arguments(&mut empty);
+        assert_eq!(empty, serde_json::json!({}));
+
+        let mut garbage = serde_json::json!("not json");
+        normalize_tool_arguments(&mut garbage);
+        assert_eq!(garbage, serde_json::json!("not json"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_tool_wait_recovers() {
+        let mut agent = Agent::new(
+            AgentRuntimeConfig::default(),
+            "system",
+            None,
+            ToolRegistry::empty(),
+        );
+        agent.send_request("list the files", RequestMode::Normal);
+
+        // Simulate a turn that stopped for tool calls whose results never came back
+        agent.state = Some(StreamState::AwaitingToolDecision);
+        agent.streaming_text = "Let me look.".to_string();
+        agent.tool_responses.push(ToolResponse::new("stale".to_string(), "partial".to_string()));
+        assert!(agent.next().await.is_none(), "agent should be blocked on tools");
+
+        assert!(agent.abort_tool_wait());
+        assert!(agent.state.is_none());
+        assert!(agent.tool_responses.is_empty());
+        assert!(!agent.abort_tool_wait());
+
+        // A late result is ignored rather than resuming the abandoned turn
+        agent.submit_tool_result("stale", "done".to_string());
+        assert!(agent.state.is_none());
+        assert!(agent.tool_responses.is_empty());
+
+        agent.send_request("try again", RequestMode::Normal);
+        assert!(matches!(agent.state, Some(StreamState::NeedsChatRequest)));
+        assert_eq!(agent.messages.len(), 3);
+        assert!(matches!(agent.messages[2].role, ChatRole::User));
+    }
+}
@@ -0,0 +1,251 @@
+//! Per-model feature support
+//!
+//! Not every model accepts every request option: extended thinking is only
+//! understood by reasoning models, and `cache_control` markers are specific to
+//! Anthropic. The agent looks up the model here before each request and only
+//! enables what the model supports. Unknown models get a conservative default.
+//!
+//! Reasoning levels are translated here too: Claude models take a thinking
+//! token budget, sized to the request's `max_tokens`, while OpenAI reasoning
+//! models take the named effort directly.
+
+use genai::chat::ReasoningEffort;
+use serde::{Deserialize, Serialize};
+
+use super::client::{is_openrouter_model, OPENROUTER_PREFIX};
+
+/// Named amount of reasoning, an alternative to a raw thinking budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningLevel {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningLevel {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Low, Self::Medium, Self::High];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
+    /// Thinking budget before it's fitted to the model's output limit
+    fn base_budget(&self) -> u32 {
+        match self {
+            Self::Off => 0,
+            Self::Low => 1_024,
+            Self::Medium => 4_096,
+            Self::High => 16_384,
+        }
+    }
+}
+
+impl std::str::FromStr for ReasoningLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown reasoning level '{}' (expected off, low, medium or high)", s))
+    }
+}
+
+impl std::fmt::Display for ReasoningLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Features a model supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    /// Extended thinking / reasoning effort
+    pub thinking: bool,
+    /// Reasoning is requested as a named effort (low, medium, high) rather
+    /// than a token budget
+    pub effort_levels: bool,
+    /// Anthropic-style `cache_control` breakpoints
+    pub prompt_caching: bool,
+    /// Image inputs
+    pub vision: bool,
+    /// Tool definitions with streamed tool calls
+    pub tool_streaming: bool,
+    /// Largest `max_tokens` the model accepts (None = unknown, not checked)
+    pub max_output_tokens: Option<u32>,
+    /// Input plus output tokens the model can handle (None = unknown)
+    pub context_window: Option<u32>,
+}
+
+impl ModelCapabilities {
+    /// Used for models not in the table: no optional request features, but
+    /// tool calling is kept since the agent can't work without it
+    pub const CONSERVATIVE: Self = Self {
+        thinking: false,
+        effort_levels: false,
+        prompt_caching: false,
+        vision: false,
+        tool_streaming: true,
+        max_output_tokens: None,
+        context_window: None,
+    };
+
+    const CLAUDE: Self = Self {
+        thinking: true,
+        effort_levels: false,
+        prompt_caching: true,
+        vision: true,
+        tool_streaming: true,
+        max_output_tokens: Some(64_000),
+        context_window: Some(200_000),
+    };
+
+    /// Claude models from before extended thinking
+    const CLAUDE_LEGACY: Self = Self {
+        thinking: false,
+        max_output_tokens: Some(8_192),
+        ..Self::CLAUDE
+    };
+
+    const GPT: Self = Self {
+        thinking: false,
+        effort_levels: false,
+        prompt_caching: false,
+        vision: true,
+        tool_streaming: true,
+        max_output_tokens: Some(16_384),
+        context_window: Some(128_000),
+    };
+
+    const OPENAI_REASONING: Self = Self {
+        thinking: true,
+        effort_levels: true,
+        max_output_tokens: Some(100_000),
+        context_window: Some(200_000),
+        ..Self::GPT
+    };
+
+    /// Capabilities for a model name as written in config
+    pub fn for_model(model: &str) -> Self {
+        let name = model.strip_prefix(OPENROUTER_PREFIX).unwrap_or(model);
+        // Drop a provider namespace: `openai::gpt-4o`, `anthropic/claude-...`
+        let name = name.rsplit("::").next().unwrap_or(name);
+        let name = name.rsplit('/').next().unwrap_or(name);
+
+        let mut capabilities = KNOWN_MODELS
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map_or(Self::CONSERVATIVE, |(_, capabilities)| *capabilities);
+
+        // Reasoning options aren't forwarded to OpenRouter
+        if is_openrouter_model(model) {
+            capabilities.thinking = false;
+        }
+        capabilities
+    }
+
+    /// Thinking budget for a reasoning level: the level's budget, capped at
+    /// half of `max_tokens` (and of the model's output limit) so the response
+    /// keeps room. 0 when the level is off, the model doesn't think, or the
+    /// cap leaves less than the smallest budget Claude accepts.
+    pub fn reasoning_budget(&self, level: ReasoningLevel, max_tokens: u32) -> u32 {
+        if !self.thinking || level == ReasoningLevel::Off {
+            return 0;
+        }
+        let limit = self.max_output_tokens.map_or(max_tokens, |limit| limit.min(max_tokens));
+        let budget = level.base_budget().min(limit / 2);
+        if budget < ReasoningLevel::Low.base_budget() {
+            return 0;
+        }
+        budget
+    }
+
+    /// How to ask this model for `budget` thinking tokens, or for `level`
+    /// when the model takes named efforts. These models can't switch
+    /// reasoning off, so `off` asks for the lowest effort rather than none,
+    /// which would leave the model on its default. None when there's
+    /// nothing to send.
+    pub fn reasoning_effort(&self, level: Option<ReasoningLevel>, budget: u32) -> Option<ReasoningEffort> {
+        match level {
+            Some(ReasoningLevel::Off | ReasoningLevel::Low) if self.effort_levels => Some(ReasoningEffort::Low),
+            Some(ReasoningLevel::Medium) if self.effort_levels => Some(ReasoningEffort::Medium),
+            Some(ReasoningLevel::High) if self.effort_levels => Some(ReasoningEffort::High),
+            _ if budget > 0 => Some(ReasoningEffort::Budget(budget)),
+            _ => None,
+        }
+    }
+}
+
+/// Model name prefixes and their capabilities; the first match wins
+const KNOWN_MODELS: &[(&str, ModelCapabilities)] = &[
+    ("claude-3-haiku", ModelCapabilities::CLAUDE_LEGACY),
+    ("claude-3-opus", ModelCapabilities::CLAUDE_LEGACY),
+    ("claude-3-5-", ModelCapabilities::CLAUDE_LEGACY),
+    ("claude-", ModelCapabilities::CLAUDE),
+    ("gpt-5", ModelCapabilities::OPENAI_REASONING),
+    ("gpt-4", ModelCapabilities::GPT),
+    ("o1", ModelCapabilities::OPENAI_REASONING),
+    ("o3", ModelCapabilities::OPENAI_REASONING),
+    ("o4", ModelCapabilities::OPENAI_REASONING),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_models() {
+        assert_eq!(ModelCapabilities::for_model("claude-sonnet-4-5-20250929"), ModelCapabilities::CLAUDE);
+        assert!(!ModelCapabilities::for_model("claude-3-5-haiku-20241022").thinking);
+        assert!(ModelCapabilities::for_model("claude-3-5-haiku-20241022").prompt_caching);
+
+        let gpt = ModelCapabilities::for_model("openai::gpt-4o");
+        assert!(!gpt.thinking && !gpt.prompt_caching && gpt.vision);
+        assert!(ModelCapabilities::for_model("o3-mini").thinking);
+    }
+
+    #[test]
+    fn test_openrouter_and_unknown_models() {
+        let routed = ModelCapabilities::for_model("openrouter::anthropic/claude-opus-4");
+        assert!(!routed.thinking);
+        assert!(routed.prompt_caching);
+
+        assert_eq!(ModelCapabilities::for_model("ollama::llama3"), ModelCapabilities::CONSERVATIVE);
+    }
+
+    #[test]
+    fn test_reasoning_levels() {
+        let claude = ModelCapabilities::for_model("claude-opus-4-6");
+        assert_eq!(claude.reasoning_budget(ReasoningLevel::Off, 32_000), 0);
+        assert_eq!(claude.reasoning_budget(ReasoningLevel::Medium, 32_000), 4_096);
+        assert_eq!(claude.reasoning_budget(ReasoningLevel::High, 32_000), 16_384);
+        assert!(matches!(
+            claude.reasoning_effort(Some(ReasoningLevel::High), 16_384),
+            Some(ReasoningEffort::Budget(16_384))
+        ));
+        assert!(claude.reasoning_effort(Some(ReasoningLevel::Off), 0).is_none());
+
+        // The default max_tokens (8192) halves `high`
+        assert_eq!(claude.reasoning_budget(ReasoningLevel::High, 8_192), 4_096);
+        // So does a small output limit
+        let small = ModelCapabilities { max_output_tokens: Some(8_192), ..claude };
+        assert_eq!(small.reasoning_budget(ReasoningLevel::High, 32_000), 4_096);
+        // Too little room for any thinking
+        assert_eq!(claude.reasoning_budget(ReasoningLevel::Low, 1_500), 0);
+
+        let o3 = ModelCapabilities::for_model("o3-mini");
+        assert!(matches!(o3.reasoning_effort(Some(ReasoningLevel::Low), 1_024), Some(ReasoningEffort::Low)));
+        assert!(matches!(o3.reasoning_effort(Some(ReasoningLevel::Off), 0), Some(ReasoningEffort::Low)));
+        assert_eq!(ModelCapabilities::for_model("gpt-4o").reasoning_budget(ReasoningLevel::High, 8_192), 0);
+
+        assert_eq!("Medium".parse::<ReasoningLevel>(), Ok(ReasoningLevel::Medium));
+        assert!("extreme".parse::<ReasoningLevel>().is_err());
+    }
+}
@@ -6,12 +6,17 @@
 //! - `openrouter::anthropic/claude-3.5-sonnet` → OpenRouter API
 //! - `claude-sonnet-4-20250514` → Native Anthropic API (default)
 //!
+//! A base URL can replace the default endpoint for every non-OpenRouter model,
+//! e.g. an Anthropic-compatible proxy or a local OpenAI-compatible server.
+//!
 //! # Environment Variables
 //!
 //! - `OPENROUTER_API_KEY` - Required for OpenRouter models
 //! - `ANTHROPIC_API_KEY` - Required for native Anthropic models
 
 use genai::adapter::AdapterKind;
+use std::sync::Arc;
+
 use genai::resolver::{AuthData, Endpoint, ServiceTargetResolver};
 use genai::{Client, ModelIden, ServiceTarget};
 
@@ -36,8 +41,20 @@ pub const OPENROUTER_PREFIX: &str = "openrouter::";
 /// // Requests to this model go to OpenRouter
 /// ```
 pub fn build_client() -> Client {
+    build_client_with_base_url(None)
+}
+
+/// Build a genai Client that sends non-OpenRouter requests to `base_url`.
+///
+/// The model's provider (and so the API format and auth) is still chosen by
+/// genai from the model name: `claude-*` uses the Anthropic API, `gpt-*` and
+/// `openai::<model>` the OpenAI API, `ollama::<model>` the Ollama API.
+/// OpenRouter models always go to OpenRouter.
+pub fn build_client_with_base_url(base_url: Option<&str>) -> Client {
+    let base_url: Option<Arc<str>> = base_url.map(|url| normalize_base_url(url).into());
+
     let target_resolver = ServiceTargetResolver::from_resolver_fn(
-        |service_target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
+        move |service_target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
             let ServiceTarget { ref model, .. } = service_target;
             
             // Check for openrouter:: prefix
@@ -49,6 +66,12 @@ pub fn build_client() -> Client {
                 return Ok(ServiceTarget { endpoint, auth, model });
             }
             
+            if let Some(ref base_url) = base_url {
+                let ServiceTarget { auth, model, .. } = service_target;
+                let endpoint = Endpoint::from_owned(base_url.clone());
+                return Ok(ServiceTarget { endpoint, auth, model });
+            }
+
             // Return unchanged for default provider resolution
             Ok(service_target)
         },
@@ -59,6 +82,16 @@ pub fn build_client() -> Client {
         .build()
 }
 
+/// genai joins request paths onto the endpoint, so it must end with a slash
+fn normalize_base_url(url: &str) -> String {
+    let url = url.trim();
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{}/", url)
+    }
+}
+
 /// Check if a model name is an OpenRouter model
 pub fn is_openrouter_model(model: &str) -> bool {
     model.starts_with(OPENROUTER_PREFIX)
@@ -82,6 +115,12 @@ mod tests {
         assert!(!is_openrouter_model("gpt-4o"));
     }
 
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(normalize_base_url("http://localhost:8080/v1"), "http://localhost:8080/v1/");
+        assert_eq!(normalize_base_url("https://proxy.internal/v1/ "), "https://proxy.internal/v1/");
+    }
+
     #[test]
     fn test_strip_openrouter_prefix() {
         assert_eq!(
@@ -0,0 +1,147 @@
+//! Classified API errors
+//!
+//! Provider errors reach us as formatted text: a status line from the HTTP
+//! client, usually followed by a JSON body like Anthropic's
+//! `{"type":"error","error":{"type":"rate_limit_error","message":"..."}}`.
+//! `AgentError::classify` turns that into something callers can match on,
+//! keeping the provider's own message for display.
+
+use std::fmt;
+
+/// Why a request to the model failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentError {
+    /// Credentials are missing, expired or rejected
+    Auth(String),
+    /// Too many requests
+    RateLimit(String),
+    /// The provider is temporarily overloaded
+    Overloaded(String),
+    /// The conversation no longer fits in the model's context window
+    ContextTooLong(String),
+    /// The provider couldn't be reached (connection, DNS, TLS, timeout)
+    Network(String),
+    /// Any other API error
+    Api(String),
+}
+
+impl AgentError {
+    /// Classify a formatted provider or transport error
+    pub fn classify(error: &str) -> Self {
+        let (kind, message) = match api_error_body(error) {
+            Some((kind, message)) => (kind, message),
+            None => (String::new(), error.to_string()),
+        };
+        let lower = error.to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+
+        match kind.as_str() {
+            "authentication_error" | "permission_error" => Self::Auth(message),
+            "rate_limit_error" => Self::RateLimit(message),
+            "overloaded_error" => Self::Overloaded(message),
+            _ if mentions(&["prompt is too long", "context_length_exceeded", "context length", "maximum context"]) => {
+                Self::ContextTooLong(message)
+            },
+            _ if mentions(&["401", "403", "unauthorized", "invalid x-api-key", "invalid api key"]) => Self::Auth(message),
+            _ if mentions(&["429", "rate limit", "rate_limit"]) => Self::RateLimit(message),
+            _ if mentions(&["529", "503", "overloaded"]) => Self::Overloaded(message),
+            _ if mentions(&["error sending request", "connection", "dns error", "timed out", "tls"]) => {
+                Self::Network(message)
+            },
+            _ => Self::Api(message),
+        }
+    }
+
+    /// The provider's message, without the classification
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Auth(m)
+            | Self::RateLimit(m)
+            | Self::Overloaded(m)
+            | Self::ContextTooLong(m)
+            | Self::Network(m)
+            | Self::Api(m) => m,
+        }
+    }
+
+    /// Whether retrying the same request might succeed
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::Auth(_) | Self::ContextTooLong(_))
+    }
+
+    /// Whether the provider is asking us to slow down
+    pub fn is_rate_limit(&self) -> bool {
+        matches!(self, Self::RateLimit(_) | Self::Overloaded(_))
+    }
+
+    /// What the user can do about it, if there's anything specific
+    pub fn guidance(&self) -> Option<&'static str> {
+        match self {
+            Self::Auth(_) => Some("run `codey --login` or check your API key"),
+            Self::RateLimit(_) => Some("retries ran out; wait a moment and send again"),
+            Self::Overloaded(_) => Some("try again shortly, or set fallback_models"),
+            Self::ContextTooLong(_) => Some("run /compact to shrink the conversation"),
+            Self::Network(_) => Some("check your network connection"),
+            Self::Api(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Auth(_) => "Authentication failed",
+            Self::RateLimit(_) => "Rate limited",
+            Self::Overloaded(_) => "API overloaded",
+            Self::ContextTooLong(_) => "Context too long",
+            Self::Network(_) => "Network error",
+            Self::Api(_) => "API error",
+        };
+        write!(f, "{}: {}", label, self.message())
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+/// `error.type` and `error.message` from a JSON body embedded in the error
+fn api_error_body(error: &str) -> Option<(String, String)> {
+    let json: serde_json::Value = serde_json::from_str(&error[error.find('{')?..]).ok()?;
+    let error = &json["error"];
+    let message = error["message"].as_str()?.to_string();
+    let kind = error["type"].as_str().or(error["code"].as_str()).unwrap_or_default();
+    Some((kind.to_string(), message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_anthropic_bodies() {
+        let body = |kind: &str| {
+            format!(
+                "Request failed with status code '400'. Response body: {{\"type\":\"error\",\"error\":{{\"type\":\"{}\",\"message\":\"details\"}}}}",
+                kind
+            )
+        };
+        assert_eq!(AgentError::classify(&body("authentication_error")), AgentError::Auth("details".into()));
+        assert_eq!(AgentError::classify(&body("rate_limit_error")), AgentError::RateLimit("details".into()));
+        assert_eq!(AgentError::classify(&body("overloaded_error")), AgentError::Overloaded("details".into()));
+        assert_eq!(AgentError::classify(&body("invalid_request_error")), AgentError::Api("details".into()));
+
+        let too_long = "status 400: {\"error\":{\"type\":\"invalid_request_error\",\"message\":\"prompt is too long: 210000 tokens > 200000 maximum\"}}";
+        assert!(matches!(AgentError::classify(too_long), AgentError::ContextTooLong(_)));
+    }
+
+    #[test]
+    fn test_classify_without_body() {
+        assert!(matches!(AgentError::classify("status 401 Unauthorized"), AgentError::Auth(_)));
+        assert!(matches!(AgentError::classify("HTTP 429 Too Many Requests"), AgentError::RateLimit(_)));
+        assert!(matches!(AgentError::classify("error sending request for url"), AgentError::Network(_)));
+        assert_eq!(AgentError::classify("something odd"), AgentError::Api("something odd".into()));
+
+        assert!(!AgentError::Auth(String::new()).is_retryable());
+        assert!(AgentError::Overloaded(String::new()).is_rate_limit());
+        assert_eq!(AgentError::RateLimit("slow down".into()).to_string(), "Rate limited: slow down");
+    }
+}
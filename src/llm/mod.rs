@@ -1,12 +1,20 @@
 //! LLM client and agent loop
 
 mod agent;
+mod capabilities;
 mod client;
+mod error;
 mod registry;
 
 #[allow(unused_imports)]
-pub use agent::{Agent, AgentStep, RequestMode, SystemPromptBuilder, Usage};
+pub use agent::{
+    Agent, AgentStep, ContextLevel, ModelPricing, RequestMode, SystemPromptBuilder, Usage, CONTEXT_WARNING_RATIO,
+};
 #[allow(unused_imports)]
-pub use client::{build_client, is_openrouter_model, OPENROUTER_PREFIX};
+pub use capabilities::{ModelCapabilities, ReasoningLevel};
+#[allow(unused_imports)]
+pub use client::{build_client, build_client_with_base_url, is_openrouter_model, OPENROUTER_PREFIX};
+#[allow(unused_imports)]
+pub use error::AgentError;
 #[allow(unused_imports)]
 pub use registry::{AgentId, AgentMetadata, AgentRegistry, AgentStatus, PRIMARY_AGENT_ID};
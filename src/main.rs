@@ -1,19 +1,26 @@
 mod app;
+mod artifacts;
 mod auth;
+mod clipboard;
 mod commands;
 mod compaction;
 mod config;
+mod diff;
 mod effect;
 mod ide;
+mod links;
 mod llm;
 mod notifications;
+mod pinned;
 #[cfg(feature = "profiling")]
 mod profiler;
 mod prompts;
+mod script;
 mod tool_filter;
 mod tools;
 mod transcript;
 mod ui;
+mod variables;
 
 use std::path::PathBuf;
 
@@ -38,10 +45,29 @@ struct Args {
     #[arg(short, long)]
     model: Option<String>,
 
+    /// Don't connect to an editor, overriding `ide.nvim.enabled`
+    #[arg(long)]
+    no_ide: bool,
+
     /// OAuth login - without code prints auth URL, with code exchanges for token
     #[arg(long, num_args = 0..=1, default_missing_value = "")]
     login: Option<String>,
 
+    /// Print the effective configuration (after CLI overrides) as TOML and exit.
+    /// API keys, tokens and MCP env vars/headers are redacted.
+    #[arg(long)]
+    print_config: bool,
+
+    /// Run the prompts in FILE (separated by `---` lines) without the TUI,
+    /// printing each response, then exit. Tool calls that would need approval
+    /// are denied.
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// With --script, keep going after a prompt fails instead of stopping
+    #[arg(long, requires = "script")]
+    continue_on_error: bool,
+
     /// Enable performance profiling (requires --features profiling)
     /// Exports profile data to the specified path on exit
     #[cfg(feature = "profiling")]
@@ -121,12 +147,24 @@ async fn main() -> Result<()> {
     if let Some(model) = args.model {
         config.agents.foreground.model = model;
     }
+    if args.no_ide {
+        config.ide.nvim.enabled = false;
+    }
+
+    if args.print_config {
+        print!("{}", config.to_redacted_toml()?);
+        return Ok(());
+    }
 
     // Set working directory
     if let Some(ref working_dir) = config.general.working_dir {
         std::env::set_current_dir(working_dir)?;
     }
 
+    if let Some(path) = args.script {
+        return script::run(config, &path, args.continue_on_error).await;
+    }
+
     // Initialize profiling if enabled
     #[cfg(feature = "profiling")]
     let profile_output = args.profile.clone();
@@ -140,6 +178,12 @@ async fn main() -> Result<()> {
     // Run the application
     let mut app = app::App::new(config, args.r#continue).await?;
     let result = app.run().await;
+    let stats = app.session_stats();
+    // Restore the terminal before printing
+    drop(app);
+    if result.is_ok() {
+        println!("{}", stats.summary());
+    }
 
     // Export profiling data if enabled
     #[cfg(feature = "profiling")]
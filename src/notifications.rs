@@ -30,6 +30,8 @@ pub enum Notification {
     /// Slash command to execute  
     Command {
         name: String,
+        /// Everything after the command name, trimmed
+        args: String,
         block_id: usize,
     },
 
@@ -184,6 +186,7 @@ mod tests {
     fn test_command_cannot_interrupt() {
         let cmd = Notification::Command {
             name: "help".to_string(),
+            args: String::new(),
             block_id: 1,
         };
         assert!(!cmd.can_interrupt());
@@ -208,6 +211,7 @@ mod tests {
         });
         queue.push(Notification::Command {
             name: "help".to_string(),
+            args: String::new(),
             block_id: 1,
         });
         queue.push(Notification::Message {
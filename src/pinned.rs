@@ -0,0 +1,143 @@
+//! Files pinned into the agent's context
+//!
+//! Pinned files (a design doc, an API spec) are re-read before every request
+//! and added to the system prompt, so the agent always sees their current
+//! contents. Because they live in the system prompt rather than the message
+//! history, they survive compaction unchanged. The total pinned size is
+//! bounded; files that don't fit are listed but their contents are left out.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::{bail, Result};
+
+/// Default bound on the total size of pinned file contents, in bytes
+pub const DEFAULT_PINNED_MAX_BYTES: usize = 64 * 1024;
+
+/// The set of pinned files, shared between `/pin` and the prompt builder
+#[derive(Debug, Clone)]
+pub struct PinnedFiles {
+    paths: Arc<RwLock<Vec<PathBuf>>>,
+    max_bytes: usize,
+}
+
+impl PinnedFiles {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>, max_bytes: usize) -> Self {
+        let mut unique = Vec::new();
+        for path in paths {
+            if !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+        Self {
+            paths: Arc::new(RwLock::new(unique)),
+            max_bytes,
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Currently pinned paths, in pin order
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.paths.read().unwrap().clone()
+    }
+
+    /// Pin a file. Returns false if it was already pinned.
+    pub fn pin(&self, path: &Path) -> Result<bool> {
+        if !path.is_file() {
+            bail!("Not a file: {}", path.display());
+        }
+        let mut paths = self.paths.write().unwrap();
+        if paths.iter().any(|p| p == path) {
+            return Ok(false);
+        }
+        paths.push(path.to_path_buf());
+        Ok(true)
+    }
+
+    /// Unpin a file. Returns false if it wasn't pinned.
+    pub fn unpin(&self, path: &Path) -> bool {
+        let mut paths = self.paths.write().unwrap();
+        let before = paths.len();
+        paths.retain(|p| p != path);
+        paths.len() != before
+    }
+
+    /// Total size of the pinned files as they are on disk now
+    pub fn total_bytes(&self) -> usize {
+        self.paths()
+            .iter()
+            .filter_map(|p| fs::metadata(p).ok())
+            .map(|m| m.len() as usize)
+            .sum()
+    }
+
+    /// Current contents of the pinned files as a prompt section.
+    /// Returns None when nothing is pinned.
+    pub fn render(&self) -> Option<String> {
+        let paths = self.paths();
+        if paths.is_empty() {
+            return None;
+        }
+
+        let mut out = String::new();
+        let mut used = 0;
+        for path in &paths {
+            out.push_str(&format!("### {}\n\n", path.display()));
+            match fs::read_to_string(path) {
+                Ok(content) if used + content.len() <= self.max_bytes => {
+                    used += content.len();
+                    out.push_str(&format!("```\n{}\n```\n\n", content.trim_end()));
+                },
+                Ok(content) => {
+                    tracing::warn!(
+                        "Pinned file {} ({} bytes) exceeds the pinned size limit of {} bytes",
+                        path.display(),
+                        content.len(),
+                        self.max_bytes
+                    );
+                    out.push_str(&format!(
+                        "(contents omitted: pinned files exceed {} bytes; use read_file)\n\n",
+                        self.max_bytes
+                    ));
+                },
+                Err(e) => out.push_str(&format!("(could not read: {})\n\n", e)),
+            }
+        }
+        Some(out.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_rereads_and_bounds() {
+        let dir = TempDir::new().unwrap();
+        let spec = dir.path().join("spec.md");
+        let design = dir.path().join("design.md");
+        fs::write(&spec, "v1").unwrap();
+        fs::write(&design, "x".repeat(20)).unwrap();
+
+        let pinned = PinnedFiles::new([spec.clone()], 16);
+        assert!(pinned.render().unwrap().contains("```\nv1\n```"));
+        fs::write(&spec, "v2").unwrap();
+        assert!(pinned.render().unwrap().contains("```\nv2\n```"));
+
+        assert!(pinned.pin(&design).unwrap());
+        assert!(!pinned.pin(&design).unwrap());
+        assert!(pinned.pin(&dir.path().join("missing.md")).is_err());
+        let rendered = pinned.render().unwrap();
+        assert!(rendered.contains("contents omitted"));
+        assert!(!rendered.contains(&"x".repeat(20)));
+
+        assert!(pinned.unpin(&spec));
+        assert!(!pinned.unpin(&spec));
+        assert_eq!(pinned.paths(), vec![design]);
+    }
+}
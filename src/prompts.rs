@@ -8,7 +8,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::config::{Config, CODEY_DIR, CORRECTIONS_FILENAME};
+use crate::config::{Config, GeneralConfig, WelcomeMessage, CODEY_DIR, CORRECTIONS_FILENAME};
+use crate::pinned::PinnedFiles;
+use crate::variables::SessionVariables;
+use crate::tools::{Memory, DEFAULT_MEMORY_MAX_BYTES};
 
 /// Embedded esh script for template processing
 const ESH_SCRIPT: &str = include_str!("../lib/esh/esh");
@@ -19,6 +22,10 @@ pub const SYSTEM_MD_FILENAME: &str = "SYSTEM.md";
 /// Default agent name
 pub const DEFAULT_AGENT_NAME: &str = "Codey";
 
+/// Project notes file: `/init` writes it, every session's system prompt
+/// includes it, and `general.project_banner` shows its first heading
+pub const PROJECT_BANNER_FILENAME: &str = "CODEY.md";
+
 /// Generate welcome message with the given agent name
 pub fn welcome_message(name: &str) -> String {
     format!(
@@ -27,6 +34,36 @@ pub fn welcome_message(name: &str) -> String {
     )
 }
 
+/// Opening turn for a new session: the project banner (if enabled) above the
+/// configured greeting. None when both are off.
+pub fn session_greeting(general: &GeneralConfig, name: &str) -> Option<String> {
+    let message = match &general.welcome_message {
+        WelcomeMessage::Enabled(true) => Some(welcome_message(name)),
+        WelcomeMessage::Enabled(false) => None,
+        WelcomeMessage::Custom(text) => Some(text.clone()),
+    };
+    let banner = general
+        .project_banner
+        .then(|| fs::read_to_string(PROJECT_BANNER_FILENAME).ok())
+        .flatten()
+        .and_then(|content| first_heading(&content));
+
+    match (banner, message) {
+        (Some(banner), Some(message)) => Some(format!("**{}**\n\n{}", banner, message)),
+        (Some(banner), None) => Some(format!("**{}**", banner)),
+        (None, message) => message,
+    }
+}
+
+/// Text of the first markdown heading, e.g. `Acme API` for `# Acme API`
+fn first_heading(markdown: &str) -> Option<String> {
+    markdown
+        .lines()
+        .find(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim().to_string())
+        .filter(|heading| !heading.is_empty())
+}
+
 /// Default intro paragraph for the system prompt
 pub const DEFAULT_SYSTEM_INTRO: &str =
     "You are Codey, an AI coding assistant running in a terminal interface.";
@@ -44,27 +81,39 @@ pub const SYSTEM_PROMPT_BODY: &str = r#"
 ## Capabilities
 You have access to the following tools:
 - `read_file`: Read file contents, optionally with line ranges
+- `read_symbol`: Read one function, type or class from a file by name
 - `write_file`: Create new files
 - `edit_file`: Make precise edits using search/replace
 - `shell`: Execute bash commands
 - `fetch_url`: Fetch web content
 - `fetch_html`: Fetch a web page and extract readable content as markdown
+- `http_request`: Call an HTTP API with any method, headers and a JSON body; returns status, headers and body
+- `download`: Download a URL to a file in the working directory, resuming partial downloads
 - `open_file`: Open a file in the user's IDE/editor at a specific line
+- `ide_open_files`: List the files the user has open in their IDE/editor
+- `annotate`: Show a short note at a line in the user's IDE/editor while explaining code
 - `web_search`: Search the web and return results with titles, URLs, and descriptions
 - `spawn_agent`: Spawn a sub-agent to handle a subtask
 - `list_agents` / `get_agent`: Check status and retrieve results from sub-agents
 - `list_background_tasks` / `get_background_task`: Check on background tool executions
 - `record_correction`: Record a correction when a command fails and you find a better approach
+- `memory_read` / `memory_write`: Read and save notes about the project that persist across sessions
+- `git_status`: Show the branch, staged/unstaged/untracked files, and optionally recent commits
+- `git_stage`: Stage or unstage paths
+- `replace_in_files`: Replace a string or regex across all project files at once
 
 ## Guidelines
 
 ### Reading Files
 - Always read a file before editing it
 - Use line ranges for large files: `read_file(path, start_line=100, end_line=200)`
+- For logs and other large or growing files, `read_file(path, tail=100)` returns just the last lines
 - Use `shell("ls -la")` to explore directories
 - When reading files, be careful about reading large files in one-go. Use line ranges,
     or check the file stats with `shell("stat <file_path>")` first.
 - shell grep is a great way to get a line number to read a targeted section of a file
+- When you know the function or type you need, `read_symbol(path, "Type::method")` returns just
+    that definition with its line range
 
 ### Editing Files
 - Use `edit_file` for existing files, `write_file` only for new files
@@ -94,6 +143,13 @@ When a shell command or approach fails and you find a better way to accomplish t
 - A path that was wrong but you found the correct one
 - A syntax that didn't work but another did
 
+### Project Memory
+Use `memory_write` to save durable facts about the project that a future session would need: conventions, gotchas, how to build and test, decisions the user made. Group notes by topic and give each a short key; write the same key again to update a note, and remove notes that are no longer true. Memory is size-limited, so don't save things that are obvious from the code or only matter for the current task.
+
+### Git
+- Use `git_status` instead of `shell("git status")` or `shell("git log")`; its output is shorter and grouped
+- Use `git_stage` to stage or unstage files; commits, resets, checkouts and anything else that changes history or discards work still go through `shell`
+
 ### General
 - Be concise but thorough
 - Explain what you're doing before executing tools
@@ -117,21 +173,81 @@ Please provide a comprehensive summary of our conversation so far in markdown fo
 
 Be thorough but concise - this summary will seed a fresh conversation context."#;
 
+/// Resolve the compaction prompt: inline config, then config file, then the default
+pub fn compaction_prompt(config: &GeneralConfig) -> anyhow::Result<String> {
+    if let Some(ref prompt) = config.compaction_prompt {
+        return Ok(prompt.clone());
+    }
+    match config.compaction_prompt_path() {
+        Some(path) => fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+        None => Ok(COMPACTION_PROMPT.to_string()),
+    }
+}
+
+/// Appended to user messages while plan mode is active
+pub const PLAN_MODE_PROMPT: &str = r#"<plan-mode>
+Plan mode is active. You can read files and run inspection commands (ls, cat,
+grep, rg, git status/diff/log/show) to explore, but file modifications and
+other shell commands are blocked until the user approves a plan.
+
+Investigate as needed, then reply with a concrete implementation plan:
+- The files you will change and what changes each one needs
+- New files, functions, or types you will add
+- How you will verify the change (tests, commands)
+- Open questions or risks the user should weigh in on
+
+Do not attempt to edit or write files. The user will approve the plan with /execute.
+</plan-mode>"#;
+
+/// Sent when the user approves the plan and leaves plan mode
+pub const PLAN_APPROVED_PROMPT: &str =
+    "The plan is approved. File modifications are now enabled; proceed with the implementation.";
+
+/// Sent by `/init`: explore the project and write (or, with `update`, revise) CODEY.md
+pub fn init_prompt(update: bool) -> String {
+    let write = if update {
+        format!(
+            "{0} already exists: read it first, then bring it up to date with edit_file, keeping \
+anything still accurate that the code can't tell you, such as notes the user wrote.",
+            PROJECT_BANNER_FILENAME
+        )
+    } else {
+        format!("Then create {} with write_file.", PROJECT_BANNER_FILENAME)
+    };
+    format!(
+        "Write a {0} at the project root that gives a future session the context it needs to work here. \
+First explore with read_file, read_symbol and read-only shell commands (ls, rg, git log) to find out:
+- how to build, test, lint and run the project, as exact commands
+- how it is laid out: the main directories and the key files worth reading first
+- the conventions the code follows: naming, error handling, test placement, formatting
+
+{1} Start it with a `# <project name>` heading and keep it short (under 100 lines), \
+concrete and specific to this project; leave out advice that applies to any codebase. \
+Don't change any other file.",
+        PROJECT_BANNER_FILENAME, write
+    )
+}
+
 /// System prompt for sub-agents (background research agents)
 pub const SUB_AGENT_PROMPT: &str = r#"You are a background agent. Your task is to investigate, explore, analyze, or implement as directed.
 
 ## Capabilities
 You have access to:
 - `read_file`: Read file contents
+- `read_symbol`: Read one function, type or class by name
 - `write_file`: Create new files
 - `edit_file`: Edit existing files
+- `replace_in_files`: Replace a string or regex across the project
 - `shell`: Execute commands
 - `fetch_url`: Fetch web content
 - `fetch_html`: Fetch and extract readable content from web pages
 - `web_search`: Search the web
 - `open_file`: Signal a file to open in the IDE
+- `memory_read`: Read notes saved about the project in earlier sessions
+- `git_status` / `git_stage`: Inspect the working tree and stage or unstage files
 
-Write operations (edit_file, write_file) route approval to the user.
+Write operations (edit_file, write_file, replace_in_files) route approval to the user.
 
 ## Guidelines
 - Focus on the specific task assigned to you
@@ -146,7 +262,10 @@ Write operations (edit_file, write_file) route approval to the user.
 /// 1. The base system prompt (configurable intro + capabilities/guidelines)
 /// 2. User SYSTEM.md from ~/.config/codey/ (optional, dynamic)
 /// 3. Project SYSTEM.md from .codey/ (optional, dynamic)
-/// 4. Project corrections.md from .codey/ (optional, contains learned corrections)
+/// 4. Project CODEY.md from the working directory (optional, written by `/init`)
+/// 5. Project corrections.md from .codey/ (optional, contains learned corrections)
+/// 6. Project memory notes from .codey/memory.json (optional, config-gated)
+/// 7. Pinned files, re-read on every build (optional)
 ///
 /// SYSTEM.md files are processed through [esh](https://github.com/jirutka/esh),
 /// allowing embedded shell commands using `<%= command %>` syntax.
@@ -154,11 +273,19 @@ Write operations (edit_file, write_file) route approval to the user.
 pub struct SystemPrompt {
     user_path: Option<PathBuf>,
     project_path: PathBuf,
+    /// Project notes written by `/init`
+    notes_path: PathBuf,
     /// Custom agent name (None = use default "Codey")
     agent_name: Option<String>,
     /// Custom system prompt intro (None = use default)
     custom_intro: Option<String>,
     corrections_path: PathBuf,
+    /// Project memory to inject (None = `tools.memory.inject` disabled)
+    memory: Option<Memory>,
+    /// Files pinned with `general.pinned_files` or `/pin`
+    pinned: Option<PinnedFiles>,
+    /// Variables set with `/set`
+    variables: Option<SessionVariables>,
 }
 
 impl SystemPrompt {
@@ -171,9 +298,13 @@ impl SystemPrompt {
         Self {
             user_path,
             project_path,
+            notes_path: PathBuf::from(PROJECT_BANNER_FILENAME),
             agent_name: None,
             custom_intro: None,
             corrections_path,
+            memory: Some(Memory::project(DEFAULT_MEMORY_MAX_BYTES)),
+            pinned: None,
+            variables: None,
         }
     }
 
@@ -186,12 +317,33 @@ impl SystemPrompt {
         Self {
             user_path,
             project_path,
+            notes_path: PathBuf::from(PROJECT_BANNER_FILENAME),
             agent_name: config.agent.name.clone(),
             custom_intro: config.agent.system_prompt.clone(),
             corrections_path,
+            memory: config
+                .tools
+                .memory
+                .inject
+                .then(|| Memory::project(config.tools.memory.max_bytes)),
+            pinned: None,
+            variables: None,
         }
     }
 
+    /// Include the current contents of these pinned files in every build.
+    pub fn with_pinned(mut self, pinned: PinnedFiles) -> Self {
+        self.pinned = Some(pinned);
+        self
+    }
+
+    /// List these session variables in every build, and fill `{name}` in
+    /// pinned files from them.
+    pub fn with_variables(mut self, variables: SessionVariables) -> Self {
+        self.variables = Some(variables);
+        self
+    }
+
     /// Get the agent name (custom or default)
     pub fn agent_name(&self) -> &str {
         self.agent_name.as_deref().unwrap_or(DEFAULT_AGENT_NAME)
@@ -204,7 +356,11 @@ impl SystemPrompt {
     /// - Base system prompt (with optional custom intro)
     /// - User SYSTEM.md content (if exists)
     /// - Project SYSTEM.md content (if exists)
+    /// - Project CODEY.md content (if exists)
     /// - Project corrections.md content (if exists)
+    /// - Project memory notes (if any and injection is enabled)
+    /// - Pinned files (if any)
+    /// - Session variables (if any)
     pub fn build(&self) -> String {
         // Build the intro portion
         let intro = if let Some(ref custom) = self.custom_intro {
@@ -229,6 +385,13 @@ impl SystemPrompt {
             prompt.push_str(&content);
         }
 
+        // Append CODEY.md if it exists (project notes from /init)
+        if let Some(content) = fs::read_to_string(&self.notes_path).ok().filter(|s| !s.trim().is_empty()) {
+            prompt.push_str("\n\n## Project Notes\n\n");
+            prompt.push_str(&format!("From {} at the project root:\n\n", PROJECT_BANNER_FILENAME));
+            prompt.push_str(&content);
+        }
+
         // Append corrections.md if it exists (learned corrections from previous sessions)
         if let Some(content) = self.load_corrections() {
             prompt.push_str("\n\n## Learned Corrections\n\n");
@@ -237,9 +400,48 @@ impl SystemPrompt {
             prompt.push_str(&content);
         }
 
+        // Append project memory (notes the agent saved with memory_write)
+        if let Some(notes) = self.load_memory() {
+            prompt.push_str("\n\n## Project Memory\n\n");
+            prompt.push_str("Notes you saved about this project in previous sessions. ");
+            prompt.push_str("Keep them current with `memory_write`:\n\n");
+            prompt.push_str(&notes);
+        }
+
+        // Append pinned files, re-read so edits show up on the next request
+        if let Some(files) = self.pinned.as_ref().and_then(|p| p.render()) {
+            prompt.push_str("\n\n## Pinned Files\n\n");
+            prompt.push_str("Reference material the user pinned. These are the files' current contents, ");
+            prompt.push_str("so there is no need to read them again:\n\n");
+            match self.variables {
+                Some(ref variables) => prompt.push_str(&variables.substitute(&files)),
+                None => prompt.push_str(&files),
+            }
+        }
+
+        // Append session variables, so the agent knows what custom tools will fill in
+        if let Some(vars) = self.variables.as_ref().and_then(|v| v.render()) {
+            prompt.push_str("\n\n## Session Variables\n\n");
+            prompt.push_str("Values the user set for this session. Custom tools fill matching `{name}` ");
+            prompt.push_str("placeholders from them when you don't pass that parameter:\n\n");
+            prompt.push_str(&vars);
+        }
+
         prompt
     }
 
+    /// Load project memory notes as markdown, if injection is enabled.
+    fn load_memory(&self) -> Option<String> {
+        let memory = self.memory.as_ref()?;
+        match memory.render(None) {
+            Ok(notes) => notes,
+            Err(e) => {
+                tracing::warn!("Failed to load project memory: {}", e);
+                None
+            },
+        }
+    }
+
     /// Load corrections from the corrections JSON file.
     fn load_corrections(&self) -> Option<String> {
         if !self.corrections_path.exists() {
@@ -0,0 +1,313 @@
+//! Script mode (`codey --script FILE`)
+//!
+//! Runs a file of prompts one after another without the TUI, in a single
+//! conversation so later prompts see the results of earlier ones. Prompts are
+//! separated by lines containing only `---`. Each response is written to stdout
+//! under a `=== [n/total] ... ===` header; tool calls and errors go to stderr.
+//!
+//! Nobody is around to answer approval prompts, so tool calls are decided by
+//! the configured filters and `[tools.approval]` defaults alone. A call that
+//! would prompt is denied.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use tokio::sync::oneshot;
+
+use crate::app::{configured_decision, shell_sandbox, tool_executor, SessionReads};
+use crate::config::{AgentRuntimeConfig, Config};
+use crate::effect::Effect;
+use crate::llm::{Agent, AgentStep, RequestMode};
+use crate::pinned::PinnedFiles;
+use crate::prompts::SystemPrompt;
+use crate::tool_filter::ToolFilters;
+use crate::tools::{
+    init_agent_context, names, ApprovalSource, EffectResult, ShellSandbox, ToolDecision, ToolEvent, ToolExecutor,
+};
+use crate::variables::SessionVariables;
+
+/// Line separating prompts in a script file
+const SEPARATOR: &str = "---";
+
+/// Split a script into prompts, dropping empty ones
+pub fn parse_script(text: &str) -> Vec<String> {
+    let mut prompts = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if line.trim_end() == SEPARATOR {
+            prompts.push(std::mem::take(&mut current));
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    prompts.push(current);
+    prompts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Run every prompt in `path`. Stops at the first failed prompt unless
+/// `continue_on_error` is set, in which case failures are reported at the end.
+pub async fn run(config: Config, path: &Path, continue_on_error: bool) -> Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read script {}", path.display()))?;
+    let prompts = parse_script(&text);
+    if prompts.is_empty() {
+        bail!("No prompts in {}", path.display());
+    }
+
+    let mut runner = ScriptRunner::new(config).await?;
+    let mut failed = 0;
+    for (i, prompt) in prompts.iter().enumerate() {
+        let first_line = prompt.lines().next().unwrap_or_default();
+        println!("=== [{}/{}] {} ===", i + 1, prompts.len(), first_line);
+        let result = runner.run_prompt(prompt).await;
+        println!();
+        if let Err(e) = result {
+            eprintln!("Prompt {} failed: {}", i + 1, e);
+            if !continue_on_error {
+                bail!("Stopped after prompt {} of {}", i + 1, prompts.len());
+            }
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} prompts failed", failed, prompts.len());
+    }
+    Ok(())
+}
+
+/// The agent and tools shared by all prompts in a script
+struct ScriptRunner {
+    config: Config,
+    agent: Agent,
+    tool_executor: ToolExecutor,
+    tool_filters: ToolFilters,
+    /// Shell calls started for `Effect::RunCommand`, answered when they finish
+    followups: HashMap<String, oneshot::Sender<EffectResult>>,
+}
+
+impl ScriptRunner {
+    async fn new(config: Config) -> Result<Self> {
+        let oauth = crate::auth::OAuthCredentials::load().ok().flatten();
+        init_agent_context(AgentRuntimeConfig::background(&config), oauth.clone());
+
+        let tool_filters = config
+            .tools
+            .compile_filters(config.tools.filter_preset.as_deref())
+            .context("Failed to compile tool filters")?;
+        // No `/set` in script mode, so custom tools get every placeholder from the agent
+        let tool_executor =
+            tool_executor(&config, &SessionVariables::new(), &SessionReads::default()).await?;
+        for warning in shell_sandbox(&config).iter().flat_map(ShellSandbox::warnings) {
+            eprintln!("warning: {}", warning);
+        }
+        let pinned = PinnedFiles::new(
+            config.general.pinned_files.iter().cloned(),
+            config.general.pinned_max_bytes,
+        );
+        let system_prompt = SystemPrompt::with_config(&config).with_pinned(pinned);
+        let agent = Agent::with_dynamic_prompt(
+            AgentRuntimeConfig::foreground(&config),
+            Box::new(move || system_prompt.build()),
+            oauth,
+            tool_executor.tools().clone(),
+        );
+
+        Ok(Self {
+            config,
+            agent,
+            tool_executor,
+            tool_filters,
+            followups: HashMap::new(),
+        })
+    }
+
+    /// Send one prompt and drive the agent and its tool calls until it finishes
+    async fn run_prompt(&mut self, prompt: &str) -> Result<()> {
+        self.agent.send_request(prompt, RequestMode::Normal);
+        loop {
+            let mut requested_tools = false;
+            while let Some(step) = self.agent.next().await {
+                match step {
+                    AgentStep::TextDelta(text) => {
+                        print!("{}", text);
+                        let _ = std::io::stdout().flush();
+                    },
+                    AgentStep::ToolRequest(tool_calls) => {
+                        requested_tools = true;
+                        self.tool_executor.enqueue(tool_calls);
+                    },
+                    AgentStep::Retrying { attempt, error, delay_secs, overloaded } => {
+                        let cause = if overloaded { "API overloaded" } else { "Request failed" };
+                        eprintln!("{} (attempt {}): {}. Retrying in {}s...", cause, attempt, error, delay_secs);
+                    },
+                    AgentStep::FallbackModel { from, to, error } => {
+                        eprintln!("{} failed: {}. Falling back to {}...", from, error, to);
+                    },
+                    AgentStep::Continuing { continuation, max } => {
+                        eprintln!("\nResponse hit max_tokens, continuing ({}/{})...", continuation, max);
+                    },
+                    AgentStep::Finished { .. } => return Ok(()),
+                    AgentStep::Error(e) => bail!(e),
+                    AgentStep::ThinkingDelta(_) | AgentStep::CompactionDelta(_) | AgentStep::ContextWarning { .. } => {},
+                }
+            }
+            if !requested_tools {
+                bail!("Agent stopped without finishing");
+            }
+
+            while let Some(event) = self.tool_executor.next().await {
+                self.handle_tool_event(event);
+            }
+        }
+    }
+
+    fn handle_tool_event(&mut self, event: ToolEvent) {
+        match event {
+            ToolEvent::Delegate {
+                agent_id,
+                call_id,
+                effect: Effect::RunCommand { command, working_dir },
+                responder,
+            } => {
+                let mut params = serde_json::json!({ "command": command });
+                if let Some(dir) = working_dir {
+                    params["working_dir"] = serde_json::json!(dir);
+                }
+                let followup =
+                    self.tool_executor.start_followup(agent_id, &call_id, names::SHELL, params);
+                self.followups.insert(followup, responder);
+            },
+            ToolEvent::Delegate {
+                call_id,
+                effect,
+                responder,
+                ..
+            } => {
+                let result = self.apply_effect(&call_id, effect);
+                let _ = responder.send(result);
+            },
+            ToolEvent::Delta { .. } => {},
+            ToolEvent::Completed { call_id, content, .. } => match self.followups.remove(&call_id) {
+                Some(responder) => {
+                    let _ = responder.send(Ok(Some(content)));
+                },
+                None => self.agent.submit_tool_result(&call_id, content),
+            },
+            ToolEvent::Error { call_id, content, .. } => {
+                eprintln!("  error: {}", content.lines().next().unwrap_or_default());
+                match self.followups.remove(&call_id) {
+                    Some(responder) => {
+                        let _ = responder.send(Err(content));
+                    },
+                    None => self.agent.submit_tool_result(&call_id, content),
+                }
+            },
+            ToolEvent::BackgroundStarted { call_id, name, .. } => {
+                tracing::info!("Background task started: {} ({})", name, call_id);
+                let placeholder = format!("Running in background (task_id: {})", call_id);
+                self.agent.submit_tool_result(&call_id, placeholder);
+            },
+            ToolEvent::BackgroundCompleted { call_id, name, .. } => {
+                // The result stays with the executor for get_background_task
+                tracing::info!("Background task completed: {} ({})", name, call_id);
+            },
+        }
+    }
+
+    /// Apply an effect without a user or an IDE
+    fn apply_effect(&mut self, call_id: &str, effect: Effect) -> EffectResult {
+        match effect {
+            Effect::AwaitApproval { name, params, .. } => {
+                let Some(tool) = self.tool_executor.tool_for_call(call_id, &name) else {
+                    return Err(format!("Unknown tool: {}", name));
+                };
+                eprintln!("> {} {}", name, tool.summarize(&params));
+
+                match configured_decision(&self.config, &self.tool_filters, tool.as_ref(), &params) {
+                    Some((ToolDecision::Approve, source)) => {
+                        self.tool_executor.record_decision(call_id, ToolDecision::Approve, source);
+                        Ok(None)
+                    },
+                    Some((decision, source)) => {
+                        self.tool_executor.record_decision(call_id, decision, source);
+                        eprintln!("  denied by config");
+                        Err("Denied by configuration".to_string())
+                    },
+                    None => {
+                        // The call would prompt, and there's nobody to answer
+                        self.tool_executor
+                            .record_decision(call_id, ToolDecision::Deny, ApprovalSource::Unattended);
+                        eprintln!("  denied: needs approval, which script mode can't ask for");
+                        Err("Denied: this tool call needs approval and nobody is available to \
+                             approve it. Allow it with a tool filter or [tools.approval]."
+                            .to_string())
+                    },
+                }
+            },
+            Effect::RunCommand { .. } => {
+                unreachable!("RunCommand is started as a follow-up in handle_tool_event")
+            },
+            Effect::IdeOpen { .. }
+            | Effect::IdeShowPreview { .. }
+            | Effect::IdeShowDiffPreview { .. }
+            | Effect::IdeReloadBuffer { .. }
+            | Effect::IdeClosePreview
+            | Effect::IdeCheckUnsavedEdits { .. } => Ok(None),
+            Effect::IdeOpenFiles => Ok(Some("No IDE connected".to_string())),
+            Effect::IdeAnnotate { .. } => Ok(Some("No IDE connected; nothing was annotated".to_string())),
+            // Nothing to attach them to; the file stays where the tool said it is
+            Effect::Artifact { .. } => Ok(None),
+            Effect::ListBackgroundTasks => {
+                let tasks = self.tool_executor.list_tasks();
+                if tasks.is_empty() {
+                    Ok(Some("No background tasks".to_string()))
+                } else {
+                    Ok(Some(
+                        tasks
+                            .iter()
+                            .map(|(call_id, name, status)| {
+                                format!("{} ({}) [{:?}]", call_id, name, status)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ))
+                }
+            },
+            Effect::GetBackgroundTask { task_id } => {
+                match self.tool_executor.take_result(&task_id) {
+                    Some((tool_name, output, status)) => Ok(Some(format!(
+                        "Task {} ({}) [{:?}]:\n{}",
+                        task_id, tool_name, status, output
+                    ))),
+                    None => Ok(Some(format!("Task {} not found or still running", task_id))),
+                }
+            },
+            Effect::SpawnAgent { .. } | Effect::ListAgents | Effect::GetAgent { .. } => {
+                Err("Sub-agents are not available in script mode".to_string())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "Summarize src/main.rs\n---\n\nAdd a --verbose flag.\nKeep it short.\n---\n---\n";
+        assert_eq!(
+            parse_script(script),
+            vec!["Summarize src/main.rs", "Add a --verbose flag.\nKeep it short."]
+        );
+        assert!(parse_script("\n---\n").is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Tool call audit log
+//!
+//! Records every tool call the agent requested, how it was decided, and how it
+//! ended. Entries are appended as JSON lines to `.codey/audit.jsonl` when
+//! `general.audit_log` is enabled.
+//!
+//! Unlike the transcript, which is shaped for display, the audit log is meant
+//! for security review: it keeps the raw params and the approval source for
+//! each call, but not the tool output.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::llm::AgentId;
+use crate::tools::{ToolCall, ToolDecision};
+use crate::transcript::Status;
+
+/// Where an approve/deny decision came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalSource {
+    /// The user answered the approval prompt
+    Manual,
+    /// A configured allow/deny pattern matched
+    Filter,
+    /// The tool category's default (`[tools.approval]`) applied
+    Category,
+    /// Nobody answered the prompt within `tools.approval_timeout_secs`
+    Timeout,
+    /// The call needed a prompt and nobody was there to answer it (script mode)
+    Unattended,
+    /// Plan mode blocked a call that could change something
+    PlanMode,
+    /// The call named a tool that isn't registered
+    UnknownTool,
+    /// The call arrived already approved (library users, tests)
+    Preapproved,
+}
+
+/// A single audit record, written once the tool call has finished
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub requested_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub agent_id: AgentId,
+    pub call_id: String,
+    pub tool: String,
+    pub params: serde_json::Value,
+    pub background: bool,
+    /// None if the tool never reached an approval step
+    pub decision: Option<ToolDecision>,
+    pub source: Option<ApprovalSource>,
+    pub status: Option<Status>,
+    pub error: Option<String>,
+}
+
+/// Append-only audit log of tool calls
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// Calls that have been requested but not yet written
+    open: HashMap<String, AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            open: HashMap::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Start tracking a requested tool call
+    pub fn begin(&mut self, call: &ToolCall) {
+        let (decision, source) = match call.decision {
+            ToolDecision::Approve => (Some(ToolDecision::Approve), Some(ApprovalSource::Preapproved)),
+            ToolDecision::Deny => (Some(ToolDecision::Deny), Some(ApprovalSource::Preapproved)),
+            _ => (None, None),
+        };
+        let now = Utc::now();
+        self.open.insert(
+            call.call_id.clone(),
+            AuditEntry {
+                requested_at: now,
+                decided_at: decision.map(|_| now),
+                finished_at: None,
+                agent_id: call.agent_id,
+                call_id: call.call_id.clone(),
+                tool: call.name.clone(),
+                params: call.params.clone(),
+                background: call.background,
+                decision,
+                source,
+                status: None,
+                error: None,
+            },
+        );
+    }
+
+    /// Record the approval decision for a tracked call
+    pub fn decide(&mut self, call_id: &str, decision: ToolDecision, source: ApprovalSource) {
+        if let Some(entry) = self.open.get_mut(call_id) {
+            entry.decided_at = Some(Utc::now());
+            entry.decision = Some(decision);
+            entry.source = Some(source);
+        }
+    }
+
+    /// Finish a tracked call and append it to the log file.
+    /// A call that errored after being denied is recorded as denied.
+    pub fn finish(&mut self, call_id: &str, status: Status, error: Option<String>) {
+        let Some(mut entry) = self.open.remove(call_id) else {
+            return;
+        };
+        entry.finished_at = Some(Utc::now());
+        entry.status = Some(match (status, entry.decision) {
+            (Status::Error, Some(ToolDecision::Deny)) => Status::Denied,
+            (status, _) => status,
+        });
+        entry.error = error;
+
+        if let Err(e) = self.append(&entry) {
+            tracing::warn!("Failed to write audit entry for {}: {}", call_id, e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let line = serde_json::to_string(entry)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn call(call_id: &str, decision: ToolDecision) -> ToolCall {
+        ToolCall {
+            agent_id: 0,
+            call_id: call_id.to_string(),
+            name: "mcp_shell".to_string(),
+            params: serde_json::json!({ "command": "ls" }),
+            decision,
+            background: false,
+        }
+    }
+
+    fn read_entries(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_records_decision_and_status() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".codey").join("audit.jsonl");
+        let mut log = AuditLog::new(&path);
+
+        log.begin(&call("a", ToolDecision::Pending));
+        log.decide("a", ToolDecision::Approve, ApprovalSource::Filter);
+        log.finish("a", Status::Complete, None);
+
+        log.begin(&call("b", ToolDecision::Pending));
+        log.decide("b", ToolDecision::Deny, ApprovalSource::Manual);
+        log.finish("b", Status::Error, Some("Denied by user".to_string()));
+
+        let entries = read_entries(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["call_id"], "a");
+        assert_eq!(entries[0]["decision"], "approve");
+        assert_eq!(entries[0]["source"], "filter");
+        assert_eq!(entries[0]["status"], "complete");
+        assert_eq!(entries[0]["params"]["command"], "ls");
+        assert_eq!(entries[1]["source"], "manual");
+        assert_eq!(entries[1]["status"], "denied");
+        assert_eq!(entries[1]["error"], "Denied by user");
+    }
+
+    #[test]
+    fn test_preapproved_and_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::new(&path);
+
+        log.begin(&call("a", ToolDecision::Approve));
+        log.begin(&call("b", ToolDecision::Pending));
+        log.finish("a", Status::Complete, None);
+        log.finish("b", Status::Cancelled, None);
+
+        let entries = read_entries(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["source"], "preapproved");
+        assert_eq!(entries[1]["status"], "cancelled");
+        assert!(entries[1]["decision"].is_null());
+    }
+
+    #[test]
+    fn test_finish_unknown_call_is_noop() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::new(&path);
+
+        log.finish("missing", Status::Complete, None);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,172 @@
+//! On-disk backups of files before they are modified
+//!
+//! When `tools.backup_on_write` is enabled, file-modifying tools copy the
+//! current contents to `.codey/backups/<path>.bak.<timestamp>` before writing.
+//! Only the most recent backups of each file are kept. `/restore <file>` puts
+//! the newest backup back in place.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+
+const BACKUP_MARKER: &str = ".bak.";
+
+/// Backup store rooted at a directory (normally `.codey/backups`)
+#[derive(Debug, Clone)]
+pub struct Backups {
+    dir: PathBuf,
+    /// Backups kept per file; older ones are deleted
+    keep: usize,
+}
+
+impl Backups {
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep: keep.max(1),
+        }
+    }
+
+    /// Copy `path` into the store. Returns the backup path, or None if the
+    /// file doesn't exist yet (nothing to back up).
+    pub fn create(&self, path: &Path) -> Result<Option<PathBuf>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let stem = self.stem(path)?;
+        if let Some(parent) = stem.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S%.3f");
+        let backup = PathBuf::from(format!("{}{}{}", stem.display(), BACKUP_MARKER, timestamp));
+        fs::copy(path, &backup)
+            .with_context(|| format!("Failed to back up {} to {}", path.display(), backup.display()))?;
+
+        self.prune(path)?;
+        Ok(Some(backup))
+    }
+
+    /// Backups of `path`, newest first
+    pub fn list(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        let stem = self.stem(path)?;
+        let (Some(parent), Some(name)) = (stem.parent(), stem.file_name()) else {
+            return Ok(Vec::new());
+        };
+        let prefix = format!("{}{}", name.to_string_lossy(), BACKUP_MARKER);
+
+        let entries = match fs::read_dir(parent) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", parent.display())),
+        };
+        let mut backups: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| {
+                p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with(&prefix))
+            })
+            .collect();
+        // Timestamps sort lexicographically
+        backups.sort();
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Restore the newest backup of `path`, backing up the current contents
+    /// first so the restore itself can be undone. Returns the backup used.
+    pub fn restore(&self, path: &Path) -> Result<PathBuf> {
+        let Some(latest) = self.list(path)?.into_iter().next() else {
+            bail!("No backups of {}", path.display());
+        };
+        let contents = fs::read(&latest)
+            .with_context(|| format!("Failed to read backup {}", latest.display()))?;
+        self.create(path)?;
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(latest)
+    }
+
+    /// Delete all but the newest `keep` backups of `path`
+    fn prune(&self, path: &Path) -> Result<()> {
+        for old in self.list(path)?.into_iter().skip(self.keep) {
+            fs::remove_file(&old)
+                .with_context(|| format!("Failed to remove old backup {}", old.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Location of `path` inside the store, without the backup suffix.
+    /// Paths under the current directory keep their relative layout; others
+    /// are stored by their absolute path.
+    fn stem(&self, path: &Path) -> Result<PathBuf> {
+        let absolute = std::path::absolute(path)
+            .with_context(|| format!("Failed to resolve {}", path.display()))?;
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        let relative = absolute.strip_prefix(&cwd).unwrap_or(&absolute);
+
+        let mut stem = self.dir.clone();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => stem.push(part),
+                Component::ParentDir => stem.push("__parent__"),
+                Component::RootDir | Component::Prefix(_) | Component::CurDir => {},
+            }
+        }
+        if stem == self.dir {
+            bail!("Cannot back up {}", path.display());
+        }
+        Ok(stem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("src").join("main.rs");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "original").unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 5);
+
+        let backup = backups.create(&file).unwrap().unwrap();
+        assert!(backup.starts_with(dir.path().join("backups")));
+        assert!(backup.to_string_lossy().contains("main.rs.bak."));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "original");
+
+        fs::write(&file, "edited").unwrap();
+        backups.restore(&file).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+
+        // The edited contents were backed up before restoring
+        let latest = &backups.list(&file).unwrap()[0];
+        assert_eq!(fs::read_to_string(latest).unwrap(), "edited");
+    }
+
+    #[test]
+    fn test_retention_and_missing_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("notes.txt");
+        let backups = Backups::new(dir.path().join("backups"), 2);
+
+        assert!(backups.create(&file).unwrap().is_none());
+        assert!(backups.restore(&file).is_err());
+
+        for i in 0..4 {
+            fs::write(&file, format!("v{}", i)).unwrap();
+            backups.create(&file).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let list = backups.list(&file).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(fs::read_to_string(&list[0]).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(&list[1]).unwrap(), "v2");
+    }
+}
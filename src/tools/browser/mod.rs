@@ -27,30 +27,30 @@
 //! 3. **Keychain Access**: chromiumoxide's default args include `--password-store=basic` and
 //!    `--use-mock-keychain`, which tell Chrome to use a mock keychain instead of the real one.
 //!    This breaks cookie decryption! We must disable these defaults when using a profile.
+//!
+//! Authentication is best effort: if the profile is missing, can't be copied, or
+//! Chrome won't launch with it (e.g. it's locked), the page is fetched with a fresh
+//! profile instead and the result carries a warning saying it was fetched without login.
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::OnceLock;
 use std::time::Duration;
 
 use chromiumoxide::browser::{Browser, BrowserConfig, HeadlessMode};
 use chromiumoxide::cdp::browser_protocol::page::EventLifecycleEvent;
 use chromiumoxide::handler::viewport::Viewport;
+use chromiumoxide::handler::Handler;
 use futures::StreamExt;
 
 use crate::config::BrowserConfig as AppBrowserConfig;
 
-// TODO: Refactor to avoid global state. See issue #46.
-// We use OnceLock here because tool handlers don't have access to app config.
-
-/// Browser configuration context, initialized at app startup
-static BROWSER_CONTEXT: OnceLock<BrowserContext> = OnceLock::new();
-
 /// Monotonic counter for unique per-session temp directory names
 static SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
 
-/// Context for browser-based tools (fetch_html, fetch_screenshot)
+/// Context for browser-based tools (fetch_html, fetch_screenshot).
+/// Each tool instance carries its own, so agents in one process can use
+/// different browser settings.
 #[derive(Debug, Clone)]
 pub struct BrowserContext {
     pub chrome_executable: Option<String>,
@@ -76,20 +76,20 @@ impl Default for BrowserContext {
     }
 }
 
-/// Initialize browser context from config. Called once at app startup.
-pub fn init_browser_context(config: &AppBrowserConfig) {
-    let user_data_dir = config.chrome_user_data_dir.as_ref().and_then(|p| {
-        let path_str = p.to_str()?;
-        // Expand ~ to home directory
-        if path_str.starts_with("~/") {
-            dirs::home_dir().map(|home| home.join(&path_str[2..]).to_string_lossy().to_string())
-        } else {
-            Some(path_str.to_string())
-        }
-    });
+impl BrowserContext {
+    /// Build a context from the `[browser]` config section
+    pub fn from_config(config: &AppBrowserConfig) -> Self {
+        let user_data_dir = config.chrome_user_data_dir.as_ref().and_then(|p| {
+            let path_str = p.to_str()?;
+            // Expand ~ to home directory
+            if path_str.starts_with("~/") {
+                dirs::home_dir().map(|home| home.join(&path_str[2..]).to_string_lossy().to_string())
+            } else {
+                Some(path_str.to_string())
+            }
+        });
 
-    BROWSER_CONTEXT
-        .set(BrowserContext {
+        Self {
             chrome_executable: config
                 .chrome_executable
                 .as_ref()
@@ -101,13 +101,8 @@ pub fn init_browser_context(config: &AppBrowserConfig) {
             viewport_width: config.viewport_width,
             viewport_height: config.viewport_height,
             page_load_wait_ms: config.page_load_wait_ms,
-        })
-        .ok();
-}
-
-/// Get the browser context, if initialized.
-pub(crate) fn browser_context() -> Option<&'static BrowserContext> {
-    BROWSER_CONTEXT.get()
+        }
+    }
 }
 
 /// Result of fetch_html operation
@@ -116,6 +111,8 @@ pub struct FetchHtmlResult {
     pub content: String,
     pub title: Option<String>,
     pub url: String,
+    /// Problems that didn't stop the fetch, e.g. falling back to a fresh profile
+    pub warnings: Vec<String>,
 }
 
 /// Detect available Chrome/Chromium browser
@@ -170,9 +167,12 @@ fn which_browser(name: &str) -> Option<String> {
 /// 3. Applies readability algorithm to extract main content
 /// 4. Converts to markdown for token-efficient representation
 ///
-/// Browser settings (executable path, profile) are read from the global
-/// BrowserContext initialized at app startup.
-pub async fn fetch_html(url: &str, max_length: Option<usize>) -> Result<FetchHtmlResult, String> {
+/// Browser settings (executable path, profile, viewport) come from `ctx`.
+pub async fn fetch_html(
+    url: &str,
+    max_length: Option<usize>,
+    ctx: &BrowserContext,
+) -> Result<FetchHtmlResult, String> {
     let max_length = max_length.unwrap_or(100000);
 
     // Validate URL
@@ -185,22 +185,17 @@ pub async fn fetch_html(url: &str, max_length: Option<usize>) -> Result<FetchHtm
         ));
     }
 
-    // Get browser settings from global context
-    let ctx = browser_context();
-
     // Resolve browser executable: context -> auto-detect
     let browser_path = ctx
-        .and_then(|c| c.chrome_executable.clone())
+        .chrome_executable
+        .clone()
         .or_else(detect_browser)
         .ok_or_else(|| {
             "No Chrome/Chromium browser found. Install chromium or google-chrome to use this tool."
                 .to_string()
         })?;
 
-    // Get settings from context
-    let ctx = ctx.cloned().unwrap_or_default();
-
-    let html = fetch_with_browser(url, &browser_path, &ctx).await?;
+    let (html, warnings) = fetch_with_browser(url, &browser_path, ctx).await?;
 
     // Apply readability to extract main content
     let readable = extract_readable_content(&html, url)?;
@@ -226,10 +221,12 @@ pub async fn fetch_html(url: &str, max_length: Option<usize>) -> Result<FetchHtm
         content: markdown,
         title: readable.title,
         url: url.to_string(),
+        warnings,
     })
 }
 
-/// Fetch page using headless browser (handles JavaScript rendering)
+/// Fetch page using headless browser (handles JavaScript rendering). Returns
+/// the HTML and any warnings about the browser profile.
 ///
 /// # Arguments
 /// * `url` - The URL to fetch
@@ -239,45 +236,177 @@ async fn fetch_with_browser(
     url: &str,
     browser_path: &str,
     ctx: &BrowserContext,
-) -> Result<String, String> {
-    // Copy profile to isolated temp directory to avoid SingletonLock conflicts.
-    // See module doc comment for full explanation of why this is necessary.
-    let temp_dir = if let (Some(data_dir), Some(prof)) =
-        (&ctx.chrome_user_data_dir, &ctx.chrome_profile)
-    {
-        let source_profile = std::path::Path::new(data_dir).join(prof);
-        if source_profile.exists() {
-            // Use PID + monotonic counter to isolate temp dirs between
-            // concurrent browser sessions within the same process
-            let session_id = SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
-            let temp_base = std::env::temp_dir().join(format!(
-                "codey-browser-{}-{}",
-                std::process::id(),
-                session_id
+) -> Result<(String, Vec<String>), String> {
+    let mut warnings = Vec::new();
+    let user_data = prepare_user_data(ctx, &mut warnings);
+
+    // A profile Chrome has locked shows up as a failed launch; retry with a
+    // fresh profile rather than failing the fetch
+    let (user_data, (mut browser, mut handler)) = match launch(browser_path, ctx, &user_data).await {
+        Ok(launched) => (user_data, launched),
+        Err(e) if !matches!(user_data, UserData::Fresh) => {
+            user_data.cleanup();
+            tracing::warn!("Browser launch with profile failed, retrying without it: {}", e);
+            warnings.push(format!(
+                "Couldn't access your Chrome profile ({}); fetched without login",
+                e
             ));
-            // Clean up any leftover temp dir from a previous run with same PID
-            if temp_base.exists() {
-                let _ = std::fs::remove_dir_all(&temp_base);
+            let launched = launch(browser_path, ctx, &UserData::Fresh).await?;
+            (UserData::Fresh, launched)
+        },
+        Err(e) => return Err(e),
+    };
+
+    // Spawn handler task (required by chromiumoxide)
+    let handle = tokio::spawn(async move {
+        while let Some(_event) = handler.next().await {
+            // Process browser events
+        }
+    });
+
+    // Navigate to page and wait for network idle
+    let page_result = tokio::time::timeout(Duration::from_secs(60), async {
+        // Create a blank page first so we can set up event listeners before navigation
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .map_err(|e| format!("Failed to create page: {}", e))?;
+
+        // Listen for lifecycle events (must be set up before navigation to catch all events)
+        let mut lifecycle_events = page
+            .event_listener::<EventLifecycleEvent>()
+            .await
+            .map_err(|e| format!("Failed to set up event listener: {}", e))?;
+
+        // Navigate to the target URL
+        page.goto(url)
+            .await
+            .map_err(|e| format!("Failed to navigate: {}", e))?;
+
+        // Wait for networkIdle (no in-flight requests for 500ms).
+        // Falls back to timeout for pages with persistent connections (WebSockets, SSE).
+        let network_idle = async {
+            while let Some(event) = lifecycle_events.next().await {
+                if event.name == "networkIdle" {
+                    break;
+                }
             }
-            std::fs::create_dir_all(&temp_base)
-                .map_err(|e| format!("Failed to create temp browser dir: {}", e))?;
+        };
+        let _ = tokio::time::timeout(
+            Duration::from_millis(ctx.page_load_wait_ms),
+            network_idle,
+        )
+        .await;
 
-            // Copy profile to temp_base/Default (becomes the default profile)
-            let dest_profile = temp_base.join("Default");
-            copy_dir_recursive(&source_profile, &dest_profile)
-                .map_err(|e| format!("Failed to copy browser profile: {}", e))?;
+        // Get rendered HTML
+        page.content()
+            .await
+            .map_err(|e| format!("Failed to get page content: {}", e))
+    })
+    .await;
 
-            Some(temp_base)
-        } else {
-            return Err(format!(
-                "Browser profile not found: {}",
-                source_profile.display()
-            ));
+    // Clean up browser
+    let _ = browser.close().await;
+    handle.abort();
+
+    user_data.cleanup();
+
+    match page_result {
+        Ok(Ok(html)) => Ok((html, warnings)),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err("Page load timed out after 60 seconds".to_string()),
+    }
+}
+
+/// Profiles larger than this get a warning before they're copied
+const PROFILE_SIZE_WARNING_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Where the browser keeps its user data for one fetch
+enum UserData {
+    /// Temp copy of the configured profile, removed after the fetch
+    Copy(PathBuf),
+    /// The configured user data directory itself, which fails if Chrome has it locked
+    Direct,
+    /// Chrome's own throwaway directory: no cookies, so no login
+    Fresh,
+}
+
+impl UserData {
+    fn cleanup(&self) {
+        if let Self::Copy(temp) = self {
+            let _ = fs::remove_dir_all(temp);
         }
-    } else {
-        None
+    }
+}
+
+/// Copy the configured profile to an isolated temp directory to avoid
+/// SingletonLock conflicts (see the module docs). A missing profile or a
+/// failed copy falls back to a fresh profile, with a warning for the result.
+fn prepare_user_data(ctx: &BrowserContext, warnings: &mut Vec<String>) -> UserData {
+    let (Some(data_dir), Some(prof)) = (&ctx.chrome_user_data_dir, &ctx.chrome_profile) else {
+        return if ctx.chrome_user_data_dir.is_some() {
+            UserData::Direct
+        } else {
+            UserData::Fresh
+        };
     };
 
+    let source_profile = Path::new(data_dir).join(prof);
+    if !source_profile.is_dir() {
+        warnings.push(format!(
+            "Browser profile not found: {}; fetched without login",
+            source_profile.display()
+        ));
+        return UserData::Fresh;
+    }
+
+    let size = dir_size(&source_profile);
+    if size > PROFILE_SIZE_WARNING_BYTES {
+        tracing::warn!("Copying a {} MB browser profile", size / (1024 * 1024));
+        warnings.push(format!(
+            "Chrome profile {} is {} MB, so copying it slows down every fetch; \
+             consider a separate, smaller profile for Codey",
+            source_profile.display(),
+            size / (1024 * 1024)
+        ));
+    }
+
+    // Use PID + monotonic counter to isolate temp dirs between
+    // concurrent browser sessions within the same process
+    let session_id = SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp_base = std::env::temp_dir().join(format!(
+        "codey-browser-{}-{}",
+        std::process::id(),
+        session_id
+    ));
+    // Clean up any leftover temp dir from a previous run with same PID
+    if temp_base.exists() {
+        let _ = fs::remove_dir_all(&temp_base);
+    }
+
+    // Copy profile to temp_base/Default (becomes the default profile)
+    let copied = fs::create_dir_all(&temp_base)
+        .and_then(|_| copy_dir_recursive(&source_profile, &temp_base.join("Default")));
+    match copied {
+        Ok(()) => UserData::Copy(temp_base),
+        Err(e) => {
+            let _ = fs::remove_dir_all(&temp_base);
+            tracing::warn!("Failed to copy browser profile {}: {}", source_profile.display(), e);
+            warnings.push(format!(
+                "Couldn't access your Chrome profile ({}); fetched without login",
+                e
+            ));
+            UserData::Fresh
+        },
+    }
+}
+
+/// Configure and launch the browser with `user_data`
+async fn launch(
+    browser_path: &str,
+    ctx: &BrowserContext,
+    user_data: &UserData,
+) -> Result<(Browser, Handler), String> {
     // Configure browser
     let headless_mode = if ctx.headless {
         HeadlessMode::True
@@ -297,7 +426,7 @@ async fn fetch_with_browser(
     //
     // Reference: chromiumoxide 0.7.0 browser.rs DEFAULT_ARGS
     // Original source: https://github.com/nickelc/chromiumoxide/blob/v0.7.0/src/browser.rs
-    let using_profile = temp_dir.is_some();
+    let using_profile = matches!(user_data, UserData::Copy(_));
 
     let mut config = BrowserConfig::builder()
         .no_sandbox()
@@ -349,14 +478,15 @@ async fn fetch_with_browser(
     config = config.chrome_executable(browser_path);
 
     // Use temp dir if we copied a profile, otherwise use user_data_dir directly
-    if let Some(ref temp) = temp_dir {
-        config = config.user_data_dir(temp);
-    } else if let Some(ref dir) = ctx.chrome_user_data_dir {
-        // Fallback: use user_data_dir directly (may conflict with running Chrome)
-        config = config.user_data_dir(dir);
-        if let Some(ref prof) = ctx.chrome_profile {
-            config = config.arg(format!("--profile-directory={}", prof));
-        }
+    match user_data {
+        UserData::Copy(temp) => config = config.user_data_dir(temp),
+        UserData::Direct => {
+            if let Some(ref dir) = ctx.chrome_user_data_dir {
+                // May conflict with running Chrome
+                config = config.user_data_dir(dir);
+            }
+        },
+        UserData::Fresh => {},
     }
 
     let config = config
@@ -367,73 +497,10 @@ async fn fetch_with_browser(
     let launch_result =
         tokio::time::timeout(Duration::from_secs(30), Browser::launch(config)).await;
 
-    let (mut browser, mut handler) = match launch_result {
-        Ok(Ok((browser, handler))) => (browser, handler),
-        Ok(Err(e)) => return Err(format!("Failed to launch browser: {}", e)),
-        Err(_) => return Err("Browser launch timed out after 30 seconds".to_string()),
-    };
-
-    // Spawn handler task (required by chromiumoxide)
-    let handle = tokio::spawn(async move {
-        while let Some(_event) = handler.next().await {
-            // Process browser events
-        }
-    });
-
-    // Navigate to page and wait for network idle
-    let page_result = tokio::time::timeout(Duration::from_secs(60), async {
-        // Create a blank page first so we can set up event listeners before navigation
-        let page = browser
-            .new_page("about:blank")
-            .await
-            .map_err(|e| format!("Failed to create page: {}", e))?;
-
-        // Listen for lifecycle events (must be set up before navigation to catch all events)
-        let mut lifecycle_events = page
-            .event_listener::<EventLifecycleEvent>()
-            .await
-            .map_err(|e| format!("Failed to set up event listener: {}", e))?;
-
-        // Navigate to the target URL
-        page.goto(url)
-            .await
-            .map_err(|e| format!("Failed to navigate: {}", e))?;
-
-        // Wait for networkIdle (no in-flight requests for 500ms).
-        // Falls back to timeout for pages with persistent connections (WebSockets, SSE).
-        let network_idle = async {
-            while let Some(event) = lifecycle_events.next().await {
-                if event.name == "networkIdle" {
-                    break;
-                }
-            }
-        };
-        let _ = tokio::time::timeout(
-            Duration::from_millis(ctx.page_load_wait_ms),
-            network_idle,
-        )
-        .await;
-
-        // Get rendered HTML
-        page.content()
-            .await
-            .map_err(|e| format!("Failed to get page content: {}", e))
-    })
-    .await;
-
-    // Clean up browser
-    let _ = browser.close().await;
-    handle.abort();
-
-    // Clean up temp profile directory
-    if let Some(ref temp) = temp_dir {
-        let _ = std::fs::remove_dir_all(temp);
-    }
-
-    match page_result {
-        Ok(Ok(html)) => Ok(html),
-        Ok(Err(e)) => Err(e),
-        Err(_) => Err("Page load timed out after 60 seconds".to_string()),
+    match launch_result {
+        Ok(Ok(launched)) => Ok(launched),
+        Ok(Err(e)) => Err(format!("Failed to launch browser: {}", e)),
+        Err(_) => Err("Browser launch timed out after 30 seconds".to_string()),
     }
 }
 
@@ -456,6 +523,21 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Total size of the files under `dir`, skipping symlinks and anything unreadable
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
 struct ReadableContent {
     content: String,
     title: Option<String>,
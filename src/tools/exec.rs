@@ -3,20 +3,46 @@
 //! Executes tool pipelines with approval flow and streaming output.
 #![allow(dead_code)]
 
+use std::any::Any;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll, Wake, Waker};
 
+use futures::FutureExt;
+use serde::Serialize;
 use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
 use crate::effect::EffectResult;
 use crate::llm::AgentId;
 use crate::transcript::Status;
-use crate::tools::pipeline::{Effect, Step, ToolPipeline};
-use crate::tools::ToolRegistry;
+use crate::tools::audit::{ApprovalSource, AuditLog};
+use crate::tools::pipeline::{Effect, Step, Tool, ToolPipeline};
+use crate::tools::{names, ToolRegistry};
+#[cfg(feature = "cli")]
+use crate::tools::{handlers, ShellSandbox};
+
+/// Longest edited value quoted back to the agent; longer ones are only named
+const EDITED_VALUE_MAX_CHARS: usize = 200;
+
+/// Top-level params whose values differ between `before` and `after`
+fn changed_params(before: &serde_json::Value, after: &serde_json::Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut keys: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
 
 // =============================================================================
 // Polling helpers
@@ -35,6 +61,17 @@ fn poll_receiver<T>(rx: &mut oneshot::Receiver<T>) -> Poll<Result<T, oneshot::er
     Pin::new(rx).poll(&mut cx)
 }
 
+/// Error message for a tool that panicked, so the agent sees what went wrong
+/// instead of the session going down with it
+fn panic_error(payload: Box<dyn Any + Send>) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string());
+    format!("Tool panicked: {}", message)
+}
+
 // =============================================================================
 // Types
 // =============================================================================
@@ -71,8 +108,27 @@ impl ToolCall {
     }
 }
 
+/// A tool call waiting to start, with the tool it was enqueued against
+/// (None if no such tool is registered)
+struct QueuedCall {
+    call: ToolCall,
+    tool: Option<Arc<dyn Tool>>,
+}
+
+/// Command run after the last edit_file/write_file call of a batch
+#[derive(Debug, Clone)]
+pub struct PostEditCheck {
+    pub command: String,
+    pub timeout_secs: u64,
+    pub max_output_bytes: Option<usize>,
+    /// The shell sandbox, which the check runs in like any shell command
+    #[cfg(feature = "cli")]
+    pub sandbox: Option<ShellSandbox>,
+}
+
 /// Decision state for a pending tool
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ToolDecision {
     #[default]
     Pending,
@@ -126,18 +182,20 @@ pub enum ToolEvent {
 
 impl ToolEvent {
     fn completed(active: ActivePipeline) -> Self {
+        let content = active.result(active.output.clone());
         Self::Completed {
             agent_id: active.agent_id,
             call_id: active.call_id,
-            content: active.output,
+            content,
         }
     }
 
     fn error(active: ActivePipeline, content: impl Into<String>) -> Self {
+        let content = active.result(content.into());
         Self::Error {
             agent_id: active.agent_id,
             call_id: active.call_id,
-            content: content.into(),
+            content,
         }
     }
 
@@ -171,6 +229,8 @@ struct ActivePipeline {
     agent_id: AgentId,
     call_id: String,
     name: String,
+    /// The tool this call runs with, kept even if the registry is swapped
+    tool: Option<Arc<dyn Tool>>,
     params: serde_json::Value,
     pipeline: ToolPipeline,
     output: String,
@@ -182,14 +242,17 @@ struct ActivePipeline {
     background: bool,
     /// Execution status
     status: Status,
+    /// The agent's params, kept when the user edits them (see `ToolExecutor::edit_params`)
+    agent_params: Option<serde_json::Value>,
 }
 
 impl ActivePipeline {
-    fn new(tool_call: ToolCall, pipeline: ToolPipeline) -> Self {
+    fn new(tool_call: ToolCall, tool: Option<Arc<dyn Tool>>, pipeline: ToolPipeline) -> Self {
         Self {
             agent_id: tool_call.agent_id,
             call_id: tool_call.call_id,
             name: tool_call.name,
+            tool,
             original_decision: tool_call.decision,
             params: tool_call.params,
             background: tool_call.background,
@@ -197,7 +260,29 @@ impl ActivePipeline {
             output: String::new(),
             waiting: WaitingFor::Nothing,
             status: Status::Running,
+            agent_params: None,
+        }
+    }
+
+    /// The result reported to the agent, noting which params the user
+    /// changed, and their new values when they're short
+    fn result(&self, content: String) -> String {
+        let Some(agent_params) = &self.agent_params else {
+            return content;
+        };
+        let changed = changed_params(agent_params, &self.params);
+        if changed.is_empty() {
+            return content;
         }
+        let changes: Vec<String> = changed
+            .iter()
+            .map(|key| match self.params.get(key).map(|v| v.to_string()) {
+                Some(value) if value.chars().count() <= EDITED_VALUE_MAX_CHARS => format!("{} = {}", key, value),
+                Some(_) => format!("{} (changed)", key),
+                None => format!("{} (removed)", key),
+            })
+            .collect();
+        format!("[The user edited the parameters before approving: {}]\n{}", changes.join(", "), content)
     }
     
     /// Check if pipeline is waiting for something
@@ -245,11 +330,17 @@ impl ActivePipeline {
 /// staying in active map until results are retrieved via take_result().
 pub struct ToolExecutor {
     tools: ToolRegistry,
-    pending: VecDeque<ToolCall>,
+    pending: VecDeque<QueuedCall>,
     /// All active pipelines, keyed by call_id
     active: HashMap<String, ActivePipeline>,
     /// Flag to signal cancellation
     cancelled: bool,
+    /// Optional on-disk record of every tool call and its decision
+    audit: Option<AuditLog>,
+    /// Follow-up calls started so far, for unique call ids
+    followups: u64,
+    /// Check appended to the last edit of each batch
+    post_edit_check: Option<PostEditCheck>,
 }
 
 impl ToolExecutor {
@@ -259,6 +350,60 @@ impl ToolExecutor {
             pending: VecDeque::new(),
             active: HashMap::new(),
             cancelled: false,
+            audit: None,
+            followups: 0,
+            post_edit_check: None,
+        }
+    }
+
+    /// Run `check` after each batch of file edits, adding its result to the
+    /// last edit's output
+    pub fn with_post_edit_check(mut self, check: PostEditCheck) -> Self {
+        self.post_edit_check = Some(check);
+        self
+    }
+
+    /// Enable the audit log for all subsequently enqueued tool calls
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Record where an approval decision came from (no-op without an audit log)
+    pub fn record_decision(&mut self, call_id: &str, decision: ToolDecision, source: ApprovalSource) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.decide(call_id, decision, source);
+        }
+    }
+
+    /// Write the audit entry for a finished tool call
+    fn audit_finish(&mut self, call_id: &str, status: Status, error: Option<String>) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.finish(call_id, status, error);
+        }
+    }
+
+    /// Write audit entries for events that end a tool call
+    fn audit_event(&mut self, event: &ToolEvent) {
+        if self.audit.is_none() {
+            return;
+        }
+        match event {
+            ToolEvent::Completed { call_id, .. } => {
+                self.audit_finish(call_id, Status::Complete, None);
+            },
+            ToolEvent::Error { call_id, content, .. } => {
+                self.audit_finish(call_id, Status::Error, Some(content.clone()));
+            },
+            ToolEvent::BackgroundCompleted { call_id, .. } => {
+                let (status, error) = match self.active.get(call_id.as_str()) {
+                    Some(p) if p.status == Status::Error => (Status::Error, Some(p.output.clone())),
+                    Some(p) => (p.status, None),
+                    None => (Status::Complete, None),
+                };
+                self.audit_finish(call_id, status, error);
+            },
+            _ => {},
         }
     }
 
@@ -270,23 +415,45 @@ impl ToolExecutor {
         &mut self.tools
     }
 
+    /// Replace the tool registry, returning the previous one. Calls already
+    /// enqueued or running keep the tools they were given; only calls
+    /// enqueued from now on use the new registry.
+    pub fn set_registry(&mut self, tools: ToolRegistry) -> ToolRegistry {
+        std::mem::replace(&mut self.tools, tools)
+    }
+
+    /// The tool a call runs with: the one it was enqueued against while it is
+    /// queued or running, otherwise the current registry's tool named `name`
+    pub fn tool_for_call(&self, call_id: &str, name: &str) -> Option<Arc<dyn Tool>> {
+        let queued = self.pending.iter().find(|q| q.call.call_id == call_id).map(|q| &q.tool);
+        let active = self.active.get(call_id).map(|p| &p.tool);
+        match queued.or(active) {
+            Some(tool) => tool.clone(),
+            None => self.tools.try_get_arc(name),
+        }
+    }
+
     /// Cancel any active or pending tool execution
     /// Hard cancel: abort all foreground tasks and clear the pending queue.
     /// Used when ending the entire turn.
     pub fn cancel(&mut self) {
         self.cancelled = true;
-        self.pending.clear();
+        let mut cancelled: Vec<String> = self.pending.drain(..).map(|q| q.call.call_id).collect();
         // Abort running foreground tasks. Background tasks are independent
         // and keep running across cancellations.
-        self.active.retain(|_, p| {
+        self.active.retain(|call_id, p| {
             if p.background {
                 return true;
             }
             if let WaitingFor::Handler(_, ref handle) = p.waiting {
                 handle.abort();
             }
+            cancelled.push(call_id.clone());
             false
         });
+        for call_id in cancelled {
+            self.audit_finish(&call_id, Status::Cancelled, None);
+        }
     }
 
     /// Soft cancel: abort the currently running foreground task and return
@@ -306,6 +473,7 @@ impl ToolExecutor {
                     handle.abort();
                 }
                 p.waiting = WaitingFor::Nothing;
+                self.audit_finish(&call_id, Status::Cancelled, None);
                 events.push(ToolEvent::Error {
                     agent_id: p.agent_id,
                     call_id: p.call_id.clone(),
@@ -324,9 +492,51 @@ impl ToolExecutor {
     }
 
     pub fn enqueue(&mut self, tool_calls: Vec<ToolCall>) {
-        self.pending.extend(tool_calls);
+        if let Some(audit) = self.audit.as_mut() {
+            for tool_call in &tool_calls {
+                audit.begin(tool_call);
+            }
+        }
+        let queued: Vec<QueuedCall> = tool_calls
+            .into_iter()
+            .map(|call| QueuedCall {
+                tool: self.tools.try_get_arc(&call.name),
+                call,
+            })
+            .collect();
+        self.pending.extend(queued);
     }
     
+    /// Start a tool call right away, outside the per-agent foreground order.
+    /// Used for commands a running tool asked for (`Effect::RunCommand`): the
+    /// requesting tool holds the agent's foreground slot until the command
+    /// finishes, so queueing it behind that tool would never start it.
+    /// Returns the new call's id, derived from the requesting call's.
+    pub fn start_followup(
+        &mut self,
+        agent_id: AgentId,
+        parent_call_id: &str,
+        name: &str,
+        params: serde_json::Value,
+    ) -> String {
+        self.followups += 1;
+        let tool_call = ToolCall {
+            agent_id,
+            call_id: format!("{}_cmd{}", parent_call_id, self.followups),
+            name: name.to_string(),
+            params,
+            decision: ToolDecision::Pending,
+            background: false,
+        };
+        if let Some(audit) = self.audit.as_mut() {
+            audit.begin(&tool_call);
+        }
+        let call_id = tool_call.call_id.clone();
+        let tool = self.tools.try_get_arc(name);
+        self.start_tool(QueuedCall { call: tool_call, tool });
+        call_id
+    }
+
     /// List all background tasks: (call_id, tool_name, status)
     pub fn list_tasks(&self) -> Vec<(&str, &str, Status)> {
         self.active.values()
@@ -340,7 +550,8 @@ impl ToolExecutor {
         match self.active.get(call_id) {
             Some(p) if p.background && p.status != Status::Running => {
                 let p = self.active.remove(call_id).unwrap();
-                Some((p.name, p.output, p.status))
+                let output = p.result(p.output.clone());
+                Some((p.name, output, p.status))
             }
             _ => None,
         }
@@ -372,14 +583,95 @@ impl ToolExecutor {
     }
     
     /// Start a tool by composing its pipeline and adding to active
-    fn start_tool(&mut self, tool_call: ToolCall) {
+    fn start_tool(&mut self, queued: QueuedCall) {
+        let QueuedCall { call: tool_call, tool } = queued;
         let call_id = tool_call.call_id.clone();
-        let tool = self.tools.get(&tool_call.name);
-        let pipeline = tool.compose(tool_call.params.clone());
-        self.active.insert(call_id, ActivePipeline::new(tool_call, pipeline));
+        let pipeline = self.compose(&tool_call, tool.as_ref());
+        self.active.insert(call_id, ActivePipeline::new(tool_call, tool, pipeline));
+    }
+
+    /// Build the pipeline for a call, with the post-edit check if it ends an edit batch
+    fn compose(&self, tool_call: &ToolCall, tool: Option<&Arc<dyn Tool>>) -> ToolPipeline {
+        let params = tool_call.params.clone();
+        let pipeline = match tool {
+            Some(tool) => std::panic::catch_unwind(AssertUnwindSafe(|| tool.compose(params)))
+                .unwrap_or_else(|payload| ToolPipeline::error(panic_error(payload))),
+            // Report it to the agent so it can recover, rather than failing the session
+            None => {
+                tracing::warn!("Agent called unknown tool: {}", tool_call.name);
+                ToolPipeline::error(format!("Unknown tool: {}", tool_call.name))
+            },
+        };
+        #[cfg(feature = "cli")]
+        let pipeline = match self.post_edit_check {
+            Some(ref check) if self.ends_edit_batch(tool_call) => pipeline.then(handlers::PostEditCheck {
+                command: check.command.clone(),
+                timeout_secs: check.timeout_secs,
+                max_output_bytes: check.max_output_bytes,
+                sandbox: check.sandbox.clone(),
+            }),
+            _ => pipeline,
+        };
+        pipeline
+    }
+
+    /// Approve a call that is waiting for approval, running it with `params`
+    /// instead of the ones the agent sent. The pipeline is composed again, so
+    /// the tool's checks run on the new params, and the result tells the
+    /// agent what was run. Returns false if the call isn't awaiting approval.
+    pub fn edit_params(&mut self, call_id: &str, params: serde_json::Value) -> bool {
+        let Some(active) = self.active.get(call_id) else {
+            return false;
+        };
+        if !matches!(active.waiting, WaitingFor::Approval(_)) {
+            return false;
+        }
+        let tool_call = ToolCall {
+            agent_id: active.agent_id,
+            call_id: active.call_id.clone(),
+            name: active.name.clone(),
+            params,
+            decision: ToolDecision::Approve,
+            background: active.background,
+        };
+        let pipeline = self.compose(&tool_call, active.tool.as_ref());
+
+        let Some(active) = self.active.get_mut(call_id) else {
+            return false;
+        };
+        let agent_params = std::mem::replace(&mut active.params, tool_call.params);
+        active.agent_params.get_or_insert(agent_params);
+        active.pipeline = pipeline;
+        active.original_decision = ToolDecision::Approve;
+        active.waiting = WaitingFor::Nothing;
+        true
+    }
+
+    /// Whether a call is a file edit with no further edits queued behind it
+    /// for the same agent, so one check covers the whole batch
+    fn ends_edit_batch(&self, tool_call: &ToolCall) -> bool {
+        let is_edit = |call: &ToolCall| {
+            !call.background
+                && call.decision != ToolDecision::Deny
+                && matches!(
+                    call.name.as_str(),
+                    names::EDIT_FILE | names::WRITE_FILE | names::REPLACE_IN_FILES
+                )
+        };
+        is_edit(tool_call)
+            && !self
+                .pending
+                .iter()
+                .any(|q| q.call.agent_id == tool_call.agent_id && is_edit(&q.call))
     }
 
     pub async fn next(&mut self) -> Option<ToolEvent> {
+        let event = self.next_event().await?;
+        self.audit_event(&event);
+        Some(event)
+    }
+
+    async fn next_event(&mut self) -> Option<ToolEvent> {
         // Check for cancellation
         if self.cancelled {
             self.cancelled = false;
@@ -392,13 +684,14 @@ impl ToolExecutor {
         
         // Start one foreground tool per agent that doesn't already have one running
         let fg_agent_ids: HashSet<_> = self.pending.iter()
+            .map(|q| &q.call)
             .filter(|t| !t.background && Self::is_ready(t))
             .map(|t| t.agent_id)
             .collect();
         
         for agent_id in fg_agent_ids {
             if !self.has_running_foreground_for_agent(agent_id) {
-                if let Some(idx) = self.pending.iter().position(|t| 
+                if let Some(idx) = self.pending.iter().map(|q| &q.call).position(|t| 
                     t.agent_id == agent_id && !t.background && Self::is_ready(t)
                 ) {
                     let tool_call = self.pending.remove(idx).unwrap();
@@ -410,6 +703,7 @@ impl ToolExecutor {
         // Start all ready background tools
         // (Collect indices first, then remove in reverse to preserve indices)
         let bg_indices: Vec<_> = self.pending.iter()
+            .map(|q| &q.call)
             .enumerate()
             .filter(|(_, t)| t.background && Self::is_ready(t))
             .map(|(i, _)| i)
@@ -639,6 +933,11 @@ impl ToolExecutor {
                         self.active.remove(call_id);
                         return None;
                     }
+
+                    if let Some(tool) = active.tool.clone() {
+                        let output = std::mem::take(&mut active.output);
+                        active.output = tool.post_process(&active.params, output);
+                    }
                     
                     if active.background {
                         active.set_complete();
@@ -659,7 +958,10 @@ impl ToolExecutor {
         // The result will be polled via WaitingFor::Handler in poll_waiting().
         let (tx, rx) = oneshot::channel();
         let handle = tokio::spawn(async move {
-            let step = handler.call().await;
+            let step = AssertUnwindSafe(handler.call())
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|payload| Step::Error(panic_error(payload)));
             let _ = tx.send(step);
         });
         
@@ -678,9 +980,21 @@ impl ToolExecutor {
                 active.output = content;
                 None
             },
+            Step::Append(content) => {
+                active.output.push_str(&content);
+                None
+            },
             Step::Delta(content) => {
                 Some(ToolEvent::delta(active, content))
             },
+            Step::Progress(content, next) => {
+                active.pipeline.push_front(next);
+                Some(ToolEvent::delta(active, content))
+            },
+            Step::Then(pipeline) => {
+                active.pipeline.prepend(pipeline);
+                None
+            },
             Step::Delegate(effect) => {
                 let (event, rx) = ToolEvent::delegate(active, effect);
                 active.waiting = WaitingFor::Effect(rx);
@@ -769,6 +1083,31 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_unknown_tool_errors() {
+        let mut executor = ToolExecutor::new(ToolRegistry::empty());
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test1".to_string(),
+            name: "mcp_no_such_tool".to_string(),
+            params: serde_json::json!({}),
+            decision: ToolDecision::Pending,
+            background: false,
+        }]);
+
+        let events = collect_events(&mut executor).await;
+
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            ToolEvent::Error { call_id, content, .. } => {
+                assert_eq!(call_id, "test1");
+                assert_eq!(content, "Unknown tool: mcp_no_such_tool");
+            }
+            other => panic!("Expected Error, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_multiple_tools_sequential() {
         let mut registry = ToolRegistry::empty();
@@ -1162,6 +1501,119 @@ mod tests {
             "Agent 0's foreground tools should complete in FIFO order");
     }
 
+    #[tokio::test]
+    async fn test_followup_runs_while_foreground_waits() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        // A foreground tool holds the agent's slot while waiting for approval
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "parent".to_string(),
+            name: "mcp_shell".to_string(),
+            params: serde_json::json!({ "command": "echo parent" }),
+            decision: ToolDecision::Pending,
+            background: false,
+        }]);
+        let responder = match executor.next().await.unwrap() {
+            ToolEvent::Delegate { effect: Effect::AwaitApproval { .. }, responder, .. } => responder,
+            other => panic!("Expected Delegate/AwaitApproval, got {:?}", other),
+        };
+
+        // A follow-up for the same agent still starts, and asks for approval
+        let followup = executor.start_followup(
+            0,
+            "parent",
+            "mcp_shell",
+            serde_json::json!({ "command": "echo followup" }),
+        );
+        assert_eq!(followup, "parent_cmd1");
+        match executor.next().await.unwrap() {
+            ToolEvent::Delegate { call_id, effect: Effect::AwaitApproval { .. }, responder, .. } => {
+                assert_eq!(call_id, followup);
+                responder.send(Ok(None)).unwrap();
+            },
+            other => panic!("Expected Delegate/AwaitApproval for the follow-up, got {:?}", other),
+        }
+        let (call_id, content) = loop {
+            match executor.next().await.unwrap() {
+                ToolEvent::Completed { call_id, content, .. } => break (call_id, content),
+                ToolEvent::Delta { .. } => continue,
+                other => panic!("Expected the follow-up to complete, got {:?}", other),
+            }
+        };
+        assert_eq!(call_id, followup);
+        assert!(content.contains("followup"));
+
+        drop(responder);
+    }
+
+    #[tokio::test]
+    async fn test_set_registry_keeps_tools_for_queued_calls() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![
+            ToolCall {
+                agent_id: 0,
+                call_id: "running".to_string(),
+                name: "mcp_shell".to_string(),
+                params: serde_json::json!({ "command": "echo running" }),
+                decision: ToolDecision::Pending,
+                background: false,
+            },
+            ToolCall {
+                agent_id: 0,
+                call_id: "queued".to_string(),
+                name: "mcp_shell".to_string(),
+                params: serde_json::json!({ "command": "echo queued" }),
+                decision: ToolDecision::Approve,
+                background: false,
+            },
+        ]);
+        let responder = match executor.next().await.unwrap() {
+            ToolEvent::Delegate { effect: Effect::AwaitApproval { .. }, responder, .. } => responder,
+            other => panic!("Expected Delegate/AwaitApproval, got {:?}", other),
+        };
+
+        // Swap to a registry without the shell while both calls are outstanding
+        let previous = executor.set_registry(ToolRegistry::empty());
+        assert!(previous.try_get("mcp_shell").is_some());
+        assert!(executor.tool_for_call("running", "mcp_shell").is_some());
+        assert!(executor.tool_for_call("queued", "mcp_shell").is_some());
+        assert!(executor.tool_for_call("later", "mcp_shell").is_none());
+
+        responder.send(Ok(None)).unwrap();
+        let events = collect_events(&mut executor).await;
+        let completed: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ToolEvent::Completed { call_id, .. } => Some(call_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(completed, vec!["running", "queued"]);
+
+        // Calls enqueued after the swap use the new registry
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "later".to_string(),
+            name: "mcp_shell".to_string(),
+            params: serde_json::json!({ "command": "echo later" }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+        match executor.next().await.unwrap() {
+            ToolEvent::Error { call_id, content, .. } => {
+                assert_eq!(call_id, "later");
+                assert!(content.contains("Unknown tool"));
+            },
+            other => panic!("Expected Error for unknown tool, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_foreground_approval_blocks_subsequent() {
         // A foreground tool waiting for approval should block subsequent foreground tools
@@ -1215,6 +1667,41 @@ mod tests {
             "After approval, tools should complete in order");
     }
 
+    #[tokio::test]
+    async fn test_edit_params_before_approval() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "fg1".to_string(),
+            name: "mcp_shell".to_string(),
+            params: serde_json::json!({ "command": "echo original" }),
+            decision: ToolDecision::Pending,
+            background: false,
+        }]);
+        assert!(!executor.edit_params("fg1", serde_json::json!({ "command": "echo edited" })));
+
+        let _responder = match executor.next().await.unwrap() {
+            ToolEvent::Delegate { effect: Effect::AwaitApproval { .. }, responder, .. } => responder,
+            other => panic!("Expected Delegate/AwaitApproval, got {:?}", other),
+        };
+        assert!(executor.edit_params("fg1", serde_json::json!({ "command": "echo edited" })));
+
+        let events = collect_events(&mut executor).await;
+        let content = events
+            .iter()
+            .find_map(|e| match e {
+                ToolEvent::Completed { content, .. } => Some(content.clone()),
+                _ => None,
+            })
+            .expect("edited call should complete");
+        assert!(content.starts_with(r#"[The user edited the parameters before approving: command = "echo edited"]"#));
+        assert!(content.contains("\nedited"));
+        assert!(!content.contains("original"));
+    }
+
     #[tokio::test]
     async fn test_foreground_denial_unblocks_next() {
         // Denying a foreground tool should allow the next foreground tool to start
@@ -1533,4 +2020,84 @@ mod tests {
         assert!(completed, "Running background task should still complete");
         assert!(executor.get_background_output("slow").unwrap().contains("slow_done"));
     }
+
+    /// Tool that panics in `compose` or in its handler, depending on params
+    struct PanicTool;
+
+    struct PanicHandler;
+
+    #[async_trait::async_trait]
+    impl crate::tools::pipeline::EffectHandler for PanicHandler {
+        async fn call(self: Box<Self>) -> Step {
+            panic!("handler exploded");
+        }
+    }
+
+    impl crate::tools::Tool for PanicTool {
+        fn name(&self) -> &'static str {
+            "panic"
+        }
+
+        fn description(&self) -> &'static str {
+            "Always panics"
+        }
+
+        fn schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+            if params["in_compose"].as_bool().unwrap_or(false) {
+                panic!("compose exploded");
+            }
+            ToolPipeline::new().then(PanicHandler)
+        }
+
+        fn create_block(
+            &self,
+            call_id: &str,
+            params: serde_json::Value,
+            background: bool,
+        ) -> Box<dyn crate::transcript::Block> {
+            Box::new(crate::transcript::ToolBlock::new(call_id, "panic", params, background))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_panic_becomes_error() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(PanicTool));
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        let call = |call_id: &str, name: &str, params: serde_json::Value| ToolCall {
+            agent_id: 0,
+            call_id: call_id.to_string(),
+            name: name.to_string(),
+            params,
+            decision: ToolDecision::Approve,
+            background: false,
+        };
+        executor.enqueue(vec![
+            call("handler", "panic", serde_json::json!({})),
+            call("compose", "panic", serde_json::json!({ "in_compose": true })),
+            call("after", "mcp_shell", serde_json::json!({ "command": "echo still alive" })),
+        ]);
+
+        let events = collect_events(&mut executor).await;
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            ToolEvent::Error { content, .. } => assert_eq!(content, "Tool panicked: handler exploded"),
+            other => panic!("Expected Error, got {:?}", other),
+        }
+        match &events[1] {
+            ToolEvent::Error { content, .. } => assert_eq!(content, "Tool panicked: compose exploded"),
+            other => panic!("Expected Error, got {:?}", other),
+        }
+        // The executor keeps working after a panic
+        match &events[2] {
+            ToolEvent::Completed { content, .. } => assert!(content.contains("still alive")),
+            other => panic!("Expected Completed, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,221 @@
+//! Session cache for file reads
+//!
+//! The agent often reads the same file several times in a turn. Contents are
+//! cached by path and invalidated when the file's mtime or size changes, so a
+//! repeat read is served from memory and can be flagged back to the agent as
+//! redundant.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::io;
+
+/// Maximum number of files kept in the cache
+const MAX_ENTRIES: usize = 128;
+
+/// Maximum total size of cached content, in bytes
+const MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// A line range as requested by the agent (`start_line`, `end_line`)
+type LineRange = (Option<i32>, Option<i32>);
+
+struct CachedFile {
+    modified: SystemTime,
+    len: u64,
+    content: String,
+    /// Ranges already returned for this version of the file
+    ranges: HashSet<LineRange>,
+}
+
+/// Bounded, least-recently-used cache of file contents keyed by path and mtime
+#[derive(Default)]
+pub struct FileCache {
+    entries: HashMap<PathBuf, CachedFile>,
+    /// Least recently used first
+    order: VecDeque<PathBuf>,
+    bytes: usize,
+}
+
+impl FileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a file with line numbers, serving unchanged files from the cache.
+    /// A cache hit is noted in the output; re-reading a range that was already
+    /// returned for the same version of the file gets a stronger hint.
+    pub fn read(
+        &mut self,
+        path: &Path,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+    ) -> Result<String, String> {
+        let metadata = fs::metadata(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("File not found: {}", path.display()),
+            _ => format!("Failed to read file: {}", e),
+        })?;
+        if !metadata.is_file() {
+            return Err(format!("Not a file: {}", path.display()));
+        }
+        let modified = metadata
+            .modified()
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let range = (start_line, end_line);
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if entry.modified == modified && entry.len == metadata.len() {
+                let repeated = !entry.ranges.insert(range);
+                let note = if repeated {
+                    "[cached: you already read this and the file has not changed since; \
+                     refer to the earlier result instead of reading it again]"
+                } else {
+                    "[cached: file unchanged since last read]"
+                };
+                let output = format!(
+                    "{}\n{}",
+                    note,
+                    io::format_lines(&entry.content, start_line, end_line)
+                );
+                self.touch(&key);
+                return Ok(output);
+            }
+            self.remove(&key);
+        }
+
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let output = io::format_lines(&content, start_line, end_line);
+        self.insert(key, CachedFile {
+            modified,
+            len: metadata.len(),
+            content,
+            ranges: HashSet::from([range]),
+        });
+        Ok(output)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every cached file, so no read is reported as redundant
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+
+    fn insert(&mut self, key: PathBuf, entry: CachedFile) {
+        // Files too large to share the cache with others aren't worth keeping
+        if entry.content.len() > MAX_BYTES / 4 {
+            return;
+        }
+        self.bytes += entry.content.len();
+        self.entries.insert(key.clone(), entry);
+        self.order.push_back(key);
+
+        while self.entries.len() > MAX_ENTRIES || self.bytes > MAX_BYTES {
+            let Some(oldest) = self.order.front().cloned() else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    fn remove(&mut self, key: &Path) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.bytes -= entry.content.len();
+        }
+        self.order.retain(|k| k != key);
+    }
+
+    fn touch(&mut self, key: &Path) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            if let Some(k) = self.order.remove(pos) {
+                self.order.push_back(k);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_repeat_read_is_cached() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "one\ntwo\n").unwrap();
+        let mut cache = FileCache::new();
+
+        let first = cache.read(&path, None, None).unwrap();
+        assert!(!first.contains("[cached"));
+        assert!(first.contains("   1│one"));
+
+        let range = cache.read(&path, Some(2), None).unwrap();
+        assert!(range.starts_with("[cached: file unchanged"));
+        assert!(range.contains("   2│two"));
+
+        let repeat = cache.read(&path, None, None).unwrap();
+        assert!(repeat.starts_with("[cached: you already read this"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_changed_file_is_reread() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "old\n").unwrap();
+        let mut cache = FileCache::new();
+        cache.read(&path, None, None).unwrap();
+
+        fs::write(&path, "new\n").unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+
+        let output = cache.read(&path, None, None).unwrap();
+        assert!(!output.contains("[cached"));
+        assert!(output.contains("new"));
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let dir = tempdir().unwrap();
+        let mut cache = FileCache::new();
+        for i in 0..MAX_ENTRIES + 10 {
+            let path = dir.path().join(format!("{}.txt", i));
+            fs::write(&path, "x\n").unwrap();
+            cache.read(&path, None, None).unwrap();
+        }
+        assert_eq!(cache.len(), MAX_ENTRIES);
+
+        // The oldest files were evicted
+        let first = cache.read(&dir.path().join("0.txt"), None, None).unwrap();
+        assert!(!first.contains("[cached"));
+    }
+
+    #[test]
+    fn test_clear_forgets_reads() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(&path, "one\n").unwrap();
+        let mut cache = FileCache::new();
+        cache.read(&path, None, None).unwrap();
+
+        cache.clear();
+        assert!(cache.is_empty());
+        let output = cache.read(&path, None, None).unwrap();
+        assert!(!output.contains("[cached"));
+    }
+}
@@ -0,0 +1,427 @@
+//! Git working tree inspection for the git tools
+//!
+//! Runs `git status --porcelain=v2 --branch` and condenses it into a short,
+//! grouped summary, which is cheaper for the agent to read than the
+//! human-oriented `git status` output. Also lists the project's files,
+//! respecting `.gitignore`, for tools that search across them, and condenses
+//! `git blame` into which commits last changed a range of lines.
+
+use std::path::{Path, PathBuf};
+
+use super::io;
+
+/// Timeout for the git commands run by the git tools
+pub const GIT_TIMEOUT_SECS: u64 = 30;
+
+/// One changed path from porcelain v2 output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStatus {
+    /// Index (staged) status code, `.` if unchanged
+    pub staged: char,
+    /// Worktree (unstaged) status code, `.` if unchanged
+    pub unstaged: char,
+    pub path: String,
+    /// Original path of a rename or copy
+    pub from: Option<String>,
+}
+
+/// Parsed `git status --porcelain=v2 --branch`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub changes: Vec<FileStatus>,
+    pub conflicts: Vec<String>,
+    pub untracked: Vec<String>,
+}
+
+impl Status {
+    pub fn parse(porcelain: &str) -> Self {
+        let mut status = Self::default();
+        for line in porcelain.lines() {
+            if let Some(header) = line.strip_prefix("# ") {
+                let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                match key {
+                    "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                    "branch.upstream" => status.upstream = Some(value.to_string()),
+                    "branch.ab" => {
+                        for part in value.split_whitespace() {
+                            if let Some(n) = part.strip_prefix('+') {
+                                status.ahead = n.parse().unwrap_or(0);
+                            } else if let Some(n) = part.strip_prefix('-') {
+                                status.behind = n.parse().unwrap_or(0);
+                            }
+                        }
+                    },
+                    _ => {},
+                }
+                continue;
+            }
+
+            let fields: Vec<&str> = match line.chars().next() {
+                Some('1') => line.splitn(9, ' ').collect(),
+                Some('2') => line.splitn(10, ' ').collect(),
+                Some('u') => line.splitn(11, ' ').collect(),
+                Some('?') => {
+                    status.untracked.push(line[2..].to_string());
+                    continue;
+                },
+                _ => continue,
+            };
+            let (Some(xy), Some(path)) = (fields.get(1), fields.last()) else {
+                continue;
+            };
+            if line.starts_with('u') {
+                status.conflicts.push(path.to_string());
+                continue;
+            }
+            let mut codes = xy.chars();
+            let (path, from) = match path.split_once('\t') {
+                Some((path, from)) => (path, Some(from.to_string())),
+                None => (*path, None),
+            };
+            status.changes.push(FileStatus {
+                staged: codes.next().unwrap_or('.'),
+                unstaged: codes.next().unwrap_or('.'),
+                path: path.to_string(),
+                from,
+            });
+        }
+        status
+    }
+
+    /// Grouped summary: branch line, then staged, unstaged, conflicted and untracked paths
+    pub fn summary(&self) -> String {
+        let mut out = match &self.branch {
+            Some(branch) => format!("On branch {}", branch),
+            None => "HEAD detached".to_string(),
+        };
+        if let Some(upstream) = &self.upstream {
+            out.push_str(&format!(" (upstream {}", upstream));
+            if self.ahead > 0 || self.behind > 0 {
+                out.push_str(&format!(", ahead {}, behind {}", self.ahead, self.behind));
+            }
+            out.push(')');
+        }
+        out.push('\n');
+
+        let staged: Vec<String> = self
+            .changes
+            .iter()
+            .filter(|c| c.staged != '.')
+            .map(|c| describe(c.staged, c))
+            .collect();
+        let unstaged: Vec<String> = self
+            .changes
+            .iter()
+            .filter(|c| c.unstaged != '.')
+            .map(|c| describe(c.unstaged, c))
+            .collect();
+
+        let sections = [
+            ("Staged", staged),
+            ("Unstaged", unstaged),
+            ("Conflicts", self.conflicts.clone()),
+            ("Untracked", self.untracked.clone()),
+        ];
+        let mut clean = true;
+        for (title, paths) in sections {
+            if paths.is_empty() {
+                continue;
+            }
+            clean = false;
+            out.push_str(&format!("{} ({}):\n", title, paths.len()));
+            for path in paths {
+                out.push_str(&format!("  {}\n", path));
+            }
+        }
+        if clean {
+            out.push_str("Working tree clean\n");
+        }
+        out
+    }
+}
+
+/// `modified: path`, `renamed: old -> new`, ...
+fn describe(code: char, change: &FileStatus) -> String {
+    let kind = match code {
+        'M' => "modified",
+        'T' => "type changed",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        _ => "changed",
+    };
+    match &change.from {
+        Some(from) => format!("{}: {} -> {}", kind, from, change.path),
+        None => format!("{}: {}", kind, change.path),
+    }
+}
+
+/// Run git with `args`, returning stdout or the error output
+pub async fn run(args: &[&str]) -> Result<String, String> {
+    let argv: Vec<String> = std::iter::once("git")
+        .chain(args.iter().copied())
+        .map(String::from)
+        .collect();
+    let result = io::execute_argv(&argv, None, GIT_TIMEOUT_SECS).await?;
+    if result.success {
+        Ok(result.output)
+    } else {
+        Err(result.output)
+    }
+}
+
+/// Files under `dir` that git would consider part of the project: tracked
+/// files plus untracked ones that aren't ignored. `glob` limits the list to
+/// matching paths (relative to `dir`, so `**/*.rs` or `src/*.toml`). Paths
+/// come back joined onto `dir`. Blocking, since tools compose synchronously.
+pub fn list_files(dir: &Path, glob: Option<&str>) -> Result<Vec<PathBuf>, String> {
+    let mut cmd = std::process::Command::new("git");
+    cmd.arg("-C")
+        .arg(dir)
+        .args(["ls-files", "-z", "--cached", "--others", "--exclude-standard"]);
+    if let Some(glob) = glob {
+        cmd.arg("--").arg(format!(":(glob){}", glob));
+    }
+    let output = cmd.output().map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let mut files: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| dir.join(path))
+        .collect();
+    // A file with unresolved conflicts is listed once per stage
+    files.dedup();
+    Ok(files)
+}
+
+/// Commits listed in a blame summary; older ones are only counted
+pub const BLAME_MAX_COMMITS: usize = 10;
+
+/// The lines of a file one commit last changed, from `git blame --porcelain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameCommit {
+    pub sha: String,
+    pub author: String,
+    /// When the commit was authored, as a Unix timestamp
+    pub time: i64,
+    pub summary: String,
+    /// Line numbers in the current file, ascending
+    pub lines: Vec<u32>,
+}
+
+impl BlameCommit {
+    /// Lines that aren't committed yet are blamed on the all-zero hash
+    fn is_uncommitted(&self) -> bool {
+        self.sha.chars().all(|c| c == '0')
+    }
+}
+
+/// Group `git blame --porcelain` output by commit. Commit details are only
+/// printed the first time a commit appears, so they're collected as they come.
+pub fn parse_blame(porcelain: &str) -> Vec<BlameCommit> {
+    let mut commits: Vec<BlameCommit> = Vec::new();
+    let mut current: Option<usize> = None;
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        let mut fields = line.split(' ');
+        let first = fields.next().unwrap_or_default();
+        if first.len() == 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            // <sha> <original line> <final line> [<lines in group>]
+            let Some(line_no) = fields.nth(1).and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            let index = match commits.iter().position(|c| c.sha == first) {
+                Some(index) => index,
+                None => {
+                    commits.push(BlameCommit {
+                        sha: first.to_string(),
+                        author: String::new(),
+                        time: 0,
+                        summary: String::new(),
+                        lines: Vec::new(),
+                    });
+                    commits.len() - 1
+                },
+            };
+            commits[index].lines.push(line_no);
+            current = Some(index);
+            continue;
+        }
+        let Some(commit) = current.map(|i| &mut commits[i]) else {
+            continue;
+        };
+        let value = line.split_once(' ').map(|(_, value)| value).unwrap_or_default();
+        match first {
+            "author" => commit.author = value.to_string(),
+            "author-time" => commit.time = value.parse().unwrap_or(0),
+            "summary" => commit.summary = value.to_string(),
+            _ => {},
+        }
+    }
+    commits
+}
+
+/// `40-45, 50` from ascending line numbers
+fn line_ranges(lines: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if line == *end + 1 => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// One line per commit, newest first:
+/// `lines 40-45, 50 last changed in abc1234 2024-05-01 (Alice: fix parser)`
+pub fn blame_summary(commits: &[BlameCommit], max_commits: usize) -> String {
+    let mut sorted: Vec<&BlameCommit> = commits.iter().collect();
+    sorted.sort_by_key(|c| (!c.is_uncommitted(), std::cmp::Reverse(c.time)));
+
+    let mut out = String::new();
+    for commit in sorted.iter().take(max_commits) {
+        let label = if commit.lines.len() == 1 { "line" } else { "lines" };
+        if commit.is_uncommitted() {
+            out.push_str(&format!("{} {} not committed yet\n", label, line_ranges(&commit.lines)));
+            continue;
+        }
+        let date = chrono::DateTime::from_timestamp(commit.time, 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{} {} last changed in {} {} ({}: {})\n",
+            label,
+            line_ranges(&commit.lines),
+            &commit.sha[..7],
+            date,
+            commit.author,
+            commit.summary
+        ));
+    }
+    if sorted.len() > max_commits {
+        out.push_str(&format!("... and {} older commits\n", sorted.len() - max_commits));
+    }
+    out
+}
+
+/// Which commits last changed lines `start_line..=end_line` of `path` (the
+/// whole file without a range; an `end_line` of -1 means the end)
+pub async fn blame(path: &Path, start_line: Option<i32>, end_line: Option<i32>) -> Result<String, String> {
+    let start = start_line.filter(|&n| n > 0).unwrap_or(1);
+    let range = match end_line.filter(|&n| n > 0) {
+        Some(end) => Some(format!("-L{},{}", start, end)),
+        None if start > 1 => Some(format!("-L{},", start)),
+        None => None,
+    };
+    let path = path.to_string_lossy();
+    let mut args = vec!["blame", "--porcelain"];
+    if let Some(ref range) = range {
+        args.push(range);
+    }
+    args.push("--");
+    args.push(&path);
+    let porcelain = run(&args).await?;
+    Ok(blame_summary(&parse_blame(&porcelain), BLAME_MAX_COMMITS))
+}
+
+/// Current status summary, with the last `log` commits if requested
+pub async fn status(log: Option<usize>) -> Result<String, String> {
+    let porcelain = run(&["status", "--porcelain=v2", "--branch"]).await?;
+    let mut out = Status::parse(&porcelain).summary();
+    if let Some(count) = log.filter(|&n| n > 0) {
+        let count = count.to_string();
+        let commits = run(&["log", "-n", &count, "--date=short", "--format=%h %ad %an: %s"]).await?;
+        out.push_str("\nRecent commits:\n");
+        for line in commits.lines().filter(|l| *l != "(no output)") {
+            out.push_str(&format!("  {}\n", line));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PORCELAIN: &str = "# branch.oid 1234abcd
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -0
+1 M. N... 100644 100644 100644 aaaa bbbb src/app.rs
+1 .M N... 100644 100644 100644 aaaa bbbb README.md
+1 MM N... 100644 100644 100644 aaaa bbbb src/my file.rs
+2 R. N... 100644 100644 100644 aaaa bbbb R100 src/new.rs\tsrc/old.rs
+u UU N... 100644 100644 100644 100644 aaaa bbbb cccc Cargo.lock
+? notes.txt
+";
+
+    #[test]
+    fn test_parse_porcelain() {
+        let status = Status::parse(PORCELAIN);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!((status.ahead, status.behind), (2, 0));
+        assert_eq!(status.changes.len(), 4);
+        assert_eq!(status.changes[2].path, "src/my file.rs");
+        assert_eq!(status.changes[3].from.as_deref(), Some("src/old.rs"));
+        assert_eq!(status.conflicts, vec!["Cargo.lock"]);
+        assert_eq!(status.untracked, vec!["notes.txt"]);
+    }
+
+    #[test]
+    fn test_blame_summary() {
+        let old = "1111111111111111111111111111111111111111";
+        let new = "2222222222222222222222222222222222222222";
+        let porcelain = format!(
+            "{old} 1 40 2\nauthor Alice\nauthor-time 1700000000\nsummary add parser\nfilename src/p.rs\n\tfn a() {{\n\
+             {old} 2 41\n\t}}\n\
+             {new} 5 42 1\nauthor Bob\nauthor-time 1710000000\nsummary fix: off by one\nfilename src/p.rs\n\tlet x = 1;\n\
+             {old} 3 43 1\n\t// end\n"
+        );
+        let commits = parse_blame(&porcelain);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].lines, vec![40, 41, 43]);
+        assert_eq!(commits[1].author, "Bob");
+
+        let summary = blame_summary(&commits, BLAME_MAX_COMMITS);
+        assert_eq!(
+            summary,
+            "line 42 last changed in 2222222 2024-03-09 (Bob: fix: off by one)\n\
+             lines 40-41, 43 last changed in 1111111 2023-11-14 (Alice: add parser)\n"
+        );
+        assert!(blame_summary(&commits, 1).ends_with("... and 1 older commits\n"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = Status::parse(PORCELAIN).summary();
+        assert!(summary.starts_with("On branch main (upstream origin/main, ahead 2, behind 0)\n"));
+        assert!(summary.contains(
+            "Staged (3):\n  modified: src/app.rs\n  modified: src/my file.rs\n  renamed: src/old.rs -> src/new.rs\n"
+        ));
+        assert!(summary.contains("Unstaged (2):\n  modified: README.md\n  modified: src/my file.rs\n"));
+        assert!(summary.contains("Conflicts (1):\n  Cargo.lock\n"));
+
+        let clean = Status::parse("# branch.head main\n").summary();
+        assert_eq!(clean, "On branch main\nWorking tree clean\n");
+    }
+}
@@ -4,11 +4,18 @@
 //! Handlers are stateless - they receive data and produce a Step result.
 
 use super::browser;
-use crate::ide::{Edit, ToolPreview};
+use crate::ide::{Edit, FileDiff, ToolPreview};
+use crate::tools::git;
 use crate::tools::io;
+use crate::tools::symbols::{self, Language};
 use crate::tools::pipeline::{Effect, EffectHandler, Step};
+use crate::tools::remote::{RemotePath, Ssh};
+use crate::tools::{Backups, FileCache, Memory, ReadTracker, ShellSandbox};
+use std::collections::HashMap;
 use std::fs;
+use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 // =============================================================================
 // Validation handlers
@@ -104,12 +111,65 @@ impl EffectHandler for ApplyEdits {
             content = content.replacen(&edit.old_string, &edit.new_string, 1);
         }
 
+        // Writes in place rather than replacing the file, so its mode
+        // (an executable script stays executable) and owner are kept
         match fs::write(&self.path, content) {
             Ok(()) => Step::Continue,
             Err(e) => Step::Error(format!("Failed to write file: {}", e)),
         }
     }
 }
+
+/// Write several files all-or-nothing. Nothing is written if any file changed
+/// since its change was computed, and if a write fails the files written so
+/// far are put back.
+pub struct ApplyFileChanges {
+    /// Each file, with its content when the change was computed and its new
+    /// content (the diff's `path` is only for display)
+    pub changes: Vec<(PathBuf, FileDiff)>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for ApplyFileChanges {
+    async fn call(self: Box<Self>) -> Step {
+        for (path, change) in &self.changes {
+            match fs::read_to_string(path) {
+                Ok(current) if current == change.original => {},
+                Ok(_) => {
+                    return Step::Error(format!(
+                        "{} changed after the edit was prepared; no files were modified",
+                        path.display()
+                    ))
+                },
+                Err(e) => {
+                    return Step::Error(format!(
+                        "Failed to read {}: {}; no files were modified",
+                        path.display(),
+                        e
+                    ))
+                },
+            }
+        }
+
+        for (i, (path, change)) in self.changes.iter().enumerate() {
+            let Err(e) = fs::write(path, &change.modified) else {
+                continue;
+            };
+            // The failed write may have truncated its file, so restore it too
+            let mut message = format!("Failed to write {}: {}", path.display(), e);
+            let mut restored = 0;
+            for (done_path, done) in self.changes[..=i].iter().rev() {
+                match fs::write(done_path, &done.original) {
+                    Ok(()) => restored += 1,
+                    Err(e) => message.push_str(&format!("\nFailed to restore {}: {}", done_path.display(), e)),
+                }
+            }
+            message.push_str(&format!("\nRolled back {} file(s)", restored));
+            return Step::Error(message);
+        }
+        Step::Continue
+    }
+}
 // =============================================================================
 // Control flow handlers
 // =============================================================================
@@ -169,22 +229,173 @@ pub struct ReadFile {
     pub path: PathBuf,
     pub start_line: Option<i32>,
     pub end_line: Option<i32>,
+    pub cache: Arc<Mutex<FileCache>>,
 }
 
 #[async_trait::async_trait]
 impl EffectHandler for ReadFile {
     async fn call(self: Box<Self>) -> Step {
-        match io::read_file(&self.path, self.start_line, self.end_line) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        match cache.read(&self.path, self.start_line, self.end_line) {
+            Ok(content) => Step::Output(content),
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Append which commits last changed the lines just read (`include_blame`).
+/// Outside a git repository, or for an untracked file, a short note says why
+/// there's no history instead of failing the read.
+pub struct AppendBlame {
+    pub path: PathBuf,
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for AppendBlame {
+    async fn call(self: Box<Self>) -> Step {
+        match git::blame(&self.path, self.start_line, self.end_line).await {
+            Ok(summary) if !summary.is_empty() => Step::Append(format!("\n\n[Recent changes]\n{}", summary)),
+            Ok(_) => Step::Continue,
+            Err(e) => {
+                let reason = e.lines().next().unwrap_or_default().trim_start_matches("fatal: ");
+                Step::Append(format!("\n\n[No blame available: {}]", reason))
+            },
+        }
+    }
+}
+
+/// Note that the agent has seen the current version of a file
+/// (`tools.require_read_before_edit`)
+pub struct RecordRead {
+    pub path: PathBuf,
+    pub tracker: ReadTracker,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for RecordRead {
+    async fn call(self: Box<Self>) -> Step {
+        self.tracker.record(&self.path);
+        Step::Continue
+    }
+}
+
+/// Fail unless the agent has read the current version of a file
+/// (`tools.require_read_before_edit`)
+pub struct RequireRead {
+    pub path: PathBuf,
+    pub tracker: ReadTracker,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for RequireRead {
+    async fn call(self: Box<Self>) -> Step {
+        match self.tracker.check(&self.path) {
+            Ok(()) => Step::Continue,
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Read the last lines of a file without loading all of it (bypasses the cache,
+/// since tailed files are usually still being written)
+pub struct TailFile {
+    pub path: PathBuf,
+    pub lines: usize,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for TailFile {
+    async fn call(self: Box<Self>) -> Step {
+        match io::tail_file(&self.path, self.lines) {
+            Ok(content) => Step::Output(content),
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Read a file on another host over SSH. With `tail`, only the last lines are
+/// fetched and numbered from the end, as for local files.
+pub struct ReadRemoteFile {
+    pub path: RemotePath,
+    pub ssh: Ssh,
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    pub tail: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for ReadRemoteFile {
+    async fn call(self: Box<Self>) -> Step {
+        let result = match self.tail {
+            Some(lines) => self.path.tail(&self.ssh, lines).await.map(|content| {
+                io::format_tail(&content.lines().collect::<Vec<_>>())
+            }),
+            None => self
+                .path
+                .read(&self.ssh)
+                .await
+                .map(|content| io::format_lines(&content, self.start_line, self.end_line)),
+        };
+        match result {
             Ok(content) => Step::Output(content),
             Err(e) => Step::Error(e),
         }
     }
 }
 
+/// Read the declarations of a named symbol, with their line ranges
+pub struct ReadSymbol {
+    pub path: PathBuf,
+    pub symbol: String,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for ReadSymbol {
+    async fn call(self: Box<Self>) -> Step {
+        let Some(language) = Language::from_path(&self.path) else {
+            return Step::Error(format!(
+                "Unsupported file type for read_symbol: {} (supported: .rs, .py, .go, .js/.ts); use read_file instead",
+                self.path.display()
+            ));
+        };
+        let content = match fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) => return Step::Error(format!("Failed to read file: {}", e)),
+        };
+
+        let spans = symbols::find_symbol(language, &content, &self.symbol);
+        if spans.is_empty() {
+            return Step::Error(format!(
+                "Symbol not found: '{}' in {}",
+                self.symbol,
+                self.path.display()
+            ));
+        }
+
+        let sections: Vec<String> = spans
+            .iter()
+            .map(|span| {
+                format!(
+                    "{}:{}-{}\n{}",
+                    self.path.display(),
+                    span.start,
+                    span.end,
+                    io::format_lines(&content, Some(span.start as i32), Some(span.end as i32))
+                )
+            })
+            .collect();
+        Step::Output(sections.join("\n"))
+    }
+}
+
 /// Write content to a file
 pub struct WriteFile {
     pub path: PathBuf,
     pub content: String,
+    /// Permission bits to give the file after writing it (e.g. 0o755)
+    pub mode: Option<u32>,
 }
 
 #[async_trait::async_trait]
@@ -202,15 +413,41 @@ impl EffectHandler for WriteFile {
                 }
             }
         }
-        match fs::write(&self.path, &self.content) {
-            Ok(()) => Step::Continue,
-            Err(e) => Step::Error(format!("Failed to write {}: {}", self.path.display(), e)),
+        if let Err(e) = fs::write(&self.path, &self.content) {
+            return Step::Error(format!("Failed to write {}: {}", self.path.display(), e));
+        }
+        if let Some(mode) = self.mode {
+            if let Err(e) = fs::set_permissions(&self.path, fs::Permissions::from_mode(mode)) {
+                return Step::Error(format!(
+                    "Wrote {} but failed to set its mode to {:o}: {}",
+                    self.path.display(),
+                    mode,
+                    e
+                ));
+            }
+        }
+        Step::Continue
+    }
+}
+
+/// Back up a file before it is modified
+pub struct BackupFile {
+    pub path: PathBuf,
+    pub backups: Backups,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for BackupFile {
+    async fn call(self: Box<Self>) -> Step {
+        match self.backups.create(&self.path) {
+            Ok(_) => Step::Continue,
+            Err(e) => Step::Error(format!("{:#}", e)),
         }
     }
 }
 
 // =============================================================================
-// Shell handler
+// Shell handlers
 // =============================================================================
 
 /// Execute a shell command
@@ -218,13 +455,24 @@ pub struct Shell {
     pub command: String,
     pub working_dir: Option<String>,
     pub timeout_secs: u64,
+    /// Kill the command once its output passes this many bytes
+    pub max_output_bytes: Option<usize>,
+    /// Run with a restricted environment, if configured
+    pub sandbox: Option<ShellSandbox>,
 }
 
 #[async_trait::async_trait]
 impl EffectHandler for Shell {
     async fn call(self: Box<Self>) -> Step {
-        match io::execute_shell(&self.command, self.working_dir.as_deref(), self.timeout_secs).await
-        {
+        let working_dir = self.working_dir.as_deref();
+        let result = match self.sandbox {
+            Some(ref sandbox) => {
+                let cmd = sandbox.command(&self.command);
+                io::execute_prepared(cmd, working_dir, self.timeout_secs, self.max_output_bytes).await
+            },
+            None => io::execute_shell(&self.command, working_dir, self.timeout_secs, self.max_output_bytes).await,
+        };
+        match result {
             Ok(result) if result.success => Step::Output(result.output),
             Ok(result) => Step::Output(result.output), // Still output, but includes exit code
             Err(e) => Step::Error(e),
@@ -232,6 +480,127 @@ impl EffectHandler for Shell {
     }
 }
 
+/// Run the configured check command after a batch of edits and add its
+/// result to the tool output, so the agent sees errors its edits introduced
+pub struct PostEditCheck {
+    pub command: String,
+    pub timeout_secs: u64,
+    pub max_output_bytes: Option<usize>,
+    /// Run with a restricted environment, if configured
+    pub sandbox: Option<ShellSandbox>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for PostEditCheck {
+    async fn call(self: Box<Self>) -> Step {
+        let result = match self.sandbox {
+            Some(ref sandbox) => {
+                let cmd = sandbox.command(&self.command);
+                io::execute_prepared(cmd, None, self.timeout_secs, self.max_output_bytes).await
+            },
+            None => io::execute_shell(&self.command, None, self.timeout_secs, self.max_output_bytes).await,
+        };
+        // A check that can't run shouldn't fail the edit that triggered it
+        Step::Append(match result {
+            Ok(result) if result.success => format!("\n\nPost-edit check passed (`{}`)", self.command),
+            Ok(result) => format!(
+                "\n\nPost-edit check failed (`{}`):\n{}",
+                self.command,
+                io::strip_ansi(&result.output)
+            ),
+            Err(e) => format!("\n\nPost-edit check could not run (`{}`): {}", self.command, e),
+        })
+    }
+}
+
+/// Execute a program from an argument vector, without a shell
+pub struct RunArgv {
+    pub argv: Vec<String>,
+    pub working_dir: Option<String>,
+    pub timeout_secs: u64,
+    /// Run with a restricted environment, if configured
+    pub sandbox: Option<ShellSandbox>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for RunArgv {
+    async fn call(self: Box<Self>) -> Step {
+        let working_dir = self.working_dir.as_deref();
+        let result = match (&self.sandbox, self.argv.split_first()) {
+            (Some(sandbox), Some((program, args))) => {
+                let cmd = sandbox.argv_command(program, args);
+                io::execute_prepared(cmd, working_dir, self.timeout_secs, None).await
+            },
+            _ => io::execute_argv(&self.argv, working_dir, self.timeout_secs).await,
+        };
+        match result {
+            Ok(result) => Step::Output(result.output),
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Run a follow-up shell command through the app, so it is approved and shown
+/// like any other shell call. Its output becomes the pipeline output.
+pub struct RunCommand {
+    pub command: String,
+    pub working_dir: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for RunCommand {
+    async fn call(self: Box<Self>) -> Step {
+        Step::Delegate(Effect::RunCommand {
+            command: self.command,
+            working_dir: self.working_dir,
+        })
+    }
+}
+
+// =============================================================================
+// Git handlers
+// =============================================================================
+
+/// Summarize the working tree, optionally with recent commits
+pub struct GitStatus {
+    pub log: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for GitStatus {
+    async fn call(self: Box<Self>) -> Step {
+        match git::status(self.log).await {
+            Ok(summary) => Step::Output(summary),
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Stage or unstage paths, then report the resulting status
+pub struct GitStage {
+    pub paths: Vec<String>,
+    pub unstage: bool,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for GitStage {
+    async fn call(self: Box<Self>) -> Step {
+        let mut args = if self.unstage {
+            vec!["restore", "--staged", "--"]
+        } else {
+            vec!["add", "--"]
+        };
+        args.extend(self.paths.iter().map(String::as_str));
+        if let Err(e) = git::run(&args).await {
+            return Step::Error(e);
+        }
+        match git::status(None).await {
+            Ok(summary) => Step::Output(summary),
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
 // =============================================================================
 // Network handlers
 // =============================================================================
@@ -240,12 +609,14 @@ impl EffectHandler for Shell {
 pub struct FetchUrl {
     pub url: String,
     pub max_length: Option<usize>,
+    /// Where to save binary responses (images, PDFs) instead of returning them
+    pub save_binary_to: Option<PathBuf>,
 }
 
 #[async_trait::async_trait]
 impl EffectHandler for FetchUrl {
     async fn call(self: Box<Self>) -> Step {
-        match io::fetch_url(&self.url, self.max_length).await {
+        match io::fetch_url(&self.url, self.max_length, self.save_binary_to.as_deref()).await {
             Ok(result) => {
                 let header = format!(
                     "[URL: {}]\n[Content-Type: {}]\n[Size: {} bytes]\n\n",
@@ -258,24 +629,189 @@ impl EffectHandler for FetchUrl {
     }
 }
 
+/// Send an HTTP request and return the status, headers and body
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub json: Option<serde_json::Value>,
+    pub body: Option<String>,
+    pub max_length: usize,
+    pub timeout_secs: u64,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for HttpRequest {
+    async fn call(self: Box<Self>) -> Step {
+        let result = io::http_request(
+            &self.method,
+            &self.url,
+            &self.headers,
+            self.json.as_ref(),
+            self.body.as_deref(),
+            self.max_length,
+            self.timeout_secs,
+        )
+        .await;
+        match result {
+            Ok(response) => {
+                let mut output = format!("{} {}\n", response.status, response.reason);
+                for (name, value) in &response.headers {
+                    output.push_str(&format!("{}: {}\n", name, value));
+                }
+                output.push('\n');
+                output.push_str(&response.body);
+                Step::Output(output)
+            }
+            Err(e) => Step::Error(e),
+        }
+    }
+}
+
+/// Download a URL to a file. The body goes to `<dest>.part` first, picking up
+/// from one an earlier attempt left, and is moved to `dest` once complete.
+pub struct Download {
+    pub url: String,
+    pub dest: PathBuf,
+    pub max_bytes: u64,
+    /// Give up on a connection or read that stalls this long
+    pub timeout_secs: u64,
+}
+
+impl Download {
+    /// The partial file next to `dest`
+    pub fn part_path(dest: &std::path::Path) -> PathBuf {
+        let mut part = dest.as_os_str().to_owned();
+        part.push(".part");
+        PathBuf::from(part)
+    }
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for Download {
+    async fn call(self: Box<Self>) -> Step {
+        if let Some(parent) = self.dest.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                if let Err(e) = fs::create_dir_all(parent) {
+                    return Step::Error(format!("Failed to create directory {}: {}", parent.display(), e));
+                }
+            }
+        }
+        let part = Download::part_path(&self.dest);
+        let stream = match io::start_download(&self.url, &part, self.max_bytes, self.timeout_secs).await {
+            Ok(stream) => stream,
+            Err(e) => return Step::Error(e),
+        };
+        let started = match stream.resumed_from {
+            Some(offset) => format!("Resuming at {}\n", io::format_size(offset)),
+            None => match stream.total {
+                Some(total) => format!("Downloading {}\n", io::format_size(total)),
+                None => "Downloading (size unknown)\n".to_string(),
+            },
+        };
+        Step::Progress(started, Box::new(DownloadProgress {
+            url: self.url,
+            dest: self.dest,
+            part,
+            stream,
+        }))
+    }
+}
+
+/// How often a download reports its progress
+const DOWNLOAD_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Continue a download started by [`Download`], reporting progress every
+/// [`DOWNLOAD_PROGRESS_INTERVAL`]
+pub struct DownloadProgress {
+    url: String,
+    dest: PathBuf,
+    part: PathBuf,
+    stream: io::DownloadStream,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for DownloadProgress {
+    async fn call(self: Box<Self>) -> Step {
+        let mut this = self;
+        let started = std::time::Instant::now();
+        while started.elapsed() < DOWNLOAD_PROGRESS_INTERVAL {
+            match this.stream.next_chunk().await {
+                Ok(true) => continue,
+                Ok(false) => return this.finish(),
+                Err(e) => return Step::Error(e),
+            }
+        }
+        let progress = match this.stream.total {
+            Some(total) if total > 0 => format!(
+                "{} of {} ({}%)\n",
+                io::format_size(this.stream.written),
+                io::format_size(total),
+                this.stream.written * 100 / total
+            ),
+            _ => format!("{}\n", io::format_size(this.stream.written)),
+        };
+        Step::Progress(progress, this)
+    }
+}
+
+impl DownloadProgress {
+    /// Move the finished download into place
+    fn finish(&self) -> Step {
+        if let Err(e) = fs::rename(&self.part, &self.dest) {
+            return Step::Error(format!("Cannot move download to {}: {}", self.dest.display(), e));
+        }
+        let resumed = self
+            .stream
+            .resumed_from
+            .map(|offset| format!(", resumed at {}", io::format_size(offset)))
+            .unwrap_or_default();
+        Step::Output(format!(
+            "Downloaded {} to {} ({} bytes{})",
+            self.url,
+            self.dest.display(),
+            self.stream.written,
+            resumed
+        ))
+    }
+}
+
+/// Search results by the params of the call that found them, until the
+/// call's display block takes them
+pub type SearchResults = Arc<Mutex<HashMap<String, Vec<io::SearchResult>>>>;
+
 /// Search the web
 pub struct WebSearch {
     pub query: String,
     pub count: u32,
+    /// Give up on the provider after this long
+    pub timeout_secs: u64,
+    /// Where the results are left for the display block, under `key`
+    pub results: SearchResults,
+    pub key: String,
 }
 
 #[async_trait::async_trait]
 impl EffectHandler for WebSearch {
     async fn call(self: Box<Self>) -> Step {
-        match io::web_search(&self.query, self.count).await {
+        match io::web_search(&self.query, self.count, self.timeout_secs).await {
             Ok(results) => {
+                if !results.is_empty() {
+                    self.results.lock().unwrap_or_else(|e| e.into_inner()).insert(self.key.clone(), results.clone());
+                }
                 if results.is_empty() {
                     Step::Output("No results found.".to_string())
                 } else {
                     let output = results
                         .iter()
                         .enumerate()
-                        .map(|(i, r)| format!("{}. [{}]({})", i + 1, r.title, r.url))
+                        .map(|(i, r)| {
+                            let mut entry = format!("{}. [{}]({})", i + 1, r.title, r.url);
+                            if !r.description.is_empty() {
+                                entry.push_str(&format!("\n   {}", r.description));
+                            }
+                            entry
+                        })
                         .collect::<Vec<_>>()
                         .join("\n");
                     Step::Output(output)
@@ -286,6 +822,26 @@ impl EffectHandler for WebSearch {
     }
 }
 
+/// Register a file an earlier handler wrote as a session artifact. Does
+/// nothing if the file wasn't written.
+pub struct RegisterArtifact {
+    pub path: PathBuf,
+    pub kind: String,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for RegisterArtifact {
+    async fn call(self: Box<Self>) -> Step {
+        if !self.path.is_file() {
+            return Step::Continue;
+        }
+        Step::Delegate(Effect::Artifact {
+            path: self.path,
+            kind: self.kind,
+        })
+    }
+}
+
 // =============================================================================
 // IDE handlers (delegate to app)
 // =============================================================================
@@ -360,6 +916,34 @@ impl EffectHandler for IdeClosePreview {
     }
 }
 
+/// List files open in the IDE
+pub struct IdeOpenFiles;
+
+#[async_trait::async_trait]
+impl EffectHandler for IdeOpenFiles {
+    async fn call(self: Box<Self>) -> Step {
+        Step::Delegate(Effect::IdeOpenFiles)
+    }
+}
+
+/// Leave an inline note at a line in the IDE
+pub struct IdeAnnotate {
+    pub path: PathBuf,
+    pub line: u32,
+    pub text: String,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for IdeAnnotate {
+    async fn call(self: Box<Self>) -> Step {
+        Step::Delegate(Effect::IdeAnnotate {
+            path: self.path,
+            line: self.line,
+            text: self.text,
+        })
+    }
+}
+
 // =============================================================================
 // Background task handlers (delegate to app)
 // =============================================================================
@@ -424,12 +1008,13 @@ impl EffectHandler for GetAgent {
 pub struct FetchHtml {
     pub url: String,
     pub max_length: Option<usize>,
+    pub browser: browser::BrowserContext,
 }
 
 #[async_trait::async_trait]
 impl EffectHandler for FetchHtml {
     async fn call(self: Box<Self>) -> Step {
-        match browser::fetch_html(&self.url, self.max_length).await {
+        match browser::fetch_html(&self.url, self.max_length, &self.browser).await {
             Ok(result) => {
                 let title_info = result
                     .title
@@ -437,7 +1022,11 @@ impl EffectHandler for FetchHtml {
                     .map(|t| format!("[Title: {}]\n", t))
                     .unwrap_or_default();
                 let header = format!("[URL: {}]\n{}\n", result.url, title_info);
-                Step::Output(header + &result.content)
+                let mut output = header + &result.content;
+                for warning in &result.warnings {
+                    output.push_str(&format!("\n\n[Warning: {}]", warning));
+                }
+                Step::Output(output)
             }
             Err(e) => Step::Error(e),
         }
@@ -521,3 +1110,56 @@ impl EffectHandler for AppendCorrection {
         ))
     }
 }
+
+// =============================================================================
+// Project memory handlers
+// =============================================================================
+
+/// Add, replace or (with no note) remove a memory note
+pub struct WriteMemory {
+    pub memory: Memory,
+    pub topic: String,
+    pub key: String,
+    pub note: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for WriteMemory {
+    async fn call(self: Box<Self>) -> Step {
+        let location = format!("{}/{}", self.topic.trim(), self.key.trim());
+        match self.note {
+            Some(note) if !note.trim().is_empty() => {
+                match self.memory.write(&self.topic, &self.key, &note) {
+                    Ok(true) => Step::Output(format!("Updated memory note {}", location)),
+                    Ok(false) => Step::Output(format!("Saved memory note {}", location)),
+                    Err(e) => Step::Error(e.to_string()),
+                }
+            },
+            _ => match self.memory.remove(&self.topic, &self.key) {
+                Ok(true) => Step::Output(format!("Removed memory note {}", location)),
+                Ok(false) => Step::Error(format!("No memory note {}", location)),
+                Err(e) => Step::Error(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Read memory notes, optionally for a single topic
+pub struct ReadMemory {
+    pub memory: Memory,
+    pub topic: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for ReadMemory {
+    async fn call(self: Box<Self>) -> Step {
+        match self.memory.render(self.topic.as_deref()) {
+            Ok(Some(notes)) => Step::Output(notes),
+            Ok(None) => Step::Output(match self.topic {
+                Some(topic) => format!("No memory notes under topic '{}'", topic),
+                None => "Memory is empty".to_string(),
+            }),
+            Err(e) => Step::Error(e.to_string()),
+        }
+    }
+}
@@ -9,7 +9,7 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_simple_tool_block;
 use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
 
@@ -73,6 +73,10 @@ impl Tool for ListAgentsTool {
          so you rarely need this. Only use to debug issues or if the user asks about agent status."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -122,6 +126,10 @@ impl Tool for GetAgentTool {
          so you rarely need this tool."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -148,7 +156,12 @@ impl Tool for GetAgentTool {
             })
     }
 
+    /// Sub-agents report back in markdown
+    fn output_is_markdown(&self) -> bool {
+        true
+    }
+
     fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
-        Box::new(GetAgentBlock::new(call_id, self.name(), params, background))
+        Box::new(GetAgentBlock::new(call_id, self.name(), params, background).with_markdown(self.output_is_markdown()))
     }
 }
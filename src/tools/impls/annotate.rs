@@ -0,0 +1,116 @@
+//! Annotate tool
+//!
+//! Leaves a short note at a line in the user's editor (virtual text in
+//! Neovim), so the agent can point at code while it explains it. Notes are
+//! cleared when the user sends their next message, or with `/annotations`.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_simple_tool_block;
+use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
+
+define_simple_tool_block! {
+    /// Block for annotate - shows as `annotate(path:line note)`
+    pub struct AnnotateBlock {
+        max_lines: 5,
+        render_header(self, params) {
+            let path = params["path"].as_str().unwrap_or("");
+            let line = params["line"].as_u64().unwrap_or(0);
+            let text = params["text"].as_str().unwrap_or("");
+
+            vec![
+                Span::styled("annotate", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{}:{}", path, line), Style::default().fg(Color::Cyan)),
+                Span::styled(format!(" {}", text), Style::default().fg(Color::Gray)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AnnotateParams {
+    path: String,
+    line: u32,
+    text: String,
+}
+
+/// Tool for leaving notes at lines in the IDE
+pub struct AnnotateTool;
+
+impl AnnotateTool {
+    pub const NAME: &'static str = "mcp_annotate";
+}
+
+impl Tool for AnnotateTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Show a short note at the end of a line in the user's IDE/editor. \
+         Use this to point out specific code while explaining it. \
+         Notes are temporary and disappear when the user replies."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the file to annotate"
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "Line number to annotate (1-indexed)"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "The note to show; keep it to a short phrase"
+                }
+            },
+            "required": ["path", "line", "text"]
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: AnnotateParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+        if parsed.line == 0 {
+            return ToolPipeline::error("Line numbers start at 1");
+        }
+
+        let path = PathBuf::from(&parsed.path);
+        let abs_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let message = format!("Annotated {}:{}", parsed.path, parsed.line);
+
+        ToolPipeline::new()
+            .then(handlers::ValidateFile { path })
+            .await_approval()
+            .then(handlers::IdeAnnotate {
+                path: abs_path,
+                line: parsed.line,
+                text: parsed.text,
+            })
+            .then(handlers::Output { content: message })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(AnnotateBlock::new(call_id, self.name(), params, background))
+    }
+}
@@ -9,7 +9,7 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_simple_tool_block;
 use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
 
@@ -73,6 +73,10 @@ impl Tool for ListBackgroundTasksTool {
          so you rarely need this. Only use to debug issues or if the user asks about task status."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -123,6 +127,10 @@ impl Tool for GetBackgroundTaskTool {
          so you rarely need this tool."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
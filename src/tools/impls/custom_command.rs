@@ -0,0 +1,350 @@
+//! User-defined command tools
+//!
+//! Tools declared in config under `[[tools.custom]]` run a command template
+//! with the agent's params substituted in:
+//!
+//! ```toml
+//! [[tools.custom]]
+//! name = "deploy"
+//! description = "Deploy the app to an environment"
+//! command = "./scripts/deploy.sh --env {env}"
+//! ```
+//!
+//! The template is split into an argument vector once, at startup, and each
+//! `{param}` placeholder is replaced inside its own argument. The program is
+//! executed directly (never through a shell), so param values cannot inject
+//! extra commands or arguments. A value that would start an argument with `-`
+//! is refused, so it can't pass as an option either.
+//!
+//! A placeholder naming a session variable (`/set env=staging`) is filled from
+//! it when the agent doesn't pass that param, and without a configured schema
+//! it's left out of the params the model is asked for.
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::config::CustomToolConfig;
+use crate::tools::ShellSandbox;
+use crate::variables::{self, SessionVariables};
+
+/// A tool backed by a command template from config
+pub struct CustomCommandTool {
+    name: &'static str,
+    description: &'static str,
+    argv: Vec<String>,
+    /// Configured schema; None derives one from the placeholders
+    schema: Option<Value>,
+    working_dir: Option<String>,
+    timeout_secs: u64,
+    variables: SessionVariables,
+    /// The shell sandbox, which custom commands run in like shell commands
+    sandbox: Option<ShellSandbox>,
+}
+
+impl CustomCommandTool {
+    pub fn new(config: &CustomToolConfig) -> Result<Self> {
+        if config.name.is_empty()
+            || !config
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            bail!("Invalid custom tool name '{}': use letters, digits, '_' or '-'", config.name);
+        }
+
+        let argv = split_template(&config.command)?;
+        if argv.is_empty() {
+            bail!("Custom tool '{}' has an empty command", config.name);
+        }
+
+        // The Tool trait hands out 'static strings; custom tools are built
+        // once at startup, so leaking them is bounded.
+        Ok(Self {
+            name: Box::leak(config.name.clone().into_boxed_str()),
+            description: Box::leak(config.description.clone().into_boxed_str()),
+            argv,
+            schema: config.schema.clone(),
+            working_dir: config.working_dir.clone(),
+            timeout_secs: config.timeout_secs,
+            variables: SessionVariables::new(),
+            sandbox: None,
+        })
+    }
+
+    /// Fill placeholders the agent doesn't pass from these session variables
+    pub fn with_variables(mut self, variables: SessionVariables) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Run commands in the shell sandbox
+    pub fn with_sandbox(mut self, sandbox: ShellSandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+}
+
+impl Tool for CustomCommandTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Exec
+    }
+
+    fn schema(&self) -> Value {
+        match self.schema {
+            Some(ref schema) => schema.clone(),
+            // Rebuilt on every request, so it follows `/set` and `/unset`
+            None => default_schema(&self.argv, |name| self.variables.get(name).is_some()),
+        }
+    }
+
+    fn compose(&self, params: Value) -> ToolPipeline {
+        let argv = match substitute(&self.argv, &params, &self.variables) {
+            Ok(argv) => argv,
+            Err(e) => return ToolPipeline::error(e),
+        };
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::RunArgv {
+                argv,
+                working_dir: self.working_dir.clone(),
+                timeout_secs: self.timeout_secs,
+                sandbox: self.sandbox.clone(),
+            })
+    }
+}
+
+/// Split a command template into arguments.
+/// Supports single quotes, double quotes and backslash escapes.
+fn split_template(template: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => bail!("Unterminated single quote in command: {}", template),
+                    }
+                }
+            },
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => current.push(c),
+                            None => bail!("Unterminated double quote in command: {}", template),
+                        },
+                        Some(c) => current.push(c),
+                        None => bail!("Unterminated double quote in command: {}", template),
+                    }
+                }
+            },
+            '\\' => {
+                in_arg = true;
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            },
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            },
+            c => {
+                in_arg = true;
+                current.push(c);
+            },
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+/// Names of all `{param}` placeholders in an argument, in order
+fn placeholders(arg: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if variables::is_valid_name(&after[..end]) => {
+                names.push(&after[..end]);
+                rest = &after[end + 1..];
+            },
+            _ => rest = after,
+        }
+    }
+    names
+}
+
+/// Schema used when none is configured: every placeholder is a required
+/// string, except those `is_set` says a session variable provides
+fn default_schema(argv: &[String], is_set: impl Fn(&str) -> bool) -> Value {
+    let names: BTreeSet<&str> = argv
+        .iter()
+        .flat_map(|arg| placeholders(arg))
+        .filter(|name| !is_set(name))
+        .collect();
+    let properties: serde_json::Map<String, Value> = names
+        .iter()
+        .map(|name| (name.to_string(), json!({ "type": "string" })))
+        .collect();
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": names.into_iter().collect::<Vec<_>>(),
+    })
+}
+
+/// Replace placeholders in each argument with the matching param value,
+/// or the session variable of that name if the param wasn't passed. Values
+/// that turn an argument into an option (a leading `-`) are refused.
+fn substitute(argv: &[String], params: &Value, variables: &SessionVariables) -> Result<Vec<String>, String> {
+    argv.iter()
+        .map(|arg| {
+            let mut result = arg.clone();
+            for name in placeholders(arg) {
+                let value = match params.get(name) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Null) | None => match variables.get(name) {
+                        Some(value) => value,
+                        None => return Err(format!("Missing parameter: {}", name)),
+                    },
+                    Some(v) => v.to_string(),
+                };
+                result = result.replacen(&format!("{{{}}}", name), &value, 1);
+            }
+            if result.starts_with('-') && !arg.starts_with('-') {
+                return Err(format!("Argument can't start with '-': {}", result));
+            }
+            Ok(result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ToolCall, ToolDecision, ToolEvent, ToolExecutor, ToolRegistry};
+
+    fn config(name: &str, command: &str) -> CustomToolConfig {
+        CustomToolConfig {
+            name: name.to_string(),
+            description: "test tool".to_string(),
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_template() {
+        assert_eq!(
+            split_template(r#"./deploy.sh --env {env} "two words" 'it''s' a\ b"#).unwrap(),
+            vec!["./deploy.sh", "--env", "{env}", "two words", "its", "a b"]
+        );
+        assert!(split_template("echo 'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_default_schema_from_placeholders() {
+        let tool = CustomCommandTool::new(&config("deploy", "deploy.sh {env} --tag={tag} {env}")).unwrap();
+        let schema = tool.schema();
+        assert_eq!(schema["properties"]["env"]["type"], "string");
+        assert_eq!(schema["properties"]["tag"]["type"], "string");
+        assert_eq!(schema["required"], json!(["env", "tag"]));
+    }
+
+    #[test]
+    fn test_substitute_keeps_values_as_single_args() {
+        let argv = split_template("echo prefix-{msg} {n}").unwrap();
+        let vars = SessionVariables::new();
+        let result = substitute(&argv, &json!({ "msg": "a; rm -rf / && echo", "n": 3 }), &vars).unwrap();
+        assert_eq!(result, vec!["echo", "prefix-a; rm -rf / && echo", "3"]);
+
+        assert!(substitute(&argv, &json!({ "msg": "x" }), &vars).is_err());
+    }
+
+    #[test]
+    fn test_substitute_refuses_option_values() {
+        let argv = split_template("deploy.sh --env {env} --tag={tag}").unwrap();
+        let vars = SessionVariables::new();
+        assert!(substitute(&argv, &json!({ "env": "--force", "tag": "v2" }), &vars).is_err());
+        // Only a leading dash is refused, and the template's own options stay
+        let result = substitute(&argv, &json!({ "env": "dev-1", "tag": "-rc" }), &vars).unwrap();
+        assert_eq!(result, vec!["deploy.sh", "--env", "dev-1", "--tag=-rc"]);
+    }
+
+    #[test]
+    fn test_session_variables_fill_placeholders() {
+        let vars = SessionVariables::new();
+        let tool = CustomCommandTool::new(&config("deploy", "deploy.sh {env} {tag}"))
+            .unwrap()
+            .with_variables(vars.clone());
+        vars.set("env", "staging").unwrap();
+        assert_eq!(tool.schema()["required"], json!(["tag"]));
+
+        let argv = substitute(&tool.argv, &json!({ "tag": "v2" }), &vars).unwrap();
+        assert_eq!(argv, vec!["deploy.sh", "staging", "v2"]);
+        // A param the agent passes wins over the variable
+        let argv = substitute(&tool.argv, &json!({ "env": "dev", "tag": "v2" }), &vars).unwrap();
+        assert_eq!(argv, vec!["deploy.sh", "dev", "v2"]);
+
+        vars.unset("env");
+        assert_eq!(tool.schema()["required"], json!(["env", "tag"]));
+    }
+
+    #[test]
+    fn test_invalid_name_rejected() {
+        assert!(CustomCommandTool::new(&config("bad name", "echo")).is_err());
+        assert!(CustomCommandTool::new(&config("ok", "   ")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_command_runs_without_shell() {
+        let tool = CustomCommandTool::new(&config("say", "echo {msg}")).unwrap();
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(tool));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: "say".to_string(),
+            params: json!({ "msg": "hi $(whoami)" }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        match executor.next().await {
+            Some(ToolEvent::Completed { content, .. }) => {
+                assert!(content.contains("hi $(whoami)"));
+            },
+            other => panic!("Expected Completed, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Download tool
+//!
+//! Saves a URL to a file inside the working directory, as a structured
+//! alternative to `curl -o` in shell:
+//! ```text
+//! download = [
+//!     ValidateFileNotExists, // Never overwrite
+//!     AwaitApproval,         // Always asked: writes a file from the network
+//!     Download,              // Stream into <dest>.part, resuming one left
+//!     DownloadProgress...,   //   by an earlier attempt, then move into place
+//! ]
+//! ```
+//! Reads that fail are retried from the bytes already saved, and a download
+//! that still fails keeps its `.part` file so calling again picks up there.
+
+use std::path::{Component, Path, PathBuf};
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_tool_block;
+use crate::tools::RemotePath;
+use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+define_tool_block! {
+    /// Download display block
+    pub struct DownloadBlock {
+        max_lines: 5,
+        params_type: DownloadParams,
+        render_header(self, params) {
+            let url = params["url"].as_str().unwrap_or("");
+            let dest = params["dest"].as_str().unwrap_or("");
+
+            vec![
+                Span::styled("download", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(url.to_string(), Style::default().fg(Color::Blue)),
+                Span::styled(" -> ", Style::default().fg(Color::DarkGray)),
+                Span::styled(dest.to_string(), Style::default().fg(Color::Green)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+/// Tool for downloading files
+pub struct DownloadTool {
+    max_bytes: u64,
+    timeout_secs: u64,
+    /// The shell sandbox cuts off the network, so downloads are refused too
+    network_disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadParams {
+    url: String,
+    dest: String,
+}
+
+/// Resolve `dest` to a path inside the working directory. Absolute paths
+/// are accepted when they're under it; `..`, `.git` and symlinks that lead
+/// out of it never are.
+fn check_dest(dest: &str, cwd: &Path) -> Result<PathBuf, String> {
+    let outside = || format!("Download destination must be inside the working directory: {}", dest);
+    let path = Path::new(dest);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(cwd).map_err(|_| outside())?
+    } else {
+        path
+    };
+    if relative.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(outside());
+    }
+    if relative.as_os_str().is_empty() {
+        return Err("Download destination must be a file path".to_string());
+    }
+    if relative.components().any(|c| c.as_os_str() == ".git") {
+        return Err(format!("Downloading into .git is not allowed: {}", dest));
+    }
+
+    // The nearest directory that exists has to resolve inside the working
+    // directory, so a symlinked directory can't carry the file out of it
+    let full = cwd.join(relative);
+    let existing = full.ancestors().skip(1).find(|dir| dir.exists()).unwrap_or(cwd);
+    match (cwd.canonicalize(), existing.canonicalize()) {
+        (Ok(root), Ok(dir)) if dir.starts_with(&root) => {},
+        _ => return Err(outside()),
+    }
+    // Nor can a symlink in place of the file or its partial download
+    for file in [full.clone(), handlers::Download::part_path(&full)] {
+        if file.symlink_metadata().is_ok_and(|meta| meta.file_type().is_symlink()) {
+            return Err(format!("Download destination is a symlink: {}", dest));
+        }
+    }
+    Ok(path.to_path_buf())
+}
+
+impl DownloadTool {
+    pub const NAME: &'static str = "mcp_download";
+    pub const DEFAULT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+    pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+    pub fn new() -> Self {
+        Self {
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            timeout_secs: Self::DEFAULT_TIMEOUT_SECS,
+            network_disabled: false,
+        }
+    }
+
+    /// Refuse downloads larger than `max_bytes`
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Give up on a connection or read that stalls for `timeout_secs`
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Refuse every download, matching a shell sandbox without network access
+    pub fn with_network_disabled(mut self, network_disabled: bool) -> Self {
+        self.network_disabled = network_disabled;
+        self
+    }
+}
+
+impl Default for DownloadTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tool for DownloadTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Download a URL to a file in the working directory and return the saved path. \
+         Progress is shown while it runs; interrupted downloads are retried, and calling again \
+         with the same dest resumes a partial one. Use this instead of curl or wget in shell. \
+         Fails if dest already exists. Always needs the user's confirmation."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "http or https URL to download"
+                },
+                "dest": {
+                    "type": "string",
+                    "description": "Path to save the file to, inside the working directory"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
+                }
+            },
+            "required": ["url", "dest"]
+        })
+    }
+
+    fn requires_confirmation(&self, _params: &serde_json::Value) -> bool {
+        true
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let params: DownloadParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+
+        if self.network_disabled {
+            return ToolPipeline::error(
+                "Downloads are disabled: the shell sandbox has no network access ([tools.shell.sandbox] no_network)",
+            );
+        }
+        if let Some(remote) = RemotePath::parse(&params.dest) {
+            return ToolPipeline::error(format!("Downloading to remote paths is not supported: {}", remote));
+        }
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let dest = match check_dest(&params.dest, &cwd) {
+            Ok(dest) => dest,
+            Err(e) => return ToolPipeline::error(e),
+        };
+
+        ToolPipeline::new()
+            .then(handlers::ValidateFileNotExists {
+                path: dest.clone(),
+                message: format!("File already exists: {}. Choose another dest or remove it first.", params.dest),
+            })
+            .await_approval()
+            .then(handlers::Download {
+                url: params.url,
+                dest,
+                max_bytes: self.max_bytes,
+                timeout_secs: self.timeout_secs,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        if let Some(block) = DownloadBlock::from_params(call_id, self.name(), params.clone(), background) {
+            Box::new(block)
+        } else {
+            Box::new(ToolBlock::new(call_id, self.name(), params, background))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dest_stays_in_working_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path();
+        assert_eq!(check_dest("vendor/lib.tar.gz", cwd).unwrap(), PathBuf::from("vendor/lib.tar.gz"));
+        assert!(check_dest(&cwd.join("data.csv").to_string_lossy(), cwd).is_ok());
+        assert!(check_dest("../outside.bin", cwd).is_err());
+        assert!(check_dest("vendor/../../outside.bin", cwd).is_err());
+        assert!(check_dest("/etc/cron.d/job", cwd).is_err());
+        assert!(check_dest(&cwd.to_string_lossy(), cwd).is_err());
+        assert!(check_dest(".git/hooks/pre-commit", cwd).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dest_rejects_symlink_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let cwd = dir.path();
+        std::os::unix::fs::symlink(outside.path(), cwd.join("linked")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("file"), cwd.join("dangling")).unwrap();
+
+        assert!(check_dest("linked/payload.sh", cwd).is_err());
+        assert!(check_dest("linked/new/payload.sh", cwd).is_err());
+        assert!(check_dest("dangling", cwd).is_err());
+        assert!(check_dest("real/payload.sh", cwd).is_ok());
+    }
+
+    #[test]
+    fn test_part_path() {
+        assert_eq!(
+            handlers::Download::part_path(Path::new("vendor/lib.tar.gz")),
+            PathBuf::from("vendor/lib.tar.gz.part")
+        );
+    }
+}
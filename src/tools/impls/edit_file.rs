@@ -4,18 +4,20 @@
 //! ```text
 //! edit_file = [
 //!     ValidateFile,           // Check file exists and is readable
+//!     RequireRead,            // (tools.require_read_before_edit) Check the agent read it
 //!     ValidateNoUnsavedEdits, // Check IDE has no unsaved changes
 //!     ValidateFileWritable,   // Check file is writable
 //!     ValidateEdits,          // Check edits are valid before prompting user
 //!     IdeShowDiffPreview,     // Show hunks with context
 //!     AwaitApproval,
 //!     ApplyEdits,             // Apply the edits
+//!     RecordRead,             // (tools.require_read_before_edit) The edit counts as a read
 //!     Output,
 //!     IdeReloadBuffer,
 //! ] + finally [IdeClosePreview]  // Closes preview on success, deny, or error
 //! ```
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use ratatui::{
     style::{Color, Style},
@@ -24,10 +26,12 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{handlers, Tool, ToolPipeline};
-use crate::ide::Edit;
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::diff::render_diff;
+use crate::ide::{Edit, ToolPreview};
 use crate::define_tool_block;
 use crate::tools::pipeline::{EffectHandler, Step};
+use crate::tools::{Backups, ReadTracker, RemotePath};
 use crate::transcript::{
     render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, Status, ToolBlock,
 };
@@ -76,11 +80,132 @@ impl EffectHandler for ValidateEdits {
     }
 }
 
+// =============================================================================
+// Fuzzy matching
+// =============================================================================
+
+/// Whitespace-insensitive fallback for edits whose old_string has no exact match
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FuzzyMatch {
+    /// Also treat runs of whitespace inside a line as a single space
+    pub collapse_whitespace: bool,
+}
+
+impl FuzzyMatch {
+    fn normalize(&self, line: &str) -> String {
+        let trimmed = line.trim();
+        if self.collapse_whitespace {
+            trimmed.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            trimmed.to_string()
+        }
+    }
+
+    /// Find the one run of whole lines in `content` that equals `old` after
+    /// normalizing whitespace. Returns the run exactly as it appears in the file.
+    fn find<'a>(&self, content: &'a str, old: &str) -> Option<&'a str> {
+        let needle: Vec<String> = old.lines().map(|l| self.normalize(l)).collect();
+        let first = needle.iter().position(|l| !l.is_empty())?;
+        let last = needle.iter().rposition(|l| !l.is_empty())?;
+        let needle = &needle[first..=last];
+
+        // Byte span of each line, excluding its line ending
+        let mut spans = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            spans.push((offset, offset + text.len()));
+            offset += line.len();
+        }
+        if needle.len() > spans.len() {
+            return None;
+        }
+        let haystack: Vec<String> = spans
+            .iter()
+            .map(|&(start, end)| self.normalize(&content[start..end]))
+            .collect();
+
+        let mut found = None;
+        for (i, window) in haystack.windows(needle.len()).enumerate() {
+            if window == needle {
+                if found.is_some() {
+                    return None; // ambiguous
+                }
+                found = Some(i);
+            }
+        }
+        let i = found?;
+        Some(&content[spans[i].0..spans[i + needle.len() - 1].1])
+    }
+
+    /// Rewrite an edit that has no exact match to use the file's own text.
+    /// The replacement is re-indented by the same amount the match was off.
+    /// Returns false if there is no unique fuzzy match.
+    fn resolve(&self, content: &str, edit: &mut Edit) -> bool {
+        let Some(actual) = self.find(content, &edit.old_string) else {
+            return false;
+        };
+
+        let old_indent = first_indent(&edit.old_string);
+        let file_indent = first_indent(actual);
+        let new_string = trim_blank_lines(&edit.new_string);
+        edit.new_string = if old_indent == file_indent {
+            new_string.to_string()
+        } else {
+            new_string
+                .split('\n')
+                .map(|line| match line.strip_prefix(old_indent) {
+                    Some(rest) => format!("{}{}", file_indent, rest),
+                    None => line.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        edit.old_string = actual.to_string();
+        true
+    }
+}
+
+/// Leading whitespace of the first non-blank line
+fn first_indent(text: &str) -> &str {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Drop whitespace-only lines at the start and end
+fn trim_blank_lines(text: &str) -> &str {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if !line.trim().is_empty() {
+            break;
+        }
+        start += line.len();
+    }
+    let rest = &text[start..];
+    let mut end = rest.len();
+    while let Some(pos) = rest[..end].rfind('\n') {
+        if !rest[pos + 1..end].trim().is_empty() {
+            break;
+        }
+        end = pos;
+    }
+    if rest[..end].trim().is_empty() {
+        return "";
+    }
+    &rest[..end]
+}
+
 define_tool_block! {
     /// Edit file display block
     pub struct EditFileBlock {
         max_lines: 5,
         params_type: EditFileParams,
+        fields {
+            /// Show each edit as a word-level diff below the header (`ui.inline_diffs`)
+            #[serde(default)]
+            pub inline_diff: bool,
+        },
         render_header(self, params) {
             let path = params["path"].as_str().unwrap_or("");
             let edit_count = params
@@ -104,11 +229,45 @@ define_tool_block! {
                 Span::styled(")", Style::default().fg(Color::DarkGray)),
             ]
         }
+        // Word-level diff of every edit, separated by a blank line
+        render_details(self) {
+            if !self.inline_diff {
+                return Vec::new();
+            }
+            let Ok(params) = serde_json::from_value::<EditFileParams>(self.params.clone()) else {
+                return Vec::new();
+            };
+            let mut lines = Vec::new();
+            for (i, edit) in params.edits.iter().enumerate() {
+                if i > 0 {
+                    lines.push(Line::from(""));
+                }
+                lines.extend(render_diff(&edit.old_string, &edit.new_string));
+            }
+            lines
+        }
+    }
+}
+
+impl EditFileBlock {
+    pub fn with_inline_diff(mut self, inline_diff: bool) -> Self {
+        self.inline_diff = inline_diff;
+        self
     }
 }
 
 /// Tool for editing existing files with search/replace
-pub struct EditFileTool;
+#[derive(Default)]
+pub struct EditFileTool {
+    /// Back up files before editing them (`tools.backup_on_write`)
+    backups: Option<Backups>,
+    /// Retry failed matches ignoring whitespace (`tools.edit_file.fuzzy`)
+    fuzzy: Option<FuzzyMatch>,
+    /// Show edits as word-level diffs in the transcript (`ui.inline_diffs`)
+    inline_diffs: bool,
+    /// Refuse files the agent hasn't read (`tools.require_read_before_edit`)
+    reads: Option<ReadTracker>,
+}
 
 #[derive(Debug, Deserialize)]
 struct EditFileParams {
@@ -124,6 +283,53 @@ struct SearchReplace {
 
 impl EditFileTool {
     pub const NAME: &'static str = "mcp_edit_file";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy each file into `backups` before applying edits
+    pub fn with_backups(mut self, backups: Backups) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Fall back to whitespace-insensitive matching when old_string isn't found
+    pub fn with_fuzzy_matching(mut self, fuzzy: FuzzyMatch) -> Self {
+        self.fuzzy = Some(fuzzy);
+        self
+    }
+
+    /// Require the current version of a file to have been read (as recorded
+    /// in `tracker`) before editing it
+    pub fn with_read_tracker(mut self, tracker: ReadTracker) -> Self {
+        self.reads = Some(tracker);
+        self
+    }
+
+    /// Render each edit as a word-level diff in its transcript block
+    pub fn with_inline_diffs(mut self, inline_diffs: bool) -> Self {
+        self.inline_diffs = inline_diffs;
+        self
+    }
+
+    /// Resolve edits with no exact match through the fuzzy fallback.
+    /// Returns the (1-based) numbers of the edits that were rewritten.
+    /// Unreadable files are left for validation to report.
+    fn resolve_fuzzy(&self, path: &Path, edits: &mut [Edit]) -> Vec<usize> {
+        let Some(fuzzy) = self.fuzzy else {
+            return Vec::new();
+        };
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        edits
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, edit)| !content.contains(&edit.old_string))
+            .filter_map(|(i, edit)| fuzzy.resolve(&content, edit).then_some(i + 1))
+            .collect()
+    }
 }
 
 impl Tool for EditFileTool {
@@ -137,6 +343,10 @@ impl Tool for EditFileTool {
          Use read_file first to see the current file contents."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -172,6 +382,22 @@ impl Tool for EditFileTool {
         })
     }
 
+    fn preview(&self, params: &serde_json::Value) -> Option<ToolPreview> {
+        let params: EditFileParams = serde_json::from_value(params.clone()).ok()?;
+        let edits = params
+            .edits
+            .into_iter()
+            .map(|e| Edit {
+                old_string: e.old_string,
+                new_string: e.new_string,
+            })
+            .collect();
+        Some(ToolPreview::Edits {
+            path: params.path,
+            edits,
+        })
+    }
+
     fn compose(&self, params: serde_json::Value) -> ToolPipeline {
         let parsed: Result<EditFileParams, _> = serde_json::from_value(params.clone());
         let params = match parsed {
@@ -181,12 +407,20 @@ impl Tool for EditFileTool {
             },
         };
 
+        if let Some(remote) = RemotePath::parse(&params.path) {
+            return ToolPipeline::error(format!(
+                "Writing to remote paths is not supported: {}. Remote files are read-only; \
+                 make remote changes through shell, which always asks for approval.",
+                remote
+            ));
+        }
+
         let path = PathBuf::from(&params.path);
         let abs_path = path.canonicalize().unwrap_or_else(|_| path.clone());
         let edit_count = params.edits.len();
 
         // Convert to Edit type for handlers
-        let edits: Vec<Edit> = params
+        let mut edits: Vec<Edit> = params
             .edits
             .iter()
             .map(|e| Edit {
@@ -194,9 +428,23 @@ impl Tool for EditFileTool {
                 new_string: e.new_string.clone(),
             })
             .collect();
+        let fuzzy_matched = self.resolve_fuzzy(&path, &mut edits);
+        let fuzzy_note = match fuzzy_matched.as_slice() {
+            [] => String::new(),
+            numbers => format!(
+                " (edit {} matched after normalizing whitespace)",
+                numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+        };
 
-        ToolPipeline::new()
-            .then(handlers::ValidateFile { path: path.clone() })
+        let mut pipeline = ToolPipeline::new().then(handlers::ValidateFile { path: path.clone() });
+        if let Some(ref tracker) = self.reads {
+            pipeline = pipeline.then(handlers::RequireRead {
+                path: path.clone(),
+                tracker: tracker.clone(),
+            });
+        }
+        pipeline = pipeline
             .then(handlers::ValidateNoUnsavedEdits { path: path.clone() })
             .then(handlers::ValidateFileWritable { path: path.clone() })
             .then(ValidateEdits {
@@ -207,15 +455,29 @@ impl Tool for EditFileTool {
                 path: abs_path.clone(),
                 edits: edits.clone(),
             })
-            .await_approval()
-            .then(handlers::ApplyEdits {
+            .await_approval();
+        if let Some(ref backups) = self.backups {
+            pipeline = pipeline.then(handlers::BackupFile {
                 path: abs_path.clone(),
-                edits,
-            })
+                backups: backups.clone(),
+            });
+        }
+        pipeline = pipeline.then(handlers::ApplyEdits {
+            path: abs_path.clone(),
+            edits,
+        });
+        // The agent knows what its own edit changed
+        if let Some(ref tracker) = self.reads {
+            pipeline = pipeline.then(handlers::RecordRead {
+                path: abs_path.clone(),
+                tracker: tracker.clone(),
+            });
+        }
+        pipeline
             .then(handlers::Output {
                 content: format!(
-                    "Successfully applied {} edit(s) to {}",
-                    edit_count, params.path
+                    "Successfully applied {} edit(s) to {}{}",
+                    edit_count, params.path, fuzzy_note
                 ),
             })
             .then(handlers::IdeReloadBuffer { path: abs_path })
@@ -224,7 +486,7 @@ impl Tool for EditFileTool {
 
     fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
         if let Some(block) = EditFileBlock::from_params(call_id, self.name(), params.clone(), background) {
-            Box::new(block)
+            Box::new(block.with_inline_diff(self.inline_diffs))
         } else {
             Box::new(ToolBlock::new(call_id, self.name(), params, background))
         }
@@ -263,7 +525,7 @@ mod tests {
         fs::write(&file_path, "fn main() {\n    println!(\"hello\");\n}").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(EditFileTool));
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -290,6 +552,41 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_edit_file_keeps_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("build.sh");
+        fs::write(&file_path, "#!/bin/sh\necho old\n").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o750)).unwrap();
+
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: EditFileTool::NAME.to_string(),
+            params: json!({
+                "path": file_path.to_str().unwrap(),
+                "edits": [{"old_string": "echo old", "new_string": "echo new"}]
+            }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        match run_to_completion(&mut executor).await {
+            ToolEvent::Completed { .. } => {
+                assert!(fs::read_to_string(&file_path).unwrap().contains("echo new"));
+                let mode = fs::metadata(&file_path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o7777, 0o750);
+            },
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_edit_file_multiple() {
         let dir = tempdir().unwrap();
@@ -297,7 +594,7 @@ mod tests {
         fs::write(&file_path, "fn foo() {}\n\nfn bar() {}\n\nfn baz() {}").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(EditFileTool));
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -328,7 +625,7 @@ mod tests {
     #[tokio::test]
     async fn test_edit_file_not_found() {
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(EditFileTool));
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -358,7 +655,7 @@ mod tests {
         fs::write(&file_path, "foo foo foo").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(EditFileTool));
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -388,7 +685,7 @@ mod tests {
         fs::write(&file_path, "hello world").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(EditFileTool));
+        registry.register(std::sync::Arc::new(EditFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -410,4 +707,61 @@ mod tests {
             other => panic!("Expected Error event, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_fuzzy_find() {
+        let content = "fn main() {\n    if ready {\n        go();\n    }\n}\n";
+        let fuzzy = FuzzyMatch::default();
+
+        // Wrong indentation and trailing whitespace still match whole lines
+        assert_eq!(
+            fuzzy.find(content, "if ready {  \n    go();\n}"),
+            Some("    if ready {\n        go();\n    }")
+        );
+        // Internal spacing only matches when collapsing
+        assert_eq!(fuzzy.find(content, "if  ready {"), None);
+        let collapse = FuzzyMatch { collapse_whitespace: true };
+        assert_eq!(collapse.find(content, "if  ready {"), Some("    if ready {"));
+        // Ambiguous matches are rejected
+        assert_eq!(fuzzy.find("a\nb\na\n", "a"), None);
+    }
+
+    #[tokio::test]
+    async fn test_edit_file_fuzzy_fallback() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.rs");
+        fs::write(&file_path, "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n").unwrap();
+
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(
+            EditFileTool::new().with_fuzzy_matching(FuzzyMatch::default()),
+        ));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: EditFileTool::NAME.to_string(),
+            params: json!({
+                "path": file_path.to_str().unwrap(),
+                "edits": [{
+                    "old_string": "  let x = 1;\n  println!(\"{}\", x);",
+                    "new_string": "  let x = 2;\n  println!(\"{}\", x);"
+                }]
+            }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        match run_to_completion(&mut executor).await {
+            ToolEvent::Completed { content, .. } => {
+                assert!(content.contains("normalizing whitespace"));
+                assert_eq!(
+                    fs::read_to_string(&file_path).unwrap(),
+                    "fn main() {\n    let x = 2;\n    println!(\"{}\", x);\n}\n"
+                );
+            },
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+    }
 }
@@ -10,8 +10,9 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_tool_block;
+use crate::tools::browser::BrowserContext;
 use crate::transcript::{
     render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, Status, ToolBlock,
 };
@@ -35,7 +36,9 @@ define_tool_block! {
 }
 
 /// Tool for fetching web content with reader view
-pub struct FetchHtmlTool;
+pub struct FetchHtmlTool {
+    browser: BrowserContext,
+}
 
 #[derive(Debug, Deserialize)]
 struct FetchHtmlParams {
@@ -45,6 +48,21 @@ struct FetchHtmlParams {
 
 impl FetchHtmlTool {
     pub const NAME: &'static str = "mcp_fetch_html";
+
+    /// Tool using the default browser settings (auto-detected Chrome, no profile)
+    pub fn new() -> Self {
+        Self::with_browser(BrowserContext::default())
+    }
+
+    pub fn with_browser(browser: BrowserContext) -> Self {
+        Self { browser }
+    }
+}
+
+impl Default for FetchHtmlTool {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Tool for FetchHtmlTool {
@@ -58,6 +76,10 @@ impl Tool for FetchHtmlTool {
         Save context by using this tool whenever you encounter URLs you expect to be html pages."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Network
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -90,14 +112,19 @@ impl Tool for FetchHtmlTool {
             .then(handlers::FetchHtml {
                 url: parsed.url,
                 max_length: parsed.max_length,
+                browser: self.browser.clone(),
             })
     }
 
+    fn output_is_markdown(&self) -> bool {
+        true
+    }
+
     fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
         if let Some(block) = FetchHtmlBlock::from_params(call_id, self.name(), params.clone(), background) {
-            Box::new(block)
+            Box::new(block.with_markdown(self.output_is_markdown()))
         } else {
-            Box::new(ToolBlock::new(call_id, self.name(), params, background))
+            Box::new(ToolBlock::new(call_id, self.name(), params, background).with_markdown(self.output_is_markdown()))
         }
     }
 }
@@ -110,7 +137,7 @@ mod tests {
     #[tokio::test]
     async fn test_fetch_html_invalid_url() {
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(FetchHtmlTool));
+        registry.register(std::sync::Arc::new(FetchHtmlTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -132,7 +159,7 @@ mod tests {
     #[tokio::test]
     async fn test_fetch_html_unsupported_scheme() {
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(FetchHtmlTool));
+        registry.register(std::sync::Arc::new(FetchHtmlTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -1,6 +1,7 @@
 //! URL fetching tool
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::artifacts::artifact_path;
 use crate::define_tool_block;
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
 use ratatui::{
@@ -49,7 +50,12 @@ impl Tool for FetchUrlTool {
     fn description(&self) -> &'static str {
         "Fetch content from a URL. Returns text content (HTML, JSON, plain text). \
          Useful for documentation, API responses, web pages. \
-         Content is truncated if it exceeds max_length."
+         Content is truncated if it exceeds max_length. Binary content (images, \
+         PDFs) is saved to a file instead and its path returned."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Network
     }
 
     fn schema(&self) -> serde_json::Value {
@@ -79,11 +85,18 @@ impl Tool for FetchUrlTool {
             Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
         };
 
+        // Binary responses are saved as a session artifact instead
+        let download = artifact_path(&parsed.url);
         ToolPipeline::new()
             .await_approval()
             .then(handlers::FetchUrl {
                 url: parsed.url,
                 max_length: parsed.max_length,
+                save_binary_to: Some(download.clone()),
+            })
+            .then(handlers::RegisterArtifact {
+                path: download,
+                kind: "download".to_string(),
             })
     }
 
@@ -0,0 +1,206 @@
+//! Git tools
+//!
+//! Structured alternatives to running git through `shell` for the most common
+//! operations: `git_status` summarizes the working tree (and recent commits),
+//! `git_stage` stages or unstages paths. Nothing here can discard changes;
+//! destructive operations still go through `shell` and its approval.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_simple_tool_block;
+use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
+
+// =============================================================================
+// GitStatus block
+// =============================================================================
+
+define_simple_tool_block! {
+    /// Block for git_status - shows as `git_status()`
+    pub struct GitStatusBlock {
+        max_lines: 10,
+        render_header(self, params) {
+            let log = params["log"]
+                .as_u64()
+                .map(|n| format!("log {}", n))
+                .unwrap_or_default();
+
+            vec![
+                Span::styled("git_status", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(log, Style::default().fg(Color::DarkGray)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+// =============================================================================
+// GitStage block
+// =============================================================================
+
+define_simple_tool_block! {
+    /// Block for git_stage - shows as `git_stage(paths)` or `git_unstage(paths)`
+    pub struct GitStageBlock {
+        max_lines: 10,
+        render_header(self, params) {
+            let name = if params["unstage"].as_bool().unwrap_or(false) {
+                "git_unstage"
+            } else {
+                "git_stage"
+            };
+            let paths: Vec<&str> = params["paths"]
+                .as_array()
+                .map(|paths| paths.iter().filter_map(|p| p.as_str()).collect())
+                .unwrap_or_default();
+
+            vec![
+                Span::styled(name, Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(paths.join(" "), Style::default().fg(Color::Cyan)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+// =============================================================================
+// git_status tool
+// =============================================================================
+
+/// Tool for summarizing the git working tree
+pub struct GitStatusTool;
+
+#[derive(Debug, Deserialize)]
+struct GitStatusParams {
+    /// Also list this many recent commits
+    #[serde(default)]
+    log: Option<usize>,
+}
+
+impl GitStatusTool {
+    pub const NAME: &'static str = "mcp_git_status";
+}
+
+impl Tool for GitStatusTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Show the git branch, its upstream, and the staged, unstaged, conflicted and untracked \
+         files, grouped and compact. Optionally list recent commits. Prefer this over running \
+         `git status` or `git log` through the shell."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "log": {
+                    "type": "integer",
+                    "description": "Also list this many recent commits (hash, date, author, subject)"
+                }
+            },
+            "required": []
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: GitStatusParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::GitStatus { log: parsed.log })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(GitStatusBlock::new(call_id, self.name(), params, background))
+    }
+}
+
+// =============================================================================
+// git_stage tool
+// =============================================================================
+
+/// Tool for staging and unstaging paths
+pub struct GitStageTool;
+
+#[derive(Debug, Deserialize)]
+struct GitStageParams {
+    paths: Vec<String>,
+    /// Remove the paths from the index instead of adding them
+    #[serde(default)]
+    unstage: bool,
+}
+
+impl GitStageTool {
+    pub const NAME: &'static str = "mcp_git_stage";
+}
+
+impl Tool for GitStageTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Stage paths for commit (`git add`), or with `unstage: true` remove them from the index \
+         (`git restore --staged`). Working tree files are never modified. Returns the updated \
+         status."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files or directories to stage (use [\".\"] for everything)"
+                },
+                "unstage": {
+                    "type": "boolean",
+                    "description": "Unstage the paths instead of staging them (default: false)"
+                }
+            },
+            "required": ["paths"]
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: GitStageParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+        if parsed.paths.is_empty() {
+            return ToolPipeline::error("paths must not be empty");
+        }
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::GitStage {
+                paths: parsed.paths,
+                unstage: parsed.unstage,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(GitStageBlock::new(call_id, self.name(), params, background))
+    }
+}
@@ -0,0 +1,223 @@
+//! HTTP request tool
+//!
+//! For calling APIs rather than reading pages: any method, custom headers and
+//! a JSON or raw body, with the response's status, headers and body returned
+//! as-is (JSON pretty-printed). Methods other than GET, HEAD and OPTIONS can
+//! change remote state, so they always need confirmation: neither the `network`
+//! approval default nor an allow pattern approves them, only the user.
+
+use std::collections::BTreeMap;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_tool_block;
+use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Methods that only read, and so follow the `network` approval default
+const SAFE_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// Methods that can change remote state
+const MUTATING_METHODS: &[&str] = &["POST", "PUT", "PATCH", "DELETE"];
+
+fn is_safe(method: &str) -> bool {
+    SAFE_METHODS.contains(&method.to_uppercase().as_str())
+}
+
+define_tool_block! {
+    /// HTTP request display block
+    pub struct HttpRequestBlock {
+        max_lines: 5,
+        params_type: HttpRequestParams,
+        render_header(self, params) {
+            let method = params["method"].as_str().unwrap_or("GET").to_uppercase();
+            let url = params["url"].as_str().unwrap_or("");
+            let method_style = if is_safe(&method) {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            };
+
+            vec![
+                Span::styled("http_request", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(method, method_style),
+                Span::raw(" "),
+                Span::styled(url.to_string(), Style::default().fg(Color::Blue)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+/// Tool for calling HTTP APIs
+pub struct HttpRequestTool {
+    timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpRequestParams {
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    json: Option<serde_json::Value>,
+    body: Option<String>,
+    max_length: Option<usize>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+impl HttpRequestTool {
+    pub const NAME: &'static str = "mcp_http_request";
+    pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+    pub const DEFAULT_MAX_LENGTH: usize = 50000;
+
+    pub fn new() -> Self {
+        Self {
+            timeout_secs: Self::DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Send an HTTP request to an API and return the status, response headers and body \
+         (JSON is pretty-printed). Supports any method, custom headers, and a JSON or raw body. \
+         Use this instead of curl in shell for REST APIs; use fetch_url or fetch_html to read web pages. \
+         POST, PUT, PATCH and DELETE always need the user's confirmation."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Network
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "URL to send the request to"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "HEAD", "OPTIONS", "POST", "PUT", "PATCH", "DELETE"],
+                    "description": "HTTP method (default: GET)"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers, e.g. {\"Authorization\": \"Bearer ...\"}"
+                },
+                "json": {
+                    "description": "JSON request body; sets Content-Type: application/json"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Raw request body, for non-JSON payloads. Set a Content-Type header to match."
+                },
+                "max_length": {
+                    "type": "integer",
+                    "description": "Maximum response body length in characters (default: 50000)"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn requires_confirmation(&self, params: &serde_json::Value) -> bool {
+        !is_safe(params["method"].as_str().unwrap_or("GET"))
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: HttpRequestParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+        let method = parsed.method.to_uppercase();
+        if !SAFE_METHODS.contains(&method.as_str()) && !MUTATING_METHODS.contains(&method.as_str()) {
+            return ToolPipeline::error(format!("Unsupported HTTP method: {}", parsed.method));
+        }
+        if parsed.json.is_some() && parsed.body.is_some() {
+            return ToolPipeline::error("Pass either json or body, not both");
+        }
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::HttpRequest {
+                method,
+                url: parsed.url,
+                headers: parsed.headers.into_iter().collect(),
+                json: parsed.json,
+                body: parsed.body,
+                max_length: parsed.max_length.unwrap_or(Self::DEFAULT_MAX_LENGTH),
+                timeout_secs: self.timeout_secs,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        if let Some(block) = HttpRequestBlock::from_params(call_id, self.name(), params.clone(), background) {
+            Box::new(block)
+        } else {
+            Box::new(ToolBlock::new(call_id, self.name(), params, background))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ToolCall, ToolDecision, ToolEvent, ToolExecutor, ToolRegistry};
+
+    #[test]
+    fn test_mutating_methods_require_confirmation() {
+        let tool = HttpRequestTool::new();
+        assert!(!tool.requires_confirmation(&json!({ "url": "https://api.example.com" })));
+        assert!(!tool.requires_confirmation(&json!({ "url": "https://api.example.com", "method": "head" })));
+        assert!(tool.requires_confirmation(&json!({ "url": "https://api.example.com", "method": "DELETE" })));
+        assert!(tool.requires_confirmation(&json!({ "url": "https://api.example.com", "method": "post" })));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_rejected() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(HttpRequestTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        let calls = [
+            (json!({ "url": "https://api.example.com", "method": "TRACE" }), "Unsupported HTTP method"),
+            (json!({ "url": "https://api.example.com", "method": "POST", "json": {}, "body": "x" }), "not both"),
+            (json!({ "url": "file:///etc/passwd" }), "Unsupported URL scheme"),
+        ];
+        for (params, expected) in calls {
+            executor.enqueue(vec![ToolCall {
+                agent_id: 0,
+                call_id: "test".to_string(),
+                name: HttpRequestTool::NAME.to_string(),
+                params,
+                decision: ToolDecision::Approve,
+                background: false,
+            }]);
+            match executor.next().await {
+                Some(ToolEvent::Error { content, .. }) => assert!(content.contains(expected), "{}", content),
+                other => panic!("Expected Error event, got {:?}", other),
+            }
+        }
+    }
+}
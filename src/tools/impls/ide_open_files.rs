@@ -0,0 +1,68 @@
+//! IDE open files tool
+//!
+//! Lists the files the user currently has open in their editor, so the agent
+//! can tell what they're working on without being told.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_simple_tool_block;
+use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
+
+define_simple_tool_block! {
+    /// Block for ide_open_files - shows as `ide_open_files()`
+    pub struct IdeOpenFilesBlock {
+        max_lines: 10,
+        render_header(self, params) {
+            vec![
+                Span::styled("ide_open_files", Style::default().fg(Color::Magenta)),
+                Span::styled("()", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+/// Tool for listing files open in the IDE
+pub struct IdeOpenFilesTool;
+
+impl IdeOpenFilesTool {
+    pub const NAME: &'static str = "mcp_ide_open_files";
+}
+
+impl Tool for IdeOpenFilesTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "List the files the user currently has open in their IDE/editor. \
+         Useful for figuring out which files the user is working on."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        })
+    }
+
+    fn compose(&self, _params: serde_json::Value) -> ToolPipeline {
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::IdeOpenFiles)
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(IdeOpenFilesBlock::new(call_id, self.name(), params, background))
+    }
+}
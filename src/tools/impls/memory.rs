@@ -0,0 +1,249 @@
+//! Project memory tools
+//!
+//! Let the agent keep notes about the project across sessions. Notes live in
+//! `.codey/memory.json`, grouped by topic and keyed within each topic, and are
+//! added to the system prompt at session start when `tools.memory.inject` is set.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_simple_tool_block;
+use crate::tools::memory::{Memory, DEFAULT_MEMORY_MAX_BYTES};
+use crate::transcript::{render_approval_prompt, render_prefix, render_result, Block, BlockType, Status};
+
+// =============================================================================
+// MemoryRead block
+// =============================================================================
+
+define_simple_tool_block! {
+    /// Block for memory_read - shows as `memory_read(topic)`
+    pub struct MemoryReadBlock {
+        max_lines: 10,
+        render_header(self, params) {
+            let topic = params["topic"].as_str().unwrap_or("");
+
+            vec![
+                Span::styled("memory_read", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(topic.to_string(), Style::default().fg(Color::Yellow)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+// =============================================================================
+// MemoryWrite block
+// =============================================================================
+
+define_simple_tool_block! {
+    /// Block for memory_write - shows as `memory_write(topic/key note)`
+    pub struct MemoryWriteBlock {
+        max_lines: 3,
+        render_header(self, params) {
+            let topic = params["topic"].as_str().unwrap_or("?");
+            let key = params["key"].as_str().unwrap_or("?");
+            let note = match params["note"].as_str() {
+                Some(note) if !note.trim().is_empty() => {
+                    let truncated: String = note.chars().take(40).collect();
+                    if truncated.len() < note.len() {
+                        format!(" {}...", truncated)
+                    } else {
+                        format!(" {}", truncated)
+                    }
+                },
+                _ => " (remove)".to_string(),
+            };
+
+            vec![
+                Span::styled("memory_write", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{}/{}", topic, key), Style::default().fg(Color::Yellow)),
+                Span::styled(note, Style::default().fg(Color::Green)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+// =============================================================================
+// memory_read tool
+// =============================================================================
+
+/// Tool for reading the project's memory notes
+pub struct MemoryReadTool {
+    memory: Memory,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryReadParams {
+    /// Only return notes under this topic
+    #[serde(default)]
+    topic: Option<String>,
+}
+
+impl MemoryReadTool {
+    pub const NAME: &'static str = "mcp_memory_read";
+
+    pub fn new() -> Self {
+        Self {
+            memory: Memory::project(DEFAULT_MEMORY_MAX_BYTES),
+        }
+    }
+}
+
+impl Default for MemoryReadTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tool for MemoryReadTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Read the notes saved in project memory with memory_write, optionally only those under \
+         one topic. Memory persists across sessions."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "topic": {
+                    "type": "string",
+                    "description": "Only return notes under this topic (omit for all notes)"
+                }
+            },
+            "required": []
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: MemoryReadParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::ReadMemory {
+                memory: self.memory.clone(),
+                topic: parsed.topic,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(MemoryReadBlock::new(call_id, self.name(), params, background))
+    }
+}
+
+// =============================================================================
+// memory_write tool
+// =============================================================================
+
+/// Tool for saving, updating and removing project memory notes
+pub struct MemoryWriteTool {
+    memory: Memory,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryWriteParams {
+    topic: String,
+    key: String,
+    /// New note text; omitted or empty removes the note
+    #[serde(default)]
+    note: Option<String>,
+}
+
+impl MemoryWriteTool {
+    pub const NAME: &'static str = "mcp_memory_write";
+
+    pub fn new() -> Self {
+        Self {
+            memory: Memory::project(DEFAULT_MEMORY_MAX_BYTES),
+        }
+    }
+
+    /// Bound the total size of all notes
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.memory = Memory::project(max_bytes);
+        self
+    }
+}
+
+impl Default for MemoryWriteTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tool for MemoryWriteTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Save a note to project memory so future sessions know it: conventions, gotchas, \
+         commands that work, decisions the user made. Notes are grouped by topic (e.g. `build`, \
+         `style`, `architecture`) and identified by a key within the topic; writing an existing \
+         key replaces its note, and omitting `note` removes it. Keep notes short and durable - \
+         memory is size-limited, and a full memory refuses new notes until old ones are pruned."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "topic": {
+                    "type": "string",
+                    "description": "Topic the note belongs to, e.g. `build`, `style`, `testing`"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Short identifier for the note within its topic"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "The note (1-2 sentences). Omit to remove the note."
+                }
+            },
+            "required": ["topic", "key"]
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: MemoryWriteParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+
+        ToolPipeline::new()
+            .await_approval()
+            .then(handlers::WriteMemory {
+                memory: self.memory.clone(),
+                topic: parsed.topic,
+                key: parsed.key,
+                note: parsed.note,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        Box::new(MemoryWriteBlock::new(call_id, self.name(), params, background))
+    }
+}
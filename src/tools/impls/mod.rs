@@ -1,27 +1,45 @@
 mod agent_management;
+mod annotate;
 mod background_tasks;
+mod custom_command;
+mod download;
 mod edit_file;
 mod fetch_html;
 mod fetch_url;
+mod git;
+mod http_request;
+mod ide_open_files;
+mod memory;
 mod open_file;
 mod read_file;
+mod read_symbol;
 mod record_correction;
+mod replace_in_files;
 mod shell;
 mod spawn_agent;
 mod web_search;
 mod write_file;
 
 pub use super::handlers;
-pub use super::pipeline::{Tool, ToolPipeline};
+pub use super::pipeline::{Tool, ToolCategory, ToolPipeline};
 
 pub use agent_management::{GetAgentTool, ListAgentsTool};
+pub use annotate::AnnotateTool;
 pub use background_tasks::{GetBackgroundTaskTool, ListBackgroundTasksTool};
-pub use edit_file::EditFileTool;
+pub use custom_command::CustomCommandTool;
+pub use download::DownloadTool;
+pub use edit_file::{EditFileTool, FuzzyMatch};
 pub use fetch_html::FetchHtmlTool;
 pub use fetch_url::FetchUrlTool;
+pub use git::{GitStageTool, GitStatusTool};
+pub use http_request::HttpRequestTool;
+pub use ide_open_files::IdeOpenFilesTool;
+pub use memory::{MemoryReadTool, MemoryWriteTool};
 pub use open_file::OpenFileTool;
 pub use read_file::ReadFileTool;
+pub use read_symbol::ReadSymbolTool;
 pub use record_correction::RecordCorrectionTool;
+pub use replace_in_files::ReplaceInFilesTool;
 pub use shell::ShellTool;
 pub use spawn_agent::{init_agent_context, update_agent_oauth, SpawnAgentTool};
 pub use web_search::WebSearchTool;
@@ -1,6 +1,6 @@
 //! Open file tool - opens a file in the IDE at a specific line
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, Block, BlockType, Status};
 use ratatui::{
     style::{Color, Style},
@@ -33,6 +33,10 @@ impl Tool for OpenFileTool {
          Use this to show the user where something is located in their codebase."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -1,6 +1,6 @@
 //! Read file tool
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_tool_block;
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
 use ratatui::{
@@ -10,6 +10,9 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::tools::{FileCache, ReadTracker, RemotePath, Ssh};
 
 define_tool_block! {
     /// Read file display block
@@ -20,13 +23,16 @@ define_tool_block! {
             let path = params["path"].as_str().unwrap_or("");
             let start_line = params.get("start_line").and_then(|v| v.as_i64());
             let end_line = params.get("end_line").and_then(|v| v.as_i64());
+            let tail = params.get("tail").and_then(|v| v.as_u64());
+            let blame = params.get("include_blame").and_then(|v| v.as_bool()).unwrap_or(false);
 
-            // Format: read_file(path:start-end) or read_file(path)
-            let range_str = match (start_line, end_line) {
-                (Some(s), Some(e)) => format!(":{}:{}", s, e),
-                (Some(s), None) => format!(":{}:", s),
-                (None, Some(e)) => format!(":{}", e),
-                (None, None) => String::new(),
+            // Format: read_file(path:start-end), read_file(path tail N) or read_file(path)
+            let range_str = match (start_line, end_line, tail) {
+                (_, _, Some(n)) => format!(" tail {}", n),
+                (Some(s), Some(e), None) => format!(":{}:{}", s, e),
+                (Some(s), None, None) => format!(":{}:", s),
+                (None, Some(e), None) => format!(":{}", e),
+                (None, None, None) => String::new(),
             };
 
             vec![
@@ -34,24 +40,67 @@ define_tool_block! {
                 Span::styled("(", Style::default().fg(Color::DarkGray)),
                 Span::styled(path.to_string(), Style::default().fg(Color::Cyan)),
                 Span::styled(range_str, Style::default().fg(Color::DarkGray)),
+                Span::styled(if blame { " +blame" } else { "" }, Style::default().fg(Color::DarkGray)),
                 Span::styled(")", Style::default().fg(Color::DarkGray)),
             ]
         }
     }
 }
 
-/// Tool for reading file contents
-pub struct ReadFileTool;
+/// Tool for reading file contents.
+/// Unchanged files are served from a cache shared by every call to this instance.
+pub struct ReadFileTool {
+    cache: Arc<Mutex<FileCache>>,
+    /// Read `ssh://` and `host:/path` paths over SSH (`tools.ssh`)
+    ssh: Option<Ssh>,
+    /// Files read, for `tools.require_read_before_edit`
+    reads: Option<ReadTracker>,
+}
 
 #[derive(Debug, Deserialize)]
 struct ReadFileParams {
     path: String,
     start_line: Option<i32>,
     end_line: Option<i32>,
+    tail: Option<usize>,
+    #[serde(default)]
+    include_blame: bool,
 }
 
 impl ReadFileTool {
     pub const NAME: &'static str = "mcp_read_file";
+
+    pub fn new() -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(FileCache::new())),
+            ssh: None,
+            reads: None,
+        }
+    }
+
+    /// Share `cache` with whoever else holds it, e.g. to clear it
+    pub fn with_cache(mut self, cache: Arc<Mutex<FileCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Allow reading files on other hosts over SSH
+    pub fn with_ssh(mut self, ssh: Ssh) -> Self {
+        self.ssh = Some(ssh);
+        self
+    }
+
+    /// Record each local file read in `tracker`
+    pub fn with_read_tracker(mut self, tracker: ReadTracker) -> Self {
+        self.reads = Some(tracker);
+        self
+    }
+}
+
+impl Default for ReadFileTool {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Tool for ReadFileTool {
@@ -60,18 +109,23 @@ impl Tool for ReadFileTool {
     }
 
     fn description(&self) -> &'static str {
-        "Read the contents of a file. Optionally specify a line range. \
+        "Read the contents of a file. Optionally specify a line range, or `tail` to get \
+         the last N lines of a large or growing file (e.g. a log) without reading all of it. \
          Returns the file contents with line numbers prefixed. \
          File content is only shown to the agent and is not displayed to the user."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file to read"
+                    "description": "Path to the file to read. Files on other hosts can be given as ssh://host/path or host:/path when remote access is configured."
                 },
                 "start_line": {
                     "type": "integer",
@@ -81,6 +135,14 @@ impl Tool for ReadFileTool {
                     "type": "integer",
                     "description": "Ending line number (inclusive, optional). Use -1 for end of file."
                 },
+                "tail": {
+                    "type": "integer",
+                    "description": "Return only the last N lines, numbered from the end (-1 is the last line). Cannot be combined with start_line/end_line."
+                },
+                "include_blame": {
+                    "type": "boolean",
+                    "description": "Also list the commits that last changed the lines read (git blame), e.g. to see what recently changed near a bug. Slow on large files, so give a line range. Not available with tail or remote paths."
+                },
                 "background": {
                     "type": "boolean",
                     "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
@@ -96,16 +158,60 @@ impl Tool for ReadFileTool {
             Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
         };
 
+        if parsed.tail.is_some() && (parsed.start_line.is_some() || parsed.end_line.is_some()) {
+            return ToolPipeline::error("tail cannot be combined with start_line/end_line");
+        }
+
+        if parsed.include_blame && parsed.tail.is_some() {
+            return ToolPipeline::error("include_blame cannot be combined with tail");
+        }
+
+        if let Some(remote) = RemotePath::parse(&parsed.path) {
+            if parsed.include_blame {
+                return ToolPipeline::error("include_blame is only available for local files");
+            }
+            let Some(ssh) = self.ssh.clone() else {
+                return ToolPipeline::error(format!(
+                    "Remote paths are disabled: {}. Enable them with [tools.ssh] in the config.",
+                    remote
+                ));
+            };
+            return ToolPipeline::new()
+                .await_approval()
+                .then(handlers::ReadRemoteFile {
+                    path: remote,
+                    ssh,
+                    start_line: parsed.start_line,
+                    end_line: parsed.end_line,
+                    tail: parsed.tail,
+                });
+        }
+
         let path = PathBuf::from(&parsed.path);
 
-        ToolPipeline::new()
+        let pipeline = ToolPipeline::new()
             .then(handlers::ValidateFile { path: path.clone() })
-            .await_approval()
-            .then(handlers::ReadFile {
-                path,
+            .await_approval();
+        let mut pipeline = match parsed.tail {
+            Some(lines) => pipeline.then(handlers::TailFile { path: path.clone(), lines }),
+            None => pipeline.then(handlers::ReadFile {
+                path: path.clone(),
                 start_line: parsed.start_line,
                 end_line: parsed.end_line,
-            })
+                cache: self.cache.clone(),
+            }),
+        };
+        if parsed.include_blame {
+            pipeline = pipeline.then(handlers::AppendBlame {
+                path: path.clone(),
+                start_line: parsed.start_line,
+                end_line: parsed.end_line,
+            });
+        }
+        match &self.reads {
+            Some(tracker) => pipeline.then(handlers::RecordRead { path, tracker: tracker.clone() }),
+            None => pipeline,
+        }
     }
 
     fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
@@ -131,7 +237,7 @@ mod tests {
         fs::write(&file_path, "line 1\nline 2\nline 3\n").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(ReadFileTool));
+        registry.register(std::sync::Arc::new(ReadFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -153,10 +259,37 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_read_file_tail() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("app.log");
+        let log: String = (1..=5000).map(|i| format!("entry {}\n", i)).collect();
+        fs::write(&file_path, log).unwrap();
+
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ReadFileTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ReadFileTool::NAME.to_string(),
+            params: json!({ "path": file_path.to_str().unwrap(), "tail": 3 }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        if let Some(crate::tools::ToolEvent::Completed { content, .. }) = executor.next().await {
+            assert_eq!(content, "  -3│entry 4998\n  -2│entry 4999\n  -1│entry 5000\n");
+        } else {
+            panic!("Expected Completed event");
+        }
+    }
+
     #[tokio::test]
     async fn test_read_nonexistent_file() {
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(ReadFileTool));
+        registry.register(std::sync::Arc::new(ReadFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -182,7 +315,7 @@ mod tests {
         fs::write(&file_path, "line 1\nline 2\nline 3\nline 4\nline 5\n").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(ReadFileTool));
+        registry.register(std::sync::Arc::new(ReadFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -222,7 +355,7 @@ mod tests {
         fs::write(&file_path, "line 1\nline 2\nline 3\nline 4\nline 5\n").unwrap();
 
         let mut registry = ToolRegistry::empty();
-        registry.register(std::sync::Arc::new(ReadFileTool));
+        registry.register(std::sync::Arc::new(ReadFileTool::new()));
         let mut executor = ToolExecutor::new(registry);
 
         executor.enqueue(vec![ToolCall {
@@ -0,0 +1,155 @@
+//! Read symbol tool - returns one function/type/class from a file
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::define_tool_block;
+use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+
+define_tool_block! {
+    /// Read symbol display block
+    pub struct ReadSymbolBlock {
+        max_lines: 10,
+        params_type: ReadSymbolParams,
+        render_header(self, params) {
+            let path = params["path"].as_str().unwrap_or("");
+            let symbol = params["symbol"].as_str().unwrap_or("");
+
+            vec![
+                Span::styled("read_symbol", Style::default().fg(Color::Magenta)),
+                Span::styled("(", Style::default().fg(Color::DarkGray)),
+                Span::styled(path.to_string(), Style::default().fg(Color::Cyan)),
+                Span::styled(format!(" {}", symbol), Style::default().fg(Color::Yellow)),
+                Span::styled(")", Style::default().fg(Color::DarkGray)),
+            ]
+        }
+    }
+}
+
+/// Tool for reading a single function, type or class by name
+pub struct ReadSymbolTool;
+
+#[derive(Debug, Deserialize)]
+struct ReadSymbolParams {
+    path: String,
+    symbol: String,
+}
+
+impl ReadSymbolTool {
+    pub const NAME: &'static str = "mcp_read_symbol";
+}
+
+impl Tool for ReadSymbolTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Read a single function, method, struct, enum, trait, impl block or class from a file \
+         by name, with line numbers. Cheaper than reading the whole file when you know what \
+         you're looking for. Qualify methods with their type, e.g. `Config::load` or \
+         `Parser.parse`. For Rust, a type name returns its definition and all its impl blocks. \
+         Supports Rust, Python, Go and JavaScript/TypeScript."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Read
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to the source file"
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "Name of the symbol, optionally qualified with its container (`Type::method`, `Class.method`)"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
+                }
+            },
+            "required": ["path", "symbol"]
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let parsed: ReadSymbolParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+
+        let path = PathBuf::from(&parsed.path);
+
+        ToolPipeline::new()
+            .then(handlers::ValidateFile { path: path.clone() })
+            .await_approval()
+            .then(handlers::ReadSymbol {
+                path,
+                symbol: parsed.symbol,
+            })
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        if let Some(block) = ReadSymbolBlock::from_params(call_id, self.name(), params.clone(), background) {
+            Box::new(block)
+        } else {
+            Box::new(ToolBlock::new(call_id, self.name(), params, background))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ToolCall, ToolDecision, ToolEvent, ToolExecutor, ToolRegistry};
+    use std::fs;
+    use tempfile::tempdir;
+
+    async fn read_symbol(path: &str, symbol: &str) -> Option<ToolEvent> {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ReadSymbolTool));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ReadSymbolTool::NAME.to_string(),
+            params: json!({ "path": path, "symbol": symbol }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+        executor.next().await
+    }
+
+    #[tokio::test]
+    async fn test_read_symbol() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("lib.rs");
+        fs::write(&file_path, "fn a() {}\n\nfn b() {\n    a();\n}\n").unwrap();
+        let path = file_path.to_str().unwrap();
+
+        match read_symbol(path, "b").await {
+            Some(ToolEvent::Completed { content, .. }) => {
+                assert!(content.starts_with(&format!("{}:3-5\n", path)));
+                assert!(content.contains("   4│    a();"));
+                assert!(!content.contains("fn a()"));
+            },
+            other => panic!("Expected Completed, got {:?}", other),
+        }
+
+        match read_symbol(path, "c").await {
+            Some(ToolEvent::Error { content, .. }) => assert!(content.contains("Symbol not found")),
+            other => panic!("Expected Error, got {:?}", other),
+        }
+    }
+}
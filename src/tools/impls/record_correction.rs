@@ -5,7 +5,7 @@
 //! `.codey/corrections.md` and loaded into the system prompt to help
 //! the agent avoid repeating the same mistakes.
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_tool_block;
 use crate::transcript::{
     render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType,
@@ -69,6 +69,10 @@ impl Tool for RecordCorrectionTool {
          This helps avoid repeating the same mistakes in future sessions."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -0,0 +1,680 @@
+//! Project-wide search and replace
+//!
+//! The replace_in_files tool as a chain of effects:
+//! ```text
+//! replace_in_files = [
+//!     PlanReplacements,         // Find the files and work out each change, then:
+//!     RequireRead,              //   (tools.require_read_before_edit) For each matching file
+//!     ValidateNoUnsavedEdits,   //   For each matching file
+//!     ValidateFileWritable,     //   For each matching file
+//!     IdeShowPreview,           //   Unified diff of every file
+//!     AwaitApproval,
+//!     BackupFile,               //   (tools.backup_on_write) For each matching file
+//!     ApplyFileChanges,         //   All files or none
+//!     RecordRead,               //   (tools.require_read_before_edit) For each matching file
+//!     Output,
+//!     IdeReloadBuffer,          //   For each matching file
+//! ] + finally [IdeClosePreview, ForgetPlan]
+//! ```
+//!
+//! Files are found with `git ls-files`, so `.gitignore` is respected. The
+//! search runs once, off the UI thread, before approval; the call's transcript
+//! block lists the files from that plan. If any file changes between then and
+//! approval, nothing is written.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use fancy_regex::Regex;
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::diff;
+use crate::ide::{FileDiff, ToolPreview};
+use crate::impl_tool_block;
+use crate::tools::git;
+use crate::tools::pipeline::{EffectHandler, Step};
+use crate::tools::{Backups, ReadTracker};
+use crate::transcript::{
+    render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, Status,
+};
+
+/// A file changed by a replace_in_files call, with its line counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStat {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// replace_in_files display block: the call, then each file it changes with
+/// the lines added and removed. Expanding shows each file's hunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceInFilesBlock {
+    pub call_id: String,
+    pub tool_name: String,
+    pub params: serde_json::Value,
+    pub status: Status,
+    pub text: String,
+    #[serde(default)]
+    pub background: bool,
+    /// Files the call changes (empty if it couldn't be worked out)
+    #[serde(default)]
+    pub files: Vec<FileStat>,
+    /// Each file before and after, for the expanded view. Not saved with the
+    /// session, since it holds whole files.
+    #[serde(skip)]
+    pub diffs: Vec<FileDiff>,
+    /// Show each file's hunks below its name
+    #[serde(skip)]
+    pub expanded: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_label: Option<String>,
+}
+
+impl ReplaceInFilesBlock {
+    pub fn new(
+        call_id: impl Into<String>,
+        tool_name: impl Into<String>,
+        params: serde_json::Value,
+        background: bool,
+        diffs: Vec<FileDiff>,
+    ) -> Self {
+        let files = diffs
+            .iter()
+            .map(|file| {
+                let (added, removed) = diff::line_counts(&file.original, &file.modified);
+                FileStat {
+                    path: file.path.clone(),
+                    added,
+                    removed,
+                }
+            })
+            .collect();
+        Self {
+            call_id: call_id.into(),
+            tool_name: tool_name.into(),
+            params,
+            status: Status::Pending,
+            text: String::new(),
+            background,
+            files,
+            diffs,
+            expanded: false,
+            agent_label: None,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Block for ReplaceInFilesBlock {
+    impl_tool_block!(BlockType::Tool);
+
+    fn render(&self, _width: u16) -> Vec<Line<'_>> {
+        let mut lines = Vec::new();
+
+        let pattern = self.params["pattern"].as_str().unwrap_or("");
+        let replacement = self.params["replacement"].as_str().unwrap_or("");
+        let scope = self.params["glob"].as_str().map(|g| format!(", {}", g)).unwrap_or_default();
+
+        // Format: replace_in_files("old" -> "new", glob)
+        lines.push(Line::from(vec![
+            self.render_status(),
+            render_agent_label(self.agent_label.as_deref()),
+            render_prefix(self.background),
+            Span::styled("replace_in_files", Style::default().fg(Color::Magenta)),
+            Span::styled("(", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{:?}", pattern), Style::default().fg(Color::Red)),
+            Span::styled(" -> ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("{:?}", replacement), Style::default().fg(Color::Green)),
+            Span::styled(scope, Style::default().fg(Color::Yellow)),
+            Span::styled(")", Style::default().fg(Color::DarkGray)),
+        ]));
+
+        for (i, file) in self.files.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {} ", file.path), Style::default().fg(Color::Yellow)),
+                Span::styled(format!("+{}", file.added), Style::default().fg(Color::Green)),
+                Span::styled(format!(" -{}", file.removed), Style::default().fg(Color::Red)),
+            ]));
+            if let Some(file) = self.diffs.get(i).filter(|_| self.expanded) {
+                lines.extend(diff::render_hunks(&file.original, &file.modified, 2));
+            }
+        }
+
+        if self.status == Status::Pending {
+            lines.push(render_approval_prompt());
+        }
+
+        if !self.text.is_empty() {
+            lines.extend(render_result(&self.text, 5));
+        }
+
+        if self.status == Status::Denied {
+            lines.push(Line::from(Span::styled(
+                "  Denied by user",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        lines
+    }
+
+    fn call_id(&self) -> Option<&str> {
+        Some(&self.call_id)
+    }
+
+    fn tool_name(&self) -> Option<&str> {
+        Some(&self.tool_name)
+    }
+
+    fn params(&self) -> Option<&serde_json::Value> {
+        Some(&self.params)
+    }
+
+    fn toggle_expanded(&mut self) -> bool {
+        self.expanded = !self.expanded;
+        true
+    }
+
+    fn set_agent_label(&mut self, label: String) {
+        self.agent_label = Some(label);
+    }
+
+    fn agent_label(&self) -> Option<&str> {
+        self.agent_label.as_deref()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplaceInFilesParams {
+    pattern: String,
+    replacement: String,
+    /// Only files matching this glob, relative to `path`
+    #[serde(default)]
+    glob: Option<String>,
+    /// Treat `pattern` as a regex and allow `$1`-style groups in `replacement`
+    #[serde(default)]
+    regex: bool,
+    /// Directory to search (defaults to the working directory)
+    #[serde(default)]
+    path: Option<String>,
+}
+
+enum Matcher {
+    Literal(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(params: &ReplaceInFilesParams) -> Result<Self, String> {
+        if params.pattern.is_empty() {
+            return Err("pattern must not be empty".to_string());
+        }
+        if params.regex {
+            Regex::new(&params.pattern)
+                .map(Matcher::Regex)
+                .map_err(|e| format!("Invalid regex: {}", e))
+        } else {
+            Ok(Matcher::Literal(params.pattern.clone()))
+        }
+    }
+
+    /// `text` with every match replaced and the number of matches, or None
+    /// if nothing matched
+    fn replace(&self, text: &str, replacement: &str) -> Result<Option<(String, usize)>, String> {
+        match self {
+            Matcher::Literal(pattern) => {
+                let count = text.matches(pattern.as_str()).count();
+                Ok((count > 0).then(|| (text.replace(pattern.as_str(), replacement), count)))
+            },
+            Matcher::Regex(regex) => {
+                let mut count = 0;
+                for found in regex.find_iter(text) {
+                    found.map_err(|e| format!("Regex failed: {}", e))?;
+                    count += 1;
+                }
+                if count == 0 {
+                    return Ok(None);
+                }
+                let replaced = regex
+                    .try_replacen(text, 0, replacement)
+                    .map_err(|e| format!("Regex failed: {}", e))?;
+                Ok(Some((replaced.into_owned(), count)))
+            },
+        }
+    }
+}
+
+/// The files each composed call changes, keyed by its params, so its
+/// transcript block can list them without searching the project again
+type Plans = Arc<Mutex<HashMap<String, Vec<FileDiff>>>>;
+
+/// Search the project and queue the rest of the call for the files it changes
+struct PlanReplacements {
+    key: String,
+    params: ReplaceInFilesParams,
+    matcher: Matcher,
+    backups: Option<Backups>,
+    reads: Option<ReadTracker>,
+    plans: Plans,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for PlanReplacements {
+    async fn call(self: Box<Self>) -> Step {
+        // `git ls-files` and reading every file block, so keep them off the runtime
+        let (this, planned) = match tokio::task::spawn_blocking(move || {
+            let planned = ReplaceInFilesTool::plan(&self.params, &self.matcher);
+            (self, planned)
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => return Step::Error(format!("Search failed: {}", e)),
+        };
+        let planned = match planned {
+            Ok(planned) => planned,
+            Err(e) => return Step::Error(e),
+        };
+        if planned.is_empty() {
+            return Step::Error(format!(
+                "No matches for {:?} in {}",
+                this.params.pattern,
+                this.params.glob.as_deref().unwrap_or("the project")
+            ));
+        }
+        Step::Then(this.pipeline(planned))
+    }
+}
+
+impl PlanReplacements {
+    /// Everything after the search, for the files it found
+    fn pipeline(self, planned: Vec<(PathBuf, FileDiff, usize)>) -> ToolPipeline {
+        let total: usize = planned.iter().map(|(_, _, count)| count).sum();
+        let changes: Vec<(PathBuf, FileDiff)> = planned.into_iter().map(|(path, file, _)| (path, file)).collect();
+        let paths: Vec<PathBuf> = changes.iter().map(|(path, _)| path.clone()).collect();
+        let files: Vec<FileDiff> = changes.iter().map(|(_, file)| file.clone()).collect();
+        self.plans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(self.key, files.clone());
+
+        let mut pipeline = ToolPipeline::new();
+        for path in &paths {
+            if let Some(ref tracker) = self.reads {
+                pipeline = pipeline.then(handlers::RequireRead {
+                    path: path.clone(),
+                    tracker: tracker.clone(),
+                });
+            }
+            pipeline = pipeline
+                .then(handlers::ValidateNoUnsavedEdits { path: path.clone() })
+                .then(handlers::ValidateFileWritable { path: path.clone() });
+        }
+        pipeline = pipeline
+            .then(handlers::IdeShowPreview {
+                preview: ToolPreview::MultiDiff(files),
+            })
+            .await_approval();
+        if let Some(ref backups) = self.backups {
+            for path in &paths {
+                pipeline = pipeline.then(handlers::BackupFile {
+                    path: path.clone(),
+                    backups: backups.clone(),
+                });
+            }
+        }
+        pipeline = pipeline.then(handlers::ApplyFileChanges { changes });
+        // The agent knows what its own replacement changed
+        if let Some(ref tracker) = self.reads {
+            for path in &paths {
+                pipeline = pipeline.then(handlers::RecordRead {
+                    path: path.clone(),
+                    tracker: tracker.clone(),
+                });
+            }
+        }
+        pipeline = pipeline.then(handlers::Output {
+            content: format!(
+                "Replaced {} occurrence(s) in {} file(s): {}",
+                total,
+                paths.len(),
+                paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+            ),
+        });
+        for path in paths {
+            pipeline = pipeline.then(handlers::IdeReloadBuffer { path });
+        }
+        pipeline
+    }
+}
+
+/// Drop a call's plan once its pipeline is done with it
+struct ForgetPlan {
+    key: String,
+    plans: Plans,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for ForgetPlan {
+    async fn call(self: Box<Self>) -> Step {
+        self.plans.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+        Step::Continue
+    }
+}
+
+/// Tool for replacing a string or regex across the project's files
+#[derive(Default)]
+pub struct ReplaceInFilesTool {
+    /// Back up files before changing them (`tools.backup_on_write`)
+    backups: Option<Backups>,
+    /// Require files to have been read before they're changed
+    /// (`tools.require_read_before_edit`)
+    reads: Option<ReadTracker>,
+    plans: Plans,
+}
+
+impl ReplaceInFilesTool {
+    pub const NAME: &'static str = "mcp_replace_in_files";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy each file into `backups` before applying the replacement
+    pub fn with_backups(mut self, backups: Backups) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Refuse to change files the agent hasn't read, and count the
+    /// replacement as a read of each file it changes
+    pub fn with_read_tracker(mut self, tracker: ReadTracker) -> Self {
+        self.reads = Some(tracker);
+        self
+    }
+
+    /// The change to every file with a match, and how many matches it had.
+    /// Files that aren't UTF-8 text (or can't be read) are skipped.
+    fn plan(params: &ReplaceInFilesParams, matcher: &Matcher) -> Result<Vec<(PathBuf, FileDiff, usize)>, String> {
+        let dir = Path::new(params.path.as_deref().unwrap_or(""));
+        let mut changes = Vec::new();
+        for path in git::list_files(dir, params.glob.as_deref())? {
+            let Ok(original) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some((modified, count)) = matcher.replace(&original, &params.replacement)? {
+                let file = FileDiff {
+                    path: path.to_string_lossy().into_owned(),
+                    original,
+                    modified,
+                };
+                changes.push((path, file, count));
+            }
+        }
+        Ok(changes)
+    }
+
+    /// The files a call changes, once its search has run and while it's in flight
+    fn planned(&self, params: &serde_json::Value) -> Option<Vec<FileDiff>> {
+        let plans = self.plans.lock().unwrap_or_else(|e| e.into_inner());
+        plans.get(&params.to_string()).cloned()
+    }
+}
+
+impl Tool for ReplaceInFilesTool {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace every occurrence of a string (or, with regex: true, a regex) across the \
+         project's files, e.g. to rename a symbol. Respects .gitignore; use glob to limit the \
+         files (e.g. \"src/**/*.rs\"). All files are changed together or not at all. \
+         Use edit_file for changes that need context to be unambiguous."
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Text to find (exact, case-sensitive), or a regex if regex is true"
+                },
+                "replacement": {
+                    "type": "string",
+                    "description": "Text to replace each match with. With regex, $1 or ${name} insert capture groups"
+                },
+                "glob": {
+                    "type": "string",
+                    "description": "Only change files matching this glob, relative to path (e.g. \"**/*.rs\")"
+                },
+                "regex": {
+                    "type": "boolean",
+                    "description": "Treat pattern as a regular expression (default: false)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory to search (default: current directory)"
+                }
+            },
+            "required": ["pattern", "replacement"]
+        })
+    }
+
+    fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let key = params.to_string();
+        let params: ReplaceInFilesParams = match serde_json::from_value(params) {
+            Ok(p) => p,
+            Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
+        };
+        let matcher = match Matcher::new(&params) {
+            Ok(matcher) => matcher,
+            Err(e) => return ToolPipeline::error(e),
+        };
+
+        ToolPipeline::new()
+            .then(PlanReplacements {
+                key: key.clone(),
+                params,
+                matcher,
+                backups: self.backups.clone(),
+                reads: self.reads.clone(),
+                plans: self.plans.clone(),
+            })
+            .finally(handlers::IdeClosePreview)
+            .finally(ForgetPlan {
+                key,
+                plans: self.plans.clone(),
+            })
+    }
+
+    /// Library users handle calls themselves, so nothing was planned and
+    /// the project is searched here
+    fn preview(&self, params: &serde_json::Value) -> Option<ToolPreview> {
+        let files = self.planned(params).unwrap_or_else(|| {
+            serde_json::from_value::<ReplaceInFilesParams>(params.clone())
+                .ok()
+                .and_then(|params| Self::plan(&params, &Matcher::new(&params).ok()?).ok())
+                .map(|planned| planned.into_iter().map(|(_, file, _)| file).collect())
+                .unwrap_or_default()
+        });
+        (!files.is_empty()).then_some(ToolPreview::MultiDiff(files))
+    }
+
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        let diffs = self.planned(&params).unwrap_or_default();
+        Box::new(ReplaceInFilesBlock::new(call_id, self.name(), params, background, diffs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::tools::{ToolCall, ToolDecision, ToolEvent, ToolExecutor, ToolRegistry};
+
+    async fn run(params: serde_json::Value) -> ToolEvent {
+        run_tool(ReplaceInFilesTool::new(), params).await
+    }
+
+    async fn run_tool(tool: ReplaceInFilesTool, params: serde_json::Value) -> ToolEvent {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(tool));
+        let mut executor = ToolExecutor::new(registry);
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ReplaceInFilesTool::NAME.to_string(),
+            params,
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+        loop {
+            match executor.next().await {
+                Some(ToolEvent::Delegate { responder, .. }) => {
+                    let _ = responder.send(Ok(None));
+                },
+                Some(event @ (ToolEvent::Completed { .. } | ToolEvent::Error { .. })) => return event,
+                Some(_) => continue,
+                None => panic!("Executor returned None before completion"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_in_files_respects_gitignore_and_glob() {
+        let dir = tempdir().unwrap();
+        let status = Command::new("git").arg("init").arg("-q").arg(dir.path()).status().unwrap();
+        assert!(status.success());
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join("src/a.rs"), "fn old_name() {}\nold_name();\n").unwrap();
+        fs::write(dir.path().join("src/b.rs"), "use crate::old_name;\n").unwrap();
+        fs::write(dir.path().join("notes.md"), "old_name\n").unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/gen.rs"), "old_name\n").unwrap();
+
+        let params = json!({
+            "pattern": "old_name",
+            "replacement": "new_name",
+            "glob": "**/*.rs",
+            "path": dir.path().to_str().unwrap(),
+        });
+        match ReplaceInFilesTool::new().preview(&params) {
+            Some(ToolPreview::MultiDiff(files)) => assert_eq!(files.len(), 2),
+            other => panic!("Expected a MultiDiff preview, got {:?}", other),
+        }
+
+        // The block lists the files the call's search found
+        let tool = ReplaceInFilesTool::new();
+        let plan = tool.compose(params.clone()).pop().unwrap();
+        assert!(matches!(plan.call().await, crate::tools::Step::Then(_)));
+        let block = tool.create_block("test", params.clone(), false);
+        let rendered: Vec<String> = block.render(80).iter().map(|line| line.to_string()).collect();
+        assert!(rendered.iter().any(|line| line.contains("a.rs") && line.contains("+2 -2")), "{:?}", rendered);
+        assert!(tool.create_block("other", json!({ "pattern": "x" }), false).render(80).len() < rendered.len());
+
+        let event = run(params).await;
+        match event {
+            ToolEvent::Completed { content, .. } => {
+                assert!(content.starts_with("Replaced 3 occurrence(s) in 2 file(s)"), "{}", content);
+            },
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+        let read = |p: &str| fs::read_to_string(dir.path().join(p)).unwrap();
+        assert_eq!(read("src/a.rs"), "fn new_name() {}\nnew_name();\n");
+        assert_eq!(read("src/b.rs"), "use crate::new_name;\n");
+        assert_eq!(read("notes.md"), "old_name\n");
+        assert_eq!(read("target/gen.rs"), "old_name\n");
+
+        // Regex with a capture group
+        let event = run(json!({
+            "pattern": r"fn (\w+)\(\)",
+            "replacement": "pub fn $1()",
+            "regex": true,
+            "path": dir.path().to_str().unwrap(),
+        }))
+        .await;
+        assert!(matches!(event, ToolEvent::Completed { .. }), "{:?}", event);
+        assert_eq!(read("src/a.rs"), "pub fn new_name() {}\nnew_name();\n");
+    }
+
+    #[tokio::test]
+    async fn test_replace_in_files_requires_reads() {
+        let dir = tempdir().unwrap();
+        let status = Command::new("git").arg("init").arg("-q").arg(dir.path()).status().unwrap();
+        assert!(status.success());
+        let lib = dir.path().join("lib.rs");
+        fs::write(&lib, "old_name();\n").unwrap();
+
+        let reads = ReadTracker::new();
+        let params = json!({
+            "pattern": "old_name",
+            "replacement": "new_name",
+            "path": dir.path().to_str().unwrap(),
+        });
+
+        let event = run_tool(ReplaceInFilesTool::new().with_read_tracker(reads.clone()), params.clone()).await;
+        assert!(matches!(event, ToolEvent::Error { ref content, .. } if content.contains("haven't read")), "{:?}", event);
+        assert_eq!(fs::read_to_string(&lib).unwrap(), "old_name();\n");
+
+        reads.record(&lib);
+        let event = run_tool(ReplaceInFilesTool::new().with_read_tracker(reads.clone()), params.clone()).await;
+        assert!(matches!(event, ToolEvent::Completed { .. }), "{:?}", event);
+        // The replacement counts as a read of what it wrote
+        assert!(reads.check(&lib).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_file_changes_is_all_or_nothing() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        fs::write(&a, "one").unwrap();
+        fs::write(&b, "edited since").unwrap();
+
+        let apply = Box::new(handlers::ApplyFileChanges {
+            changes: vec![
+                (
+                    a.clone(),
+                    FileDiff {
+                        path: a.to_string_lossy().into_owned(),
+                        original: "one".into(),
+                        modified: "uno".into(),
+                    },
+                ),
+                (
+                    b.clone(),
+                    FileDiff {
+                        path: b.to_string_lossy().into_owned(),
+                        original: "two".into(),
+                        modified: "dos".into(),
+                    },
+                ),
+            ],
+        });
+        match apply.call().await {
+            crate::tools::Step::Error(e) => assert!(e.contains("no files were modified"), "{}", e),
+            _ => panic!("Expected an error"),
+        }
+        assert_eq!(fs::read_to_string(&a).unwrap(), "one");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "edited since");
+    }
+}
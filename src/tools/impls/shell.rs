@@ -1,12 +1,16 @@
 //! Shell command execution tool
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::define_tool_block;
+use crate::tools::io;
+use crate::tools::ShellSandbox;
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
 use ratatui::{
     style::{Color, Style},
     text::{Line, Span},
 };
+use anyhow::{Context, Result};
+use fancy_regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -36,14 +40,122 @@ define_tool_block! {
 /// Tool for executing shell commands
 pub struct ShellTool {
     timeout_secs: u64,
+    /// Patterns for commands that need a terminal; matched per pipeline segment
+    interactive: Vec<Regex>,
+    /// Output size at which a command is killed
+    max_output_bytes: usize,
+    /// Restricted environment commands run in
+    sandbox: Option<ShellSandbox>,
 }
 
 impl ShellTool {
     pub fn new() -> Self {
-        Self { timeout_secs: 120 }
+        Self::with_interactive_commands(Self::INTERACTIVE_COMMANDS)
+            .expect("built-in interactive command patterns are valid")
+    }
+
+    /// Create a shell tool that refuses commands matching any of `patterns`
+    pub fn with_interactive_commands(patterns: &[impl AsRef<str>]) -> Result<Self> {
+        let interactive = patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p.as_ref())
+                    .with_context(|| format!("Invalid interactive command pattern: {}", p.as_ref()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            timeout_secs: 120,
+            interactive,
+            max_output_bytes: io::SHELL_MAX_OUTPUT_BYTES,
+            sandbox: None,
+        })
+    }
+
+    /// Kill commands whose combined stdout and stderr pass `max_bytes`
+    pub fn with_max_output_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_output_bytes = max_bytes;
+        self
+    }
+
+    /// Run commands inside `sandbox`
+    pub fn with_sandbox(mut self, sandbox: ShellSandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// The first segment of `command` that looks interactive, if any. A REPL
+    /// fed by a pipe runs what it's given, so only the rest count there.
+    fn interactive_segment<'a>(&self, command: &'a str) -> Option<&'a str> {
+        split_commands(command)
+            .into_iter()
+            .find(|(segment, piped)| {
+                self.interactive
+                    .iter()
+                    .filter(|re| !(*piped && re.as_str() == Self::REPL_COMMANDS))
+                    .any(|re| re.is_match(segment).unwrap_or(false))
+            })
+            .map(|(segment, _)| segment)
     }
 }
 
+/// The simple commands in `command`, split at `;`, `&&`, `||`, `|`, `&` and
+/// newlines outside quotes, each with whether a pipe feeds its input.
+/// Redirections such as `2>&1` and `&>file` don't split.
+fn split_commands(command: &str) -> Vec<(&str, bool)> {
+    let bytes = command.as_bytes();
+    let mut segments = Vec::new();
+    let (mut start, mut piped) = (0, false);
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if let Some(open) = quote {
+            if byte == b'\\' && open == b'"' {
+                i += 1;
+            } else if byte == open {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        let next = bytes.get(i + 1).copied();
+        let (len, pipe) = match byte {
+            b'\\' => {
+                i += 2;
+                continue;
+            },
+            b'\'' | b'"' => {
+                quote = Some(byte);
+                i += 1;
+                continue;
+            },
+            b'\n' | b';' => (1, false),
+            b'|' if next == Some(b'|') => (2, false),
+            b'|' if next == Some(b'&') => (2, true),
+            b'|' => (1, true),
+            b'&' if next == Some(b'&') => (2, false),
+            b'&' if next == Some(b'>') || (i > 0 && matches!(bytes[i - 1], b'>' | b'<')) => {
+                i += 1;
+                continue;
+            },
+            b'&' => (1, false),
+            _ => {
+                i += 1;
+                continue;
+            },
+        };
+        segments.push((command[start..i].trim(), piped));
+        piped = pipe;
+        i += len;
+        start = i;
+    }
+    if start < command.len() {
+        segments.push((command[start..].trim(), piped));
+    }
+    segments.retain(|(segment, _)| !segment.is_empty());
+    segments
+}
+
 impl Default for ShellTool {
     fn default() -> Self {
         Self::new()
@@ -54,10 +166,28 @@ impl Default for ShellTool {
 struct ShellParams {
     command: String,
     working_dir: Option<String>,
+    /// Keep ANSI escape codes in the output
+    #[serde(default)]
+    raw: bool,
 }
 
 impl ShellTool {
     pub const NAME: &'static str = "mcp_shell";
+
+    /// REPLs started without a script or -c/-e
+    pub const REPL_COMMANDS: &'static str = r"^(python3?|ipython|node|irb|ghci|lua|psql|mysql|sqlite3|redis-cli)$";
+
+    /// Default patterns for commands that wait on a terminal or editor
+    pub const INTERACTIVE_COMMANDS: &'static [&'static str] = &[
+        // Editors, pagers and full-screen programs
+        r"^(sudo\s+)?(vi|vim|nvim|nano|emacs|pico|less|more|top|htop|man|watch|tmux|screen)\b",
+        Self::REPL_COMMANDS,
+        // Remote shells without a command
+        r"^ssh(\s+-\S+)*\s+\S+$",
+        // git commands that open an editor or prompt
+        r"^git\s+commit\b(?!.*(\s-[a-zA-Z]*[mF]|\s--message|\s--file|\s--no-edit))",
+        r"^git\s+(rebase|add|checkout|reset|stash)\b.*\s(-i|--interactive|-p|--patch)\b",
+    ];
 }
 
 impl Tool for ShellTool {
@@ -72,6 +202,10 @@ impl Tool for ShellTool {
          Commands are executed with a timeout."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Exec
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -84,6 +218,10 @@ impl Tool for ShellTool {
                     "type": "string",
                     "description": "Working directory for the command (optional, defaults to current directory)"
                 },
+                "raw": {
+                    "type": "boolean",
+                    "description": "Keep ANSI escape codes (colors, cursor movement) in the output. By default they are stripped."
+                },
                 "background": {
                     "type": "boolean",
                     "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
@@ -93,18 +231,38 @@ impl Tool for ShellTool {
         })
     }
 
+    fn post_process(&self, params: &serde_json::Value, output: String) -> String {
+        if params["raw"].as_bool() == Some(true) {
+            return output;
+        }
+        // Color codes from cargo, git and friends are noise to the agent and
+        // garble the transcript
+        io::strip_ansi(&output).into_owned()
+    }
+
     fn compose(&self, params: serde_json::Value) -> ToolPipeline {
         let parsed: ShellParams = match serde_json::from_value(params) {
             Ok(p) => p,
             Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
         };
 
+        if let Some(segment) = self.interactive_segment(&parsed.command) {
+            return ToolPipeline::error(format!(
+                "`{}` looks interactive, and commands run without a terminal or stdin. \
+                 Run it non-interactively instead: pass the message with -m, use `-c`/a script \
+                 for interpreters, pipe input in, or use a non-paging flag.",
+                segment
+            ));
+        }
+
         ToolPipeline::new()
             .await_approval()
             .then(handlers::Shell {
                 command: parsed.command,
                 working_dir: parsed.working_dir,
                 timeout_secs: self.timeout_secs,
+                max_output_bytes: Some(self.max_output_bytes),
+                sandbox: self.sandbox.clone(),
             })
     }
 
@@ -144,6 +302,136 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_shell_strips_ansi() {
+        assert_eq!(
+            io::strip_ansi("\x1b[1m\x1b[32mCompiling\x1b[0m foo\x1b]8;;http://x\x07link\x1b]8;;\x1b\\ \x1b(Bok"),
+            "Compiling foolink ok"
+        );
+
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ShellTool::NAME.to_string(),
+            params: json!({ "command": "printf '\\033[31merror\\033[0m: failed\\n'" }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        if let Some(crate::tools::ToolEvent::Completed { content, .. }) = executor.next().await {
+            assert!(content.contains("error: failed"), "{:?}", content);
+            assert!(!content.contains('\x1b'));
+        } else {
+            panic!("Expected Completed event");
+        }
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "raw".to_string(),
+            name: ShellTool::NAME.to_string(),
+            params: json!({ "command": "printf '\\033[31merror\\033[0m\\n'", "raw": true }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        if let Some(crate::tools::ToolEvent::Completed { content, .. }) = executor.next().await {
+            assert!(content.contains("\x1b[31merror"), "{:?}", content);
+        } else {
+            panic!("Expected Completed event");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shell_output_cap_kills_command() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new().with_max_output_bytes(100)));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ShellTool::NAME.to_string(),
+            params: json!({ "command": "yes" }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        if let Some(crate::tools::ToolEvent::Completed { content, .. }) = executor.next().await {
+            assert!(content.starts_with("y\ny\n"));
+            assert!(content.ends_with("[output truncated at 100 bytes, process terminated]"));
+            assert!(!content.contains("[exit code"));
+        } else {
+            panic!("Expected Completed event");
+        }
+    }
+
+    #[test]
+    fn test_interactive_commands_detected() {
+        let tool = ShellTool::new();
+        for command in [
+            "vim src/main.rs",
+            "git add . && git commit",
+            "python3",
+            "ssh -A build-host",
+            "git rebase -i HEAD~3",
+            "cat log.txt | less",
+            "make 2>&1 | less",
+            "cargo build || vim Cargo.toml",
+        ] {
+            assert!(tool.interactive_segment(command).is_some(), "{}", command);
+        }
+        for command in [
+            "git commit -m 'fix'",
+            "git commit --amend --no-edit",
+            "git commit -am 'wip'",
+            "python3 -c 'print(1)'",
+            "python3 script.py",
+            "ssh host uptime",
+            "cargo test",
+            "echo vim",
+            "cat data.csv | python3",
+            "cat x | python3 script.py",
+            "cargo build 2>&1 | tail -n 20",
+            "echo 'done; vim' &> log.txt",
+        ] {
+            assert!(tool.interactive_segment(command).is_none(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_custom_interactive_commands() {
+        let tool = ShellTool::with_interactive_commands(&["^my-tui\\b"]).unwrap();
+        assert!(tool.interactive_segment("my-tui --fullscreen").is_some());
+        assert!(tool.interactive_segment("vim notes.md").is_none());
+        assert!(ShellTool::with_interactive_commands(&["("]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shell_stdin_closed() {
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(ShellTool::new()));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: ShellTool::NAME.to_string(),
+            params: json!({ "command": "read line; echo \"got: [$line]\"" }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        if let Some(crate::tools::ToolEvent::Completed { content, .. }) = executor.next().await {
+            assert!(content.contains("got: []"));
+        } else {
+            panic!("Expected Completed event");
+        }
+    }
+
     #[tokio::test]
     async fn test_shell_with_working_dir() {
         let mut registry = ToolRegistry::empty();
@@ -2,7 +2,7 @@
 
 use std::sync::OnceLock;
 
-use super::{Tool, ToolPipeline};
+use super::{Tool, ToolCategory, ToolPipeline};
 use crate::auth::OAuthCredentials;
 use crate::config::AgentRuntimeConfig;
 use crate::impl_tool_block;
@@ -177,6 +177,10 @@ impl Tool for SpawnAgentTool {
          Continue with other work or go idle while waiting."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Exec
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -1,10 +1,11 @@
 //! Brave Web Search tool
 
-use super::{handlers, Tool, ToolPipeline};
-use crate::impl_tool_block;
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
+use crate::tools::handlers::SearchResults;
+use crate::tools::io::SearchResult;
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, Status, ToolBlock};
 use ratatui::{
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
 };
 use serde::{Deserialize, Serialize};
@@ -22,6 +23,12 @@ pub struct WebSearchBlock {
     pub background: bool,
     #[serde(default)]
     pub agent_label: Option<String>,
+    /// The results, once the search has finished
+    #[serde(default)]
+    pub results: Vec<SearchResult>,
+    /// Where the tool leaves this call's results
+    #[serde(skip)]
+    searches: Option<(SearchResults, String)>,
 }
 
 impl WebSearchBlock {
@@ -34,6 +41,8 @@ impl WebSearchBlock {
             text: String::new(),
             background,
             agent_label: None,
+            results: Vec::new(),
+            searches: None,
         }
     }
 
@@ -41,13 +50,54 @@ impl WebSearchBlock {
         let _: WebSearchParams = serde_json::from_value(params.clone()).ok()?;
         Some(Self::new(call_id, tool_name, params, background))
     }
+
+    /// Take the results from `searches` once the search has run
+    fn with_searches(mut self, searches: SearchResults) -> Self {
+        let key = self.params.to_string();
+        self.searches = Some((searches, key));
+        self
+    }
+}
+
+/// Cut `text` to `max` characters, with an ellipsis if anything was dropped
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
 }
 
 #[typetag::serde]
 impl Block for WebSearchBlock {
-    impl_tool_block!(BlockType::Tool);
+    fn kind(&self) -> BlockType {
+        BlockType::Tool
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
 
-    fn render(&self, _width: u16) -> Vec<Line<'_>> {
+    fn set_status(&mut self, status: Status) {
+        self.status = status;
+    }
+
+    fn append_text(&mut self, text: &str) {
+        self.text.push_str(text);
+        // The handler left the results before reporting its output
+        if let Some((searches, key)) = &self.searches {
+            if let Some(results) = searches.lock().unwrap_or_else(|e| e.into_inner()).remove(key) {
+                self.results = results;
+            }
+        }
+    }
+
+    fn text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+
+    fn render(&self, width: u16) -> Vec<Line<'_>> {
         let mut lines = Vec::new();
 
         let query = self.params["query"].as_str().unwrap_or("");
@@ -67,9 +117,30 @@ impl Block for WebSearchBlock {
             lines.push(render_approval_prompt());
         }
 
-        if !self.text.is_empty() {
-            lines.extend(render_result(
-                &format!("{} results.", self.text.split("\n").count()), 1));
+        if self.results.is_empty() && !self.text.is_empty() {
+            // Errors and "No results found."
+            lines.extend(render_result(&self.text, 3));
+        }
+        let max = (width as usize).saturating_sub(5).max(20);
+        for (i, result) in self.results.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:>2}. ", i + 1), Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    truncate(&result.title, max),
+                    Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            // The URL gets a span of its own so link focus can pick it out
+            lines.push(Line::from(vec![
+                Span::raw("      "),
+                Span::styled(result.url.clone(), Style::default().fg(Color::Blue)),
+            ]));
+            if !result.description.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("      {}", truncate(&result.description, max)),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
         }
 
         if self.status == Status::Denied {
@@ -104,7 +175,12 @@ impl Block for WebSearchBlock {
 }
 
 /// Tool for performing web searches using Brave Search API
-pub struct WebSearchTool;
+pub struct WebSearchTool {
+    /// How long to wait for the search provider (`tools.web_search.timeout_secs`)
+    timeout_secs: u64,
+    /// Finished searches, for the blocks of their calls to list
+    searches: SearchResults,
+}
 
 #[derive(Debug, Deserialize)]
 struct WebSearchParams {
@@ -119,6 +195,20 @@ fn default_count() -> u32 {
 
 impl WebSearchTool {
     pub const NAME: &'static str = "mcp_web_search";
+    pub const DEFAULT_TIMEOUT_SECS: u64 = 20;
+
+    pub fn new() -> Self {
+        Self {
+            timeout_secs: Self::DEFAULT_TIMEOUT_SECS,
+            searches: SearchResults::default(),
+        }
+    }
+
+    /// Fail the search if the provider hasn't answered within `secs`
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
 }
 
 impl Tool for WebSearchTool {
@@ -130,6 +220,10 @@ impl Tool for WebSearchTool {
         "Returns relevant web results with titles, URLs, and descriptions."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Network
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -152,6 +246,7 @@ impl Tool for WebSearchTool {
     }
 
     fn compose(&self, params: serde_json::Value) -> ToolPipeline {
+        let key = params.to_string();
         let parsed: WebSearchParams = match serde_json::from_value(params) {
             Ok(p) => p,
             Err(e) => return ToolPipeline::error(format!("Invalid params: {}", e)),
@@ -162,14 +257,54 @@ impl Tool for WebSearchTool {
             .then(handlers::WebSearch {
                 query: parsed.query,
                 count: parsed.count,
+                timeout_secs: self.timeout_secs,
+                results: self.searches.clone(),
+                key,
             })
     }
 
     fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
         if let Some(block) = WebSearchBlock::from_params(call_id, self.name(), params.clone(), background) {
-            Box::new(block)
+            Box::new(block.with_searches(self.searches.clone()))
         } else {
             Box::new(ToolBlock::new(call_id, self.name(), params, background))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_takes_structured_results() {
+        let searches = SearchResults::default();
+        let params = json!({ "query": "rust" });
+        let mut block = WebSearchBlock::from_params("c1", WebSearchTool::NAME, params.clone(), false)
+            .unwrap()
+            .with_searches(searches.clone());
+        let result = SearchResult {
+            title: "Vec [docs]".to_string(),
+            url: "https://doc.rust-lang.org/std/vec/struct.Vec.html".to_string(),
+            description: String::new(),
+        };
+        searches.lock().unwrap().insert(params.to_string(), vec![result.clone()]);
+
+        block.append_text("1. [Vec [docs]](https://doc.rust-lang.org/std/vec/struct.Vec.html)");
+        assert_eq!(block.results, vec![result]);
+        assert!(searches.lock().unwrap().is_empty());
+        let urls: Vec<String> = block
+            .render(80)
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .filter(|span| span.content.starts_with("https://"))
+            .map(|span| span.content.to_string())
+            .collect();
+        assert_eq!(urls, ["https://doc.rust-lang.org/std/vec/struct.Vec.html"]);
+
+        // Anything else is shown as the tool's text
+        let mut block = WebSearchBlock::from_params("c2", WebSearchTool::NAME, params, false).unwrap();
+        block.append_text("No results found.");
+        assert!(block.results.is_empty());
+    }
+}
@@ -11,9 +11,10 @@
 //! ]
 //! ```
 
-use super::{handlers, Tool, ToolPipeline};
+use super::{handlers, Tool, ToolCategory, ToolPipeline};
 use crate::ide::ToolPreview;
 use crate::define_tool_block;
+use crate::tools::RemotePath;
 use crate::transcript::{render_agent_label, render_approval_prompt, render_prefix, render_result, Block, BlockType, ToolBlock, Status};
 use ratatui::{
     style::{Color, Style},
@@ -50,6 +51,18 @@ pub struct WriteFileTool;
 struct WriteFileParams {
     path: String,
     content: String,
+    /// Octal permission bits, e.g. "755"
+    mode: Option<String>,
+}
+
+/// Parse an octal file mode like "755" or "0o644". Only permission bits:
+/// setuid, setgid and sticky aren't the agent's to set.
+fn parse_mode(mode: &str) -> Result<u32, String> {
+    let digits = mode.trim_start_matches("0o");
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|bits| *bits <= 0o777)
+        .ok_or_else(|| format!("Invalid mode {:?}: expected octal permission bits like \"755\" (at most 777)", mode))
 }
 
 impl WriteFileTool {
@@ -66,6 +79,10 @@ impl Tool for WriteFileTool {
          Use edit_file to modify existing files."
     }
 
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Write
+    }
+
     fn schema(&self) -> serde_json::Value {
         json!({
             "type": "object",
@@ -78,6 +95,10 @@ impl Tool for WriteFileTool {
                     "type": "string",
                     "description": "Content to write to the file"
                 },
+                "mode": {
+                    "type": "string",
+                    "description": "Octal permissions for the new file (optional), e.g. \"755\" for an executable script"
+                },
                 "background": {
                     "type": "boolean",
                     "description": "Run in background. Returns immediately with a task_id. You will be automatically notified when the task completes — do not poll."
@@ -87,6 +108,14 @@ impl Tool for WriteFileTool {
         })
     }
 
+    fn preview(&self, params: &serde_json::Value) -> Option<ToolPreview> {
+        let params: WriteFileParams = serde_json::from_value(params.clone()).ok()?;
+        Some(ToolPreview::File {
+            path: params.path,
+            content: params.content,
+        })
+    }
+
     fn compose(&self, params: serde_json::Value) -> ToolPipeline {
         let parsed: Result<WriteFileParams, _> = serde_json::from_value(params.clone());
         let params = match parsed {
@@ -96,6 +125,18 @@ impl Tool for WriteFileTool {
             }
         };
 
+        if let Some(remote) = RemotePath::parse(&params.path) {
+            return ToolPipeline::error(format!(
+                "Writing to remote paths is not supported: {}. Remote files are read-only; \
+                 make remote changes through shell, which always asks for approval.",
+                remote
+            ));
+        }
+
+        let mode = match params.mode.as_deref().map(parse_mode).transpose() {
+            Ok(mode) => mode,
+            Err(e) => return ToolPipeline::error(e),
+        };
         let path = PathBuf::from(&params.path);
 
         ToolPipeline::new()
@@ -113,13 +154,14 @@ impl Tool for WriteFileTool {
                 },
             })
             .await_approval()
-            .then(handlers::WriteFile { path: path.clone(), content: params.content.clone() })
+            .then(handlers::WriteFile { path: path.clone(), content: params.content.clone(), mode })
             .then(handlers::Output {
                 content: format!(
-                    "Created file: {} ({} lines, {} bytes)",
+                    "Created file: {} ({} lines, {} bytes{})",
                     params.path,
                     params.content.lines().count(),
-                    params.content.len()
+                    params.content.len(),
+                    mode.map(|mode| format!(", mode {:o}", mode)).unwrap_or_default()
                 ),
             })
             .finally(handlers::IdeClosePreview)
@@ -137,7 +179,7 @@ impl Tool for WriteFileTool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tools::{ToolExecutor, ToolRegistry, ToolCall, ToolDecision, ToolEvent};
+    use crate::tools::{PostEditCheck, ToolExecutor, ToolRegistry, ToolCall, ToolDecision, ToolEvent};
     use std::fs;
     use tempfile::tempdir;
 
@@ -186,6 +228,83 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_write_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        assert_eq!(parse_mode("0o644"), Ok(0o644));
+        assert!(parse_mode("789").is_err());
+        assert!(parse_mode("4755").is_err());
+        assert!(parse_mode("").is_err());
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("run.sh");
+
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(WriteFileTool));
+        let mut executor = ToolExecutor::new(registry);
+
+        executor.enqueue(vec![ToolCall {
+            agent_id: 0,
+            call_id: "test".to_string(),
+            name: WriteFileTool::NAME.to_string(),
+            params: json!({
+                "path": file_path.to_str().unwrap(),
+                "content": "#!/bin/sh\necho hi\n",
+                "mode": "755"
+            }),
+            decision: ToolDecision::Approve,
+            background: false,
+        }]);
+
+        match run_to_completion(&mut executor).await {
+            ToolEvent::Completed { content, .. } => {
+                assert!(content.contains("mode 755"), "{}", content);
+                let mode = fs::metadata(&file_path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o7777, 0o755);
+            },
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_edit_check_runs_once_per_batch() {
+        let dir = tempdir().unwrap();
+        let mut registry = ToolRegistry::empty();
+        registry.register(std::sync::Arc::new(WriteFileTool));
+        let mut executor = ToolExecutor::new(registry).with_post_edit_check(PostEditCheck {
+            command: "false".to_string(),
+            timeout_secs: 10,
+            max_output_bytes: None,
+            sandbox: None,
+        });
+
+        let call = |id: &str| ToolCall {
+            agent_id: 0,
+            call_id: id.to_string(),
+            name: WriteFileTool::NAME.to_string(),
+            params: json!({
+                "path": dir.path().join(id).to_str().unwrap(),
+                "content": "x"
+            }),
+            decision: ToolDecision::Approve,
+            background: false,
+        };
+        executor.enqueue(vec![call("first"), call("second")]);
+
+        match run_to_completion(&mut executor).await {
+            ToolEvent::Completed { content, .. } => assert!(!content.contains("Post-edit check")),
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+        match run_to_completion(&mut executor).await {
+            ToolEvent::Completed { content, .. } => {
+                assert!(content.starts_with("Created file"));
+                assert!(content.contains("Post-edit check failed (`false`)"));
+            },
+            other => panic!("Expected Completed event, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_write_existing_file_fails() {
         let dir = tempdir().unwrap();
@@ -3,12 +3,15 @@
 //! Pure functions for filesystem, shell, and network operations.
 //! These are decoupled from the effect/tool system and use standard types.
 
+use std::borrow::Cow;
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use std::os::unix::process::CommandExt;
 use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
 use tokio::process::Command;
 
 /// Wrapper that kills the entire process group on drop.
@@ -57,11 +60,24 @@ pub struct FetchResult {
     pub size: usize,
 }
 
-/// A web search result
+/// Response to an `http_request` call. Error statuses are responses too, so
+/// the agent can read what the API said.
 #[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    /// Body as text; JSON is pretty-printed
+    pub body: String,
+}
+
+/// A web search result
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct SearchResult {
     pub title: String,
     pub url: String,
+    /// Snippet from the result page, markup removed
+    pub description: String,
 }
 
 /// Default tab width for user display
@@ -103,6 +119,12 @@ pub fn read_file(
     let content =
         fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
 
+    Ok(format_lines(&content, start_line, end_line))
+}
+
+/// Format file content with line numbers, limited to a line range.
+/// Range semantics match [`read_file`].
+pub fn format_lines(content: &str, start_line: Option<i32>, end_line: Option<i32>) -> String {
     let lines: Vec<&str> = content.lines().collect();
     let total_lines = lines.len();
 
@@ -122,7 +144,7 @@ pub fn read_file(
 
     // Validate range
     if start_idx >= total_lines {
-        return Ok(String::new()); // Start is past end of file
+        return String::new(); // Start is past end of file
     }
 
     let end_idx = end_idx.max(start_idx); // Ensure end >= start
@@ -140,17 +162,171 @@ pub fn read_file(
         ));
     }
 
+    output
+}
+
+/// Most bytes `tail_file` reads back from the end of a file
+pub const TAIL_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Chunk size for reading backwards from the end of a file
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Return the last `count` lines of a file, reading backwards from the end so
+/// large logs aren't loaded whole. At most [`TAIL_MAX_BYTES`] are scanned.
+/// Absolute line numbers aren't known without reading the whole file, so
+/// lines are numbered relative to the end (`-1` is the last line).
+pub fn tail_file(path: &Path, count: usize) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+
+    // Read chunks from the end until there are enough newlines
+    let mut buf: Vec<u8> = Vec::new();
+    let mut pos = len;
+    while pos > 0 && len - pos < TAIL_MAX_BYTES {
+        let chunk = TAIL_CHUNK.min(pos).min(TAIL_MAX_BYTES - (len - pos));
+        pos -= chunk;
+        let mut bytes = vec![0; chunk as usize];
+        file.seek(SeekFrom::Start(pos))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        bytes.extend_from_slice(&buf);
+        buf = bytes;
+        // One newline more than requested marks the start of the first line
+        if buf.iter().filter(|&&b| b == b'\n').count() > count {
+            break;
+        }
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    // A partial first line (cut by the scan limit or chunking) is dropped
+    let complete = if pos > 0 { &all[1.min(all.len())..] } else { &all[..] };
+    let lines = &complete[complete.len().saturating_sub(count)..];
+
+    let mut output = String::new();
+    if lines.len() < count && pos > 0 {
+        output.push_str(&format!(
+            "[only the last {} KB of this {} byte file were scanned]\n",
+            TAIL_MAX_BYTES / 1024,
+            len
+        ));
+    }
+    output.push_str(&format_tail(lines));
     Ok(output)
 }
 
-/// Execute a shell command
+/// Number the last lines of a file relative to the end (`-1` is the last line)
+pub fn format_tail(lines: &[&str]) -> String {
+    let mut output = String::new();
+    let width = (lines.len() + 1).to_string().len().max(4);
+    for (i, line) in lines.iter().enumerate() {
+        output.push_str(&format!(
+            "{:>width$}│{}\n",
+            format!("-{}", lines.len() - i),
+            format_for_agent(line),
+            width = width
+        ));
+    }
+    output
+}
+
+/// Default cap on the output a shell command may produce before it is killed
+pub const SHELL_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Execute a shell command. Once stdout and stderr together pass
+/// `max_output_bytes`, reading stops and the command is killed.
 pub async fn execute_shell(
     command: &str,
     working_dir: Option<&str>,
     timeout_secs: u64,
+    max_output_bytes: Option<usize>,
 ) -> Result<ShellResult, String> {
     let mut cmd = Command::new("bash");
     cmd.arg("-c").arg(command);
+    run_command(cmd, working_dir, timeout_secs, max_output_bytes).await
+}
+
+/// Remove ANSI escape sequences (colors, cursor movement, window titles,
+/// hyperlinks) from command output
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in '@'..='~'
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            },
+            // OSC: up to BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' {
+                        chars.next();
+                        break;
+                    }
+                }
+            },
+            // Character set selection takes one more character
+            Some('(' | ')' | '*' | '+') => {
+                chars.next();
+            },
+            // Other two-character escapes
+            _ => {},
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Execute a program directly from an argument vector.
+/// Arguments are passed as-is to the program, never parsed by a shell.
+pub async fn execute_argv(
+    argv: &[String],
+    working_dir: Option<&str>,
+    timeout_secs: u64,
+) -> Result<ShellResult, String> {
+    let (program, args) = argv.split_first().ok_or("Empty command")?;
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    run_command(cmd, working_dir, timeout_secs, None).await
+}
+
+/// Execute a command the caller has set up, e.g. inside a sandbox
+pub async fn execute_prepared(
+    cmd: Command,
+    working_dir: Option<&str>,
+    timeout_secs: u64,
+    max_output_bytes: Option<usize>,
+) -> Result<ShellResult, String> {
+    run_command(cmd, working_dir, timeout_secs, max_output_bytes).await
+}
+
+/// Run a prepared command in its own process group, collecting its output
+async fn run_command(
+    mut cmd: Command,
+    working_dir: Option<&str>,
+    timeout_secs: u64,
+    max_output_bytes: Option<usize>,
+) -> Result<ShellResult, String> {
+    // No stdin: commands that wait for input see EOF instead of hanging
+    // (or reading keystrokes meant for the TUI).
+    cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
     // Spawn in its own process group so KillOnDrop can kill all children.
@@ -178,39 +354,25 @@ pub async fn execute_shell(
 
     let stdout = child.0.stdout.take();
     let stderr = child.0.stderr.take();
+    let cap = OutputCap::new(max_output_bytes, child.0.id());
 
-    let mut collected = String::new();
-
-    if let Some(stdout) = stdout {
-        let mut reader = BufReader::new(stdout).lines();
-        while let Ok(Some(line)) = reader.next_line().await {
-            collected.push_str(&line);
-            collected.push('\n');
-        }
-    }
-
-    let mut stderr_output = String::new();
-    if let Some(stderr) = stderr {
-        let mut reader = BufReader::new(stderr).lines();
-        while let Ok(Some(line)) = reader.next_line().await {
-            stderr_output.push_str(&line);
-            stderr_output.push('\n');
-        }
-    }
+    // Output is read under the timeout too, so a command that never closes
+    // its stdout can't block forever.
+    let run = async {
+        let (collected, stderr_output) =
+            tokio::join!(read_lines(stdout, &cap), read_lines(stderr, &cap));
+        child.0.wait().await.map(|status| (collected, stderr_output, status))
+    };
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), run).await;
 
-    let status = match tokio::time::timeout(
-        std::time::Duration::from_secs(timeout_secs),
-        child.0.wait(),
-    )
-    .await
-    {
-        Ok(Ok(status)) => status,
+    let (collected, stderr_output, status) = match outcome {
+        Ok(Ok(result)) => result,
         Ok(Err(e)) => return Err(format!("Wait failed: {}", e)),
         Err(_) => {
             // KillOnDrop will handle cleanup, but we can be explicit here too.
             let _ = child.0.start_kill();
             return Err(format!(
-                "Command timed out after {} seconds",
+                "Command timed out after {} seconds (stdin is closed; interactive commands are not supported)",
                 timeout_secs
             ));
         }
@@ -231,7 +393,8 @@ pub async fn execute_shell(
         output = "(no output)".to_string();
     }
 
-    if exit_code != 0 {
+    // A command killed for its output has no meaningful exit code
+    if exit_code != 0 && !cap.exceeded() {
         output.push_str(&format!("\n[exit code: {}]", exit_code));
     }
 
@@ -249,6 +412,12 @@ pub async fn execute_shell(
             output.len()
         );
     }
+    if cap.exceeded() {
+        output.push_str(&format!(
+            "\n[output truncated at {} bytes, process terminated]",
+            cap.max_bytes
+        ));
+    }
 
     Ok(ShellResult {
         output,
@@ -257,8 +426,83 @@ pub async fn execute_shell(
     })
 }
 
+/// Shared byte budget for a command's stdout and stderr
+struct OutputCap {
+    max_bytes: usize,
+    used: AtomicUsize,
+    exceeded: AtomicBool,
+    pid: Option<u32>,
+}
+
+impl OutputCap {
+    fn new(max_bytes: Option<usize>, pid: Option<u32>) -> Self {
+        Self {
+            max_bytes: max_bytes.unwrap_or(usize::MAX),
+            used: AtomicUsize::new(0),
+            exceeded: AtomicBool::new(false),
+            pid,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.max_bytes.saturating_sub(self.used.load(Ordering::SeqCst))
+    }
+
+    /// Take up to `len` bytes from the budget, returning how many fit.
+    /// Going over kills the process group, which closes both streams.
+    fn take(&self, len: usize) -> usize {
+        let fits = len.min(self.remaining());
+        self.used.fetch_add(fits, Ordering::SeqCst);
+        if fits < len && !self.exceeded.swap(true, Ordering::SeqCst) {
+            if let Some(pid) = self.pid {
+                unsafe {
+                    libc_kill(-(pid as i32), 9); // SIGKILL = 9
+                }
+            }
+        }
+        fits
+    }
+
+    fn exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::SeqCst)
+    }
+}
+
+/// Read a child process stream to the end (or until the output cap is
+/// reached), line by line
+async fn read_lines(stream: Option<impl AsyncRead + Unpin>, cap: &OutputCap) -> String {
+    let mut collected = String::new();
+    let Some(stream) = stream else {
+        return collected;
+    };
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    while !cap.exceeded() {
+        line.clear();
+        // Never buffer more than the remaining budget, plus a byte to notice going over
+        let limit = cap.remaining().saturating_add(1) as u64;
+        match (&mut reader).take(limit).read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {},
+        }
+        let fits = cap.take(line.len());
+        let text = String::from_utf8_lossy(&line[..fits]);
+        if fits < line.len() {
+            collected.push_str(&text);
+            break;
+        }
+        collected.push_str(text.trim_end_matches(['\n', '\r']));
+        collected.push('\n');
+    }
+    collected
+}
+
 /// Fetch content from a URL
-pub async fn fetch_url(url: &str, max_length: Option<usize>) -> Result<FetchResult, String> {
+pub async fn fetch_url(
+    url: &str,
+    max_length: Option<usize>,
+    save_binary_to: Option<&Path>,
+) -> Result<FetchResult, String> {
     let max_length = max_length.unwrap_or(50000);
 
     let parsed_url =
@@ -301,6 +545,22 @@ pub async fn fetch_url(url: &str, max_length: Option<usize>) -> Result<FetchResu
                 ));
             }
 
+            if let Some(path) = save_binary_to.filter(|_| !is_textual(&content_type)) {
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("Failed to read response body: {}", e))?;
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| format!("Failed to save download: {}", e))?;
+                }
+                fs::write(path, &bytes).map_err(|e| format!("Failed to save download: {}", e))?;
+                return Ok(FetchResult {
+                    content: format!("[Binary content saved to {}]", path.display()),
+                    content_type,
+                    size: bytes.len(),
+                });
+            }
+
             match response.text().await {
                 Ok(mut text) => {
                     let original_len = text.len();
@@ -331,8 +591,325 @@ pub async fn fetch_url(url: &str, max_length: Option<usize>) -> Result<FetchResu
     }
 }
 
+/// Send an HTTP request with an optional JSON or raw body. Any status is
+/// returned as a response; only transport failures are errors.
+pub async fn http_request(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+    json: Option<&serde_json::Value>,
+    body: Option<&str>,
+    max_length: usize,
+    timeout_secs: u64,
+) -> Result<HttpResponse, String> {
+    let parsed_url = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if parsed_url.scheme() != "http" && parsed_url.scheme() != "https" {
+        return Err(format!(
+            "Unsupported URL scheme: {}. Only http and https are allowed.",
+            parsed_url.scheme()
+        ));
+    }
+    let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .user_agent(format!("Codey/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap();
+
+    let mut request = client.request(method, url);
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    if let Some(json) = json {
+        request = request.json(json);
+    } else if let Some(body) = body {
+        request = request.body(body.to_string());
+    }
+
+    let send = async {
+        let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        let status = response.status();
+        let headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or("<binary>").to_string()))
+            .collect();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+        Ok::<_, String>((status, headers, content_type, bytes))
+    };
+    let (status, headers, content_type, bytes) =
+        tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), send)
+            .await
+            .map_err(|_| format!("Request timed out after {} seconds", timeout_secs))??;
+
+    let mut body = if !is_textual(&content_type) {
+        format!("[{} bytes of {} not shown]", bytes.len(), content_type)
+    } else if content_type.contains("json") {
+        match serde_json::from_slice::<serde_json::Value>(&bytes) {
+            Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_default(),
+            Err(_) => String::from_utf8_lossy(&bytes).into_owned(),
+        }
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    if body.len() > max_length {
+        let mut end = max_length;
+        while end > 0 && !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        let original_len = body.len();
+        body.truncate(end);
+        body.push_str(&format!("\n\n[... truncated, {} of {} bytes shown]", end, original_len));
+    }
+
+    Ok(HttpResponse {
+        status: status.as_u16(),
+        reason: status.canonical_reason().unwrap_or("").to_string(),
+        headers,
+        body,
+    })
+}
+
+/// Whether a response with this content type is text worth returning inline
+pub fn is_textual(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    mime.is_empty()
+        || mime == "unknown"
+        || mime.starts_with("text/")
+        || ["json", "xml", "javascript", "yaml", "toml", "csv", "x-www-form-urlencoded"]
+            .iter()
+            .any(|kind| mime.contains(kind))
+}
+
+/// Attempts at reading a download before giving up. Each retry resumes
+/// from the bytes already saved.
+pub const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// A download streaming into a `.part` file. Read it with
+/// [`DownloadStream::next_chunk`] until it returns `Ok(false)`.
+pub struct DownloadStream {
+    url: String,
+    part: std::path::PathBuf,
+    client: reqwest::Client,
+    response: reqwest::Response,
+    file: tokio::fs::File,
+    max_bytes: u64,
+    timeout_secs: u64,
+    retries_left: u32,
+    /// Bytes in the part file so far
+    pub written: u64,
+    /// Where this download picked up from an earlier one, if it did
+    pub resumed_from: Option<u64>,
+    /// Final size, when the server gave one
+    pub total: Option<u64>,
+}
+
+/// Start downloading `url` into `part`, resuming with a Range request if
+/// `part` already has bytes from an earlier attempt. Fails up front when the
+/// server reports a size over `max_bytes`.
+pub async fn start_download(
+    url: &str,
+    part: &Path,
+    max_bytes: u64,
+    timeout_secs: u64,
+) -> Result<DownloadStream, String> {
+    let parsed_url = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if parsed_url.scheme() != "http" && parsed_url.scheme() != "https" {
+        return Err(format!(
+            "Unsupported URL scheme: {}. Only http and https are allowed.",
+            parsed_url.scheme()
+        ));
+    }
+
+    // No overall timeout: a large download can take much longer than any one
+    // chunk, so each read is timed instead
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(timeout_secs))
+        .user_agent(format!("Codey/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap();
+
+    let offset = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let (response, file, written, total) = open_download(&client, url, part, offset, timeout_secs).await?;
+    if let Some(total) = total.filter(|total| *total > max_bytes) {
+        return Err(format!(
+            "Download is {}, over the {} limit (tools.download.max_bytes)",
+            format_size(total),
+            format_size(max_bytes)
+        ));
+    }
+
+    Ok(DownloadStream {
+        url: url.to_string(),
+        part: part.to_path_buf(),
+        client,
+        response,
+        file,
+        max_bytes,
+        timeout_secs,
+        retries_left: DOWNLOAD_ATTEMPTS - 1,
+        written,
+        resumed_from: (written > 0).then_some(written),
+        total,
+    })
+}
+
+/// Send the request for everything after `offset` and open the part file to
+/// match what the server sends back. Returns the response, the file, the
+/// bytes already in it and the final size if known.
+async fn open_download(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    mut offset: u64,
+    timeout_secs: u64,
+) -> Result<(reqwest::Response, tokio::fs::File, u64, Option<u64>), String> {
+    loop {
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        let response = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), request.send())
+            .await
+            .map_err(|_| format!("Request timed out after {} seconds", timeout_secs))?
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+            // The part file doesn't match what the server has now; start over
+            offset = 0;
+            continue;
+        }
+        if !status.is_success() {
+            return Err(format!("Download failed: HTTP {}", status));
+        }
+
+        let resuming = status == reqwest::StatusCode::PARTIAL_CONTENT && offset > 0;
+        let file = if resuming {
+            tokio::fs::OpenOptions::new().append(true).open(part).await
+        } else {
+            // The server ignored the range (or there wasn't one), so the body
+            // is the whole file
+            offset = 0;
+            tokio::fs::File::create(part).await
+        }
+        .map_err(|e| format!("Cannot write {}: {}", part.display(), e))?;
+
+        let total = content_range_total(&response).or_else(|| response.content_length().map(|len| len + offset));
+        return Ok((response, file, offset, total));
+    }
+}
+
+/// The full size from a `Content-Range: bytes 100-199/1000` header
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    let range = response.headers().get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?;
+    range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+impl DownloadStream {
+    /// Save the next chunk. Returns `Ok(false)` once the download is complete
+    /// and its size checks out. Failed reads are retried from where they
+    /// stopped; an error leaves the part file in place for a later resume.
+    pub async fn next_chunk(&mut self) -> Result<bool, String> {
+        use tokio::io::AsyncWriteExt;
+
+        let read = tokio::time::timeout(std::time::Duration::from_secs(self.timeout_secs), self.response.chunk()).await;
+        let chunk = match read {
+            Ok(Ok(chunk)) => chunk,
+            Ok(Err(e)) => return self.retry(format!("Download interrupted: {}", e)).await,
+            Err(_) => {
+                return self
+                    .retry(format!("No data received for {} seconds", self.timeout_secs))
+                    .await
+            },
+        };
+
+        let Some(bytes) = chunk else {
+            self.file
+                .flush()
+                .await
+                .map_err(|e| format!("Cannot write {}: {}", self.part.display(), e))?;
+            return match self.total {
+                Some(total) if total != self.written => {
+                    self.retry(format!(
+                        "Download ended at {} of {} bytes",
+                        self.written, total
+                    ))
+                    .await
+                },
+                _ => Ok(false),
+            };
+        };
+
+        if self.written + bytes.len() as u64 > self.max_bytes {
+            let _ = tokio::fs::remove_file(&self.part).await;
+            return Err(format!(
+                "Download passed the {} limit (tools.download.max_bytes)",
+                format_size(self.max_bytes)
+            ));
+        }
+        self.file
+            .write_all(&bytes)
+            .await
+            .map_err(|e| format!("Cannot write {}: {}", self.part.display(), e))?;
+        self.written += bytes.len() as u64;
+        Ok(true)
+    }
+
+    /// Request the rest of the file again after a failed read
+    async fn retry(&mut self, mut error: String) -> Result<bool, String> {
+        while self.retries_left > 0 {
+            self.retries_left -= 1;
+            tracing::warn!("{}; retrying from byte {}", error, self.written);
+            match open_download(&self.client, &self.url, &self.part, self.written, self.timeout_secs).await {
+                Ok((response, file, written, total)) => {
+                    self.response = response;
+                    self.file = file;
+                    self.written = written;
+                    self.total = total.or(self.total);
+                    return Ok(true);
+                },
+                Err(e) => error = e,
+            }
+        }
+        Err(format!(
+            "{} (gave up after {} attempts; {} saved in {}, downloading again resumes from there)",
+            error,
+            DOWNLOAD_ATTEMPTS,
+            format_size(self.written),
+            self.part.display()
+        ))
+    }
+}
+
+/// A byte count for people, e.g. "1.5 MiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// Search the web using Brave Search API
-pub async fn web_search(query: &str, count: u32) -> Result<Vec<SearchResult>, String> {
+pub async fn web_search(query: &str, count: u32, timeout_secs: u64) -> Result<Vec<SearchResult>, String> {
     let api_key = std::env::var("BRAVE_API_KEY").map_err(|_| {
         "BRAVE_API_KEY environment variable not set. \
          Get an API key from https://brave.com/search/api/"
@@ -347,55 +924,103 @@ pub async fn web_search(query: &str, count: u32) -> Result<Vec<SearchResult>, St
     );
 
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
+        .timeout(std::time::Duration::from_secs(timeout_secs))
         .user_agent(format!("Codey/{}", env!("CARGO_PKG_VERSION")))
         .build()
         .unwrap();
 
-    let result = tokio::time::timeout(
-        std::time::Duration::from_secs(30),
-        client
+    // The timeout covers reading the body too, so a provider that stalls
+    // mid-response can't hold up the turn
+    let request = async {
+        let response = client
             .get(&url)
             .header("Accept", "application/json")
             .header("X-Subscription-Token", &api_key)
-            .send(),
-    )
-    .await;
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
 
-    match result {
-        Ok(Ok(response)) => {
-            let status = response.status();
-            if !status.is_success() {
-                let error_text = response.text().await.unwrap_or_default();
-                return Err(format!(
-                    "Brave Search API error: {} {} - {}",
-                    status.as_u16(),
-                    status.canonical_reason().unwrap_or("Unknown"),
-                    error_text
-                ));
-            }
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(search_api_error(status, &error_text));
+        }
 
-            match response.json::<BraveSearchResponse>().await {
-                Ok(search_response) => {
-                    let results = search_response
-                        .web
-                        .map(|w| {
-                            w.results
-                                .into_iter()
-                                .map(|r| SearchResult {
-                                    title: r.title,
-                                    url: r.url,
-                                })
-                                .collect()
-                        })
-                        .unwrap_or_default();
-                    Ok(results)
-                }
-                Err(e) => Err(format!("Failed to parse Brave Search response: {}", e)),
-            }
+        response
+            .json::<BraveSearchResponse>()
+            .await
+            .map_err(|e| format!("Failed to parse Brave Search response: {}", e))
+    };
+    let search_response = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), request)
+        .await
+        .map_err(|_| {
+            format!(
+                "Web search timed out after {} seconds. The search provider may be slow or \
+                 unreachable; continue without search results or try again later.",
+                timeout_secs
+            )
+        })??;
+
+    Ok(search_response
+        .web
+        .map(|w| {
+            w.results
+                .into_iter()
+                .map(|r| SearchResult {
+                    title: strip_tags(&r.title),
+                    url: r.url,
+                    description: strip_tags(&r.description),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Brave highlights query terms with `<strong>` and escapes entities; keep
+/// just the text
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {},
         }
-        Ok(Err(e)) => Err(format!("Request failed: {}", e)),
-        Err(_) => Err("Request timed out after 30 seconds".to_string()),
+    }
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Error for a failed Brave Search request. Key and quota problems get their
+/// own messages, since retrying won't help until the user fixes them.
+fn search_api_error(status: reqwest::StatusCode, body: &str) -> String {
+    match status.as_u16() {
+        401 | 403 => format!(
+            "Brave Search rejected the API key ({}). Check that BRAVE_API_KEY holds a valid, \
+             active key from https://brave.com/search/api/. Web search is unavailable until it does.",
+            status.as_u16()
+        ),
+        402 | 429 => format!(
+            "Brave Search rate limit or quota exceeded ({}). Wait before searching again; if the \
+             plan's monthly quota is used up, search is unavailable until it resets. {}",
+            status.as_u16(),
+            body
+        ),
+        _ => format!(
+            "Brave Search API error: {} {} - {}",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or("Unknown"),
+            body
+        ),
     }
 }
 
@@ -416,4 +1041,6 @@ struct WebResults {
 struct WebResult {
     title: String,
     url: String,
+    #[serde(default)]
+    description: String,
 }
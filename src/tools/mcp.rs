@@ -0,0 +1,487 @@
+//! MCP (Model Context Protocol) host support
+//!
+//! Connects to the servers configured under `[mcp.servers]`, discovers their
+//! tools and registers each one as a [`Tool`] that proxies calls to the server
+//! over JSON-RPC.
+//!
+//! Two transports are supported:
+//! - **stdio**: `command`/`args` spawn a subprocess speaking newline-delimited JSON-RPC
+//! - **streamable HTTP**: `url` points at an endpoint accepting JSON-RPC POSTs,
+//!   answering with either `application/json` or `text/event-stream`
+//!
+//! Discovered tools are advertised as `mcp_<server>__<tool>` and always require
+//! approval before they run.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use super::pipeline::{EffectHandler, Step, Tool, ToolCategory, ToolPipeline};
+use crate::config::McpServerConfig;
+
+/// MCP protocol revision we speak
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Timeout for the initialize handshake and tool discovery
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout for a single tool call
+const CALL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Maximum tool name length accepted by the API
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+// =============================================================================
+// Transport
+// =============================================================================
+
+/// Pipes to a spawned stdio server. Held under one lock so each request
+/// and its response form a single exchange.
+struct StdioPipes {
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+enum Transport {
+    Stdio {
+        /// Kept alive for the lifetime of the client; killed on drop
+        _child: Child,
+        pipes: Mutex<StdioPipes>,
+    },
+    Http {
+        client: reqwest::Client,
+        url: String,
+        headers: HashMap<String, String>,
+        session_id: Mutex<Option<String>>,
+    },
+}
+
+/// A tool advertised by an MCP server
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "empty_schema")]
+    pub input_schema: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// Connection to a single MCP server
+pub struct McpClient {
+    server: String,
+    transport: Transport,
+    next_id: AtomicU64,
+}
+
+impl McpClient {
+    /// Start (or connect to) a server and complete the initialize handshake
+    pub async fn connect(server: &str, config: &McpServerConfig) -> Result<Self> {
+        let transport = if let Some(ref command) = config.command {
+            let mut child = Command::new(command)
+                .args(&config.args)
+                .envs(&config.env)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .with_context(|| format!("Failed to start MCP server '{}' ({})", server, command))?;
+            let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+            let stdout = child.stdout.take().context("MCP server stdout unavailable")?;
+            Transport::Stdio {
+                _child: child,
+                pipes: Mutex::new(StdioPipes {
+                    stdin,
+                    stdout: BufReader::new(stdout).lines(),
+                }),
+            }
+        } else if let Some(ref url) = config.url {
+            Transport::Http {
+                client: reqwest::Client::new(),
+                url: url.clone(),
+                headers: config.headers.clone(),
+                session_id: Mutex::new(None),
+            }
+        } else {
+            bail!("MCP server '{}' needs either `command` or `url`", server);
+        };
+
+        let client = Self {
+            server: server.to_string(),
+            transport,
+            next_id: AtomicU64::new(1),
+        };
+
+        tokio::time::timeout(CONNECT_TIMEOUT, client.initialize())
+            .await
+            .map_err(|_| anyhow!("MCP server '{}' did not respond to initialize", server))??;
+
+        Ok(client)
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        self.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "codey", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        )
+        .await?;
+        self.notify("notifications/initialized", json!({})).await
+    }
+
+    /// List every tool the server offers (following pagination cursors)
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match cursor {
+                Some(ref c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            let page: Vec<McpToolInfo> = serde_json::from_value(result["tools"].clone())
+                .context("Invalid tools/list response")?;
+            tools.extend(page);
+
+            match result.get("nextCursor").and_then(|c| c.as_str()) {
+                Some(next) if !next.is_empty() => cursor = Some(next.to_string()),
+                _ => break,
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Call a tool. Returns Ok(text) on success and Err(text) if the server
+    /// reported a tool-level error.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<std::result::Result<String, String>> {
+        let result = self
+            .request("tools/call", json!({ "name": name, "arguments": arguments }))
+            .await?;
+        let text = format_call_result(&result);
+        if result.get("isError").and_then(|v| v.as_bool()).unwrap_or(false) {
+            Ok(Err(text))
+        } else {
+            Ok(Ok(text))
+        }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let response = self
+            .send(message, Some(id))
+            .await?
+            .ok_or_else(|| anyhow!("MCP server '{}' sent no response to {}", self.server, method))?;
+
+        if let Some(error) = response.get("error") {
+            bail!(
+                "MCP server '{}' returned error for {}: {}",
+                self.server,
+                method,
+                error["message"].as_str().unwrap_or("unknown error")
+            );
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.send(message, None).await.map(|_| ())
+    }
+
+    /// Send a message and, if `id` is set, wait for the matching response
+    async fn send(&self, message: Value, id: Option<u64>) -> Result<Option<Value>> {
+        match &self.transport {
+            Transport::Stdio { pipes, .. } => {
+                let mut pipes = pipes.lock().await;
+                let mut line = serde_json::to_string(&message)?;
+                line.push('\n');
+                pipes.stdin.write_all(line.as_bytes()).await?;
+                pipes.stdin.flush().await?;
+
+                let Some(id) = id else {
+                    return Ok(None);
+                };
+                loop {
+                    let line = pipes
+                        .stdout
+                        .next_line()
+                        .await?
+                        .ok_or_else(|| anyhow!("MCP server '{}' exited", self.server))?;
+                    match serde_json::from_str::<Value>(&line) {
+                        Ok(msg) if is_response_to(&msg, id) => return Ok(Some(msg)),
+                        // Server-initiated requests, notifications and stale responses are ignored
+                        Ok(_) => continue,
+                        Err(_) => {
+                            tracing::debug!("MCP server '{}' wrote non-JSON line: {}", self.server, line);
+                            continue;
+                        },
+                    }
+                }
+            },
+
+            Transport::Http { client, url, headers, session_id } => {
+                let mut request = client
+                    .post(url)
+                    .header("Accept", "application/json, text/event-stream")
+                    .json(&message);
+                for (key, value) in headers {
+                    request = request.header(key, value);
+                }
+                if let Some(ref sid) = *session_id.lock().await {
+                    request = request.header("Mcp-Session-Id", sid);
+                }
+
+                let response = request.send().await?.error_for_status()?;
+                if let Some(sid) = response
+                    .headers()
+                    .get("mcp-session-id")
+                    .and_then(|v| v.to_str().ok())
+                {
+                    *session_id.lock().await = Some(sid.to_string());
+                }
+                let is_sse = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|ct| ct.starts_with("text/event-stream"));
+                let body = response.text().await?;
+
+                let Some(id) = id else {
+                    return Ok(None);
+                };
+                if is_sse {
+                    Ok(parse_sse_response(&body, id))
+                } else {
+                    let msg: Value = serde_json::from_str(&body)
+                        .with_context(|| format!("Invalid JSON from MCP server '{}'", self.server))?;
+                    Ok(Some(msg))
+                }
+            },
+        }
+    }
+}
+
+/// Check whether a JSON-RPC message is the response to request `id`
+fn is_response_to(msg: &Value, id: u64) -> bool {
+    msg.get("id").and_then(|v| v.as_u64()) == Some(id)
+        && (msg.get("result").is_some() || msg.get("error").is_some())
+}
+
+/// Find the response to request `id` in a server-sent event stream body
+fn parse_sse_response(body: &str, id: u64) -> Option<Value> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str::<Value>(data.trim()).ok())
+        .find(|msg| is_response_to(msg, id))
+}
+
+/// Flatten a tools/call result's content array into text for the agent
+fn format_call_result(result: &Value) -> String {
+    let Some(content) = result.get("content").and_then(|c| c.as_array()) else {
+        return result.to_string();
+    };
+    content
+        .iter()
+        .map(|item| match item["type"].as_str() {
+            Some("text") => item["text"].as_str().unwrap_or("").to_string(),
+            Some("image") | Some("audio") => format!(
+                "[{} content: {}]",
+                item["type"].as_str().unwrap_or(""),
+                item["mimeType"].as_str().unwrap_or("unknown")
+            ),
+            Some("resource") => item["resource"]["text"]
+                .as_str()
+                .map(String::from)
+                .unwrap_or_else(|| format!("[resource: {}]", item["resource"]["uri"].as_str().unwrap_or(""))),
+            _ => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the name a server's tool is advertised under
+fn tool_name(server: &str, tool: &str) -> String {
+    let name: String = format!("mcp_{}__{}", server, tool)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    name.chars().take(MAX_TOOL_NAME_LEN).collect()
+}
+
+// =============================================================================
+// Tool
+// =============================================================================
+
+/// A tool discovered on an MCP server
+pub struct McpTool {
+    client: Arc<McpClient>,
+    /// Name advertised to the LLM
+    name: &'static str,
+    /// Name of the tool on the server
+    remote_name: String,
+    description: &'static str,
+    schema: Value,
+}
+
+impl McpTool {
+    fn new(client: Arc<McpClient>, info: McpToolInfo) -> Self {
+        // Tool names and descriptions are 'static in the Tool trait. MCP tools are
+        // discovered once at startup, so leaking them is bounded.
+        let name = tool_name(client.server(), &info.name);
+        let description = info
+            .description
+            .unwrap_or_else(|| format!("{} (MCP server: {})", info.name, client.server()));
+        Self {
+            client,
+            name: Box::leak(name.into_boxed_str()),
+            remote_name: info.name,
+            description: Box::leak(description.into_boxed_str()),
+            schema: info.input_schema,
+        }
+    }
+}
+
+impl Tool for McpTool {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Exec
+    }
+
+    fn schema(&self) -> Value {
+        self.schema.clone()
+    }
+
+    fn compose(&self, params: Value) -> ToolPipeline {
+        ToolPipeline::new().await_approval().then(CallMcpTool {
+            client: self.client.clone(),
+            name: self.remote_name.clone(),
+            arguments: params,
+        })
+    }
+}
+
+/// Forward a tool call to its MCP server
+struct CallMcpTool {
+    client: Arc<McpClient>,
+    name: String,
+    arguments: Value,
+}
+
+#[async_trait::async_trait]
+impl EffectHandler for CallMcpTool {
+    async fn call(self: Box<Self>) -> Step {
+        let call = self.client.call_tool(&self.name, self.arguments);
+        match tokio::time::timeout(CALL_TIMEOUT, call).await {
+            Ok(Ok(Ok(output))) => Step::Output(output),
+            Ok(Ok(Err(message))) => Step::Error(message),
+            Ok(Err(e)) => Step::Error(e.to_string()),
+            Err(_) => Step::Error(format!(
+                "MCP tool {} timed out after {}s",
+                self.name,
+                CALL_TIMEOUT.as_secs()
+            )),
+        }
+    }
+}
+
+/// Connect to every enabled server and collect their tools.
+/// Servers that fail to start or list tools are logged and skipped.
+pub async fn connect_servers(servers: &HashMap<String, McpServerConfig>) -> Vec<Arc<dyn Tool>> {
+    let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
+
+    for (server, config) in servers {
+        if !config.enabled {
+            continue;
+        }
+        let client = match McpClient::connect(server, config).await {
+            Ok(client) => Arc::new(client),
+            Err(e) => {
+                tracing::warn!("Failed to connect to MCP server '{}': {:#}", server, e);
+                continue;
+            },
+        };
+        match tokio::time::timeout(CONNECT_TIMEOUT, client.list_tools()).await {
+            Ok(Ok(infos)) => {
+                tracing::info!("MCP server '{}' provides {} tools", server, infos.len());
+                for info in infos {
+                    tools.push(Arc::new(McpTool::new(client.clone(), info)));
+                }
+            },
+            Ok(Err(e)) => tracing::warn!("Failed to list tools for MCP server '{}': {:#}", server, e),
+            Err(_) => tracing::warn!("MCP server '{}' timed out listing tools", server),
+        }
+    }
+
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_name_is_sanitized() {
+        assert_eq!(tool_name("github", "create_issue"), "mcp_github__create_issue");
+        assert_eq!(tool_name("my server", "do.thing"), "mcp_my_server__do_thing");
+        assert_eq!(tool_name("s", &"x".repeat(100)).len(), MAX_TOOL_NAME_LEN);
+    }
+
+    #[test]
+    fn test_format_call_result() {
+        let result = json!({
+            "content": [
+                { "type": "text", "text": "first" },
+                { "type": "image", "data": "...", "mimeType": "image/png" },
+                { "type": "resource", "resource": { "uri": "file:///a", "text": "body" } },
+            ]
+        });
+        assert_eq!(format_call_result(&result), "first\n[image content: image/png]\nbody");
+    }
+
+    #[test]
+    fn test_parse_sse_response() {
+        let body = "event: message\n\
+                    data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n\n\
+                    event: message\n\
+                    data: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"ok\":true}}\n\n";
+        let msg = parse_sse_response(body, 7).unwrap();
+        assert_eq!(msg["result"]["ok"], true);
+        assert!(parse_sse_response(body, 8).is_none());
+    }
+
+    #[test]
+    fn test_tool_info_defaults_schema() {
+        let info: McpToolInfo = serde_json::from_value(json!({ "name": "ping" })).unwrap();
+        assert_eq!(info.input_schema["type"], "object");
+        assert!(info.description.is_none());
+    }
+}
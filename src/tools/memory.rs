@@ -0,0 +1,182 @@
+//! Project memory that persists across sessions
+//!
+//! The agent keeps short notes about the project (conventions, gotchas,
+//! commands that work) in `.codey/memory.json`, grouped by topic and keyed
+//! within each topic so a note can be updated in place. The store is bounded
+//! by total size; writes that would exceed it are refused so the agent has to
+//! prune or shorten existing notes. When `tools.memory.inject` is enabled the
+//! notes are added to the system prompt at session start.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::{CODEY_DIR, MEMORY_FILENAME};
+
+/// Notes by topic, then by key
+pub type Notes = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Default bound on the total size of all notes, in bytes
+pub const DEFAULT_MEMORY_MAX_BYTES: usize = 16 * 1024;
+
+/// Memory file and its size bound
+#[derive(Debug, Clone)]
+pub struct Memory {
+    path: PathBuf,
+    max_bytes: usize,
+}
+
+impl Memory {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// The project's memory file, `.codey/memory.json`
+    pub fn project(max_bytes: usize) -> Self {
+        Self::new(Path::new(CODEY_DIR).join(MEMORY_FILENAME), max_bytes)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load all notes; a missing file is an empty memory
+    pub fn load(&self) -> Result<Notes> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Notes::new()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    /// Add or replace a note. Returns true if an existing note was replaced.
+    pub fn write(&self, topic: &str, key: &str, note: &str) -> Result<bool> {
+        let (topic, key, note) = (topic.trim(), key.trim(), note.trim());
+        if topic.is_empty() || key.is_empty() {
+            bail!("topic and key must not be empty");
+        }
+        if note.is_empty() {
+            bail!("note must not be empty (use remove to delete a note)");
+        }
+
+        let mut notes = self.load()?;
+        let replaced = notes
+            .entry(topic.to_string())
+            .or_default()
+            .insert(key.to_string(), note.to_string())
+            .is_some();
+
+        let size = size(&notes);
+        if size > self.max_bytes {
+            bail!(
+                "Memory is full ({} of {} bytes with this note); remove or shorten existing notes first",
+                size,
+                self.max_bytes
+            );
+        }
+        self.save(&notes)?;
+        Ok(replaced)
+    }
+
+    /// Delete a note. Returns false if there was no such note.
+    pub fn remove(&self, topic: &str, key: &str) -> Result<bool> {
+        let mut notes = self.load()?;
+        let Some(entries) = notes.get_mut(topic.trim()) else {
+            return Ok(false);
+        };
+        if entries.remove(key.trim()).is_none() {
+            return Ok(false);
+        }
+        if entries.is_empty() {
+            notes.remove(topic.trim());
+        }
+        self.save(&notes)?;
+        Ok(true)
+    }
+
+    /// Notes as markdown, optionally limited to one topic.
+    /// Returns None when there is nothing to show.
+    pub fn render(&self, topic: Option<&str>) -> Result<Option<String>> {
+        let notes = self.load()?;
+        let mut out = String::new();
+        for (name, entries) in &notes {
+            if topic.is_some_and(|t| t.trim() != name) {
+                continue;
+            }
+            out.push_str(&format!("### {}\n\n", name));
+            for (key, note) in entries {
+                out.push_str(&format!("- **{}**: {}\n", key, note));
+            }
+            out.push('\n');
+        }
+        Ok((!out.is_empty()).then(|| out.trim_end().to_string()))
+    }
+
+    fn save(&self, notes: &Notes) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(notes).context("Failed to serialize memory")?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Size of the notes as they're counted against the bound
+fn size(notes: &Notes) -> usize {
+    notes
+        .iter()
+        .flat_map(|(topic, entries)| {
+            entries
+                .iter()
+                .map(move |(key, note)| topic.len() + key.len() + note.len())
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_read_remove() {
+        let dir = TempDir::new().unwrap();
+        let memory = Memory::new(dir.path().join("memory.json"), DEFAULT_MEMORY_MAX_BYTES);
+        assert!(memory.render(None).unwrap().is_none());
+
+        assert!(!memory.write("build", "test", "cargo test --workspace").unwrap());
+        assert!(memory.write("build", "test", "cargo nextest run").unwrap());
+        memory.write("style", "errors", "use anyhow in the binary").unwrap();
+
+        let all = memory.render(None).unwrap().unwrap();
+        assert!(all.contains("### build\n\n- **test**: cargo nextest run"));
+        assert!(all.contains("### style"));
+        let build = memory.render(Some("build")).unwrap().unwrap();
+        assert!(!build.contains("### style"));
+
+        assert!(memory.remove("build", "test").unwrap());
+        assert!(!memory.remove("build", "test").unwrap());
+        assert!(!memory.load().unwrap().contains_key("build"));
+    }
+
+    #[test]
+    fn test_memory_is_bounded() {
+        let dir = TempDir::new().unwrap();
+        let memory = Memory::new(dir.path().join("memory.json"), 20);
+
+        memory.write("a", "b", "short").unwrap();
+        let err = memory.write("a", "c", "this note is too long").unwrap_err();
+        assert!(err.to_string().contains("Memory is full"));
+        // The rejected note wasn't saved
+        assert_eq!(memory.load().unwrap()["a"].len(), 1);
+        assert!(memory.write("", "b", "x").is_err());
+    }
+}
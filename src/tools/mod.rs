@@ -7,24 +7,46 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+mod audit;
 mod exec;
 #[cfg(feature = "cli")]
 pub mod browser;
 #[cfg(feature = "cli")]
+mod backup;
+#[cfg(feature = "cli")]
+mod file_cache;
+#[cfg(feature = "cli")]
+mod git;
+#[cfg(feature = "cli")]
 pub mod handlers;
 #[cfg(feature = "cli")]
 mod impls;
 pub mod io;
+#[cfg(feature = "cli")]
+pub mod mcp;
+#[cfg(feature = "cli")]
+mod memory;
 mod pipeline;
+#[cfg(feature = "cli")]
+mod read_tracker;
+#[cfg(feature = "cli")]
+mod remote;
+#[cfg(feature = "cli")]
+mod sandbox;
+#[cfg(feature = "cli")]
+mod symbols;
 
 /// Tool name constants (always available for configuration)
 pub mod names {
     pub const READ_FILE: &str = "mcp_read_file";
+    pub const READ_SYMBOL: &str = "mcp_read_symbol";
     pub const WRITE_FILE: &str = "mcp_write_file";
     pub const EDIT_FILE: &str = "mcp_edit_file";
     pub const SHELL: &str = "mcp_shell";
     pub const FETCH_URL: &str = "mcp_fetch_url";
     pub const FETCH_HTML: &str = "mcp_fetch_html";
+    pub const HTTP_REQUEST: &str = "mcp_http_request";
+    pub const DOWNLOAD: &str = "mcp_download";
     pub const WEB_SEARCH: &str = "mcp_web_search";
     pub const OPEN_FILE: &str = "mcp_open_file";
     pub const SPAWN_AGENT: &str = "mcp_spawn_agent";
@@ -33,22 +55,52 @@ pub mod names {
     pub const LIST_AGENTS: &str = "mcp_list_agents";
     pub const GET_AGENT: &str = "mcp_get_agent";
     pub const RECORD_CORRECTION: &str = "mcp_record_correction";
+    pub const IDE_OPEN_FILES: &str = "mcp_ide_open_files";
+    pub const ANNOTATE: &str = "mcp_annotate";
+    pub const MEMORY_READ: &str = "mcp_memory_read";
+    pub const MEMORY_WRITE: &str = "mcp_memory_write";
+    pub const GIT_STATUS: &str = "mcp_git_status";
+    pub const GIT_STAGE: &str = "mcp_git_stage";
+    pub const REPLACE_IN_FILES: &str = "mcp_replace_in_files";
+
+    /// Tools offered in plan mode. All but `shell` cannot modify the
+    /// workspace; plan mode only lets it run inspection commands (see
+    /// `tool_filter::is_read_only_command`).
+    pub const READ_ONLY: &[&str] = &[
+        READ_FILE, READ_SYMBOL, SHELL, FETCH_URL, FETCH_HTML, WEB_SEARCH, OPEN_FILE, IDE_OPEN_FILES,
+        ANNOTATE, MEMORY_READ, GIT_STATUS,
+    ];
 }
 
+use crate::ide::ToolPreview;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 pub use crate::effect::EffectResult;
-pub use exec::{ToolCall, ToolDecision, ToolEvent, ToolExecutor};
+pub use audit::{ApprovalSource, AuditEntry, AuditLog};
+pub use exec::{PostEditCheck, ToolCall, ToolDecision, ToolEvent, ToolExecutor};
 #[cfg(feature = "cli")]
 pub use impls::{
-    init_agent_context, update_agent_oauth, EditFileTool, FetchHtmlTool, FetchUrlTool,
-    GetAgentTool, GetBackgroundTaskTool, ListAgentsTool, ListBackgroundTasksTool, OpenFileTool,
-    ReadFileTool, RecordCorrectionTool, ShellTool, SpawnAgentTool, WebSearchTool, WriteFileTool,
+    init_agent_context, update_agent_oauth, AnnotateTool, CustomCommandTool, DownloadTool, EditFileTool, FetchHtmlTool, FuzzyMatch,
+    FetchUrlTool, GetAgentTool, GitStageTool, GitStatusTool, GetBackgroundTaskTool, HttpRequestTool, IdeOpenFilesTool, ListAgentsTool,
+    ListBackgroundTasksTool, MemoryReadTool, MemoryWriteTool, OpenFileTool, ReadFileTool, ReadSymbolTool,
+    RecordCorrectionTool, ReplaceInFilesTool, ShellTool, SpawnAgentTool, WebSearchTool, WriteFileTool,
 };
 #[cfg(feature = "cli")]
-pub use browser::init_browser_context;
-pub use pipeline::{Effect, Step, Tool, ToolPipeline};
+pub use browser::BrowserContext;
+#[cfg(feature = "cli")]
+pub use backup::Backups;
+#[cfg(feature = "cli")]
+pub use file_cache::FileCache;
+#[cfg(feature = "cli")]
+pub use memory::{Memory, DEFAULT_MEMORY_MAX_BYTES};
+#[cfg(feature = "cli")]
+pub use read_tracker::ReadTracker;
+#[cfg(feature = "cli")]
+pub use remote::{RemotePath, Ssh};
+#[cfg(feature = "cli")]
+pub use sandbox::ShellSandbox;
+pub use pipeline::{Effect, Step, Tool, ToolCategory, ToolPipeline};
 
 #[cfg(feature = "cli")]
 use crate::transcript::{Block, BlockType, ToolBlock};
@@ -88,6 +140,7 @@ pub struct SimpleTool {
     name: &'static str,
     description: &'static str,
     schema: serde_json::Value,
+    preview: Option<Box<dyn Fn(&serde_json::Value) -> Option<ToolPreview> + Send + Sync>>,
 }
 
 impl SimpleTool {
@@ -97,7 +150,17 @@ impl SimpleTool {
     /// - `description`: Human-readable description of what the tool does
     /// - `schema`: JSON Schema describing the tool's parameters
     pub fn new(name: &'static str, description: &'static str, schema: serde_json::Value) -> Self {
-        Self { name, description, schema }
+        Self { name, description, schema, preview: None }
+    }
+
+    /// Compute a preview of each call from its params, returned by
+    /// [`Agent::preview_tool`] so it can be shown before approving the call
+    pub fn with_preview(
+        mut self,
+        preview: impl Fn(&serde_json::Value) -> Option<ToolPreview> + Send + Sync + 'static,
+    ) -> Self {
+        self.preview = Some(Box::new(preview));
+        self
     }
 }
 
@@ -114,17 +177,15 @@ impl Tool for SimpleTool {
         self.schema.clone()
     }
 
+    fn preview(&self, params: &serde_json::Value) -> Option<ToolPreview> {
+        self.preview.as_ref().and_then(|preview| preview(params))
+    }
+
     fn compose(&self, _params: serde_json::Value) -> ToolPipeline {
         // SimpleTool is for library users who handle tool execution themselves.
         // This method should never be called in that context.
         ToolPipeline::error("SimpleTool does not support compose() - handle tool calls via AgentStep::ToolRequest")
     }
-
-    #[cfg(feature = "cli")]
-    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
-        // Return a basic ToolBlock for compatibility
-        Box::new(ToolBlock::new(call_id, self.name, params, background))
-    }
 }
 
 /// Registry of available tools
@@ -141,20 +202,29 @@ impl ToolRegistry {
             tools: HashMap::new(),
         };
 
-        registry.register(Arc::new(ReadFileTool));
+        registry.register(Arc::new(ReadFileTool::new()));
+        registry.register(Arc::new(ReadSymbolTool));
         registry.register(Arc::new(WriteFileTool));
-        registry.register(Arc::new(EditFileTool));
+        registry.register(Arc::new(EditFileTool::new()));
+        registry.register(Arc::new(ReplaceInFilesTool::new()));
         registry.register(Arc::new(ShellTool::new()));
         registry.register(Arc::new(FetchUrlTool));
-        registry.register(Arc::new(FetchHtmlTool));
-        registry.register(Arc::new(WebSearchTool));
+        registry.register(Arc::new(FetchHtmlTool::new()));
+        registry.register(Arc::new(HttpRequestTool::new()));
+        registry.register(Arc::new(DownloadTool::new()));
+        registry.register(Arc::new(WebSearchTool::new()));
         registry.register(Arc::new(OpenFileTool));
+        registry.register(Arc::new(IdeOpenFilesTool));
+        registry.register(Arc::new(AnnotateTool));
         registry.register(Arc::new(SpawnAgentTool));
         registry.register(Arc::new(ListBackgroundTasksTool));
         registry.register(Arc::new(GetBackgroundTaskTool));
         registry.register(Arc::new(ListAgentsTool));
         registry.register(Arc::new(GetAgentTool));
         registry.register(Arc::new(RecordCorrectionTool));
+        registry.register(Arc::new(MemoryReadTool::new()));
+        registry.register(Arc::new(GitStatusTool));
+        registry.register(Arc::new(GitStageTool));
 
         registry
     }
@@ -170,14 +240,19 @@ impl ToolRegistry {
             tools: HashMap::new(),
         };
 
-        registry.register(Arc::new(ReadFileTool));
+        registry.register(Arc::new(ReadFileTool::new()));
+        registry.register(Arc::new(ReadSymbolTool));
         registry.register(Arc::new(WriteFileTool));
-        registry.register(Arc::new(EditFileTool));
+        registry.register(Arc::new(EditFileTool::new()));
+        registry.register(Arc::new(ReplaceInFilesTool::new()));
         registry.register(Arc::new(ShellTool::new()));
         registry.register(Arc::new(FetchUrlTool));
-        registry.register(Arc::new(FetchHtmlTool));
-        registry.register(Arc::new(WebSearchTool));
+        registry.register(Arc::new(FetchHtmlTool::new()));
+        registry.register(Arc::new(WebSearchTool::new()));
         registry.register(Arc::new(OpenFileTool));
+        registry.register(Arc::new(MemoryReadTool::new()));
+        registry.register(Arc::new(GitStatusTool));
+        registry.register(Arc::new(GitStageTool));
 
         registry
     }
@@ -188,12 +263,17 @@ impl ToolRegistry {
             tools: HashMap::new(),
         };
 
-        registry.register(Arc::new(ReadFileTool));
+        registry.register(Arc::new(ReadFileTool::new()));
+        registry.register(Arc::new(ReadSymbolTool));
         registry.register(Arc::new(ShellTool::new()));
         registry.register(Arc::new(FetchUrlTool));
-        registry.register(Arc::new(FetchHtmlTool));
-        registry.register(Arc::new(WebSearchTool));
+        registry.register(Arc::new(FetchHtmlTool::new()));
+        registry.register(Arc::new(WebSearchTool::new()));
         registry.register(Arc::new(OpenFileTool));
+        registry.register(Arc::new(IdeOpenFilesTool));
+        registry.register(Arc::new(AnnotateTool));
+        registry.register(Arc::new(MemoryReadTool::new()));
+        registry.register(Arc::new(GitStatusTool));
 
         registry
     }
@@ -208,11 +288,25 @@ impl ToolRegistry {
         self.tools.insert(tool.name().to_string(), tool);
     }
 
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.contains_key(name)
+    }
+
+    /// Look up a tool. The model can ask for tools that aren't registered
+    /// (a hallucinated name, or one a filter removed), so lookups of names
+    /// that came from the model must use this rather than `get`.
+    pub fn try_get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools.get(name).map(|t| t.as_ref())
+    }
+
+    /// Look up a tool known to be registered. Panics otherwise.
     pub fn get(&self, name: &str) -> &dyn Tool {
-        self.tools
-            .get(name)
-            .map(|t| t.as_ref())
-            .expect("unknown tool")
+        self.try_get(name).expect("unknown tool")
+    }
+
+    /// Shared handle to a tool, if registered
+    pub fn try_get_arc(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.get(name).cloned()
     }
 
     pub fn get_arc(&self, name: &str) -> Arc<dyn Tool> {
@@ -225,4 +319,15 @@ impl ToolRegistry {
     pub fn values(&self) -> impl Iterator<Item = &dyn Tool> {
         self.tools.values().map(|t| t.as_ref())
     }
+
+    /// Name, description and parameter JSON schema of every registered tool,
+    /// sorted by name: the definitions the agent advertises to the model
+    pub fn schemas(&self) -> Vec<(&'static str, &'static str, serde_json::Value)> {
+        let mut schemas: Vec<_> = self
+            .values()
+            .map(|tool| (tool.name(), tool.description(), tool.schema()))
+            .collect();
+        schemas.sort_by_key(|(name, _, _)| *name);
+        schemas
+    }
 }
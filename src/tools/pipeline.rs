@@ -12,8 +12,10 @@
 //! ]
 //! ```
 
+use crate::ide::ToolPreview;
 #[cfg(feature = "cli")]
-use crate::transcript::Block;
+use crate::transcript::{summarize_params, Block, ToolBlock, PARAM_SUMMARY_LEN};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
 pub use crate::effect::Effect;
@@ -24,8 +26,16 @@ pub enum Step {
     Continue,
     /// Set pipeline output
     Output(String),
+    /// Add to the end of the pipeline output
+    Append(String),
     /// Emit streaming content
     Delta(String),
+    /// Emit streaming content, then run this handler next. For long-running
+    /// effects that report progress as they go.
+    Progress(String, Box<dyn EffectHandler>),
+    /// Run these effects next, before the rest of the chain. For effects
+    /// that only know what follows once they've run.
+    Then(ToolPipeline),
     /// Delegate effect to app layer
     Delegate(Effect),
     /// Pause and wait for user approval
@@ -117,6 +127,18 @@ impl ToolPipeline {
         })
     }
 
+    /// Run a handler before the rest of the chain
+    pub fn push_front(&mut self, handler: Box<dyn EffectHandler>) {
+        self.effects.push_front(EffectTiming::Normal(handler));
+    }
+
+    /// Run another pipeline's effects before the rest of the chain
+    pub fn prepend(&mut self, pipeline: ToolPipeline) {
+        for effect in pipeline.effects.into_iter().rev() {
+            self.effects.push_front(effect);
+        }
+    }
+
     /// Skip to finally effects (for deny/error - removes all Normal effects)
     pub fn skip_to_finally(&mut self) {
         self.effects.retain(|e| matches!(e, EffectTiming::Finally(_)));
@@ -134,14 +156,70 @@ impl ToolPipeline {
     }
 }
 
+/// Coarse risk category of a tool, used for default approval behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolCategory {
+    /// Reads local state (files, task/agent status, the IDE)
+    Read,
+    /// Modifies files
+    Write,
+    /// Runs commands or other code
+    Exec,
+    /// Talks to the network
+    Network,
+}
+
 /// Tool that composes effect handlers
 pub trait Tool: Send + Sync {
     fn name(&self) -> &'static str;
     fn description(&self) -> &'static str;
     fn schema(&self) -> serde_json::Value;
+    /// Risk category; tools that don't declare one are treated as `Exec`
+    fn category(&self) -> ToolCategory {
+        ToolCategory::Exec
+    }
+    /// Whether this call must be confirmed even when the category default
+    /// or an allow pattern would approve it (a DELETE to a remote API). Only
+    /// the user can approve it; deny patterns still refuse it.
+    fn requires_confirmation(&self, _params: &serde_json::Value) -> bool {
+        false
+    }
     fn compose(&self, params: serde_json::Value) -> ToolPipeline;
+    /// What a call will do, for approval UIs that show it before the user
+    /// decides (None = nothing worth showing). Library users get it through
+    /// `Agent::preview_tool`.
+    fn preview(&self, _params: &serde_json::Value) -> Option<ToolPreview> {
+        None
+    }
+    /// Clean up a call's output before it reaches the agent (strip noise,
+    /// escape codes, tracking params). Applied by the executor once the
+    /// pipeline completes; the default leaves the output as it is.
+    fn post_process(&self, _params: &serde_json::Value, output: String) -> String {
+        output
+    }
+    /// One-line description of a call, shown after the tool name in the
+    /// transcript (`name(summary)`). Defaults to the call's key params.
     #[cfg(feature = "cli")]
-    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block>;
+    fn summarize(&self, params: &serde_json::Value) -> String {
+        summarize_params(params, PARAM_SUMMARY_LEN)
+    }
+    /// Whether the tool's output is markdown worth rendering (tables, lists)
+    /// rather than raw text shown as-is
+    fn output_is_markdown(&self) -> bool {
+        false
+    }
+    /// Block that displays calls to this tool. Defaults to a generic
+    /// `ToolBlock` headed by `summarize`.
+    #[cfg(feature = "cli")]
+    fn create_block(&self, call_id: &str, params: serde_json::Value, background: bool) -> Box<dyn Block> {
+        let summary = self.summarize(&params);
+        Box::new(
+            ToolBlock::new(call_id, self.name(), params, background)
+                .with_summary(summary)
+                .with_markdown(self.output_is_markdown()),
+        )
+    }
 }
 
 #[cfg(all(test, feature = "cli"))]
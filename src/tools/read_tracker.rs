@@ -0,0 +1,105 @@
+//! Which files the agent has read this session
+//!
+//! With `tools.require_read_before_edit`, `edit_file` refuses to touch a file
+//! the agent hasn't read with `read_file`, so it can't edit from a guess of
+//! what the file contains. A read only counts for the version of the file it
+//! saw: if the file's mtime or size changes afterwards, it has to be read
+//! again. The agent's own edits keep the read current.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Version of a file as seen when it was read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Session record of file reads, keyed by absolute path. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct ReadTracker {
+    reads: Arc<Mutex<HashMap<PathBuf, Stamp>>>,
+}
+
+impl ReadTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    /// Note that the current version of `path` has been read
+    pub fn record(&self, path: &Path) {
+        let Some(stamp) = Stamp::of(path) else {
+            return;
+        };
+        let mut reads = self.reads.lock().unwrap_or_else(|e| e.into_inner());
+        reads.insert(Self::key(path), stamp);
+    }
+
+    /// Check that the current version of `path` has been read, with an
+    /// instruction for the agent if it hasn't
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        let reads = self.reads.lock().unwrap_or_else(|e| e.into_inner());
+        match reads.get(&Self::key(path)) {
+            None => Err(format!(
+                "You haven't read {} yet. Read it with read_file before editing it.",
+                path.display()
+            )),
+            Some(stamp) if Stamp::of(path) != Some(*stamp) => Err(format!(
+                "{} changed on disk since you last read it. Read it again with read_file before editing it.",
+                path.display()
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Forget every read, so each file has to be read again before editing
+    pub fn clear(&self) {
+        self.reads.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_read_required_and_reset_on_change() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "fn a() {}\n").unwrap();
+
+        let tracker = ReadTracker::new();
+        assert!(tracker.check(&path).unwrap_err().contains("haven't read"));
+
+        tracker.clone().record(&dir.path().join(".").join("lib.rs"));
+        assert!(tracker.check(&path).is_ok());
+
+        fs::write(&path, "fn a() {}\nfn b() {}\n").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+        assert!(tracker.check(&path).unwrap_err().contains("changed on disk"));
+
+        tracker.record(&path);
+        assert!(tracker.check(&path).is_ok());
+    }
+}
@@ -0,0 +1,164 @@
+//! Remote file access over SSH
+//!
+//! `read_file` accepts `ssh://[user@]host[:port]/path` and scp-style
+//! `[user@]host:/path` paths, and reads them by running `cat` (or `tail`) on
+//! the host through the system `ssh` client. Authentication is left to ssh
+//! itself (agent, keys, `~/.ssh/config`); `BatchMode` is always set so a
+//! password prompt fails the read instead of hanging the tool.
+
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// Connection settings for remote paths (`[tools.ssh]`)
+#[derive(Debug, Clone)]
+pub struct Ssh {
+    /// Extra arguments for every ssh invocation (e.g. `["-i", "~/.ssh/dev"]`)
+    pub options: Vec<String>,
+    /// Give up on a remote command after this long
+    pub timeout_secs: u64,
+}
+
+/// A file on another host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePath {
+    /// `host` or `user@host`, as passed to ssh
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl RemotePath {
+    /// Parse a remote path. Returns None for anything that looks local.
+    ///
+    /// scp-style paths need an absolute or home-relative path after the colon
+    /// (`host:/etc/hosts`, `host:~/notes.md`) so `file.rs:12` isn't mistaken
+    /// for a host.
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(rest) = s.strip_prefix("ssh://") {
+            let (authority, path) = rest.split_at(rest.find('/')?);
+            let (host, port) = match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port.parse().ok()?)),
+                None => (authority, None),
+            };
+            // ssh://host/~/file is relative to the remote home directory
+            let path = path.strip_prefix("/~").map(|p| format!("~{}", p)).unwrap_or(path.to_string());
+            return Self::new(host, port, &path);
+        }
+
+        let (host, path) = s.split_once(':')?;
+        if host.contains('/') || !(path.starts_with('/') || path.starts_with("~/")) {
+            return None;
+        }
+        Self::new(host, None, path)
+    }
+
+    fn new(host: &str, port: Option<u16>, path: &str) -> Option<Self> {
+        if host.is_empty() || host.starts_with('-') || path.len() < 2 {
+            return None;
+        }
+        Some(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// The whole file
+    pub async fn read(&self, ssh: &Ssh) -> Result<String, String> {
+        self.run(ssh, &format!("cat -- {}", self.quoted_path())).await
+    }
+
+    /// The last `count` lines of the file
+    pub async fn tail(&self, ssh: &Ssh, count: usize) -> Result<String, String> {
+        self.run(ssh, &format!("tail -n {} -- {}", count, self.quoted_path()))
+            .await
+    }
+
+    /// The path quoted for the remote shell, leaving a leading `~/` to expand
+    fn quoted_path(&self) -> String {
+        match self.path.strip_prefix("~/") {
+            Some(rest) => format!("~/{}", shell_quote(rest)),
+            None => shell_quote(&self.path),
+        }
+    }
+
+    async fn run(&self, ssh: &Ssh, remote_command: &str) -> Result<String, String> {
+        let mut cmd = Command::new("ssh");
+        cmd.args(["-o", "BatchMode=yes"]);
+        if let Some(port) = self.port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        cmd.args(&ssh.options)
+            .arg(&self.host)
+            .arg(remote_command)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(Duration::from_secs(ssh.timeout_secs), cmd.output())
+            .await
+            .map_err(|_| format!("Timed out after {}s reading {}", ssh.timeout_secs, self))?
+            .map_err(|e| format!("Failed to run ssh: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to read {}: {}", self, stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl fmt::Display for RemotePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "ssh://{}:{}/{}", self.host, port, self.path.trim_start_matches('/')),
+            None => write!(f, "{}:{}", self.host, self.path),
+        }
+    }
+}
+
+/// Quote a string as a single POSIX shell word
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(host: &str, port: Option<u16>, path: &str) -> Option<RemotePath> {
+        Some(RemotePath {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_parse_remote_paths() {
+        assert_eq!(RemotePath::parse("ssh://dev/var/log/app.log"), remote("dev", None, "/var/log/app.log"));
+        assert_eq!(
+            RemotePath::parse("ssh://deploy@dev:2222/etc/hosts"),
+            remote("deploy@dev", Some(2222), "/etc/hosts")
+        );
+        assert_eq!(RemotePath::parse("ssh://dev/~/notes.md"), remote("dev", None, "~/notes.md"));
+        assert_eq!(RemotePath::parse("deploy@dev:/etc/hosts"), remote("deploy@dev", None, "/etc/hosts"));
+        assert_eq!(RemotePath::parse("dev:~/notes.md"), remote("dev", None, "~/notes.md"));
+
+        // Local paths
+        assert_eq!(RemotePath::parse("src/main.rs"), None);
+        assert_eq!(RemotePath::parse("src/main.rs:12"), None);
+        assert_eq!(RemotePath::parse("./a:/b"), None);
+        assert_eq!(RemotePath::parse("ssh://dev"), None);
+        assert_eq!(RemotePath::parse("-oProxyCommand=x:/etc/hosts"), None);
+    }
+
+    #[test]
+    fn test_quoted_path() {
+        let path = RemotePath::parse("dev:/tmp/it's here").unwrap();
+        assert_eq!(path.quoted_path(), "'/tmp/it'\\''s here'");
+        let home = RemotePath::parse("dev:~/a b").unwrap();
+        assert_eq!(home.quoted_path(), "~/'a b'");
+    }
+}
@@ -0,0 +1,157 @@
+//! Restricted environment for shell commands
+//!
+//! With `[tools.shell.sandbox]` enabled, `shell` commands, custom tools and
+//! the post-edit check run with only an allowlist of environment variables
+//! and a fixed `PATH`, optionally under a wrapper such as `firejail`, `bwrap`
+//! or `unshare`. A wrapper that can't be
+//! found is dropped with a warning rather than failing every command, so the
+//! environment restrictions still apply.
+
+use std::ffi::OsStr;
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::config::ShellSandboxConfig;
+
+/// Flags that cut off network access, by wrapper program. unshare can only
+/// make a network namespace as root, so it also maps the user to root in a
+/// new user namespace.
+const NO_NETWORK_FLAGS: &[(&str, &[&str])] = &[
+    ("firejail", &["--net=none"]),
+    ("bwrap", &["--unshare-net"]),
+    ("unshare", &["--map-root-user", "--net"]),
+];
+
+/// How sandboxed shell commands are launched
+#[derive(Debug, Clone)]
+pub struct ShellSandbox {
+    /// Wrapper argv, with the program resolved to an absolute path
+    wrapper: Vec<String>,
+    env: Vec<String>,
+    path: String,
+    /// Why the sandbox is weaker than configured, if it is
+    warnings: Vec<String>,
+}
+
+impl ShellSandbox {
+    pub fn new(config: &ShellSandboxConfig) -> Self {
+        let mut warnings = Vec::new();
+        let mut wrapper = Vec::new();
+
+        if let Some((program, args)) = config.wrapper.split_first() {
+            match which(program) {
+                Some(resolved) => {
+                    wrapper.push(resolved);
+                    wrapper.extend(args.iter().cloned());
+                },
+                None => warnings.push(format!(
+                    "Shell sandbox wrapper `{}` not found; commands run without it (environment is still restricted)",
+                    program
+                )),
+            }
+        }
+
+        if config.no_network {
+            let flags = wrapper.first().and_then(|program| {
+                let name = Path::new(program).file_name()?.to_str()?;
+                NO_NETWORK_FLAGS
+                    .iter()
+                    .find(|(wrapper, _)| *wrapper == name)
+                    .map(|(_, flags)| *flags)
+            });
+            match flags {
+                Some(flags) => wrapper.extend(flags.iter().map(|flag| flag.to_string())),
+                None => warnings.push(
+                    "Shell sandbox no_network needs a firejail, bwrap or unshare wrapper; network access is not restricted"
+                        .to_string(),
+                ),
+            }
+        }
+
+        Self {
+            wrapper,
+            env: config.env.clone(),
+            path: config.path.clone(),
+            warnings,
+        }
+    }
+
+    /// Ways the sandbox falls short of its config, to show at startup
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// A command that runs `script` with bash inside the sandbox
+    pub fn command(&self, script: &str) -> Command {
+        self.argv_command("bash", ["-c", script])
+    }
+
+    /// A command that runs `program` directly, without a shell, inside the sandbox
+    pub fn argv_command<I, S>(&self, program: &str, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd = match self.wrapper.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                let mut cmd = Command::new(wrapper);
+                cmd.args(wrapper_args).arg(program);
+                cmd
+            },
+            None => Command::new(program),
+        };
+        cmd.args(args);
+
+        cmd.env_clear();
+        for name in &self.env {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+        cmd.env("PATH", &self.path);
+        cmd
+    }
+}
+
+/// Resolve a program against the host PATH, since sandboxed commands get a
+/// different one
+fn which(program: &str) -> Option<String> {
+    if program.contains('/') {
+        return Path::new(program).is_file().then(|| program.to_string());
+    }
+    std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+            .map(|candidate| candidate.to_string_lossy().into_owned())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::io;
+
+    #[tokio::test]
+    async fn test_sandbox_scrubs_environment() {
+        std::env::set_var("CODEY_SANDBOX_SECRET", "hunter2");
+        let sandbox = ShellSandbox::new(&ShellSandboxConfig {
+            enabled: true,
+            wrapper: vec!["codey-no-such-wrapper".to_string()],
+            no_network: true,
+            ..Default::default()
+        });
+        assert_eq!(sandbox.warnings().len(), 2);
+
+        let cmd = sandbox.command("echo \"[$CODEY_SANDBOX_SECRET]\" \"$PATH\"");
+        let result = io::execute_prepared(cmd, None, 10, None).await.unwrap();
+        assert!(result.output.contains("[]"));
+        assert!(result.output.contains("/usr/local/bin:/usr/bin:/bin"));
+        assert!(!result.output.contains("hunter2"));
+
+        let cmd = sandbox.argv_command("printenv", ["CODEY_SANDBOX_SECRET"]);
+        let result = io::execute_prepared(cmd, None, 10, None).await.unwrap();
+        assert!(!result.output.contains("hunter2"));
+    }
+}
@@ -0,0 +1,324 @@
+//! Locate a named function, type or class in a source file
+//!
+//! A lightweight, line-based extractor used by `read_symbol`. Declarations are
+//! matched with per-language patterns and their extent is found by brace
+//! matching (Rust, Go, JavaScript/TypeScript) or indentation (Python). It
+//! doesn't parse the language, so unusual formatting can defeat it; the agent
+//! can always fall back to `read_file` with a line range.
+
+use std::path::Path;
+
+use regex::Regex;
+
+/// Languages the extractor understands, keyed on file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    Go,
+    JavaScript,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::JavaScript),
+            _ => None,
+        }
+    }
+
+    /// Declaration patterns; `{name}` is replaced with the escaped symbol name
+    fn patterns(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &[
+                r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*fn\s+{name}\b"#,
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:struct|enum|union|trait|type|mod)\s+{name}\b",
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const|static)\s+(?:mut\s+)?{name}\s*:",
+                r"^\s*(?:unsafe\s+)?impl\b(?:<.*?>)?\s+(?:.*?\s+for\s+)?(?:\w+::)*{name}\b",
+                r"^\s*macro_rules!\s+{name}\b",
+            ],
+            Self::Python => &[r"^\s*(?:async\s+)?def\s+{name}\b", r"^\s*class\s+{name}\b"],
+            Self::Go => &[
+                r"^func\s+(?:\([^)]*\)\s*)?{name}\b",
+                r"^\s*type\s+{name}\b",
+                r"^(?:var|const)\s+{name}\b",
+            ],
+            Self::JavaScript => &[
+                r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*{name}\b",
+                r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+{name}\b",
+                r"^\s*(?:export\s+)?(?:declare\s+)?(?:interface|type|enum)\s+{name}\b",
+                r"^\s*(?:export\s+)?(?:const|let|var)\s+{name}\b",
+                r"^\s*(?:(?:public|private|protected|static|async|readonly|get|set)\s+)*{name}\s*(?:<[^>]*>)?\([^)]*\)?[^;]*\{\s*$",
+            ],
+        }
+    }
+}
+
+/// Where a symbol was found (1-indexed, inclusive line numbers)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find every declaration of `symbol` in `content`.
+///
+/// `symbol` may be qualified with its container, e.g. `Config::load` or
+/// `Parser.parse`, to pick a method out of a type's `impl`/class body.
+/// Spans include doc comments, attributes and decorators directly above
+/// the declaration.
+pub fn find_symbol(language: Language, content: &str, symbol: &str) -> Vec<SymbolSpan> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (container, name) = match symbol.rsplit_once("::").or_else(|| symbol.rsplit_once('.')) {
+        Some((container, name)) => (Some(container), name),
+        None => (None, symbol),
+    };
+
+    let scopes = match container {
+        Some(container) => declarations(language, &lines, container, 0..lines.len()),
+        None => vec![0..lines.len()],
+    };
+
+    scopes
+        .into_iter()
+        .flat_map(|scope| {
+            // Skip the container's own declaration line when searching inside it
+            let scope = match container {
+                Some(_) => scope.start + 1..scope.end,
+                None => scope,
+            };
+            declarations(language, &lines, name, scope)
+        })
+        .map(|range| SymbolSpan {
+            start: leading_comments(&lines, range.start) + 1,
+            end: range.end,
+        })
+        .collect()
+}
+
+/// Line ranges (0-indexed, end exclusive) of declarations of `name` within `scope`
+fn declarations(
+    language: Language,
+    lines: &[&str],
+    name: &str,
+    scope: std::ops::Range<usize>,
+) -> Vec<std::ops::Range<usize>> {
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let patterns: Vec<Regex> = language
+        .patterns()
+        .iter()
+        .filter_map(|p| Regex::new(&p.replace("{name}", &regex::escape(name))).ok())
+        .collect();
+
+    let mut found = Vec::new();
+    let mut i = scope.start;
+    while i < scope.end {
+        if patterns.iter().any(|re| re.is_match(lines[i])) {
+            let end = match language {
+                Language::Python => indented_block_end(lines, i),
+                _ => braced_block_end(language, lines, i),
+            }
+            .min(scope.end);
+            found.push(i..end);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// Index of the first line above `start` that belongs to its doc comments,
+/// attributes or decorators (or `start` itself if there are none)
+fn leading_comments(lines: &[&str], start: usize) -> usize {
+    let mut first = start;
+    while first > 0 {
+        let above = lines[first - 1].trim_start();
+        if ["//", "#", "@", "/*", "*"].iter().any(|p| above.starts_with(p)) {
+            first -= 1;
+        } else {
+            break;
+        }
+    }
+    first
+}
+
+/// End (exclusive) of a declaration delimited by braces. A declaration that
+/// reaches `;` before any `{` (e.g. `struct Unit;`) ends on that line.
+fn braced_block_end(language: Language, lines: &[&str], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut opened = false;
+    let mut in_block_comment = false;
+
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let mut chars = line.chars().peekable();
+        let mut in_string: Option<char> = None;
+        while let Some(c) = chars.next() {
+            if in_block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            if let Some(quote) = in_string {
+                if c == '\\' {
+                    chars.next();
+                } else if c == quote {
+                    in_string = None;
+                }
+                continue;
+            }
+            match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    in_block_comment = true;
+                },
+                '"' | '`' => in_string = Some(c),
+                '\'' if language == Language::JavaScript => in_string = Some(c),
+                // Char literals like '{' (but not lifetimes like 'a)
+                '\'' => {
+                    let mut ahead = chars.clone();
+                    match (ahead.next(), ahead.next(), ahead.next()) {
+                        (Some('\\'), Some(_), Some('\'')) => {
+                            chars.nth(2);
+                        },
+                        (Some(_), Some('\''), _) => {
+                            chars.nth(1);
+                        },
+                        _ => {},
+                    }
+                },
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                },
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    if opened && depth == 0 {
+                        return i + 1;
+                    }
+                },
+                ';' if !opened => return i + 1,
+                _ => {},
+            }
+        }
+    }
+    lines.len()
+}
+
+/// End (exclusive) of a Python `def`/`class`: the signature, then every
+/// following line indented deeper than the declaration
+fn indented_block_end(lines: &[&str], start: usize) -> usize {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let base = indent(lines[start]);
+
+    // The signature may span several lines
+    let mut parens = 0i32;
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        parens += line.matches(['(', '[', '{']).count() as i32;
+        parens -= line.matches([')', ']', '}']).count() as i32;
+        end = i + 1;
+        if parens <= 0 {
+            break;
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate().skip(end) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= base {
+            break;
+        }
+        end = i + 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST: &str = r#"use std::fmt;
+
+/// A point
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+}
+
+pub struct Unit;
+
+impl Point {
+    pub fn new(x: i32) -> Self {
+        let s = "}";
+        Self { x }
+    }
+
+    fn norm(&self) -> i32 {
+        self.x.abs()
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.x)
+    }
+}
+"#;
+
+    const PYTHON: &str = r#"import os
+
+class Parser:
+    def __init__(self):
+        self.x = 1
+
+    @property
+    def parse(self,
+              text):
+
+        return text.strip()
+
+def main():
+    Parser().parse("x")
+"#;
+
+    fn spans(language: Language, content: &str, symbol: &str) -> Vec<(usize, usize)> {
+        find_symbol(language, content, symbol)
+            .into_iter()
+            .map(|s| (s.start, s.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_symbols() {
+        // The struct (with its doc comment and attribute) and both impl blocks
+        assert_eq!(spans(Language::Rust, RUST, "Point"), vec![(3, 7), (11, 20), (22, 26)]);
+        assert_eq!(spans(Language::Rust, RUST, "Unit"), vec![(9, 9)]);
+        assert_eq!(spans(Language::Rust, RUST, "new"), vec![(12, 15)]);
+        assert_eq!(spans(Language::Rust, RUST, "Point::norm"), vec![(17, 19)]);
+        assert!(spans(Language::Rust, RUST, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_python_symbols() {
+        assert_eq!(spans(Language::Python, PYTHON, "Parser"), vec![(3, 11)]);
+        assert_eq!(spans(Language::Python, PYTHON, "Parser.parse"), vec![(7, 11)]);
+        assert_eq!(spans(Language::Python, PYTHON, "main"), vec![(13, 14)]);
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(Language::from_path(Path::new("src/main.rs")), Some(Language::Rust));
+        assert_eq!(Language::from_path(Path::new("app.tsx")), Some(Language::JavaScript));
+        assert_eq!(Language::from_path(Path::new("notes.txt")), None);
+    }
+}
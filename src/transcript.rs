@@ -4,6 +4,7 @@
 //! A Transcript contains Turns, and each Turn contains Blocks.
 #![allow(dead_code)]
 
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -29,6 +30,79 @@ pub fn next_block_id() -> usize {
     NEXT_BLOCK_ID.fetch_add(1, Ordering::Relaxed)
 }
 
+/// Default for `ui.max_line_length`
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 4000;
+
+/// Appended where a long line was cut
+pub const LONG_LINE_MARKER: &str = " [long line truncated]";
+
+/// Longest line, in characters, passed on to wrapping and rendering
+/// (`ui.max_line_length`). A single huge line (minified JS, a JSON blob)
+/// would otherwise be wrapped and re-rendered in full on every frame.
+static MAX_LINE_LENGTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LINE_LENGTH);
+
+/// Set the line length cap used by `clamp_long_lines` and `clamp_line`
+pub fn set_max_line_length(max: usize) {
+    MAX_LINE_LENGTH.store(max.max(1), Ordering::Relaxed);
+}
+
+/// Byte offset at which `line` exceeds `max` characters, if it does
+fn cut_point(line: &str, max: usize) -> Option<usize> {
+    if line.len() <= max {
+        return None;
+    }
+    line.char_indices().nth(max).map(|(i, _)| i)
+}
+
+/// Cut each line of `text` longer than `ui.max_line_length`, marking the cut
+pub fn clamp_long_lines(text: &str) -> Cow<'_, str> {
+    let max = MAX_LINE_LENGTH.load(Ordering::Relaxed);
+    if text.len() <= max || !text.split('\n').any(|line| cut_point(line, max).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    let mut clamped = String::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            clamped.push('\n');
+        }
+        match cut_point(line, max) {
+            Some(end) => {
+                clamped.push_str(&line[..end]);
+                clamped.push_str(LONG_LINE_MARKER);
+            },
+            None => clamped.push_str(line),
+        }
+    }
+    Cow::Owned(clamped)
+}
+
+/// Cut a rendered line longer than `ui.max_line_length`, for lines built
+/// from text that didn't go through `clamp_long_lines`
+#[cfg(feature = "cli")]
+pub fn clamp_line(line: Line<'static>) -> Line<'static> {
+    let max = MAX_LINE_LENGTH.load(Ordering::Relaxed);
+    if line.spans.iter().map(|span| span.content.len()).sum::<usize>() <= max {
+        return line;
+    }
+    let Line { spans: original, style, alignment } = line;
+    let mut spans = Vec::new();
+    let mut remaining = max;
+    let mut truncated = false;
+    for span in original {
+        if let Some(end) = cut_point(&span.content, remaining) {
+            spans.push(Span::styled(span.content[..end].to_string(), span.style));
+            truncated = true;
+            break;
+        }
+        remaining -= span.content.chars().count();
+        spans.push(span);
+    }
+    if truncated {
+        spans.push(Span::styled(LONG_LINE_MARKER, Style::default().fg(Color::DarkGray)));
+    }
+    Line { spans, style, alignment }
+}
+
 /// Role of the message sender
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -160,10 +234,27 @@ pub trait Block: Send + Sync {
     /// Set the agent label (for sub-agent tools)
     fn set_agent_label(&mut self, _label: String) {}
 
+    /// Toggle display of the full tool params.
+    /// Returns false if this block has nothing to expand.
+    fn toggle_expanded(&mut self) -> bool {
+        false
+    }
+
     /// Get the agent label (for sub-agent tools)
     fn agent_label(&self) -> Option<&str> {
         None
     }
+
+    /// Get the transcript this block links back to (for restoring compacted context)
+    fn linked_transcript(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Facts a compaction summary cites, with their turns in the linked transcript
+    #[cfg(feature = "cli")]
+    fn compaction_sources(&self) -> &[crate::compaction::CompactionSource] {
+        &[]
+    }
 }
 
 /// Macro to implement common Block trait methods for blocks with text and status fields
@@ -254,14 +345,42 @@ macro_rules! impl_tool_block {
 ///     }
 /// }
 /// ```
+///
+/// A block can also declare extra fields, which start out as their
+/// `Default`, and lines to show below the header while it's collapsed:
+///
+/// ```ignore
+/// define_tool_block! {
+///     pub struct MyToolBlock {
+///         max_lines: 5,
+///         params_type: MyToolParams,
+///         fields {
+///             #[serde(default)]
+///             pub verbose: bool,
+///         },
+///         render_header(self, params) { ... }
+///         render_details(self) { ... } // returns Vec<Line<'static>>
+///     }
+/// }
+/// ```
 #[macro_export]
 macro_rules! define_tool_block {
+    (@details $lines:ident, $this:ident,) => {};
+    (@details $lines:ident, $this:ident, $details_self:ident) => {
+        if !$this.expanded {
+            $lines.extend($this.render_detail_lines());
+        }
+    };
     (
         $(#[$attr:meta])*
         $vis:vis struct $name:ident {
             max_lines: $max_lines:expr,
             params_type: $params_type:ty,
+            $(fields {
+                $( $(#[$field_attr:meta])* $field_vis:vis $field:ident: $field_ty:ty ),* $(,)?
+            },)?
             render_header($self:ident, $params:ident) $render_body:block
+            $(render_details($details_self:ident) $details_body:block)?
         }
     ) => {
         $(#[$attr])*
@@ -274,8 +393,15 @@ macro_rules! define_tool_block {
             pub text: String,
             #[serde(default)]
             pub background: bool,
+            /// Show the full params below the header
+            #[serde(skip)]
+            pub expanded: bool,
+            /// Render the result as markdown (`Tool::output_is_markdown`)
+            #[serde(default)]
+            pub markdown: bool,
             #[serde(default, skip_serializing_if = "Option::is_none")]
             pub agent_label: Option<String>,
+            $($( $(#[$field_attr])* $field_vis $field: $field_ty, )*)?
         }
 
         impl $name {
@@ -292,10 +418,19 @@ macro_rules! define_tool_block {
                     status: Status::Pending,
                     text: String::new(),
                     background,
+                    expanded: false,
+                    markdown: false,
                     agent_label: None,
+                    $($( $field: Default::default(), )*)?
                 }
             }
 
+            /// Render the result as markdown instead of plain lines
+            pub fn with_markdown(mut self, markdown: bool) -> Self {
+                self.markdown = markdown;
+                self
+            }
+
             pub fn from_params(
                 call_id: &str,
                 tool_name: &str,
@@ -311,13 +446,15 @@ macro_rules! define_tool_block {
                 let $params = &$self.params;
                 $render_body
             }
+
+            $(fn render_detail_lines(&$details_self) -> Vec<Line<'static>> $details_body)?
         }
 
         #[typetag::serde]
         impl Block for $name {
             $crate::impl_tool_block!(BlockType::Tool);
 
-            fn render(&self, _width: u16) -> Vec<Line<'_>> {
+            fn render(&self, width: u16) -> Vec<Line<'_>> {
                 let mut lines = Vec::new();
 
                 // Build header line: status + agent_label + prefix + custom spans
@@ -329,13 +466,20 @@ macro_rules! define_tool_block {
                 spans.extend(self.render_header_spans());
                 lines.push(Line::from(spans));
 
+                if self.expanded {
+                    lines.extend($crate::transcript::render_params(&self.params));
+                }
+                $crate::define_tool_block!(@details lines, self, $($details_self)?);
+
                 // Approval prompt if pending
                 if self.status == Status::Pending {
                     lines.push(render_approval_prompt());
                 }
 
                 // Result output
-                if !self.text.is_empty() {
+                if self.markdown && !self.text.is_empty() {
+                    lines.extend($crate::transcript::render_markdown_result(&self.text, width, $max_lines));
+                } else if !self.text.is_empty() {
                     lines.extend(render_result(&self.text, $max_lines));
                 }
 
@@ -362,6 +506,11 @@ macro_rules! define_tool_block {
                 Some(&self.params)
             }
 
+            fn toggle_expanded(&mut self) -> bool {
+                self.expanded = !self.expanded;
+                true
+            }
+
             fn set_agent_label(&mut self, label: String) {
                 self.agent_label = Some(label);
             }
@@ -394,6 +543,12 @@ macro_rules! define_simple_tool_block {
             pub text: String,
             #[serde(default)]
             pub background: bool,
+            /// Show the full params below the header
+            #[serde(skip)]
+            pub expanded: bool,
+            /// Render the result as markdown (`Tool::output_is_markdown`)
+            #[serde(default)]
+            pub markdown: bool,
         }
 
         impl $name {
@@ -410,9 +565,17 @@ macro_rules! define_simple_tool_block {
                     status: Status::Pending,
                     text: String::new(),
                     background,
+                    expanded: false,
+                    markdown: false,
                 }
             }
 
+            /// Render the result as markdown instead of plain lines
+            pub fn with_markdown(mut self, markdown: bool) -> Self {
+                self.markdown = markdown;
+                self
+            }
+
             #[allow(unused_variables)]
             fn render_header_spans(&$self) -> Vec<Span<'static>> {
                 let $params = &$self.params;
@@ -424,7 +587,7 @@ macro_rules! define_simple_tool_block {
         impl Block for $name {
             $crate::impl_tool_block!(BlockType::Tool);
 
-            fn render(&self, _width: u16) -> Vec<Line<'_>> {
+            fn render(&self, width: u16) -> Vec<Line<'_>> {
                 let mut lines = Vec::new();
 
                 // Build header line: status + prefix + custom spans
@@ -435,13 +598,19 @@ macro_rules! define_simple_tool_block {
                 spans.extend(self.render_header_spans());
                 lines.push(Line::from(spans));
 
+                if self.expanded {
+                    lines.extend($crate::transcript::render_params(&self.params));
+                }
+
                 // Approval prompt if pending
                 if self.status == Status::Pending {
                     lines.push(render_approval_prompt());
                 }
 
                 // Result output
-                if !self.text.is_empty() {
+                if self.markdown && !self.text.is_empty() {
+                    lines.extend($crate::transcript::render_markdown_result(&self.text, width, $max_lines));
+                } else if !self.text.is_empty() {
                     lines.extend(render_result(&self.text, $max_lines));
                 }
 
@@ -467,6 +636,11 @@ macro_rules! define_simple_tool_block {
             fn params(&self) -> Option<&serde_json::Value> {
                 Some(&self.params)
             }
+
+            fn toggle_expanded(&mut self) -> bool {
+                self.expanded = !self.expanded;
+                true
+            }
         }
     };
 }
@@ -514,9 +688,7 @@ impl Block for TextBlock {
     #[cfg(feature = "cli")]
     fn render(&self, width: u16) -> Vec<Line<'_>> {
         // Use ratskin for markdown rendering
-        let skin = ratskin::RatSkin::default();
-        let text = ratskin::RatSkin::parse_text(&self.text);
-        skin.parse(text, width)
+        render_markdown(&self.text, width)
     }
 }
 
@@ -550,7 +722,10 @@ impl Block for ThinkingBlock {
             .fg(Color::DarkGray)
             .add_modifier(Modifier::ITALIC);
 
-        let wrapped = textwrap::wrap(&self.text, width as usize);
+        let wrapped = textwrap::wrap(&clamp_long_lines(&self.text), width as usize)
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect::<Vec<_>>();
         for line in wrapped {
             lines.push(Line::from(Span::styled(line, style)));
         }
@@ -574,6 +749,16 @@ pub struct ToolBlock {
     /// Agent label for sub-agent tools
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent_label: Option<String>,
+    /// One-line summary from the tool (`Tool::summarize`); None falls back
+    /// to listing the params
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Show the full params instead of the one-line summary
+    #[serde(skip)]
+    pub expanded: bool,
+    /// Render the result as markdown (`Tool::output_is_markdown`)
+    #[serde(default)]
+    pub markdown: bool,
 }
 
 impl ToolBlock {
@@ -592,8 +777,23 @@ impl ToolBlock {
             text: String::new(),
             background,
             agent_label: None,
+            summary: None,
+            expanded: false,
+            markdown: false,
         }
     }
+
+    /// Show `summary` after the tool name instead of the params
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Render the result as markdown instead of plain lines
+    pub fn with_markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
+    }
 }
 
 #[typetag::serde]
@@ -601,11 +801,15 @@ impl Block for ToolBlock {
     impl_base_block!(BlockType::Tool);
 
     #[cfg(feature = "cli")]
-    fn render(&self, _width: u16) -> Vec<Line<'_>> {
+    fn render(&self, width: u16) -> Vec<Line<'_>> {
         let mut lines = Vec::new();
 
+        // Params are shown in full while awaiting approval, so the user sees
+        // exactly what they're allowing; otherwise only on request.
+        let show_params = self.expanded || self.status == Status::Pending;
+
         // Tool name with status icon, optional agent label, and optional [bg] prefix
-        lines.push(Line::from(vec![
+        let mut header = vec![
             self.render_status(),
             render_agent_label(self.agent_label.as_deref()),
             render_prefix(self.background),
@@ -615,21 +819,23 @@ impl Block for ToolBlock {
                     .fg(Color::Magenta)
                     .add_modifier(Modifier::BOLD),
             ),
-        ]));
-
-        // Params
-        let params_str = serde_json::to_string_pretty(&self.params).unwrap_or_default();
-        for param_line in params_str.lines().take(10) {
-            lines.push(Line::from(Span::styled(
-                format!("  {}", param_line),
-                Style::default().fg(Color::DarkGray),
-            )));
+        ];
+        if !show_params {
+            let summary = match &self.summary {
+                Some(summary) => summary.clone(),
+                None => summarize_params(&self.params, PARAM_SUMMARY_LEN),
+            };
+            if !summary.is_empty() {
+                header.push(Span::styled(
+                    format!("({})", summary),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
         }
-        if params_str.lines().count() > 10 {
-            lines.push(Line::from(Span::styled(
-                "  ...",
-                Style::default().fg(Color::DarkGray),
-            )));
+        lines.push(Line::from(header));
+
+        if show_params {
+            lines.extend(render_params(&self.params));
         }
 
         // Approval prompt if pending
@@ -638,7 +844,9 @@ impl Block for ToolBlock {
         }
 
         // Result if completed
-        if !self.text.is_empty() {
+        if self.markdown && !self.text.is_empty() {
+            lines.extend(render_markdown_result(&self.text, width, 5));
+        } else if !self.text.is_empty() {
             lines.extend(render_result(&self.text, 5));
         }
 
@@ -672,6 +880,11 @@ impl Block for ToolBlock {
     fn agent_label(&self) -> Option<&str> {
         self.agent_label.as_deref()
     }
+
+    fn toggle_expanded(&mut self) -> bool {
+        self.expanded = !self.expanded;
+        true
+    }
 }
 
 /// Notification block for mid-turn injected messages
@@ -754,7 +967,17 @@ pub fn render_approval_prompt() -> Line<'static> {
             "n",
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         ),
-        Span::styled("]o", Style::default().fg(Color::DarkGray)),
+        Span::styled("]o  [", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            "e",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("]xpand  [", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            "p",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("]arams", Style::default().fg(Color::DarkGray)),
     ])
 }
 
@@ -764,7 +987,7 @@ pub fn render_result(result: &str, max_lines: usize) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
     let preview_lines: Vec<&str> = result.lines().take(max_lines).collect();
     for line in &preview_lines {
-        let formatted = format_for_user(line, DEFAULT_TAB_WIDTH);
+        let formatted = format_for_user(&clamp_long_lines(line), DEFAULT_TAB_WIDTH);
         lines.push(Line::from(Span::styled(
             format!("  {}", formatted),
             Style::default().fg(Color::DarkGray),
@@ -779,6 +1002,184 @@ pub fn render_result(result: &str, max_lines: usize) -> Vec<Line<'static>> {
     lines
 }
 
+/// Helper: render markdown with ratskin, long lines clamped first
+#[cfg(feature = "cli")]
+pub fn render_markdown(text: &str, width: u16) -> Vec<Line<'_>> {
+    let skin = ratskin::RatSkin::default();
+    match clamp_long_lines(text) {
+        Cow::Borrowed(text) => skin.parse(ratskin::RatSkin::parse_text(text), width),
+        // Clamped text is a temporary, so the lines can't borrow from it
+        Cow::Owned(text) => skin
+            .parse(ratskin::RatSkin::parse_text(&text), width)
+            .into_iter()
+            .map(|line| {
+                let spans: Vec<Span<'static>> = line
+                    .spans
+                    .into_iter()
+                    .map(|span| Span::styled(span.content.into_owned(), span.style))
+                    .collect();
+                Line::from(spans)
+            })
+            .collect(),
+    }
+}
+
+/// Helper: render a markdown result (tables, lists, emphasis) with line
+/// limit, indented like `render_result`
+#[cfg(feature = "cli")]
+pub fn render_markdown_result(result: &str, width: u16, max_lines: usize) -> Vec<Line<'_>> {
+    let rendered = render_markdown(result, width.saturating_sub(2));
+    let truncated = rendered.len() > max_lines;
+
+    let mut lines: Vec<Line> = rendered
+        .into_iter()
+        .take(max_lines)
+        .map(|mut line| {
+            line.spans.insert(0, Span::raw("  "));
+            line
+        })
+        .collect();
+    if truncated {
+        lines.push(Line::from(Span::styled(
+            "  ...",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines
+}
+
+/// Max characters in the one-line params summary of a collapsed tool block
+#[cfg(feature = "cli")]
+pub const PARAM_SUMMARY_LEN: usize = 80;
+
+/// Max lines of params shown when a tool block is expanded
+#[cfg(feature = "cli")]
+const MAX_PARAM_LINES: usize = 40;
+
+/// Params that identify what a tool call acts on; listed first in summaries
+#[cfg(feature = "cli")]
+const PRIMARY_PARAMS: &[&str] = &["path", "file_path", "command", "url", "query", "pattern", "name"];
+
+/// Helper: one-line summary of tool params, e.g. `path: "src/main.rs", limit: 20`
+#[cfg(feature = "cli")]
+pub fn summarize_params(params: &serde_json::Value, max_len: usize) -> String {
+    use serde_json::Value;
+
+    fn summarize_value(value: &Value) -> String {
+        match value {
+            Value::String(s) => {
+                let first = s.lines().next().unwrap_or("");
+                if first.chars().count() > 40 || first.len() < s.trim_end().len() {
+                    let head: String = first.chars().take(40).collect();
+                    format!("\"{}…\"", head)
+                } else {
+                    format!("\"{}\"", first)
+                }
+            },
+            Value::Array(items) => format!("[{} items]", items.len()),
+            Value::Object(map) if map.is_empty() => "{}".to_string(),
+            Value::Object(_) => "{…}".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    let summary = match params {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort_by_key(|k| {
+                PRIMARY_PARAMS
+                    .iter()
+                    .position(|p| p == k)
+                    .unwrap_or(PRIMARY_PARAMS.len())
+            });
+            keys.iter()
+                .map(|k| format!("{}: {}", k, summarize_value(&map[k.as_str()])))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+        Value::Null => String::new(),
+        other => summarize_value(other),
+    };
+
+    if summary.chars().count() > max_len {
+        let head: String = summary.chars().take(max_len.saturating_sub(1)).collect();
+        format!("{}…", head)
+    } else {
+        summary
+    }
+}
+
+/// Helper: render params as indented, syntax-highlighted JSON
+#[cfg(feature = "cli")]
+pub fn render_params(params: &serde_json::Value) -> Vec<Line<'static>> {
+    let pretty = serde_json::to_string_pretty(params).unwrap_or_default();
+    let mut lines: Vec<Line<'static>> = pretty
+        .lines()
+        .take(MAX_PARAM_LINES)
+        .map(|line| {
+            let mut spans = vec![Span::raw("  ")];
+            spans.extend(highlight_json_line(line));
+            Line::from(spans)
+        })
+        .collect();
+    if pretty.lines().count() > MAX_PARAM_LINES {
+        lines.push(Line::from(Span::styled(
+            "  ...",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines
+}
+
+/// Color one line of pretty-printed JSON: keys, strings, numbers and literals
+#[cfg(feature = "cli")]
+fn highlight_json_line(line: &str) -> Vec<Span<'static>> {
+    let punct = Style::default().fg(Color::DarkGray);
+    let mut spans = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let style = match c {
+            '"' => {
+                let mut escaped = false;
+                for (i, c) in chars.by_ref() {
+                    end = i + c.len_utf8();
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {},
+                    }
+                }
+                if line[end..].trim_start().starts_with(':') {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::Green)
+                }
+            },
+            c if c.is_whitespace() => Style::default(),
+            c if c == '-' || c.is_ascii_digit() || c.is_ascii_alphabetic() => {
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                if c.is_ascii_alphabetic() {
+                    Style::default().fg(Color::Magenta)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                }
+            },
+            _ => punct,
+        };
+        spans.push(Span::styled(line[start..end].to_string(), style));
+    }
+    spans
+}
+
 /// Staging area for pending blocks (notifications, approvals) awaiting consumption.
 /// Similar to Turn but without role/timestamp - blocks here haven't entered the conversation yet.
 #[derive(Default)]
@@ -866,6 +1267,32 @@ impl Stage {
     }
 }
 
+/// Token usage and estimated cost of one assistant turn, across all of its
+/// requests
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TurnCost {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// None when the model's prices aren't known
+    pub dollars: Option<f64>,
+}
+
+impl TurnCost {
+    /// e.g. `1.2k in / 3.4k out / $0.03`
+    pub fn summary(&self) -> String {
+        let tokens = |count: u32| match count {
+            0..=999 => count.to_string(),
+            1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0),
+            _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+        };
+        let mut summary = format!("{} in / {} out", tokens(self.input_tokens), tokens(self.output_tokens));
+        if let Some(dollars) = self.dollars {
+            summary.push_str(&format!(" / ${:.2}", dollars));
+        }
+        summary
+    }
+}
+
 /// A turn in the conversation - one user or assistant response
 #[derive(Deserialize)]
 pub struct Turn {
@@ -873,6 +1300,9 @@ pub struct Turn {
     pub role: Role,
     pub content: Vec<Box<dyn Block>>,
     pub timestamp: DateTime<Utc>,
+    /// Usage footer for assistant turns (when `show_per_turn_cost` is on)
+    #[serde(default)]
+    pub cost: Option<TurnCost>,
     /// Index of the currently active (streaming) block, if any
     #[serde(skip)]
     pub active_block_idx: Option<usize>,
@@ -888,11 +1318,15 @@ impl Serialize for Turn {
         let persistent_content: Vec<&Box<dyn Block>> =
             self.content.iter().filter(|b| !b.is_ephemeral()).collect();
 
-        let mut state = serializer.serialize_struct("Turn", 4)?;
+        let mut state = serializer.serialize_struct("Turn", 5)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("role", &self.role)?;
         state.serialize_field("content", &persistent_content)?;
         state.serialize_field("timestamp", &self.timestamp)?;
+        match &self.cost {
+            Some(cost) => state.serialize_field("cost", cost)?,
+            None => state.skip_field("cost")?,
+        }
         state.end()
     }
 }
@@ -904,6 +1338,7 @@ impl Turn {
             role,
             content,
             timestamp: Utc::now(),
+            cost: None,
             active_block_idx: None,
         }
     }
@@ -976,15 +1411,105 @@ impl Turn {
                 lines.push(Line::from(""));
             }
         }
+        if let Some(cost) = &self.cost {
+            lines.push(Line::from(Span::styled(
+                cost.summary(),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
         lines
     }
 }
 
+/// How long a session has run and how much of that the agent was working
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionStats {
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// Time spent in assistant turns: waiting on the model and running tools
+    pub active: chrono::Duration,
+    pub user_turns: usize,
+}
+
+impl SessionStats {
+    pub fn duration(&self) -> chrono::Duration {
+        (self.ended - self.started).max(chrono::Duration::zero())
+    }
+
+    /// Time the agent wasn't working: reading, typing, or away
+    pub fn idle(&self) -> chrono::Duration {
+        (self.duration() - self.active).max(chrono::Duration::zero())
+    }
+
+    /// Multi-line breakdown, as shown by `/stats`
+    pub fn report(&self) -> String {
+        format!(
+            "Started: {}\nDuration: {}\nAgent active: {}\nIdle: {}\nMessages: {}",
+            self.started.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            format_duration(self.duration()),
+            format_duration(self.active.min(self.duration())),
+            format_duration(self.idle()),
+            self.user_turns
+        )
+    }
+
+    /// One-line summary, e.g. `Session: 1h 12m (agent active 23m 4s, idle 48m 56s), 14 messages`
+    pub fn summary(&self) -> String {
+        format!(
+            "Session: {} (agent active {}, idle {}), {} message{}",
+            format_duration(self.duration()),
+            format_duration(self.active.min(self.duration())),
+            format_duration(self.idle()),
+            self.user_turns,
+            if self.user_turns == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Format a duration as `2h 5m`, `5m 12s` or `12s`
+fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}h {}m", h, m)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+/// Longest title derived from a transcript's first message
+const TITLE_MAX_CHARS: usize = 60;
+
+/// A saved transcript, as listed by `/resume`
+#[derive(Debug, Clone)]
+pub struct TranscriptInfo {
+    pub number: u32,
+    pub path: PathBuf,
+    /// When the file was last written
+    pub modified: Option<DateTime<Utc>>,
+    pub title: Option<String>,
+}
+
 /// The chat transcript - display log of all turns for UI rendering
 #[derive(Serialize, Deserialize)]
 pub struct Transcript {
     turns: Vec<Turn>,
     next_id: usize,
+    /// Human-readable title, set with `/title` or derived from the first
+    /// user message when first saved; carried across compaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    /// When the session began; carried across `--continue` and compaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    started_at: Option<DateTime<Utc>>,
+    /// Total time spent in assistant turns (generating and running tools), in milliseconds
+    #[serde(default)]
+    active_ms: u64,
+    /// Start of the assistant turn being timed
+    #[serde(skip)]
+    active_since: Option<std::time::Instant>,
     #[serde(skip)]
     path: Option<PathBuf>,
     /// ID of the current turn being streamed to (if any)
@@ -993,6 +1518,24 @@ pub struct Transcript {
     /// Staging area for pending blocks
     #[serde(skip)]
     pub stage: Stage,
+    /// Max characters of tool output written to disk (None = keep everything).
+    /// Only affects the saved file; in-memory blocks keep their full output.
+    #[serde(skip)]
+    tool_output_limit: Option<usize>,
+    /// Max characters of each thinking block written to disk, keeping the head
+    /// and tail (None = keep everything). In-memory blocks and the agent's API
+    /// history, including thinking signatures, are unaffected.
+    #[serde(skip)]
+    thinking_limit: Option<usize>,
+    /// Max characters of each string in a tool call's params written to disk
+    /// (None = keep everything). The tool ran with the full params and
+    /// in-memory blocks keep them.
+    #[serde(skip)]
+    param_limit: Option<usize>,
+    /// End of the last streamed delta, held back because the next delta may
+    /// complete it (see `split_incomplete_tail`). Belongs to the active block.
+    #[serde(skip)]
+    held_delta: String,
 }
 
 impl Transcript {
@@ -1001,12 +1544,36 @@ impl Transcript {
         Self {
             turns: Vec::new(),
             next_id: 0,
+            title: None,
+            started_at: Some(Utc::now()),
+            active_ms: 0,
+            active_since: None,
             path: Some(path),
             current_turn_id: None,
             stage: Stage::new(),
+            tool_output_limit: None,
+            thinking_limit: None,
+            param_limit: None,
+            held_delta: String::new(),
         }
     }
 
+    /// Truncate tool output to `limit` characters when saving
+    pub fn set_tool_output_limit(&mut self, limit: Option<usize>) {
+        self.tool_output_limit = limit;
+    }
+
+    /// Truncate thinking blocks to `limit` characters (head and tail) when saving
+    pub fn set_thinking_limit(&mut self, limit: Option<usize>) {
+        self.thinking_limit = limit;
+    }
+
+    /// Truncate long strings in tool call params (a `write_file` content) to
+    /// `limit` characters when saving
+    pub fn set_param_limit(&mut self, limit: Option<usize>) {
+        self.param_limit = limit;
+    }
+
     /// Get the current path
     pub fn path(&self) -> Option<&Path> {
         self.path.as_deref()
@@ -1040,6 +1607,25 @@ impl Transcript {
         &self.turns
     }
 
+    /// Path of the transcript this one was compacted from, if any
+    pub fn compacted_from(&self) -> Option<&Path> {
+        self.turns
+            .iter()
+            .flat_map(|turn| turn.content.iter())
+            .find_map(|block| block.linked_transcript())
+    }
+
+    /// Facts the compaction summary cites, if this transcript was compacted
+    #[cfg(feature = "cli")]
+    pub fn compaction_sources(&self) -> &[crate::compaction::CompactionSource] {
+        self.turns
+            .iter()
+            .flat_map(|turn| turn.content.iter())
+            .map(|block| block.compaction_sources())
+            .find(|sources| !sources.is_empty())
+            .unwrap_or_default()
+    }
+
     // =========================================================================
     // Turn streaming lifecycle
     // =========================================================================
@@ -1051,12 +1637,53 @@ impl Transcript {
         }
         let id = self.add_empty(role);
         self.current_turn_id = Some(id);
+        if role == Role::Assistant {
+            self.active_since = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Attach a usage footer to the current turn. No-op if no turn is active.
+    pub fn set_turn_cost(&mut self, cost: TurnCost) {
+        if let Some(turn) = self.current_turn_id.and_then(|id| self.get_mut(id)) {
+            turn.cost = Some(cost);
+        }
     }
 
     /// Finish the current turn - marks active block complete, clears current turn.
     pub fn finish_turn(&mut self) {
+        self.flush_held_delta();
         self.mark_active_block(Status::Complete);
         self.current_turn_id = None;
+        if let Some(since) = self.active_since.take() {
+            self.active_ms += since.elapsed().as_millis() as u64;
+        }
+    }
+
+    // =========================================================================
+    // Session timing
+    // =========================================================================
+
+    /// Time from the start of the session until now
+    pub fn duration(&self) -> chrono::Duration {
+        self.stats().duration()
+    }
+
+    /// Session start, end and how the time was spent
+    pub fn stats(&self) -> SessionStats {
+        let started = self
+            .started_at
+            .or_else(|| self.turns.first().map(|t| t.timestamp))
+            .unwrap_or_else(Utc::now);
+        let mut active_ms = self.active_ms;
+        if let Some(since) = self.active_since {
+            active_ms += since.elapsed().as_millis() as u64;
+        }
+        SessionStats {
+            started,
+            ended: Utc::now(),
+            active: chrono::Duration::milliseconds(active_ms as i64),
+            user_turns: self.turns.iter().filter(|t| t.role == Role::User).count(),
+        }
     }
 
     /// Get mutable reference to the current turn. Panics if no turn is active.
@@ -1070,10 +1697,24 @@ impl Transcript {
     /// Stream a delta to the current turn.
     /// Appends to active block if type matches, otherwise starts a new block.
     /// Panics if no turn is active.
+    ///
+    /// Text that may be the first half of a character sequence split across
+    /// deltas (a ZWJ emoji, a flag) is held back until the next delta, so the
+    /// UI never renders half of it.
     pub fn stream_delta(&mut self, kind: BlockType, text: &str) {
+        if !self.current_turn_mut().is_active_block_type(kind) {
+            self.flush_held_delta();
+        }
+        let mut text = std::mem::take(&mut self.held_delta) + text;
+        let complete = split_incomplete_tail(&text).0.len();
+        self.held_delta = text.split_off(complete);
+        if text.is_empty() {
+            return;
+        }
+
         let turn = self.current_turn_mut();
         if turn.is_active_block_type(kind) {
-            turn.append_to_active(text);
+            turn.append_to_active(&text);
         } else {
             let block: Box<dyn Block> = match kind {
                 BlockType::Text => Box::new(TextBlock::new(text)),
@@ -1090,15 +1731,79 @@ impl Transcript {
 
     /// Start a new block on the current turn. Panics if no turn is active.
     pub fn start_block(&mut self, block: Box<dyn Block>) {
+        self.flush_held_delta();
         self.current_turn_mut().start_block(block);
     }
 
+    /// Append held-back delta text to the active block; the stream moved on
+    /// without completing it.
+    fn flush_held_delta(&mut self) {
+        if self.held_delta.is_empty() {
+            return;
+        }
+        let text = std::mem::take(&mut self.held_delta);
+        if let Some(turn_id) = self.current_turn_id {
+            if let Some(turn) = self.get_mut(turn_id) {
+                turn.append_to_active(&text);
+            }
+        }
+    }
+
     /// Get mutable reference to the active block.
     pub fn active_block_mut(&mut self) -> Option<&mut (dyn Block + 'static)> {
         let turn_id = self.current_turn_id?;
         self.get_mut(turn_id)?.get_active_block_mut()
     }
 
+    /// Text blocks of the most recent assistant turn that has any, joined
+    pub fn last_assistant_text(&self) -> Option<String> {
+        self.turns
+            .iter()
+            .rev()
+            .filter(|turn| turn.role == Role::Assistant)
+            .find_map(|turn| {
+                let parts: Vec<&str> = turn
+                    .content
+                    .iter()
+                    .filter(|block| block.kind() == BlockType::Text)
+                    .filter_map(|block| block.text())
+                    .collect();
+                (!parts.is_empty()).then(|| parts.join("\n\n"))
+            })
+    }
+
+    /// The transcript's title: the one set with `set_title`, or else the
+    /// first line of the first user message that isn't a slash command
+    pub fn title(&self) -> Option<String> {
+        self.title.clone().or_else(|| self.derived_title())
+    }
+
+    /// Set the title shown in listings; an empty title clears it
+    pub fn set_title(&mut self, title: &str) {
+        let title = title.trim();
+        self.title = (!title.is_empty()).then(|| title.to_string());
+    }
+
+    /// Title derived from the first user message, truncated
+    fn derived_title(&self) -> Option<String> {
+        let text = self
+            .turns
+            .iter()
+            .filter(|turn| turn.role == Role::User)
+            .flat_map(|turn| turn.content.iter())
+            .filter(|block| block.kind() == BlockType::Text)
+            .filter_map(|block| block.text())
+            .map(str::trim)
+            .find(|text| !text.is_empty() && !text.starts_with('/'))?;
+
+        let line = text.lines().next().unwrap_or(text).trim();
+        let mut title: String = line.chars().take(TITLE_MAX_CHARS).collect();
+        if title.len() < line.len() {
+            title.push_str("...");
+        }
+        Some(title)
+    }
+
     /// Find a tool block by its call_id.
     pub fn find_tool_block_mut(&mut self, call_id: &str) -> Option<&mut (dyn Block + 'static)> {
         for turn in &mut self.turns {
@@ -1111,6 +1816,25 @@ impl Transcript {
         None
     }
 
+    /// Most recent tool block, checking staged blocks before the transcript.
+    pub fn last_tool_block_mut(&mut self) -> Option<&mut (dyn Block + 'static)> {
+        if let Some(block) = self
+            .stage
+            .blocks
+            .iter_mut()
+            .rev()
+            .find(|b| b.call_id().is_some())
+        {
+            return Some(block.as_mut());
+        }
+        self.turns
+            .iter_mut()
+            .rev()
+            .flat_map(|turn| turn.content.iter_mut().rev())
+            .find(|b| b.call_id().is_some())
+            .map(|b| b.as_mut())
+    }
+
     /// Set status on the active block.
     pub fn mark_active_block(&mut self, status: Status) {
         if let Some(block) = self.active_block_mut() {
@@ -1141,8 +1865,32 @@ impl Transcript {
             std::fs::create_dir_all(parent)?;
         }
         let file = std::fs::File::create(path)?;
-        serde_json::to_writer_pretty(file, self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        // Persist the derived title so listings don't depend on the first message
+        let derived_title = self.title.is_none().then(|| self.derived_title()).flatten();
+        let result = if self.tool_output_limit.is_some()
+            || self.thinking_limit.is_some()
+            || self.param_limit.is_some()
+            || derived_title.is_some()
+        {
+            serde_json::to_value(self).and_then(|mut value| {
+                if let Some(title) = derived_title {
+                    value["title"] = serde_json::Value::String(title);
+                }
+                if let Some(limit) = self.tool_output_limit {
+                    elide_tool_output(&mut value, limit);
+                }
+                if let Some(limit) = self.thinking_limit {
+                    elide_thinking(&mut value, limit);
+                }
+                if let Some(limit) = self.param_limit {
+                    elide_tool_params(&mut value, limit);
+                }
+                serde_json::to_writer_pretty(file, &value)
+            })
+        } else {
+            serde_json::to_writer_pretty(file, self)
+        };
+        result.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
     }
 
     /// Load the latest transcript from the transcripts directory
@@ -1151,12 +1899,7 @@ impl Transcript {
         let dir = get_transcripts_dir()?;
 
         if let Some(latest_number) = find_latest_transcript_number(&dir) {
-            let path = transcript_path(&dir, latest_number);
-            let file = std::fs::File::open(&path)?;
-            let mut transcript: Self = serde_json::from_reader(file)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-            transcript.path = Some(path);
-            Ok(transcript)
+            Self::load_from(&transcript_path(&dir, latest_number))
         } else {
             // No transcripts exist, create a new one with number 0
             let path = transcript_path(&dir, 0);
@@ -1164,6 +1907,54 @@ impl Transcript {
         }
     }
 
+    /// Load a transcript from a specific path
+    pub fn load_from(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let mut transcript: Self = serde_json::from_reader(file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        transcript.path = Some(path.to_path_buf());
+        Ok(transcript)
+    }
+
+    /// Load the transcript with the given number
+    pub fn load_numbered(number: u32) -> std::io::Result<Self> {
+        let dir = get_transcripts_dir()?;
+        Self::load_from(&transcript_path(&dir, number))
+    }
+
+    /// The `limit` most recent saved transcripts, newest first.
+    /// Unreadable files are skipped.
+    pub fn list(limit: usize) -> std::io::Result<Vec<TranscriptInfo>> {
+        let dir = get_transcripts_dir()?;
+        let mut numbers: Vec<u32> = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_suffix(".json")?.parse().ok()
+            })
+            .collect();
+        numbers.sort_unstable_by(|a, b| b.cmp(a));
+
+        Ok(numbers
+            .into_iter()
+            .take(limit)
+            .filter_map(|number| {
+                let path = transcript_path(&dir, number);
+                let modified = std::fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(DateTime::<Utc>::from);
+                let transcript = Self::load_from(&path).ok()?;
+                Some(TranscriptInfo {
+                    number,
+                    title: transcript.title(),
+                    path,
+                    modified,
+                })
+            })
+            .collect())
+    }
+
     /// Create a new empty transcript with the next available number
     pub fn new_numbered() -> std::io::Result<Self> {
         let dir = get_transcripts_dir()?;
@@ -1178,7 +1969,8 @@ impl Transcript {
 
     /// Rotate to a new transcript file
     /// Saves the current transcript and returns a new one with the next numbered path
-    /// If the last turn contains a CompactionBlock, it will be added to the new transcript
+    /// If the last turn contains a CompactionBlock, it will be added to the new transcript,
+    /// linked back to this one so the full context can be restored later
     pub fn rotate(&self) -> std::io::Result<Self> {
         // Save current transcript
         self.save()?;
@@ -1194,6 +1986,12 @@ impl Transcript {
         // Create new transcript with next path
         let new_path = transcript_path(&dir, next_number);
         let mut new_transcript = Self::with_path(new_path);
+        new_transcript.tool_output_limit = self.tool_output_limit;
+        new_transcript.thinking_limit = self.thinking_limit;
+        new_transcript.param_limit = self.param_limit;
+        new_transcript.started_at = self.started_at;
+        new_transcript.active_ms = self.active_ms;
+        new_transcript.title = self.title();
 
         // Check if last turn has a CompactionBlock and carry it over
         #[cfg(feature = "cli")]
@@ -1204,6 +2002,8 @@ impl Transcript {
                         use crate::compaction::CompactionBlock;
                         let mut compaction_block = CompactionBlock::new(summary_text.to_string());
                         compaction_block.status = Status::Complete;
+                        compaction_block.previous_transcript = self.path.clone();
+                        compaction_block.sources = crate::compaction::parse_sources(summary_text).1;
                         new_transcript.add_turn(Role::Assistant, compaction_block);
                         break; // Only add the first compaction block found
                     }
@@ -1215,10 +2015,221 @@ impl Transcript {
     }
 }
 
+/// Split `text` before a trailing sequence that the next streamed delta may
+/// still extend into a single character on screen: a zero-width joiner and the
+/// emoji it follows (`👩‍` + `💻`), or an unpaired regional indicator (half of a
+/// flag). Deltas arrive as `String`s, so a UTF-8 byte sequence is never split,
+/// but these multi-codepoint sequences can be.
+fn split_incomplete_tail(text: &str) -> (&str, &str) {
+    const ZWJ: char = '\u{200D}';
+    let is_regional_indicator = |c: char| ('\u{1F1E6}'..='\u{1F1FF}').contains(&c);
+    // Variation selector-16 and skin tones attach to the emoji before them
+    let is_modifier = |c: char| c == '\u{FE0F}' || ('\u{1F3FB}'..='\u{1F3FF}').contains(&c);
+
+    let mut chars = text.char_indices().rev().peekable();
+    match chars.peek() {
+        Some(&(_, ZWJ)) => {
+            // Hold back the whole joined sequence so far
+            let mut start = text.len();
+            while let Some((idx, c)) = chars.next() {
+                start = idx;
+                if c != ZWJ && !is_modifier(c) && chars.peek().map(|&(_, c)| c) != Some(ZWJ) {
+                    break;
+                }
+            }
+            text.split_at(start)
+        },
+        Some(&(idx, c)) if is_regional_indicator(c) => {
+            let run = text.chars().rev().take_while(|&c| is_regional_indicator(c)).count();
+            if run % 2 == 1 {
+                text.split_at(idx)
+            } else {
+                (text, "")
+            }
+        },
+        _ => (text, ""),
+    }
+}
+
+/// Truncate the output of every serialized tool block to `limit` characters.
+/// Tool blocks are recognized by their `call_id`, whatever their concrete type.
+fn elide_tool_output(transcript: &mut serde_json::Value, limit: usize) {
+    let Some(turns) = transcript["turns"].as_array_mut() else {
+        return;
+    };
+    let blocks = turns
+        .iter_mut()
+        .filter_map(|turn| turn["content"].as_array_mut())
+        .flatten();
+
+    for block in blocks {
+        if block.get("call_id").is_none() {
+            continue;
+        }
+        let Some(text) = block["text"].as_str() else {
+            continue;
+        };
+        let total = text.chars().count();
+        if total <= limit {
+            continue;
+        }
+        let kept: String = text.chars().take(limit).collect();
+        block["text"] = serde_json::Value::String(format!(
+            "{}\n[... {} characters of output not saved]",
+            kept,
+            total - limit
+        ));
+    }
+}
+
+/// Ends a tool call param string that was shortened when saving
+const ELIDED_PARAM_SUFFIX: &str = " characters not saved]";
+const ELIDED_PARAM_OPEN: &str = "\n[... ";
+
+/// Shorten long strings in the tool call params of a serialized transcript
+/// to `limit` characters. The call's result, which says what the tool did,
+/// is kept; a restored session replays the params through `replay_params`.
+fn elide_tool_params(transcript: &mut serde_json::Value, limit: usize) {
+    fn elide(value: &mut serde_json::Value, limit: usize) {
+        match value {
+            serde_json::Value::String(text) => {
+                let total = text.chars().count();
+                if total > limit {
+                    let kept: String = text.chars().take(limit).collect();
+                    *text = format!("{}{}{}{}", kept, ELIDED_PARAM_OPEN, total - limit, ELIDED_PARAM_SUFFIX);
+                }
+            },
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| elide(item, limit)),
+            serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| elide(field, limit)),
+            _ => {},
+        }
+    }
+
+    let Some(turns) = transcript["turns"].as_array_mut() else {
+        return;
+    };
+    let blocks = turns
+        .iter_mut()
+        .filter_map(|turn| turn["content"].as_array_mut())
+        .flatten();
+
+    for block in blocks {
+        if block.get("call_id").is_none() {
+            continue;
+        }
+        if let Some(params) = block.get_mut("params") {
+            elide(params, limit);
+        }
+    }
+}
+
+/// Tool call params from a loaded transcript as the model should see them
+/// again: strings shortened when saving become a note, so a restored session
+/// doesn't show the model a cut-off argument as if it had written it
+pub fn replay_params(params: &serde_json::Value) -> serde_json::Value {
+    match params {
+        serde_json::Value::String(text) => {
+            let elided = text
+                .strip_suffix(ELIDED_PARAM_SUFFIX)
+                .and_then(|rest| rest.rsplit_once(ELIDED_PARAM_OPEN))
+                .and_then(|(kept, omitted)| Some(kept.chars().count() + omitted.parse::<usize>().ok()?));
+            match elided {
+                Some(total) => serde_json::Value::String(format!(
+                    "[{} characters, not kept in the saved session; the result shows what the call did]",
+                    total
+                )),
+                None => params.clone(),
+            }
+        },
+        serde_json::Value::Array(items) => items.iter().map(replay_params).collect(),
+        serde_json::Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| (key.clone(), replay_params(value)))
+            .collect(),
+        _ => params.clone(),
+    }
+}
+
+/// Shorten thinking blocks in a serialized transcript to `limit` characters,
+/// keeping the beginning and end of the reasoning
+fn elide_thinking(transcript: &mut serde_json::Value, limit: usize) {
+    let Some(turns) = transcript["turns"].as_array_mut() else {
+        return;
+    };
+    let blocks = turns
+        .iter_mut()
+        .filter_map(|turn| turn["content"].as_array_mut())
+        .flatten();
+
+    for block in blocks {
+        if block["type"] != "ThinkingBlock" {
+            continue;
+        }
+        let Some(text) = block["text"].as_str() else {
+            continue;
+        };
+        let total = text.chars().count();
+        if total <= limit {
+            continue;
+        }
+        let head_len = limit.div_ceil(2);
+        let head: String = text.chars().take(head_len).collect();
+        let tail: String = text.chars().skip(total - (limit - head_len)).collect();
+        block["text"] = serde_json::Value::String(format!(
+            "{}\n[... {} characters of thinking not saved ...]\n{}",
+            head,
+            total - limit,
+            tail
+        ));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_long_lines_clamped() {
+        let huge = "x".repeat(3 * 1024 * 1024);
+        assert!(matches!(clamp_long_lines("short\nlines"), Cow::Borrowed(_)));
+        let clamped = clamp_long_lines(&format!("before\n{}\nafter", huge));
+        assert_eq!(clamped.lines().count(), 3);
+        assert!(clamped.len() < DEFAULT_MAX_LINE_LENGTH + 100);
+        assert!(clamped.lines().nth(1).unwrap().ends_with(LONG_LINE_MARKER));
+
+        let block = TextBlock::complete(huge.clone());
+        let rendered: usize = block
+            .render(80)
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .map(|span| span.content.len())
+            .sum();
+        assert!(rendered < DEFAULT_MAX_LINE_LENGTH + 1000);
+
+        let line = clamp_line(Line::from(vec![Span::raw("ab"), Span::raw(huge)]));
+        assert_eq!(line.spans.len(), 3);
+        assert_eq!(line.spans[1].content.chars().count(), DEFAULT_MAX_LINE_LENGTH - 2);
+        assert_eq!(line.spans[2].content, LONG_LINE_MARKER);
+    }
+
+    #[test]
+    fn test_tool_block_markdown_result() {
+        let table = "| name | size |\n|------|------|\n| a.rs | 10 |\n| b.rs | 20 |";
+        let mut block = ToolBlock::new("call", "tool", serde_json::json!({}), false);
+        block.text = table.to_string();
+        block.status = Status::Complete;
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect()).collect()
+        };
+        let plain = text(block.render(40));
+        assert!(plain.iter().any(|l| l.contains("|------|")));
+
+        let block = block.with_markdown(true);
+        let rendered = text(block.render(40));
+        assert!(!rendered.iter().any(|l| l.contains("|------|")));
+        assert!(rendered.iter().any(|l| l.contains("a.rs")));
+    }
+
     #[test]
     fn test_text_block_render() {
         let block = TextBlock::new("Hello\nWorld");
@@ -1239,6 +2250,54 @@ mod tests {
         assert_eq!(block.status(), Status::Complete);
     }
 
+    #[test]
+    fn test_summarize_params() {
+        let params = serde_json::json!({
+            "limit": 20,
+            "path": "src/main.rs",
+            "body": "line one\nline two",
+            "tags": ["a", "b"],
+        });
+        assert_eq!(
+            summarize_params(&params, 80),
+            r#"path: "src/main.rs", body: "line one…", limit: 20, tags: [2 items]"#
+        );
+        assert_eq!(summarize_params(&params, 10), "path: \"sr…");
+        assert_eq!(summarize_params(&serde_json::json!({}), 80), "");
+    }
+
+    #[test]
+    fn test_tool_block_params_collapsed_until_expanded() {
+        let params = serde_json::json!({ "query": "rust", "count": 3 });
+        let mut block = ToolBlock::new("call_1", "search", params, false);
+        block.set_status(Status::Complete);
+
+        let text = |block: &ToolBlock| -> Vec<String> {
+            block
+                .render(80)
+                .iter()
+                .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect()
+        };
+        assert_eq!(text(&block), vec![r#"✓ search(query: "rust", count: 3)"#]);
+
+        assert!(block.toggle_expanded());
+        let expanded = text(&block);
+        assert_eq!(expanded[0], "✓ search");
+        assert!(expanded.contains(&r#"    "query": "rust""#.to_string()));
+
+        // A tool-provided summary replaces the params list
+        let mut block = block.with_summary("\"rust\", top 3");
+        block.toggle_expanded();
+        assert_eq!(text(&block), vec![r#"✓ search("rust", top 3)"#]);
+
+        // Keys and values are highlighted differently
+        let line = highlight_json_line(r#"  "count": 3,"#);
+        let key = line.iter().find(|s| s.content == "\"count\"").unwrap();
+        let value = line.iter().find(|s| s.content == "3").unwrap();
+        assert_ne!(key.style, value.style);
+    }
+
     #[test]
     fn test_transcript_add_and_get() {
         let mut transcript = Transcript::with_path(std::path::PathBuf::from("/tmp/test.md"));
@@ -1251,6 +2310,44 @@ mod tests {
         assert_eq!(transcript.get_mut(id2).unwrap().role, Role::Assistant);
     }
 
+    #[test]
+    fn test_transcript_title() {
+        let mut transcript = Transcript::with_path(std::path::PathBuf::from("/tmp/test.md"));
+        assert!(transcript.title().is_none());
+
+        transcript.add_turn(Role::User, TextBlock::new("/stats"));
+        transcript.add_turn(Role::Assistant, TextBlock::new("Session: 5m"));
+        transcript.add_turn(Role::User, TextBlock::new("  Fix the flaky filter test\nIt fails on CI."));
+        assert_eq!(transcript.title().as_deref(), Some("Fix the flaky filter test"));
+
+        let mut long = Transcript::with_path(std::path::PathBuf::from("/tmp/test.md"));
+        long.add_turn(Role::User, TextBlock::new("x".repeat(100)));
+        assert_eq!(long.title().unwrap(), format!("{}...", "x".repeat(TITLE_MAX_CHARS)));
+
+        transcript.set_title("Filter flakiness");
+        assert_eq!(transcript.title().as_deref(), Some("Filter flakiness"));
+        transcript.set_title("  ");
+        assert_eq!(transcript.title().as_deref(), Some("Fix the flaky filter test"));
+    }
+
+    #[test]
+    fn test_transcript_title_persists() {
+        let path = std::env::temp_dir().join("codey_test_transcript_title.json");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.add_turn(Role::User, TextBlock::new("Add a /title command"));
+        transcript.save().expect("Failed to save transcript");
+        let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(raw["title"], "Add a /title command");
+
+        transcript.set_title("Titles");
+        transcript.save().expect("Failed to save transcript");
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(loaded.title().as_deref(), Some("Titles"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn test_turn_streaming() {
         let mut turn = Turn::new(0, Role::Assistant, vec![]);
@@ -1265,6 +2362,30 @@ mod tests {
         assert_eq!(turn.content.len(), 1);
     }
 
+    #[test]
+    fn test_stream_delta_holds_split_sequences() {
+        let mut transcript = Transcript::with_path(std::path::PathBuf::from("/tmp/test.md"));
+        let active_text = |t: &mut Transcript| t.active_block_mut().and_then(|b| b.text().map(String::from));
+
+        transcript.begin_turn(Role::Assistant);
+        // A ZWJ emoji (woman + ZWJ + laptop) split across two deltas
+        transcript.stream_delta(BlockType::Text, "Coder: \u{1F469}\u{200D}");
+        assert_eq!(active_text(&mut transcript).as_deref(), Some("Coder: "));
+        transcript.stream_delta(BlockType::Text, "\u{1F4BB} flag: \u{1F1FA}");
+        assert_eq!(
+            active_text(&mut transcript).as_deref(),
+            Some("Coder: \u{1F469}\u{200D}\u{1F4BB} flag: ")
+        );
+        transcript.stream_delta(BlockType::Text, "\u{1F1F8}.");
+        assert!(active_text(&mut transcript).unwrap().ends_with("\u{1F1FA}\u{1F1F8}."));
+
+        // Held text is flushed when the turn ends without completing it
+        transcript.stream_delta(BlockType::Text, " \u{1F1FA}");
+        transcript.finish_turn();
+        let last = transcript.turns().last().unwrap();
+        assert!(last.content[0].text().unwrap().ends_with(". \u{1F1FA}"));
+    }
+
     #[test]
     fn test_transcript_save_load_roundtrip() {
         let temp_dir = std::env::temp_dir();
@@ -1294,6 +2415,55 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn test_turn_cost_footer() {
+        let path = std::env::temp_dir().join("codey_test_turn_cost.json");
+        let cost = TurnCost { input_tokens: 1_234, output_tokens: 3_400, dollars: Some(0.031) };
+        assert_eq!(cost.summary(), "1.2k in / 3.4k out / $0.03");
+        assert_eq!(TurnCost { dollars: None, ..cost }.summary(), "1.2k in / 3.4k out");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.add_turn(Role::User, TextBlock::new("Hello"));
+        transcript.begin_turn(Role::Assistant);
+        transcript.stream_delta(BlockType::Text, "Hi there!");
+        transcript.set_turn_cost(cost);
+        transcript.finish_turn();
+        transcript.save().expect("Failed to save transcript");
+
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(loaded.turns()[0].cost, None);
+        assert_eq!(loaded.turns()[1].cost, Some(cost));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_compaction_block_links_previous_transcript() {
+        use crate::compaction::CompactionBlock;
+
+        let temp_dir = std::env::temp_dir();
+        let path = temp_dir.join("codey_test_compacted_transcript.json");
+
+        let mut block = CompactionBlock::new("Summary");
+        block.status = Status::Complete;
+        block.previous_transcript = Some(PathBuf::from(".codey/transcripts/000001.json"));
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.add_turn(Role::Assistant, block);
+        transcript.add_turn(Role::User, TextBlock::new("Continue"));
+        transcript.save().expect("Failed to save transcript");
+
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(
+            loaded.compacted_from(),
+            Some(Path::new(".codey/transcripts/000001.json"))
+        );
+        assert_eq!(loaded.path(), Some(path.as_path()));
+
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn test_transcript_save_load_with_tool_blocks() {
         let temp_dir = std::env::temp_dir();
@@ -1334,6 +2504,123 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn test_save_elides_tool_output() {
+        let temp_dir = std::env::temp_dir();
+        let path = temp_dir.join("codey_test_elided_transcript.json");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.set_tool_output_limit(Some(5));
+        transcript.add_turn(Role::User, TextBlock::new("A long message that is not tool output"));
+
+        let mut tool_block = ToolBlock::new("call_1", "shell", serde_json::json!({}), false);
+        tool_block.set_status(Status::Complete);
+        tool_block.append_text("0123456789");
+        transcript.add_turn(Role::Assistant, tool_block);
+
+        transcript.save().expect("Failed to save transcript");
+
+        // In-memory output is untouched
+        assert_eq!(transcript.turns()[1].content[0].text(), Some("0123456789"));
+
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(
+            loaded.turns()[0].content[0].text(),
+            Some("A long message that is not tool output")
+        );
+        assert_eq!(
+            loaded.turns()[1].content[0].text(),
+            Some("01234\n[... 5 characters of output not saved]")
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_save_elides_tool_params() {
+        let temp_dir = std::env::temp_dir();
+        let path = temp_dir.join("codey_test_elided_params.json");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.set_param_limit(Some(4));
+        let params = serde_json::json!({ "path": "a.rs", "content": "0123456789", "edits": [{ "old": "abcdef" }] });
+        let mut tool_block = ToolBlock::new("call_1", "write_file", params.clone(), false);
+        tool_block.set_status(Status::Complete);
+        tool_block.append_text("Wrote a.rs");
+        transcript.add_turn(Role::Assistant, tool_block);
+
+        transcript.save().expect("Failed to save transcript");
+        assert_eq!(transcript.turns()[0].content[0].params(), Some(&params));
+
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        let saved = loaded.turns()[0].content[0].params().unwrap();
+        assert_eq!(saved["path"], "a.rs");
+        assert_eq!(saved["content"], "0123\n[... 6 characters not saved]");
+        assert_eq!(saved["edits"][0]["old"], "abcd\n[... 2 characters not saved]");
+        assert_eq!(loaded.turns()[0].content[0].text(), Some("Wrote a.rs"));
+
+        // Replayed to the model as a note rather than a cut-off argument
+        let replayed = replay_params(saved);
+        assert_eq!(replayed["path"], "a.rs");
+        assert!(replayed["content"].as_str().unwrap().starts_with("[10 characters, not kept"));
+        assert_eq!(replay_params(&params), params);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_session_stats_persist() {
+        let temp_dir = std::env::temp_dir();
+        let path = temp_dir.join("codey_test_session_stats.json");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.started_at = Some(Utc::now() - chrono::Duration::minutes(10));
+        transcript.add_turn(Role::User, TextBlock::new("hi"));
+        transcript.begin_turn(Role::Assistant);
+        transcript.active_since = Some(std::time::Instant::now() - std::time::Duration::from_secs(90));
+        transcript.finish_turn();
+
+        let stats = transcript.stats();
+        assert_eq!(stats.active.num_seconds(), 90);
+        assert_eq!(stats.duration().num_minutes(), 10);
+        assert_eq!(stats.idle().num_seconds(), stats.duration().num_seconds() - 90);
+        assert_eq!(stats.user_turns, 1);
+        assert!(stats.summary().starts_with("Session: 10m 0s (agent active 1m 30s, idle 8m 30s)"));
+
+        // --continue picks up the original start time and active time
+        transcript.save().expect("Failed to save transcript");
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(loaded.stats().started, stats.started);
+        assert_eq!(loaded.stats().active, stats.active);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_save_elides_thinking() {
+        let temp_dir = std::env::temp_dir();
+        let path = temp_dir.join("codey_test_elided_thinking.json");
+
+        let mut transcript = Transcript::with_path(path.clone());
+        transcript.set_thinking_limit(Some(6));
+        let mut thinking = ThinkingBlock::new("abcdefghijklmnop");
+        thinking.status = Status::Complete;
+        transcript.add_turn(Role::Assistant, thinking);
+        transcript.add_turn(Role::Assistant, TextBlock::new("abcdefghijklmnop"));
+
+        transcript.save().expect("Failed to save transcript");
+        assert_eq!(transcript.turns()[0].content[0].text(), Some("abcdefghijklmnop"));
+
+        let loaded = Transcript::load_from(&path).expect("Failed to load transcript");
+        assert_eq!(
+            loaded.turns()[0].content[0].text(),
+            Some("abc\n[... 10 characters of thinking not saved ...]\nnop")
+        );
+        assert_eq!(loaded.turns()[1].content[0].text(), Some("abcdefghijklmnop"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
     // ========================================================================
     // Stage Tests - Simulating concurrent agent behavior
     // ========================================================================
@@ -28,9 +28,10 @@ use ratatui::{
     Terminal,
 };
 
+use crate::links::{style_links, Link};
 #[cfg(feature = "profiling")]
 use crate::profile_span;
-use crate::transcript::{Block, Role, Stage, Status, Transcript, Turn};
+use crate::transcript::{clamp_line, Block, Role, Stage, Status, Transcript, Turn};
 
 /// Chat view with native scrollback support.
 ///
@@ -52,6 +53,8 @@ pub struct ChatView {
     turn_line_counts: HashMap<usize, usize>,
     /// Agent name for display (configurable)
     agent_name: String,
+    /// Link highlighted for opening (cycled with Ctrl+L)
+    pub focused_link: Option<Link>,
 }
 
 impl ChatView {
@@ -65,6 +68,7 @@ impl ChatView {
             frozen_turn_ids: HashSet::new(),
             turn_line_counts: HashMap::new(),
             agent_name,
+            focused_link: None,
         }
     }
 
@@ -133,7 +137,12 @@ impl ChatView {
             if self.frozen_turn_ids.contains(&turn.id) {
                 continue;
             }
-            let render = Self::render_turn_to_lines(turn, self.width, &self.agent_name);
+            let render = Self::render_turn_to_lines(
+                turn,
+                self.width,
+                &self.agent_name,
+                self.focused_link.as_ref(),
+            );
             self.turn_line_counts.insert(turn.id, render.len());
             active_lines.extend(render);
         }
@@ -207,7 +216,12 @@ impl ChatView {
     }
 
     /// Render a turn to lines (header + content + separator)
-    fn render_turn_to_lines(turn: &Turn, width: u16, agent_name: &str) -> Vec<Line<'static>> {
+    fn render_turn_to_lines(
+        turn: &Turn,
+        width: u16,
+        agent_name: &str,
+        focused_link: Option<&Link>,
+    ) -> Vec<Line<'static>> {
         #[cfg(feature = "profiling")]
         let _span = profile_span!("ChatView::render_turn_to_lines");
 
@@ -246,14 +260,14 @@ impl ChatView {
         ]);
         lines.push(header);
 
-        // Content lines - convert to owned by mapping spans
+        // Content lines - convert to owned by mapping spans, underlining links
         for line in turn.render(width) {
             let owned_spans: Vec<Span<'static>> = line
                 .spans
                 .iter()
                 .map(|span| Span::styled(span.content.to_string(), span.style))
                 .collect();
-            lines.push(Line::from(owned_spans));
+            lines.push(style_links(clamp_line(Line::from(owned_spans)), focused_link));
         }
 
         // Separator (empty line)
@@ -282,7 +296,7 @@ impl ChatView {
                 .iter()
                 .map(|span| Span::styled(span.content.to_string(), span.style))
                 .collect();
-            lines.push(Line::from(owned_spans));
+            lines.push(clamp_line(Line::from(owned_spans)));
         }
 
         // Separator (empty line)
@@ -28,6 +28,8 @@ use ratatui::{
 use textwrap::wrap;
 use unicode_width::UnicodeWidthStr;
 
+use crate::llm::ContextLevel;
+
 /// Format a token count with "k" suffix for thousands
 fn format_tokens(count: u32) -> String {
     if count >= 1000 {
@@ -48,6 +50,9 @@ pub enum AttachmentKind {
         start_line: u32,
         end_line: u32,
     },
+    /// File a tool produced (`/artifacts attach`); `content` is empty for
+    /// binary files, which are referenced by path
+    Artifact { path: String, kind: String },
 }
 
 /// Attached content shown as a pill
@@ -74,6 +79,14 @@ impl Attachment {
         }
     }
 
+    /// Create a new artifact attachment
+    pub fn artifact(path: String, kind: String, content: Option<String>) -> Self {
+        Self {
+            kind: AttachmentKind::Artifact { path, kind },
+            content: content.unwrap_or_default(),
+        }
+    }
+
     /// Get the label for this attachment
     pub fn label(&self) -> String {
         match &self.kind {
@@ -90,6 +103,10 @@ impl Attachment {
                     format!("{}:{}-{}", filename, start_line, end_line)
                 }
             }
+            AttachmentKind::Artifact { path, .. } => std::path::Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.clone()),
         }
     }
 
@@ -98,6 +115,7 @@ impl Attachment {
         let icon = match &self.kind {
             AttachmentKind::PastedText { .. } => "\u{00B6}",  // ¶ pilcrow
             AttachmentKind::IdeSelection { .. } => "\u{00A7}",  // § section
+            AttachmentKind::Artifact { .. } => "\u{00A4}",  // ¤ currency sign
         };
         format!("[{} {}] ", icon, self.label())
     }
@@ -130,6 +148,13 @@ impl Attachment {
                 };
                 format!("\n```\n# {}:{}\n{}```\n\n", path, range, numbered_content)
             }
+            AttachmentKind::Artifact { path, kind } => {
+                if self.content.is_empty() {
+                    format!("\n[Attached file ({}): {}]\n\n", kind, path)
+                } else {
+                    format!("\n```\n# {} ({})\n{}\n```\n\n", path, kind, self.content.trim_end())
+                }
+            }
         }
     }
 }
@@ -338,6 +363,25 @@ impl InputBox {
         self.cursor_offset = content.len();
     }
 
+    /// Take everything typed so far, attachments included, leaving the input
+    /// empty. Put it back with `restore_draft`.
+    pub fn take_draft(&mut self) -> Vec<Segment> {
+        let draft = std::mem::replace(&mut self.segments, vec![Segment::Text(String::new())]);
+        self.cursor_seg = 0;
+        self.cursor_offset = 0;
+        draft
+    }
+
+    /// Replace the input with a draft from `take_draft`, cursor at the end
+    pub fn restore_draft(&mut self, draft: Vec<Segment>) {
+        self.segments = draft;
+        if self.segments.is_empty() {
+            self.segments.push(Segment::Text(String::new()));
+        }
+        self.cursor_seg = self.segments.len() - 1;
+        self.cursor_offset = self.segments[self.cursor_seg].end_offset();
+    }
+
     /// Calculate required height for the input box given a width
     pub fn required_height(&self, width: u16) -> u16 {
         let inner_width = width.saturating_sub(2) as usize;
@@ -434,11 +478,101 @@ impl InputBox {
         self.cursor_offset = self.segments[self.cursor_seg].end_offset();
     }
 
+    /// Text of the cursor's segment, if it is a text segment
+    fn cursor_text(&self) -> Option<&str> {
+        self.segments[self.cursor_seg].text_content()
+    }
+
+    /// Move to the start of the next word (vi `w`), crossing into the next
+    /// segment at the end of this one
+    pub fn move_word_forward(&mut self) {
+        match self.cursor_text() {
+            Some(text) if self.cursor_offset < text.len() => {
+                self.cursor_offset = word_forward(text, self.cursor_offset);
+            },
+            _ => {
+                if self.cursor_seg + 1 < self.segments.len() {
+                    self.cursor_seg += 1;
+                    self.cursor_offset = 0;
+                }
+            },
+        }
+    }
+
+    /// Move to the start of the previous word (vi `b`), crossing into the
+    /// previous segment at the start of this one
+    pub fn move_word_back(&mut self) {
+        match self.cursor_text() {
+            Some(text) if self.cursor_offset > 0 => {
+                self.cursor_offset = word_back(text, self.cursor_offset);
+            },
+            _ => {
+                if self.cursor_seg > 0 {
+                    self.cursor_seg -= 1;
+                    self.cursor_offset = self.segments[self.cursor_seg].end_offset();
+                }
+            },
+        }
+    }
+
+    /// Move to the start of the cursor's line (vi `0`)
+    pub fn move_line_start(&mut self) {
+        if let Some(text) = self.cursor_text() {
+            self.cursor_offset = line_bounds(text, self.cursor_offset).0;
+        }
+    }
+
+    /// Move to the end of the cursor's line (vi `$`)
+    pub fn move_line_end(&mut self) {
+        if let Some(text) = self.cursor_text() {
+            self.cursor_offset = line_bounds(text, self.cursor_offset).1;
+        }
+    }
+
+    /// Delete the character under the cursor (vi `x`)
+    pub fn delete_char_forward(&mut self) {
+        let offset = self.cursor_offset;
+        if let Segment::Text(s) = &mut self.segments[self.cursor_seg] {
+            if let Some(c) = s[offset..].chars().next() {
+                s.drain(offset..offset + c.len_utf8());
+            }
+        }
+    }
+
+    /// Delete the cursor's line, with its line break (vi `dd`)
+    pub fn delete_line(&mut self) {
+        let offset = self.cursor_offset;
+        if let Segment::Text(s) = &mut self.segments[self.cursor_seg] {
+            let (start, end) = line_bounds(s, offset);
+            let range = if end < s.len() {
+                start..end + 1
+            } else if start > 0 {
+                start - 1..end
+            } else {
+                start..end
+            };
+            s.drain(range);
+            let start = start.min(s.len());
+            self.cursor_offset = line_bounds(s, start).0;
+        }
+    }
+
     /// Insert a newline
     pub fn insert_newline(&mut self) {
         self.insert_char('\n');
     }
 
+    /// Insert text at the cursor, as if typed. Carriage returns from
+    /// pasted Windows or classic Mac line endings become newlines.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.ensure_text_segment();
+        if let Segment::Text(s) = &mut self.segments[self.cursor_seg] {
+            s.insert_str(self.cursor_offset, &text);
+            self.cursor_offset += text.len();
+        }
+    }
+
     /// Add attachment at cursor position
     pub fn add_attachment(&mut self, attachment: Attachment) {
         let seg = &self.segments[self.cursor_seg];
@@ -581,8 +715,10 @@ impl InputBox {
             model,
             model_icon,
             context_tokens,
+            context_level: ContextLevel::Normal,
             background_tasks,
             agent_active,
+            status: None,
         }
     }
 }
@@ -600,16 +736,39 @@ pub struct InputBoxWidget<'a> {
     model_icon: &'a str,
     /// Current context window size in tokens
     context_tokens: u32,
+    /// How close `context_tokens` is to compaction; colors the token count
+    context_level: ContextLevel,
     /// Number of running background tasks
     background_tasks: usize,
     /// Whether the agent is actively processing (streaming, tool execution)
     agent_active: bool,
+    /// Activity indicator shown in the bottom border (e.g. a spinner frame and label)
+    status: Option<&'a str>,
+}
+
+impl<'a> InputBoxWidget<'a> {
+    /// Show an activity indicator in the bottom border
+    pub fn status(mut self, status: Option<&'a str>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Color the token count by how close the context is to compaction
+    pub fn context_level(mut self, level: ContextLevel) -> Self {
+        self.context_level = level;
+        self
+    }
 }
 
 impl Widget for InputBoxWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // Build the usage string for right title
         let usage_title = format!(" {} ", format_tokens(self.context_tokens));
+        let usage_color = match self.context_level {
+            ContextLevel::Normal => Color::Green,
+            ContextLevel::Warning => Color::Yellow,
+            ContextLevel::Critical => Color::Red,
+        };
         
         // Build model title with background indicator
         let model_title = if self.background_tasks > 0 {
@@ -618,11 +777,17 @@ impl Widget for InputBoxWidget<'_> {
             format!(" {}{} ", self.model_icon, self.model)
         };
 
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Cyan))
             .title(model_title)
-            .title_top(Line::from(usage_title).right_aligned());
+            .title_top(Line::from(Span::styled(usage_title, Style::default().fg(usage_color))).right_aligned());
+        if let Some(status) = self.status {
+            block = block.title_bottom(Line::from(Span::styled(
+                format!(" {} ", status),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
 
         let inner = block.inner(area);
         block.render(area, buf);
@@ -726,6 +891,33 @@ impl Widget for InputBoxWidget<'_> {
     }
 }
 
+/// Byte offset of the next word start after `pos`, where words are runs of
+/// non-whitespace (or `text.len()` if there is none)
+fn word_forward(text: &str, pos: usize) -> usize {
+    let rest = &text[pos..];
+    let after_word = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let gap = rest[after_word..]
+        .find(|c: char| !c.is_whitespace())
+        .unwrap_or(rest.len() - after_word);
+    pos + after_word + gap
+}
+
+/// Byte offset of the start of the word before `pos`
+fn word_back(text: &str, pos: usize) -> usize {
+    let before = text[..pos].trim_end();
+    before
+        .rfind(char::is_whitespace)
+        .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0)
+}
+
+/// Byte range (start, end) of the line containing `pos`, excluding the line break
+fn line_bounds(text: &str, pos: usize) -> (usize, usize) {
+    let start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[pos..].find('\n').map_or(text.len(), |i| pos + i);
+    (start, end)
+}
+
 /// Wrap text into lines, handling explicit newlines
 fn wrap_text(content: &str, width: usize) -> Vec<String> {
     if width == 0 {
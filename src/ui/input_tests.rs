@@ -299,6 +299,29 @@ fn test_render_token_count_display() {
         "Should display '5k' for 5000 tokens. Got:\n{}", full_render);
 }
 
+#[test]
+fn test_render_token_count_colored_by_context_level() {
+    let input = InputBox::new();
+    let token_color = |level| {
+        let backend = TestBackend::new(40, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|frame| {
+            let widget = input.widget("model", "", 5000, 0, false).context_level(level);
+            frame.render_widget(widget, frame.area());
+        }).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..40)
+            .map(|x| buffer.cell((x, 0)).unwrap())
+            .find(|cell| cell.symbol() == "5")
+            .map(|cell| cell.fg)
+            .expect("token count should be in the top border")
+    };
+
+    assert_eq!(token_color(ContextLevel::Normal), Color::Green);
+    assert_eq!(token_color(ContextLevel::Warning), Color::Yellow);
+    assert_eq!(token_color(ContextLevel::Critical), Color::Red);
+}
+
 #[test]
 fn test_render_background_tasks_indicator() {
     let input = InputBox::new();
@@ -350,6 +373,26 @@ fn test_render_background_tasks_indicator() {
         "Should display indicator when background tasks running. Got:\n{}", render_with_bg);
 }
 
+#[test]
+fn test_status_in_bottom_border() {
+    let input = InputBox::new();
+    let backend = TestBackend::new(40, 5);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal.draw(|frame| {
+        let widget = input.widget("model", "", 1000, 0, true).status(Some("⠋ thinking"));
+        frame.render_widget(widget, frame.area());
+    }).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut bottom = String::new();
+    for x in 0..40 {
+        bottom.push_str(buffer.cell((x, 4)).unwrap().symbol());
+    }
+    assert!(bottom.contains(" ⠋ thinking "),
+        "Should display status in bottom border. Got:\n{}", bottom);
+}
+
 // ==================== Snapshot Tests ====================
 
 #[test]
@@ -979,3 +1022,42 @@ fn test_clear_ide_selection() {
     let rendered2 = render_input_content(&input, 50, 5);
     assert_eq!(rendered2, expected_content(&["Type your message here..."], 50, 5));
 }
+
+#[test]
+fn test_vi_motions_and_deletes() {
+    let mut input = InputBox::new();
+    input.set_content("fix the  bug\nthen test");
+
+    input.move_cursor_start();
+    input.move_word_forward();
+    assert_eq!(input.cursor(), (0, 4));
+    input.move_word_forward();
+    assert_eq!(input.cursor(), (0, 9));
+    input.move_line_end();
+    assert_eq!(input.cursor(), (0, 12));
+    input.move_word_back();
+    assert_eq!(input.cursor(), (0, 9));
+    input.move_line_start();
+    assert_eq!(input.cursor(), (0, 0));
+
+    input.delete_char_forward();
+    assert_eq!(input.content(), "ix the  bug\nthen test");
+
+    input.delete_line();
+    assert_eq!(input.content(), "then test");
+    assert_eq!(input.cursor(), (0, 0));
+    input.delete_line();
+    assert!(input.is_empty());
+}
+
+#[test]
+fn test_inline_paste_and_attachment_expand_on_submit() {
+    let mut input = InputBox::new();
+    input.insert_str("see:\r\nfn a() {}\r\n");
+    assert_eq!(input.content(), "see:\nfn a() {}\n");
+
+    input.add_attachment(Attachment::pasted("long\nlog".to_string()));
+    input.insert_str(" thanks");
+    assert_eq!(input.submit(), "see:\nfn a() {}\nlong\nlog thanks");
+    assert!(input.is_empty());
+}
@@ -4,4 +4,4 @@ mod chat;
 mod input;
 
 pub use chat::ChatView;
-pub use input::{Attachment, InputBox};
+pub use input::{Attachment, InputBox, Segment};
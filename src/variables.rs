@@ -0,0 +1,125 @@
+//! Session variables
+//!
+//! Values the user sets with `/set name=value` for the rest of the session.
+//! Custom tool command templates fill `{name}` placeholders the agent didn't
+//! provide from them, and `{name}` in pinned files is replaced before they're
+//! added to the prompt, so a workflow can be parameterized (`/set env=staging`)
+//! without editing the config for each task. Variables aren't saved; a new
+//! `codey` starts without any.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{bail, Result};
+
+/// The session's variables, shared between `/set`, custom tools and the
+/// prompt builder
+#[derive(Debug, Clone, Default)]
+pub struct SessionVariables {
+    vars: Arc<RwLock<BTreeMap<String, String>>>,
+}
+
+/// Variable names are the same as placeholder names: letters, digits and `_`
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl SessionVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a variable, returning its previous value
+    pub fn set(&self, name: &str, value: &str) -> Result<Option<String>> {
+        if !is_valid_name(name) {
+            bail!("Invalid variable name '{}': use letters, digits or '_'", name);
+        }
+        Ok(self
+            .vars
+            .write()
+            .unwrap()
+            .insert(name.to_string(), value.to_string()))
+    }
+
+    /// Remove a variable, returning its value if it was set
+    pub fn unset(&self, name: &str) -> Option<String> {
+        self.vars.write().unwrap().remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.vars.read().unwrap().get(name).cloned()
+    }
+
+    /// All variables, sorted by name
+    pub fn all(&self) -> Vec<(String, String)> {
+        self.vars
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Replace `{name}` with the value of each set variable. Other braces,
+    /// including placeholders for unset variables, are left as they are.
+    pub fn substitute(&self, text: &str) -> String {
+        let vars = self.vars.read().unwrap();
+        if vars.is_empty() || !text.contains('{') {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find('}').and_then(|end| Some((end, vars.get(&after[..end])?))) {
+                Some((end, value)) => {
+                    out.push_str(value);
+                    rest = &after[end + 1..];
+                },
+                None => {
+                    out.push('{');
+                    rest = after;
+                },
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// The variables as a prompt section. Returns None when none are set.
+    pub fn render(&self) -> Option<String> {
+        let vars = self.all();
+        if vars.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = vars
+            .iter()
+            .map(|(name, value)| format!("- `{}` = `{}`", name, value))
+            .collect();
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_substitute() {
+        let vars = SessionVariables::new();
+        assert_eq!(vars.substitute("deploy {env}"), "deploy {env}");
+
+        assert_eq!(vars.set("env", "staging").unwrap(), None);
+        assert_eq!(vars.set("env", "prod").unwrap().as_deref(), Some("staging"));
+        assert!(vars.set("bad name", "x").is_err());
+        assert_eq!(
+            vars.substitute("deploy {env} {region} fn() { {env} }"),
+            "deploy prod {region} fn() { prod }"
+        );
+
+        assert_eq!(vars.unset("env").as_deref(), Some("prod"));
+        assert_eq!(vars.unset("env"), None);
+        assert!(vars.render().is_none());
+    }
+}